@@ -0,0 +1,1265 @@
+use std::{collections::{HashMap, HashSet}, fmt::Write};
+
+use crate::parser::{
+    enums::{self, Variant},
+    expr::{self, Expr, ExprData, ExprType, Lit},
+    functions::{BuiltInFunction, Fun},
+    operators::{BinOp, UnOp},
+    patterns::{MatchArm, MatchPattern},
+    stmt::{Stmt, StmtData},
+    structs::Struct
+};
+
+use super::CompilerError;
+
+/// Runtime support every generated program is prefixed with.  The builtins mirror
+/// what `Interpreter::evaluate_expression` does for them, so a compiled program and
+/// an interpreted one produce the same stdout.
+const PRELUDE: &str = r#"#include <inttypes.h>
+#include <math.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+#ifdef __SIZEOF_INT128__
+typedef __int128 sg_i128;
+typedef unsigned __int128 sg_u128;
+#endif
+
+typedef struct sg_str {
+    const char *ptr;
+    size_t len;
+} sg_str;
+
+static void sg_print_string(sg_str string) {
+    fwrite(string.ptr, 1, string.len, stdout);
+}
+
+static void sg_print_i32(int32_t value) {
+    printf("%" PRId32, value);
+}
+
+static void sg_panic(size_t line, sg_str message) {
+    fflush(stdout);
+    fprintf(stderr, "line %zu: %.*s\n", line, (int) message.len, message.ptr);
+    exit(101);
+}
+
+/* reads until the next ascii whitespace, the same way text_io's read!() does */
+static bool sg_read_token(char *buffer, size_t capacity, size_t *len) {
+    bool fits = true;
+    int chr;
+    *len = 0;
+    while ((chr = getchar()) != EOF && chr != ' ' && chr != '\t' && chr != '\n' && chr != '\r') {
+        if (*len + 1 < capacity) {
+            buffer[(*len)++] = (char) chr;
+        } else {
+            fits = false;
+        }
+    }
+    buffer[*len] = '\0';
+    return fits;
+}
+
+static bool sg_read_i32_raw(int32_t *value) {
+    char buffer[64];
+    size_t len, index = 0;
+    int64_t result = 0;
+    bool negative = false;
+
+    if (!sg_read_token(buffer, sizeof buffer, &len) || len == 0) {
+        return false;
+    }
+    if (buffer[0] == '+' || buffer[0] == '-') {
+        negative = buffer[0] == '-';
+        index = 1;
+        if (len == 1) {
+            return false;
+        }
+    }
+    for (; index < len; index++) {
+        if (buffer[index] < '0' || buffer[index] > '9') {
+            return false;
+        }
+        result = result * 10 + (buffer[index] - '0');
+        if (result > (int64_t) INT32_MAX + 1) {
+            return false;
+        }
+    }
+    if (negative) {
+        result = -result;
+    }
+    if (result > INT32_MAX || result < INT32_MIN) {
+        return false;
+    }
+    *value = (int32_t) result;
+    return true;
+}
+
+static bool sg_read_char_raw(uint32_t *value) {
+    unsigned char buffer[8];
+    size_t len, expected;
+    uint32_t result;
+
+    if (!sg_read_token((char *) buffer, sizeof buffer, &len) || len == 0) {
+        return false;
+    }
+    if (buffer[0] < 0x80) {
+        expected = 1;
+        result = buffer[0];
+    } else if ((buffer[0] & 0xE0) == 0xC0) {
+        expected = 2;
+        result = buffer[0] & 0x1F;
+    } else if ((buffer[0] & 0xF0) == 0xE0) {
+        expected = 3;
+        result = buffer[0] & 0x0F;
+    } else if ((buffer[0] & 0xF8) == 0xF0) {
+        expected = 4;
+        result = buffer[0] & 0x07;
+    } else {
+        return false;
+    }
+    if (len != expected) {
+        return false;
+    }
+    for (size_t index = 1; index < len; index++) {
+        if ((buffer[index] & 0xC0) != 0x80) {
+            return false;
+        }
+        result = (result << 6) | (buffer[index] & 0x3F);
+    }
+    *value = result;
+    return true;
+}
+
+#define SG_DEFINE_IPOW(name, T) \
+    static T name(T base, T exponent, size_t line) { \
+        T result = 1; \
+        if (exponent < 0) { \
+            static const char text[] = "Exponent for Integer Types cannot use a negative value for the Exponent."; \
+            sg_str message = { text, sizeof text - 1 }; \
+            sg_panic(line, message); \
+        } \
+        while (exponent > 0) { \
+            if (exponent & 1) { \
+                result *= base; \
+            } \
+            base *= base; \
+            exponent >>= 1; \
+        } \
+        return result; \
+    }
+
+SG_DEFINE_IPOW(sg_ipow_i8, int8_t)
+SG_DEFINE_IPOW(sg_ipow_i16, int16_t)
+SG_DEFINE_IPOW(sg_ipow_i32, int32_t)
+SG_DEFINE_IPOW(sg_ipow_i64, int64_t)
+SG_DEFINE_IPOW(sg_ipow_isize, intptr_t)
+SG_DEFINE_IPOW(sg_ipow_u8, uint8_t)
+SG_DEFINE_IPOW(sg_ipow_u16, uint16_t)
+SG_DEFINE_IPOW(sg_ipow_u32, uint32_t)
+SG_DEFINE_IPOW(sg_ipow_u64, uint64_t)
+SG_DEFINE_IPOW(sg_ipow_usize, size_t)
+#ifdef __SIZEOF_INT128__
+SG_DEFINE_IPOW(sg_ipow_i128, sg_i128)
+SG_DEFINE_IPOW(sg_ipow_u128, sg_u128)
+#endif
+#undef SG_DEFINE_IPOW
+
+#define SG_DEFINE_FTOI(name, T, min, max) \
+    static T name(double value) { \
+        if (value != value) { \
+            return 0; \
+        } \
+        if (value <= (double) (min)) { \
+            return (min); \
+        } \
+        if (value >= (double) (max)) { \
+            return (max); \
+        } \
+        return (T) value; \
+    }
+
+SG_DEFINE_FTOI(sg_ftoi_i8, int8_t, INT8_MIN, INT8_MAX)
+SG_DEFINE_FTOI(sg_ftoi_i16, int16_t, INT16_MIN, INT16_MAX)
+SG_DEFINE_FTOI(sg_ftoi_i32, int32_t, INT32_MIN, INT32_MAX)
+SG_DEFINE_FTOI(sg_ftoi_i64, int64_t, INT64_MIN, INT64_MAX)
+SG_DEFINE_FTOI(sg_ftoi_isize, intptr_t, INTPTR_MIN, INTPTR_MAX)
+SG_DEFINE_FTOI(sg_ftoi_u8, uint8_t, 0, UINT8_MAX)
+SG_DEFINE_FTOI(sg_ftoi_u16, uint16_t, 0, UINT16_MAX)
+SG_DEFINE_FTOI(sg_ftoi_u32, uint32_t, 0, UINT32_MAX)
+SG_DEFINE_FTOI(sg_ftoi_u64, uint64_t, 0, UINT64_MAX)
+SG_DEFINE_FTOI(sg_ftoi_usize, size_t, 0, SIZE_MAX)
+#ifdef __SIZEOF_INT128__
+SG_DEFINE_FTOI(sg_ftoi_i128, sg_i128, -(sg_i128) (((sg_u128) 1) << 127), (sg_i128) ((((sg_u128) 1) << 127) - 1))
+SG_DEFINE_FTOI(sg_ftoi_u128, sg_u128, 0, ~(sg_u128) 0)
+#endif
+#undef SG_DEFINE_FTOI
+
+"#;
+
+const C_KEYWORDS: &[&str] = &[
+    "auto", "break", "case", "char", "const", "continue", "default", "do", "double",
+    "else", "enum", "extern", "float", "for", "goto", "if", "inline", "int", "long",
+    "register", "restrict", "return", "short", "signed", "sizeof", "static", "struct",
+    "switch", "typedef", "union", "unsigned", "void", "volatile", "while", "_Bool",
+    "_Complex", "_Imaginary", "bool", "true", "false"
+];
+
+/// Lowers a parsed program into a single C99 translation unit.
+pub struct CSource<'tkns, 'bumps, 'defs> {
+    structs: &'defs [Struct],
+    functions: &'defs [Fun<'tkns, 'bumps, 'defs>],
+
+    /// C names of every struct, anonymous struct and helper emitted so far
+    type_names: HashMap<ExprType, String>,
+    pending_types: HashSet<ExprType>,
+    type_defs: String,
+    anonymous_count: usize,
+    emitted_helpers: HashSet<&'static str>,
+
+    /// Sugar variable names mapped to their C names, innermost scope last
+    scopes: Vec<HashMap<String, String>>,
+    variable_count: usize,
+    /// The loops around the statement being lowered, innermost last
+    loops: Vec<LoopTarget>,
+}
+
+/// A loop `break` and `continue` can leave.  C can only leave the innermost loop, so jumps 
+/// out of an outer, labeled loop go to the C labels placed at the end of its body and after it.
+struct LoopTarget {
+    label: Option<String>,
+    continue_label: String,
+    break_label: String,
+    /// The C variable a `break` with a value assigns to
+    assign: Option<String>
+}
+
+impl<'tkns, 'bumps, 'defs> CSource<'tkns, 'bumps, 'defs> {
+    const INDENT: &'static str = "    ";
+
+    pub fn new(
+        structs: &'defs [Struct],
+        functions: &'defs [Fun<'tkns, 'bumps, 'defs>]
+    ) -> Self {
+        CSource {
+            structs,
+            functions,
+            type_names: HashMap::new(),
+            pending_types: HashSet::new(),
+            type_defs: String::new(),
+            anonymous_count: 0,
+            emitted_helpers: HashSet::new(),
+            scopes: vec![],
+            variable_count: 0,
+            loops: vec![],
+        }
+    }
+
+    pub fn lower_program(mut self) -> Result<String, CompilerError> {
+        let Some(main_fun) = self.functions.iter().find(|e| e.name == "main") else {
+            return Err(CompilerError::NoEntryPoint);
+        };
+
+        if !main_fun.left_args.is_empty() || !main_fun.right_args.is_empty() {
+            return Err(CompilerError::InvalidEntryPoint {
+                reason: "a main function with arguments is not supported"
+            });
+        }
+
+        if main_fun.return_type != ExprType::Void {
+            return Err(CompilerError::InvalidEntryPoint {
+                reason: "a main function with return type is not supported"
+            });
+        }
+
+        // the instances of generic structs are only defined once something uses them
+        for custom_struct in self.structs.iter().filter(|custom_struct| custom_struct.type_params.is_empty()) {
+            self.c_type(&ExprType::Custom { ident: custom_struct.name.clone(), type_args: vec![] }, custom_struct.line)
+                .map_err(|error| error.in_file(&custom_struct.file_name))?;
+        }
+
+        let mut prototypes = String::new();
+        let mut definitions = String::new();
+        for fun in self.functions {
+            let prototype = self.lower_prototype(fun).map_err(|error| error.in_file(&fun.file_name))?;
+            writeln!(prototypes, "{prototype};").unwrap();
+
+            definitions += &self.lower_function(fun).map_err(|error| error.in_file(&fun.file_name))?;
+        }
+
+        let mut output = String::from(PRELUDE);
+        output += &self.type_defs;
+        output += &prototypes;
+        output += "\n";
+        output += &definitions;
+        output += "int main(void) {\n";
+        output += "    fn_main();\n";
+        output += "    return 0;\n";
+        output += "}\n";
+
+        return Ok(output);
+    }
+
+    fn lower_prototype(&mut self, fun: &Fun<'tkns, 'bumps, 'defs>) -> Result<String, CompilerError> {
+        let return_type = self.c_type(&fun.return_type, fun.line)?;
+        let mut params = vec![];
+        for param in fun.left_args.iter().chain(fun.right_args) {
+            params.push(self.c_type(&param.param_type, param.tkn.line_number)?);
+        }
+
+        let params = if params.is_empty() {
+            String::from("void")
+        } else {
+            params.join(", ")
+        };
+
+        return Ok(format!("static {return_type} {}({params})", function_identifier(&fun.name)));
+    }
+
+    fn lower_function(&mut self, fun: &Fun<'tkns, 'bumps, 'defs>) -> Result<String, CompilerError> {
+        self.variable_count = 0;
+        self.scopes.push(HashMap::new());
+
+        let return_type = self.c_type(&fun.return_type, fun.line)?;
+        let mut params = vec![];
+        for param in fun.left_args.iter().chain(fun.right_args) {
+            let param_type = self.c_type(&param.param_type, param.tkn.line_number)?;
+            let name = self.declare_variable(param.param_name.as_ref().unwrap());
+            params.push(format!("{param_type} {name}"));
+        }
+        let params = if params.is_empty() {
+            String::from("void")
+        } else {
+            params.join(", ")
+        };
+        let signature = format!("static {return_type} {}({params})", function_identifier(&fun.name));
+
+        let mut output = format!("{signature} {{\n");
+        let body = self.lower_statements(&fun.body, 1, &mut output);
+        self.scopes.pop();
+        body?;
+        output += "}\n\n";
+
+        return Ok(output);
+    }
+
+    fn lower_statements(
+        &mut self,
+        stmts: &[&'bumps StmtData<'bumps, 'defs>],
+        depth: usize,
+        output: &mut String
+    ) -> Result<(), CompilerError> {
+        for stmt in stmts {
+            self.lower_statement(stmt, depth, output)?;
+        }
+        return Ok(());
+    }
+
+    fn lower_block(
+        &mut self,
+        stmts: &[&'bumps StmtData<'bumps, 'defs>],
+        depth: usize,
+        output: &mut String
+    ) -> Result<(), CompilerError> {
+        self.scopes.push(HashMap::new());
+        let block = self.lower_statements(stmts, depth + 1, output);
+        self.scopes.pop();
+        block?;
+        *output += &Self::INDENT.repeat(depth);
+        *output += "}";
+        return Ok(());
+    }
+
+    /// Like [`Self::lower_block`], but for the body of `target`.  A labeled loop gets its 
+    /// `continue` label at the end of the body and its `break` label right after the `}`.
+    fn lower_loop_body(
+        &mut self,
+        target: LoopTarget,
+        stmts: &[&'bumps StmtData<'bumps, 'defs>],
+        depth: usize,
+        output: &mut String
+    ) -> Result<(), CompilerError> {
+        let indent = Self::INDENT.repeat(depth);
+        let jump_labels = target.label.is_some().then(|| (target.continue_label.clone(), target.break_label.clone()));
+
+        self.loops.push(target);
+        self.scopes.push(HashMap::new());
+        let block = self.lower_statements(stmts, depth + 1, output);
+        self.scopes.pop();
+        self.loops.pop();
+        block?;
+
+        if let Some((continue_label, _)) = &jump_labels {
+            writeln!(output, "{indent}{}{continue_label}: ;", Self::INDENT).unwrap();
+        }
+        *output += &indent;
+        *output += "}";
+        if let Some((_, break_label)) = &jump_labels {
+            write!(output, "\n{indent}{break_label}: ;").unwrap();
+        }
+        return Ok(());
+    }
+
+    fn lower_statement(
+        &mut self,
+        StmtData { line, stmt }: &StmtData<'bumps, 'defs>,
+        depth: usize,
+        output: &mut String
+    ) -> Result<(), CompilerError> {
+        let line = *line;
+        let indent = Self::INDENT.repeat(depth);
+
+        match stmt {
+            Stmt::Compound(stmts) => {
+                *output += &indent;
+                *output += "{\n";
+                self.lower_block(stmts, depth, output)?;
+                *output += "\n";
+            },
+            Stmt::While { label, cond, body } => {
+                let cond = self.lower_expression(cond)?;
+                let target = self.loop_target(label, None);
+                writeln!(output, "{indent}while ({cond}) {{").unwrap();
+                self.lower_loop_body(target, body, depth, output)?;
+                *output += "\n";
+            },
+            Stmt::Loop { label, body, assign } => {
+                let assign = match assign {
+                    Some((name, _)) => Some(self.lookup_variable(name)
+                        .ok_or_else(|| CompilerError::UnknownIdentifier { line, name: name.clone() })?),
+                    None => None
+                };
+                let target = self.loop_target(label, assign);
+                writeln!(output, "{indent}for (;;) {{").unwrap();
+                self.lower_loop_body(target, body, depth, output)?;
+                *output += "\n";
+            },
+            Stmt::For { label, binder, iter, body } => {
+                let ExprType::Range { exclusive_start, inclusive_end, expr_type: element_type } = iter.expr_type.clone_inner() else {
+                    return Err(CompilerError::Unsupported { line, feature: String::from("arrays") });
+                };
+                let ExprData::BinaryOp(_, start, end) = iter.expr_data else {
+                    return Err(CompilerError::Unsupported { line, feature: String::from("ranges outside of for loops") });
+                };
+                let c_type = self.c_type(&element_type, line)?;
+                let start = self.lower_expression(start)?;
+                let end = self.lower_expression(end)?;
+
+                // the counter only steps while it is below the end, so it never overflows, 
+                // even for inclusive ranges ending at the largest value of their type
+                let counter = self.declare_temporary("counter");
+                let last = self.declare_temporary("end");
+                let more = self.declare_temporary("more");
+                let check = if inclusive_end { String::from("true") } else { format!("{counter} < {last}") };
+                let init = match (exclusive_start, inclusive_end) {
+                    (false, false) => format!("{counter} < {last}"),
+                    (false, true) => format!("{counter} <= {last}"),
+                    (true, _) => format!("{counter} < {last} && (++{counter}, {check})"),
+                };
+                let step = if inclusive_end {
+                    format!("{counter} != {last} && (++{counter}, true)")
+                } else {
+                    format!("(++{counter}, {check})")
+                };
+
+                writeln!(output, "{indent}{{").unwrap();
+                writeln!(output, "{indent}{}{c_type} {counter} = {start};", Self::INDENT).unwrap();
+                writeln!(output, "{indent}{}{c_type} {last} = {end};", Self::INDENT).unwrap();
+                writeln!(output, "{indent}{}for (bool {more} = {init}; {more}; {more} = {step}) {{", Self::INDENT).unwrap();
+
+                self.scopes.push(HashMap::new());
+                if let Some((name, _)) = binder {
+                    let name = self.declare_variable(name);
+                    // every iteration gets its own binder, so mutating it leaves the counter alone
+                    writeln!(output, "{indent}{0}{0}{c_type} {name} = {counter};", Self::INDENT).unwrap();
+                }
+                let target = self.loop_target(label, None);
+                let block = self.lower_loop_body(target, body, depth + 1, output);
+                self.scopes.pop();
+                block?;
+
+                write!(output, "\n{indent}}}\n").unwrap();
+            },
+            Stmt::Conditional { conds, bodies } => {
+                *output += &indent;
+                for (i, (cond, body)) in conds.iter().zip(bodies).enumerate() {
+                    let cond = self.lower_expression(cond)?;
+                    if i > 0 {
+                        *output += " else ";
+                    }
+                    writeln!(output, "if ({cond}) {{").unwrap();
+                    self.lower_block(body, depth, output)?;
+                }
+                if bodies.len() > conds.len() {
+                    *output += " else {\n";
+                    self.lower_block(bodies.last().unwrap(), depth, output)?;
+                }
+                *output += "\n";
+            },
+            Stmt::Match { scrutinee, arms } => {
+                let expr_type = scrutinee.expr_type.clone_inner();
+                let c_type = self.c_type(&expr_type, line)?;
+                let value = self.lower_expression(scrutinee)?;
+
+                // the arms bind parts of a copy, so the bodies can change the scrutinee without
+                // changing what the arms bound
+                let scrutinee = self.declare_temporary("match");
+                writeln!(output, "{indent}{{").unwrap();
+                writeln!(output, "{indent}{}{c_type} {scrutinee} = {value};", Self::INDENT).unwrap();
+                write!(output, "{indent}{}", Self::INDENT).unwrap();
+                for (i, MatchArm { pattern, guard, body }) in arms.iter().enumerate() {
+                    if i > 0 {
+                        *output += " else ";
+                    }
+
+                    self.scopes.push(HashMap::new());
+                    let block = self.lower_match_arm(pattern, guard, &scrutinee, &expr_type, line)
+                        .and_then(|cond| {
+                            match cond {
+                                Some(cond) => writeln!(output, "if ({cond}) {{").unwrap(),
+                                None => writeln!(output, "{{").unwrap(),
+                            }
+                            self.lower_block(body, depth + 1, output)
+                        });
+                    self.scopes.pop();
+                    block?;
+                }
+                write!(output, "\n{indent}}}\n").unwrap();
+            },
+            Stmt::Return(None, _) => {
+                writeln!(output, "{indent}return;").unwrap();
+            },
+            Stmt::Return(Some(expr), _) => {
+                let expr = self.lower_expression(expr)?;
+                writeln!(output, "{indent}return {expr};").unwrap();
+            },
+            Stmt::Break { label, value } => {
+                let target = self.jump_target(label);
+                if let Some(value) = value {
+                    let value = self.lower_expression(value)?;
+                    let assign = self.loops[target].assign.as_ref()
+                        .expect("only loops whose value is assigned are left with a value");
+                    writeln!(output, "{indent}{assign} = {value};").unwrap();
+                }
+                if target + 1 == self.loops.len() {
+                    writeln!(output, "{indent}break;").unwrap();
+                } else {
+                    writeln!(output, "{indent}goto {};", self.loops[target].break_label).unwrap();
+                }
+            },
+            Stmt::Continue { label } => {
+                let target = self.jump_target(label);
+                if target + 1 == self.loops.len() {
+                    writeln!(output, "{indent}continue;").unwrap();
+                } else {
+                    writeln!(output, "{indent}goto {};", self.loops[target].continue_label).unwrap();
+                }
+            },
+            Stmt::Declare(name, _, expr_type) => {
+                let expr_type = expr_type.borrow().clone();
+                if matches!(expr_type, ExprType::Void | ExprType::Never) {
+                    return Err(CompilerError::Unsupported {
+                        line,
+                        feature: format!("declaring a variable of type {expr_type}")
+                    });
+                }
+                let c_type = self.c_type(&expr_type, line)?;
+                let name = self.declare_variable(name);
+                // the interpreter zeroes every allocation, so declarations are zeroed too
+                writeln!(output, "{indent}{c_type} {name} = {{0}};").unwrap();
+            },
+            Stmt::Assign { variable, assign } => {
+                let variable = self.lower_expression(variable)?;
+                let assign = self.lower_expression(assign)?;
+                writeln!(output, "{indent}{variable} = {assign};").unwrap();
+            },
+            Stmt::Expr(expr) => {
+                let expr = self.lower_expression(expr)?;
+                writeln!(output, "{indent}{expr};").unwrap();
+            },
+        }
+
+        return Ok(());
+    }
+
+    pub fn lower_expression(&mut self, expr: &Expr<'bumps, 'defs>) -> Result<String, CompilerError> {
+        let expr_type = expr.expr_type.clone_inner();
+        return self.lower_expression_data(expr.expr_data, &expr_type, expr.line);
+    }
+
+    fn lower_expression_data(
+        &mut self,
+        expr_data: &ExprData<'bumps, 'defs>,
+        expr_type: &ExprType,
+        line: usize
+    ) -> Result<String, CompilerError> {
+        match expr_data {
+            ExprData::Identifier(ident, _) => {
+                return self.lookup_variable(ident)
+                    .ok_or_else(|| CompilerError::UnknownIdentifier { line, name: ident.clone() });
+            },
+            ExprData::Literal(literal) => return self.lower_literal(literal, expr_type, line),
+            ExprData::Custom { fields } => {
+                let ExprType::Custom { ident, type_args } = expr_type else {
+                    return Err(CompilerError::UnknownType { line, expr_type: expr_type.clone() });
+                };
+                let c_type = self.c_type(expr_type, line)?;
+                let custom_struct = self.structs.iter()
+                    .find(|custom_struct| custom_struct.name == *ident)
+                    .ok_or_else(|| CompilerError::UnknownType { line, expr_type: expr_type.clone() })?;
+
+                let mut initializers = vec![];
+                for field in &custom_struct.fields {
+                    let Some(field_data) = fields.get(field.field_name.as_str()) else {
+                        continue;
+                    };
+                    let value = self.lower_expression_data(field_data, &custom_struct.field_type(field, type_args), line)?;
+                    initializers.push(format!(".{} = {value}", field_identifier(&field.field_name)));
+                }
+
+                return Ok(compound_literal(&c_type, initializers));
+            },
+            ExprData::AnonymousCustom { fields: data_fields } => {
+                let ExprType::AnonymousCustom { fields: type_fields } = expr_type else {
+                    return Err(CompilerError::UnknownType { line, expr_type: expr_type.clone() });
+                };
+                let c_type = self.c_type(expr_type, line)?;
+
+                let mut initializers = vec![];
+                for ((field_name, field_data), (_, field_type)) in data_fields.iter().zip(type_fields.iter()) {
+                    let value = self.lower_expression_data(field_data, field_type, line)?;
+                    initializers.push(format!(".{} = {value}", field_identifier(field_name)));
+                }
+
+                return Ok(compound_literal(&c_type, initializers));
+            },
+            ExprData::EnumVariant { tag, fields } => {
+                let ExprType::Enum { variants, .. } = expr_type else {
+                    return Err(CompilerError::UnknownType { line, expr_type: expr_type.clone() });
+                };
+                let c_type = self.c_type(expr_type, line)?;
+                let variant = &variants[*tag];
+
+                let mut initializers = vec![format!(".tag = {tag}")];
+                if !variant.fields.is_empty() {
+                    let mut payload = vec![];
+                    for ((field_name, field_data), (_, field_type)) in fields.iter().zip(variant.fields.iter()) {
+                        let value = self.lower_expression_data(field_data, field_type, line)?;
+                        payload.push(format!(".{} = {value}", variant_field_identifier(field_name)));
+                    }
+                    initializers.push(format!(
+                        ".payload.{} = {{ {} }}", 
+                        variant_identifier(variant), 
+                        payload.join(", ")
+                    ));
+                }
+
+                return Ok(compound_literal(&c_type, initializers));
+            },
+            ExprData::CustomField { data, field } => {
+                let data = self.lower_expression(data)?;
+                return Ok(format!("({data}).{}", field_identifier(&field.field_name)));
+            },
+            ExprData::AnonymousCustomField { data, field_name } => {
+                let data = self.lower_expression(data)?;
+                return Ok(format!("({data}).{}", field_identifier(field_name)));
+            },
+            ExprData::Function { name, left_args, right_args } => {
+                if let Some(built_in) = BuiltInFunction::from_name(name) &&
+                    built_in.match_args(left_args, right_args)
+                {
+                    return self.lower_built_in(built_in, right_args, line);
+                }
+
+                let mut args = vec![];
+                for arg in left_args.iter().chain(right_args) {
+                    args.push(self.lower_expression(arg)?);
+                }
+                // defaults cannot refer to variables, so the ones the call leaves out are written in here
+                let params = self.functions.iter()
+                    .find(|fun| fun.name == *name)
+                    .map_or(&[][..], |fun| fun.right_args);
+                for param in params.iter().skip(right_args.len()) {
+                    let default = param.param_default.expect("arguments left out of a call should have defaults");
+                    args.push(self.lower_expression_data(default, &param.param_type, line)?);
+                }
+                return Ok(format!("{}({})", function_identifier(name), args.join(", ")));
+            },
+            ExprData::BinaryOp(binary_operator, left, right) => {
+                return self.lower_bin_op(*binary_operator, left, right, expr_type, line);
+            },
+            ExprData::UnaryOp(unary_operator, expr) => {
+                let c_type = self.c_type(expr_type, line)?;
+                let expr = self.lower_expression(expr)?;
+                return match unary_operator {
+                    UnOp::Plus | UnOp::PlusFloat => Ok(format!("({expr})")),
+                    UnOp::Minus => Ok(format!("(({c_type}) -({expr}))")),
+                    UnOp::MinusFloat => Ok(format!("(-({expr}))")),
+                    UnOp::LogicNot => Ok(format!("(!({expr}))")),
+                    UnOp::BitwiseNegate => Ok(format!("(({c_type}) ~({expr}))")),
+                    UnOp::Borrow | UnOp::BorrowInteriorMutable | UnOp::BorrowMutable | UnOp::Deref => Err(
+                        CompilerError::Unsupported { line, feature: String::from("borrowing") }
+                    ),
+                };
+            },
+            ExprData::AmbiguousGroup(expr) => {
+                let expr = self.lower_expression(expr)?;
+                return Ok(format!("({expr})"));
+            },
+            ExprData::Conditional { .. } => return Err(CompilerError::Unsupported {
+                line, feature: String::from("conditional expressions")
+            }),
+            ExprData::Match { scrutinee, arms } => {
+                // without a temporary, every arm reads the scrutinee again
+                if !expr::is_place(scrutinee.expr_data) {
+                    return Err(CompilerError::Unsupported {
+                        line, feature: String::from("match expressions on values other than variables and fields")
+                    });
+                }
+                let scrutinee_type = scrutinee.expr_type.clone_inner();
+                let scrutinee = self.lower_expression(scrutinee)?;
+
+                let mut lowered_arms = vec![];
+                for MatchArm { pattern, guard, body } in arms {
+                    self.scopes.push(HashMap::new());
+                    let arm = self.lower_match_arm(pattern, guard, &scrutinee, &scrutinee_type, line)
+                        .and_then(|cond| Ok((cond, self.lower_expression(body)?)));
+                    self.scopes.pop();
+                    let (cond, mut lowered_body) = arm?;
+
+                    // arms that never return, like a panic, still need a value of the match's type
+                    if body.expr_type.clone_inner() == ExprType::Never &&
+                        !matches!(expr_type, ExprType::Void | ExprType::Never)
+                    {
+                        let c_type = self.c_type(expr_type, line)?;
+                        lowered_body = format!("({lowered_body}, {})", compound_literal(&c_type, vec![]));
+                    }
+                    lowered_arms.push((cond, lowered_body));
+                }
+
+                // the arms cover every value, so the last one needs no condition
+                let (_, mut value) = lowered_arms.pop().expect("a match has at least one arm");
+                for (cond, body) in lowered_arms.into_iter().rev() {
+                    value = match cond {
+                        Some(cond) => format!("(({cond}) ? {body} : {value})"),
+                        None => body
+                    };
+                }
+                return Ok(value);
+            },
+            ExprData::Array(_) | ExprData::Index { .. } => return Err(CompilerError::Unsupported {
+                line, feature: String::from("arrays")
+            }),
+            ExprData::Tuple(_) => return Err(CompilerError::Unsupported {
+                line, feature: String::from("tuples")
+            }),
+            ExprData::Closure { .. } | ExprData::Call { .. } | ExprData::Partial { .. } => return Err(CompilerError::Unsupported {
+                line, feature: String::from("function values")
+            }),
+        }
+    }
+
+    fn lower_literal(
+        &mut self,
+        literal: &Lit,
+        expr_type: &ExprType,
+        line: usize
+    ) -> Result<String, CompilerError> {
+        return Ok(match literal {
+            Lit::IntegerLiteral(value) => {
+                let c_type = self.c_type(expr_type, line)?;
+                if matches!(expr_type, ExprType::I128 | ExprType::U128) {
+                    let value = *value as u128;
+                    format!(
+                        "(({c_type}) (((sg_u128) {}ULL << 64) | (sg_u128) {}ULL))",
+                        (value >> 64) as u64,
+                        value as u64
+                    )
+                } else {
+                    // integers are stored as i128 by the parser, truncating matches `as` in the interpreter
+                    format!("(({c_type}) {}ULL)", *value as u64)
+                }
+            },
+            Lit::FloatLiteral(value) => {
+                let c_type = self.c_type(expr_type, line)?;
+                format!("(({c_type}) {value:?})")
+            },
+            Lit::CharLiteral(value) => format!("((uint32_t) {}u)", *value as u32),
+            Lit::StringLiteral(value) => {
+                let value = unicode_escape::decode(value)
+                    .map_err(|_| CompilerError::InvalidString { line, string: value.clone() })?;
+                string_literal(&value)
+            },
+            Lit::BooleanLiteral(value) => String::from(if *value { "true" } else { "false" }),
+        });
+    }
+
+    /// The condition under which `scrutinee` matches the arm, `None` if it always does. The variables
+    /// the pattern binds are added to the innermost scope as the parts of `scrutinee` they name.
+    fn lower_match_arm(
+        &mut self,
+        pattern: &MatchPattern,
+        guard: &Option<Expr<'bumps, 'defs>>,
+        scrutinee: &str,
+        expr_type: &ExprType,
+        line: usize
+    ) -> Result<Option<String>, CompilerError> {
+        let mut conds = vec![];
+        self.lower_match_pattern(pattern, scrutinee, expr_type, line, &mut conds)?;
+        if let Some(guard) = guard {
+            conds.push(format!("({})", self.lower_expression(guard)?));
+        }
+
+        if conds.is_empty() {
+            return Ok(None);
+        }
+        return Ok(Some(conds.join(" && ")));
+    }
+
+    fn lower_match_pattern(
+        &mut self,
+        pattern: &MatchPattern,
+        path: &str,
+        expr_type: &ExprType,
+        line: usize,
+        conds: &mut Vec<String>
+    ) -> Result<(), CompilerError> {
+        match pattern {
+            MatchPattern::Discard => (),
+            MatchPattern::Bind(name) => {
+                self.scopes.last_mut().unwrap().insert(name.clone(), path.to_string());
+            },
+            MatchPattern::Literal(Lit::BooleanLiteral(true)) => conds.push(path.to_string()),
+            MatchPattern::Literal(Lit::BooleanLiteral(false)) => conds.push(format!("!{path}")),
+            MatchPattern::Literal(literal @ Lit::StringLiteral(_)) => {
+                if self.emitted_helpers.insert("sg_str_equals") {
+                    write!(self.type_defs,
+                        "static bool sg_str_equals(sg_str left, sg_str right) {{\n\
+                        {indent}if (left.len != right.len) {{\n\
+                        {indent}{indent}return false;\n\
+                        {indent}}}\n\
+                        {indent}for (size_t i = 0; i < left.len; i++) {{\n\
+                        {indent}{indent}if (left.ptr[i] != right.ptr[i]) {{\n\
+                        {indent}{indent}{indent}return false;\n\
+                        {indent}{indent}}}\n\
+                        {indent}}}\n\
+                        {indent}return true;\n\
+                        }}\n\n",
+                        indent = Self::INDENT,
+                    ).unwrap();
+                }
+                let literal = self.lower_literal(literal, expr_type, line)?;
+                conds.push(format!("sg_str_equals({path}, {literal})"));
+            },
+            MatchPattern::Literal(literal) => {
+                let literal = self.lower_literal(literal, expr_type, line)?;
+                conds.push(format!("{path} == {literal}"));
+            },
+            MatchPattern::Range { start, end } => {
+                let (start, end) = if *expr_type == ExprType::Char {
+                    (format!("((uint32_t) {start}u)"), format!("((uint32_t) {end}u)"))
+                } else {
+                    (
+                        self.lower_literal(&Lit::IntegerLiteral(*start), expr_type, line)?,
+                        self.lower_literal(&Lit::IntegerLiteral(*end), expr_type, line)?
+                    )
+                };
+                conds.push(format!("{path} >= {start} && {path} <= {end}"));
+            },
+            MatchPattern::Group { .. } => return Err(CompilerError::Unsupported {
+                line, feature: String::from("group patterns")
+            }),
+            MatchPattern::Fields(fields) => {
+                let field_types: Vec<(String, ExprType)> = match expr_type {
+                    ExprType::Custom { ident, type_args } => {
+                        let custom_struct = self.structs.iter()
+                            .find(|custom_struct| custom_struct.name == *ident)
+                            .ok_or_else(|| CompilerError::UnknownType { line, expr_type: expr_type.clone() })?;
+                        custom_struct.fields.iter()
+                            .map(|field| (field.field_name.clone(), custom_struct.field_type(field, type_args)))
+                            .collect()
+                    },
+                    ExprType::AnonymousCustom { fields } => fields.to_vec(),
+                    _ => return Err(CompilerError::UnknownType { line, expr_type: expr_type.clone() }),
+                };
+
+                for (field_name, pattern) in fields {
+                    let (_, field_type) = field_types.iter()
+                        .find(|(name, _)| name == field_name)
+                        .expect("the parser checks that field patterns name existing fields");
+                    let field_path = format!("{path}.{}", field_identifier(field_name));
+                    self.lower_match_pattern(pattern, &field_path, field_type, line, conds)?;
+                }
+            },
+            MatchPattern::Variant { tag, fields } => {
+                let ExprType::Enum { variants, .. } = expr_type else {
+                    return Err(CompilerError::UnknownType { line, expr_type: expr_type.clone() });
+                };
+                let variant = &variants[*tag];
+                conds.push(format!("{path}.tag == {tag}"));
+                for (field_name, pattern) in fields {
+                    let (_, field_type) = variant.fields.iter()
+                        .find(|(name, _)| name == field_name)
+                        .expect("the parser checks that variant patterns name existing fields");
+                    let field_path = format!(
+                        "{path}.payload.{}.{}",
+                        variant_identifier(variant),
+                        variant_field_identifier(field_name)
+                    );
+                    self.lower_match_pattern(pattern, &field_path, field_type, line, conds)?;
+                }
+            },
+        }
+        return Ok(());
+    }
+
+    fn lower_built_in(
+        &mut self,
+        built_in: BuiltInFunction,
+        right_args: &[Expr<'bumps, 'defs>],
+        line: usize
+    ) -> Result<String, CompilerError> {
+        match built_in {
+            BuiltInFunction::print_string => {
+                let string = self.lower_expression(&right_args[0])?;
+                return Ok(format!("sg_print_string({string})"));
+            },
+            BuiltInFunction::print_i32 => {
+                let value = self.lower_expression(&right_args[0])?;
+                return Ok(format!("sg_print_i32({value})"));
+            },
+            BuiltInFunction::read_char | BuiltInFunction::read_i32 => {
+                let ExprType::Function { return_type, .. } = built_in.get_type() else {
+                    unreachable!("BuiltInFunction should be of type Function");
+                };
+                let c_type = self.c_type(&return_type, line)?;
+                let (helper, raw, value_type) = match built_in {
+                    BuiltInFunction::read_char => ("sg_read_char", "sg_read_char_raw", "uint32_t"),
+                    _ => ("sg_read_i32", "sg_read_i32_raw", "int32_t"),
+                };
+
+                if self.emitted_helpers.insert(helper) {
+                    write!(self.type_defs,
+                        "static {c_type} {helper}(void) {{\n\
+                        {indent}{c_type} read = {{0}};\n\
+                        {indent}{value_type} value = 0;\n\
+                        {indent}read.{success} = {raw}(&value);\n\
+                        {indent}read.{value} = value;\n\
+                        {indent}return read;\n\
+                        }}\n\n",
+                        indent = Self::INDENT,
+                        success = field_identifier("success"),
+                        value = field_identifier("value"),
+                    ).unwrap();
+                }
+
+                return Ok(format!("{helper}()"));
+            },
+            BuiltInFunction::panic => {
+                let message = self.lower_expression(&right_args[0])?;
+                return Ok(format!("sg_panic({line}, {message})"));
+            },
+        }
+    }
+
+    fn lower_bin_op(
+        &mut self,
+        binary_operator: BinOp,
+        left: &Expr<'bumps, 'defs>,
+        right: &Expr<'bumps, 'defs>,
+        expr_type: &ExprType,
+        line: usize
+    ) -> Result<String, CompilerError> {
+        let c_type = self.c_type(expr_type, line)?;
+        let from_float = matches!(left.expr_type.clone_inner(), ExprType::F32 | ExprType::F64 | ExprType::AmbiguousFloat);
+        let left = self.lower_expression(left)?;
+        if binary_operator == BinOp::Cast {
+            // a C cast of an out of range float is undefined, so it saturates like the interpreter
+            return Ok(match expr_type {
+                ExprType::Bool | ExprType::F32 | ExprType::F64 => format!("(({c_type}) ({left}))"),
+                _ if from_float => format!("sg_ftoi_{}({left})", integer_suffix(expr_type, line)?),
+                _ => format!("(({c_type}) ({left}))"),
+            });
+        }
+        let right = self.lower_expression(right)?;
+
+        // arithmetic is cast back to the result type so narrow integers wrap like they do in the interpreter
+        let wrapped = |op: &str| format!("(({c_type}) ({left} {op} {right}))");
+        let compared = |op: &str| format!("({left} {op} {right})");
+
+        return Ok(match binary_operator {
+            BinOp::Plus | BinOp::PlusFloat => wrapped("+"),
+            BinOp::Minus | BinOp::MinusFloat => wrapped("-"),
+            BinOp::Multiply | BinOp::MultiplyFloat => wrapped("*"),
+            BinOp::Divide | BinOp::DivideFloat => wrapped("/"),
+            BinOp::Modulo => wrapped("%"),
+            BinOp::BitwiseAnd => wrapped("&"),
+            BinOp::BitwiseOr => wrapped("|"),
+            BinOp::BitwiseXor => wrapped("^"),
+            BinOp::BitwiseShiftLeft => wrapped("<<"),
+            BinOp::BitwiseShiftRight => wrapped(">>"),
+            BinOp::Exponent => format!("sg_ipow_{}({left}, {right}, {line})", integer_suffix(expr_type, line)?),
+            BinOp::ExponentFloat => match expr_type {
+                ExprType::F64 => format!("pow({left}, {right})"),
+                _ => format!("powf({left}, {right})"),
+            },
+            BinOp::ModuloFloat => match expr_type {
+                ExprType::F64 => format!("fmod({left}, {right})"),
+                _ => format!("fmodf({left}, {right})"),
+            },
+            BinOp::Equals => compared("=="),
+            BinOp::NotEquals => compared("!="),
+            BinOp::LessThan => compared("<"),
+            BinOp::LessThanEqualTo => compared("<="),
+            BinOp::GreaterThan => compared(">"),
+            BinOp::GreaterThanEqualTo => compared(">="),
+            BinOp::LogicAnd => compared("&&"),
+            BinOp::LogicOr => compared("||"),
+            BinOp::Range | BinOp::RangeEquals | BinOp::BangRange | BinOp::BangRangeEquals => {
+                return Err(CompilerError::Unsupported { line, feature: String::from("ranges") });
+            },
+            BinOp::Concat => {
+                return Err(CompilerError::Unsupported { line, feature: String::from("concatenation") });
+            },
+            BinOp::Cast => unreachable!("casts are lowered before their type operand"),
+        });
+    }
+
+    fn c_type(&mut self, expr_type: &ExprType, line: usize) -> Result<String, CompilerError> {
+        let primitive = match expr_type {
+            ExprType::I8 => "int8_t",
+            ExprType::I16 => "int16_t",
+            ExprType::I32 => "int32_t",
+            ExprType::I64 => "int64_t",
+            ExprType::I128 => "sg_i128",
+            ExprType::ISize => "intptr_t",
+            ExprType::U8 => "uint8_t",
+            ExprType::U16 => "uint16_t",
+            ExprType::U32 => "uint32_t",
+            ExprType::U64 => "uint64_t",
+            ExprType::U128 => "sg_u128",
+            ExprType::USize => "size_t",
+            // ambiguous types never got pinned down, so they get the width `size_of` gives them
+            ExprType::AmbiguousNegInteger | ExprType::AmbiguousPosInteger => "intptr_t",
+            ExprType::F32 | ExprType::AmbiguousFloat => "float",
+            ExprType::F64 => "double",
+            ExprType::Char => "uint32_t",
+            ExprType::Bool => "bool",
+            ExprType::StringLiteral => "sg_str",
+            ExprType::Void | ExprType::Never => "void",
+            ExprType::Custom { .. } | ExprType::AnonymousCustom { .. } | ExprType::Enum { .. } => "",
+            ExprType::Array { .. } => return Err(CompilerError::Unsupported {
+                line, feature: String::from("arrays")
+            }),
+            ExprType::Range { .. } => return Err(CompilerError::Unsupported {
+                line, feature: String::from("ranges outside of for loops")
+            }),
+            ExprType::Tuple { .. } | ExprType::AmbiguousGroup { .. } => return Err(CompilerError::Unsupported {
+                line, feature: String::from("tuples")
+            }),
+            ExprType::Ref { .. } | ExprType::MutRef { .. } | ExprType::ImRef { .. } => return Err(CompilerError::Unsupported {
+                line, feature: String::from("references")
+            }),
+            ExprType::Function { .. } | ExprType::FunctionPass { .. } => return Err(CompilerError::Unsupported {
+                line, feature: String::from("function values")
+            }),
+            ExprType::AmbiguousType | ExprType::Generic { .. } | ExprType::DiscardSingle | ExprType::DiscardMany => return Err(
+                CompilerError::UnknownType { line, expr_type: expr_type.clone() }
+            ),
+        };
+
+        if !primitive.is_empty() {
+            return Ok(String::from(primitive));
+        }
+
+        if let Some(name) = self.type_names.get(expr_type) {
+            return Ok(name.clone());
+        }
+
+        if !self.pending_types.insert(expr_type.clone()) {
+            return Err(CompilerError::RecursiveType { line, expr_type: expr_type.clone() });
+        }
+
+        if let ExprType::Enum { ident, variants } = expr_type {
+            let name = format!("Sg_{}", escape_identifier(ident));
+            let tag_type = match enums::tag_size(variants.len()) {
+                1 => "uint8_t",
+                2 => "uint16_t",
+                _ => "uint32_t",
+            };
+
+            // unit variants have nothing to store, and C99 does not allow empty structs
+            let mut payload = String::new();
+            for variant in variants.iter().filter(|variant| !variant.fields.is_empty()) {
+                let mut members = String::new();
+                for (field_name, field_type) in variant.fields.iter() {
+                    let field_type = self.c_type(field_type, line)?;
+                    write!(members, "{field_type} {}; ", variant_field_identifier(field_name)).unwrap();
+                }
+                writeln!(payload, "{0}{0}struct {{ {members}}} {1};", Self::INDENT, variant_identifier(variant)).unwrap();
+            }
+            if payload.is_empty() {
+                writeln!(payload, "{0}{0}char unused;", Self::INDENT).unwrap();
+            }
+
+            write!(
+                self.type_defs, 
+                "typedef struct {name} {{\n{0}{tag_type} tag;\n{0}union {{\n{payload}{0}}} payload;\n}} {name};\n\n",
+                Self::INDENT
+            ).unwrap();
+            self.pending_types.remove(expr_type);
+            self.type_names.insert(expr_type.clone(), name.clone());
+
+            return Ok(name);
+        }
+
+        let (name, fields) = match expr_type {
+            ExprType::Custom { ident, type_args } => {
+                let custom_struct = self.structs.iter()
+                    .find(|custom_struct| custom_struct.name == *ident)
+                    .ok_or_else(|| CompilerError::UnknownType { line, expr_type: expr_type.clone() })?;
+                let fields = custom_struct.fields.iter()
+                    .map(|field| (field.field_name.clone(), custom_struct.field_type(field, type_args)))
+                    .collect::<Vec<_>>();
+                // every instance of a generic struct is its own C struct
+                (format!("Sg_{}", escape_identifier(&expr_type.to_string())), fields)
+            },
+            ExprType::AnonymousCustom { fields } => {
+                self.anonymous_count += 1;
+                (format!("sg_anonymous_{}", self.anonymous_count), fields.to_vec())
+            },
+            _ => unreachable!(),
+        };
+
+        let mut members = String::new();
+        for (field_name, field_type) in &fields {
+            let field_type = self.c_type(field_type, line)?;
+            writeln!(members, "{}{field_type} {};", Self::INDENT, field_identifier(field_name)).unwrap();
+        }
+        if fields.is_empty() {
+            // C99 does not allow empty structs
+            writeln!(members, "{}char unused;", Self::INDENT).unwrap();
+        }
+
+        write!(self.type_defs, "typedef struct {name} {{\n{members}}} {name};\n\n").unwrap();
+        self.pending_types.remove(expr_type);
+        self.type_names.insert(expr_type.clone(), name.clone());
+
+        return Ok(name);
+    }
+
+    fn declare_variable(&mut self, name: &str) -> String {
+        // sugar allows shadowing within a scope, so every declaration gets a fresh C name
+        let c_name = format!("v_{}_{}", escape_identifier(name), self.variable_count);
+        self.variable_count += 1;
+        self.scopes.last_mut().unwrap().insert(name.to_string(), c_name.clone());
+        return c_name;
+    }
+
+    /// A C variable no sugar variable can be named after.
+    fn declare_temporary(&mut self, hint: &str) -> String {
+        let c_name = format!("t_{hint}_{}", self.variable_count);
+        self.variable_count += 1;
+        return c_name;
+    }
+
+    fn lookup_variable(&self, name: &str) -> Option<String> {
+        return self.scopes.iter().rev().find_map(|scope| scope.get(name).cloned());
+    }
+
+    fn loop_target(&mut self, label: &Option<String>, assign: Option<String>) -> LoopTarget {
+        return LoopTarget {
+            label: label.clone(),
+            continue_label: self.declare_temporary("continue"),
+            break_label: self.declare_temporary("break"),
+            assign
+        };
+    }
+
+    /// The index in `loops` of the loop a `break` or `continue` with `label` leaves.
+    fn jump_target(&self, label: &Option<String>) -> usize {
+        let Some(label) = label else {
+            return self.loops.len() - 1;
+        };
+        return self.loops.iter()
+            .rposition(|target| target.label.as_ref() == Some(label))
+            .expect("the parser checks that labels name an enclosing loop");
+    }
+}
+
+fn integer_suffix(expr_type: &ExprType, line: usize) -> Result<&'static str, CompilerError> {
+    return Ok(match expr_type {
+        ExprType::I8 => "i8",
+        ExprType::I16 => "i16",
+        ExprType::I32 => "i32",
+        ExprType::I64 => "i64",
+        ExprType::I128 => "i128",
+        ExprType::ISize | ExprType::AmbiguousNegInteger | ExprType::AmbiguousPosInteger => "isize",
+        ExprType::U8 => "u8",
+        ExprType::U16 => "u16",
+        ExprType::U32 => "u32",
+        ExprType::U64 => "u64",
+        ExprType::U128 => "u128",
+        ExprType::USize => "usize",
+        _ => return Err(CompilerError::UnknownType { line, expr_type: expr_type.clone() }),
+    });
+}
+
+fn compound_literal(c_type: &str, initializers: Vec<String>) -> String {
+    if initializers.is_empty() {
+        return format!("(({c_type}) {{0}})");
+    }
+    return format!("(({c_type}) {{ {} }})", initializers.join(", "));
+}
+
+fn string_literal(value: &str) -> String {
+    let mut output = String::from("((sg_str) { \"");
+    for byte in value.bytes() {
+        match byte {
+            b'"' => output += "\\\"",
+            b'\\' => output += "\\\\",
+            // escaped so the C preprocessor never sees a trigraph
+            b'?' => output += "\\?",
+            b' '..=b'~' => output.push(byte as char),
+            _ => write!(output, "\\{byte:03o}").unwrap(),
+        }
+    }
+    write!(output, "\", {} }})", value.len()).unwrap();
+    return output;
+}
+
+fn escape_identifier(name: &str) -> String {
+    let mut output = String::new();
+    for chr in name.chars() {
+        if chr.is_ascii_alphanumeric() || chr == '_' {
+            output.push(chr);
+        } else {
+            write!(output, "_u{:x}_", chr as u32).unwrap();
+        }
+    }
+    return output;
+}
+
+fn function_identifier(name: &str) -> String {
+    return format!("fn_{}", escape_identifier(name));
+}
+
+fn variant_identifier(variant: &Variant) -> String {
+    return format!("v_{}", escape_identifier(&variant.variant_name));
+}
+
+/// Tuple variants name their fields `0`, `1`, ..., which are not valid C identifiers on their own.
+fn variant_field_identifier(name: &str) -> String {
+    return format!("f_{}", escape_identifier(name));
+}
+
+fn field_identifier(name: &str) -> String {
+    let name = escape_identifier(name);
+    if C_KEYWORDS.contains(&name.as_str()) {
+        return format!("{name}_");
+    }
+    return name;
+}
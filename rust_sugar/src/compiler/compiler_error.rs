@@ -0,0 +1,100 @@
+use crate::{parser::{expr::ExprType, parser_error::Sources}, term};
+
+#[derive(Debug)]
+pub enum CompilerError {
+    /// The program uses a construct the backend cannot lower yet
+    Unsupported { line: usize, feature: String },
+    UnknownIdentifier { line: usize, name: String },
+    UnknownType { line: usize, expr_type: ExprType },
+    RecursiveType { line: usize, expr_type: ExprType },
+    InvalidString { line: usize, string: String },
+    NoEntryPoint,
+    InvalidEntryPoint { reason: &'static str },
+    Io(std::io::Error),
+    CCompilerFailed { compiler: String, stderr: String },
+    LoadFailed { reason: String },
+    /// An error raised while lowering a function, tagged with the file the function is written in
+    InFile { file_name: String, error: Box<CompilerError> },
+}
+
+impl From<std::io::Error> for CompilerError {
+    fn from(error: std::io::Error) -> Self {
+        CompilerError::Io(error)
+    }
+}
+
+impl CompilerError {
+    pub fn in_file(self, file_name: &str) -> Self {
+        return CompilerError::InFile { file_name: file_name.to_string(), error: Box::new(self) };
+    }
+
+    pub fn write(&self, f: &mut impl std::io::Write, file_name: &str, sources: &Sources) -> std::io::Result<()> {
+        use CompilerError as CE;
+        let (line_number, message) = match self {
+            CE::InFile { file_name, error } => return error.write(f, file_name, sources),
+            CE::Unsupported { line, feature } => (
+                *line,
+                format!("{feature} is not supported by the C backend yet")
+            ),
+            CE::UnknownIdentifier { line, name } => (
+                *line,
+                format!("could not resolve `{name}` while lowering to C")
+            ),
+            CE::UnknownType { line, expr_type } => (
+                *line,
+                format!("type {expr_type:?} does not exist")
+            ),
+            CE::RecursiveType { line, expr_type } => (
+                *line,
+                format!("type {expr_type:?} contains itself and has no finite size")
+            ),
+            CE::InvalidString { line, string } => (
+                *line,
+                format!("\"{string}\" is not a valid string literal")
+            ),
+            CE::NoEntryPoint => return write!(f,
+                "{red}error:{clear} no entry point to the program was found\n\n",
+                red = term::HIGH_RED_ANSI,
+                clear = term::CLEAR_ANSI,
+            ),
+            CE::InvalidEntryPoint { reason } => return write!(f,
+                "{red}error:{clear} {reason}\n\n",
+                red = term::HIGH_RED_ANSI,
+                clear = term::CLEAR_ANSI,
+            ),
+            CE::Io(error) => return write!(f,
+                "{red}error:{clear} {error}\n\n",
+                red = term::HIGH_RED_ANSI,
+                clear = term::CLEAR_ANSI,
+            ),
+            CE::CCompilerFailed { compiler, stderr } => return write!(f,
+                "{red}error:{clear} {compiler} failed to compile the generated C source\n\
+                {stderr}\n\
+                ",
+                red = term::HIGH_RED_ANSI,
+                clear = term::CLEAR_ANSI,
+            ),
+            CE::LoadFailed { reason } => return write!(f,
+                "{red}error:{clear} could not load the compiled program: {reason}\n\n",
+                red = term::HIGH_RED_ANSI,
+                clear = term::CLEAR_ANSI,
+            ),
+        };
+
+        write!(f,
+            "{red}error:{clear} {message}\n\
+            {blue}{pad}-->{clear} {file_name}:{line_number}\n\
+            {blue}{pad} | {clear}\n\
+            {blue}{line_number} | {clear}{line_of_code}\n\
+            {blue}{pad}:::{clear}\n\
+            \n\
+            ",
+            pad = " ".repeat(line_number.to_string().len()),
+            line_of_code = sources.get_line(file_name, line_number).trim_end(),
+
+            red = term::HIGH_RED_ANSI,
+            blue = term::HIGH_BOLD_BLUE_ANSI,
+            clear = term::CLEAR_ANSI,
+        )
+    }
+}
@@ -0,0 +1,143 @@
+#[cfg(target_os = "linux")]
+use std::ffi::{CStr, CString};
+use std::io::Write;
+
+use crate::parser::{functions::Fun, structs::Struct};
+
+use super::{emit_c, run_c_compiler, CompilerError};
+
+/// Lowers the program to C, has the system C compiler link it as a shared object straight into
+/// an anonymous in-memory file, then loads that object into this process and calls the generated
+/// C `main`.  Nothing is written to disk.
+#[cfg(target_os = "linux")]
+pub fn run<'tkns, 'bumps, 'defs>(
+    structs: &'defs [Struct],
+    functions: &'defs [Fun<'tkns, 'bumps, 'defs>]
+) -> Result<(), CompilerError> {
+    let source = emit_c(structs, functions)?;
+
+    let image = MemoryFile::new()?;
+    run_c_compiler(&source, &[
+        String::from("-shared"),
+        String::from("-fPIC"),
+        String::from("-o"),
+        image.path_from_child(),
+        String::from("-lm")
+    ])?;
+
+    let library = Library::open(&image.path())?;
+    let entry_point = library.symbol("main")?;
+    let entry_point: extern "C" fn() -> libc::c_int = unsafe { std::mem::transmute(entry_point) };
+
+    std::io::stdout().flush()?;
+    entry_point();
+    // the program writes through C's stdio, which has its own buffer
+    unsafe { libc::fflush(std::ptr::null_mut()); }
+
+    return Ok(());
+}
+
+/// Without `memfd_create` and `/proc` to load the program from memory, it is built into an 
+/// executable in the temporary directory, which is run and then removed.  A program that panics 
+/// ends this process with the same status, like it does when it is loaded in.
+#[cfg(not(target_os = "linux"))]
+pub fn run<'tkns, 'bumps, 'defs>(
+    structs: &'defs [Struct],
+    functions: &'defs [Fun<'tkns, 'bumps, 'defs>]
+) -> Result<(), CompilerError> {
+    let source = emit_c(structs, functions)?;
+
+    let executable = std::env::temp_dir()
+        .join(format!("sugar-run-{}{}", std::process::id(), std::env::consts::EXE_SUFFIX));
+    run_c_compiler(&source, &[
+        String::from("-o"),
+        executable.to_string_lossy().into_owned(),
+        String::from("-lm")
+    ])?;
+
+    std::io::stdout().flush()?;
+    let status = std::process::Command::new(&executable).status();
+    let _ = std::fs::remove_file(&executable);
+    let status = status?;
+    if !status.success() {
+        std::process::exit(status.code().unwrap_or(101));
+    }
+
+    return Ok(());
+}
+
+/// An anonymous file living only in memory, created with `memfd_create`.
+#[cfg(target_os = "linux")]
+struct MemoryFile {
+    fd: libc::c_int
+}
+
+#[cfg(target_os = "linux")]
+impl MemoryFile {
+    fn new() -> Result<Self, CompilerError> {
+        let fd = unsafe { libc::memfd_create(c"sugar-jit".as_ptr(), 0) };
+        if fd < 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        return Ok(MemoryFile { fd });
+    }
+
+    /// The path this process can open the file through.
+    fn path(&self) -> String {
+        return format!("/proc/self/fd/{}", self.fd);
+    }
+
+    /// The path a child process (the C compiler) can open the file through.
+    fn path_from_child(&self) -> String {
+        return format!("/proc/{}/fd/{}", std::process::id(), self.fd);
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for MemoryFile {
+    fn drop(&mut self) {
+        unsafe { libc::close(self.fd); }
+    }
+}
+
+#[cfg(target_os = "linux")]
+struct Library {
+    handle: *mut libc::c_void
+}
+
+#[cfg(target_os = "linux")]
+impl Library {
+    fn open(path: &str) -> Result<Self, CompilerError> {
+        let path = CString::new(path).unwrap();
+        let handle = unsafe { libc::dlopen(path.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL) };
+        if handle.is_null() {
+            return Err(CompilerError::LoadFailed { reason: dl_error() });
+        }
+        return Ok(Library { handle });
+    }
+
+    fn symbol(&self, name: &str) -> Result<*mut libc::c_void, CompilerError> {
+        let name = CString::new(name).unwrap();
+        let symbol = unsafe { libc::dlsym(self.handle, name.as_ptr()) };
+        if symbol.is_null() {
+            return Err(CompilerError::LoadFailed { reason: dl_error() });
+        }
+        return Ok(symbol);
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for Library {
+    fn drop(&mut self) {
+        unsafe { libc::dlclose(self.handle); }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn dl_error() -> String {
+    let error = unsafe { libc::dlerror() };
+    if error.is_null() {
+        return String::from("unknown error");
+    }
+    return unsafe { CStr::from_ptr(error) }.to_string_lossy().into_owned();
+}
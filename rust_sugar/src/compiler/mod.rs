@@ -0,0 +1,59 @@
+use std::{env, io::Write, path::Path, process::{Command, Stdio}};
+
+use crate::parser::{functions::Fun, structs::Struct};
+
+pub mod c_source;
+pub mod compiler_error;
+pub mod jit;
+
+pub use compiler_error::CompilerError;
+pub use jit::run;
+
+/// Lowers the parsed program to a C99 translation unit.
+pub fn emit_c<'tkns, 'bumps, 'defs>(
+    structs: &'defs [Struct],
+    functions: &'defs [Fun<'tkns, 'bumps, 'defs>]
+) -> Result<String, CompilerError> {
+    return c_source::CSource::new(structs, functions).lower_program();
+}
+
+/// Lowers the parsed program to C and compiles it with the system C compiler
+/// (`$CC`, falling back to `cc`), writing the executable to `output`.
+pub fn build<'tkns, 'bumps, 'defs>(
+    structs: &'defs [Struct],
+    functions: &'defs [Fun<'tkns, 'bumps, 'defs>],
+    output: &Path
+) -> Result<(), CompilerError> {
+    let source = emit_c(structs, functions)?;
+    return run_c_compiler(&source, &[
+        String::from("-o"),
+        output.to_string_lossy().into_owned(),
+        String::from("-lm")
+    ]);
+}
+
+/// Feeds `source` to the system C compiler over stdin.  `-fwrapv` makes signed overflow
+/// wrap, which is what the interpreter does when it truncates its i128 arithmetic.
+pub(crate) fn run_c_compiler(source: &str, extra_args: &[String]) -> Result<(), CompilerError> {
+    let compiler = env::var("CC").unwrap_or_else(|_| String::from("cc"));
+
+    let mut child = Command::new(&compiler)
+        .args(["-std=c99", "-O2", "-fwrapv", "-w", "-x", "c", "-"])
+        .args(extra_args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    child.stdin.take().unwrap().write_all(source.as_bytes())?;
+    let output = child.wait_with_output()?;
+
+    if !output.status.success() {
+        return Err(CompilerError::CCompilerFailed {
+            compiler,
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned()
+        });
+    }
+
+    return Ok(());
+}
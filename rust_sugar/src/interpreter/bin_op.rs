@@ -1,4 +1,4 @@
-use crate::parser::{expr::{Expr, ExprData, ExprType, ExprTypeCons, Lit}, operators::BinOp, stmt::StackLocation, ExprBump};
+use crate::parser::{exhaustiveness::integer_bounds, expr::{Expr, ExprData, ExprType, ExprTypeCons, Lit}, operators::BinOp, stmt::StackLocation, ExprBump};
 
 use super::{Interpreter, VariableData};
 
@@ -13,6 +13,10 @@ pub fn evaluate_bin_op<'tkns, 'bumps, 'defs>(
     expected_type: &ExprType,
     local_scoping: bool,
 ) -> Option<VariableData> {
+    if binary_operator == BinOp::Cast {
+        return evaluate_cast(interpreter, expr_bump, line, left, &right.expr_type.clone_inner(), expected_type, local_scoping);
+    }
+
     let left_expr; 
     let right_expr;
     let left_data = interpreter.evaluate_expression(expr_bump, left.clone(), expected_type, local_scoping)?;
@@ -346,9 +350,83 @@ pub fn evaluate_bin_op<'tkns, 'bumps, 'defs>(
             panic!("line {line}: types {:?} and {:?} for binary operation {:?} in expression {expr:?} is not supported.", left_expr.expr_type, right_expr.expr_type, binary_operator);
         }
         BinOp::Concat => todo!(),
-        BinOp::Range => todo!(), 
-        BinOp::BangRangeEquals => todo!(), 
-        BinOp::BangRange => todo!(), 
-        BinOp::RangeEquals => todo!(),
+        BinOp::Cast => unreachable!("casts never evaluate their type operand"),
+        BinOp::Range | BinOp::BangRangeEquals | BinOp::BangRange | BinOp::RangeEquals => {
+            // a range is laid out as its start followed by its end, 
+            // which bounds are inclusive is part of its type
+            let ExprType::Range { expr_type: ref element_type, .. } = out_type else {
+                unreachable!("range operators should produce a range");
+            };
+            let element_size = element_type.size_of(interpreter.defs);
+
+            let bytes = unsafe {[
+                interpreter.get_bytes_from_index(left_data.index, element_size),
+                interpreter.get_bytes_from_index(right_data.index, element_size)
+            ].concat()};
+
+            let range_data = interpreter.stack_alloc(line, &out_type, StackLocation::Oxy);
+            interpreter.stack_write(&range_data, &bytes);
+            return Some(range_data);
+        }
     }
-}
\ No newline at end of file
+}
+
+/// Converts `value` to `cast_type` the way a C cast does: integers are truncated or sign/zero extended 
+/// to the new width, floats saturate at the bounds of an integer type with NaN becoming 0, and anything 
+/// compared against zero gives a bool.
+fn evaluate_cast<'tkns, 'bumps, 'defs>(
+    interpreter: &mut Interpreter<'tkns, 'bumps, 'defs, '_>,
+    expr_bump: &'bumps ExprBump,
+    line: usize,
+    value: &Expr<'bumps, 'defs>,
+    cast_type: &ExprType,
+    expected_type: &ExprType,
+    local_scoping: bool,
+) -> Option<VariableData> {
+    let value_data = interpreter.evaluate_expression(expr_bump, value.clone(), expected_type, local_scoping)?;
+    let value_expr = unsafe {
+        interpreter.get_expr_from_variable_data(expr_bump, &value_data)
+    };
+    let ExprData::Literal(literal) = value_expr.expr_data else {
+        panic!("line {line}: cannot cast {:?} to {cast_type:?}", value_expr.expr_data);
+    };
+
+    let integer = match literal {
+        Lit::IntegerLiteral(int) => *int,
+        Lit::FloatLiteral(float) => {
+            // saturates at the bounds of the type, and NaN becomes 0, the same as compiled programs
+            let (min, max) = integer_bounds(cast_type).unwrap_or((0, i128::MAX));
+            (*float as i128).clamp(min, max)
+        },
+        Lit::CharLiteral(chr) => *chr as i128,
+        Lit::BooleanLiteral(bool) => *bool as i128,
+        Lit::StringLiteral(_) => panic!("line {line}: cannot cast a string to {cast_type:?}"),
+    };
+    let cast_literal = match (literal, cast_type) {
+        (Lit::FloatLiteral(float), ExprType::Bool) => Lit::BooleanLiteral(*float != 0.0),
+        (_, ExprType::Bool) => Lit::BooleanLiteral(integer != 0),
+        (Lit::FloatLiteral(float), ExprType::F32 | ExprType::F64) => Lit::FloatLiteral(*float),
+        (_, ExprType::F32 | ExprType::F64) => Lit::FloatLiteral(integer as f64),
+        (Lit::CharLiteral(chr), ExprType::Char) => Lit::CharLiteral(*chr),
+        (_, ExprType::Char) => Lit::CharLiteral(char::from(integer as u8)),
+        _ => Lit::IntegerLiteral(integer),
+    };
+
+    let cast_data = interpreter.stack_alloc(line, cast_type, StackLocation::Oxy);
+    let bytes = interpreter.to_interpreter_bytes(
+        expr_bump, 
+        expr_bump.alloc(ExprData::Literal(cast_literal)), 
+        cast_type, 
+        line, 
+        expected_type,
+        local_scoping
+    ).expect(
+        format!(
+            "line {}: could not interpret cast to {:?}",
+            line, cast_type
+        ).as_str()
+    );
+
+    interpreter.stack_write(&cast_data, &bytes);
+    return Some(cast_data);
+}
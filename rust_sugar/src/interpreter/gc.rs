@@ -0,0 +1,142 @@
+use std::collections::BTreeMap;
+
+/// The heap every variable declared without `oxy` lives on.  An object stays alive for as long as
+/// a running scope roots it, which lets a GC value outlive the frame that made it, and gets
+/// reclaimed by a mark-and-sweep collection once allocating runs out of room.
+pub struct GcHeap {
+    bytes: Vec<u8>,
+    /// Every object on the heap, keyed by the index it starts at
+    objects: BTreeMap<usize, GcObject>,
+    /// The size of every gap between objects, keyed by the index it starts at
+    free: BTreeMap<usize, usize>,
+    /// Indexes into the objects the running scopes hold on to, innermost scope last
+    roots: Vec<usize>
+}
+
+struct GcObject {
+    size: usize,
+    marked: bool
+}
+
+impl Default for GcHeap {
+    fn default() -> Self {
+        return GcHeap {
+            bytes: vec![0; Self::INITIAL_SIZE],
+            objects: BTreeMap::new(),
+            free: BTreeMap::from([(0, Self::INITIAL_SIZE)]),
+            roots: vec![]
+        };
+    }
+}
+
+impl GcHeap {
+    const INITIAL_SIZE: usize = 1024;
+
+    /// Allocates a zeroed object of `size` bytes, rooted in the innermost scope.  When no gap
+    /// fits it, the heap is collected first and only grown if that did not free up enough room.
+    pub fn alloc(&mut self, size: usize) -> usize {
+        // zero sized values still get an index no other object starts at
+        let size = size.max(1);
+
+        let start = match self.take_free(size) {
+            Some(start) => start,
+            None => {
+                self.collect();
+                match self.take_free(size) {
+                    Some(start) => start,
+                    None => {
+                        self.grow(size);
+                        self.take_free(size).expect("a grown heap should fit the object")
+                    }
+                }
+            }
+        };
+
+        self.bytes[start..start + size].fill(0);
+        self.objects.insert(start, GcObject { size, marked: false });
+        self.roots.push(start);
+        return start;
+    }
+
+    /// Keeps the object `index` points into alive until the innermost scope ends.
+    pub fn root(&mut self, index: usize) {
+        self.roots.push(index);
+    }
+
+    /// How many roots the running scopes hold, to hand to [`GcHeap::truncate_roots`] once the
+    /// scope that is about to start ends.
+    pub fn root_count(&self) -> usize {
+        return self.roots.len();
+    }
+
+    /// Drops the roots of every scope that started after the heap held `root_count` roots.
+    pub fn truncate_roots(&mut self, root_count: usize) {
+        self.roots.truncate(root_count);
+    }
+
+    pub fn bytes(&self, index: usize, size: usize) -> &[u8] {
+        return &self.bytes[index..index + size];
+    }
+
+    pub fn write(&mut self, index: usize, bytes: &[u8]) {
+        self.bytes[index..index + bytes.len()].copy_from_slice(bytes);
+    }
+
+    /// Frees every object no root points into.  The only values holding indexes into other
+    /// objects are references, and borrow checking keeps those from outliving the variable they
+    /// borrow, which stays rooted, so the rooted objects are all that is reachable.
+    pub fn collect(&mut self) {
+        for &root in &self.roots {
+            if let Some((start, object)) = self.objects.range_mut(..=root).next_back()
+                && root < start + object.size
+            {
+                object.marked = true;
+            }
+        }
+
+        self.objects.retain(|_, object| object.marked);
+        for object in self.objects.values_mut() {
+            object.marked = false;
+        }
+
+        self.free.clear();
+        let mut gap_start = 0;
+        for (&start, object) in &self.objects {
+            if start > gap_start {
+                self.free.insert(gap_start, start - gap_start);
+            }
+            gap_start = start + object.size;
+        }
+        if self.bytes.len() > gap_start {
+            self.free.insert(gap_start, self.bytes.len() - gap_start);
+        }
+    }
+
+    /// Takes `size` bytes from the front of the first gap that fits them.
+    fn take_free(&mut self, size: usize) -> Option<usize> {
+        let (&start, &gap_size) = self.free.iter().find(|&(_, &gap_size)| gap_size >= size)?;
+
+        self.free.remove(&start);
+        if gap_size > size {
+            self.free.insert(start + size, gap_size - size);
+        }
+        return Some(start);
+    }
+
+    /// Grows the heap so its end has a gap of at least `size` bytes.
+    fn grow(&mut self, size: usize) {
+        let old_len = self.bytes.len();
+        let new_len = (old_len * 2).max(old_len + size);
+        self.bytes.resize(new_len, 0);
+
+        let last_gap = self.free.iter().next_back().map(|(&start, &gap_size)| (start, gap_size));
+        match last_gap {
+            Some((start, gap_size)) if start + gap_size == old_len => {
+                self.free.insert(start, new_len - start);
+            },
+            _ => {
+                self.free.insert(old_len, new_len - old_len);
+            }
+        }
+    }
+}
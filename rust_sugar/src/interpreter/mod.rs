@@ -1,24 +1,46 @@
-use std::{collections::HashMap, ops::{Add, AddAssign}, ptr::NonNull};
+use std::{cell::RefCell, collections::HashMap, ops::{Add, AddAssign, ControlFlow}, ptr::NonNull};
 
 use stack_frame_allocators::stack_frame_dict_allocator::StackFrameDictAllocator;
 use text_io::try_read;
 
-use crate::parser::{accessors::Accessor, expr::{Expr, ExprData, ExprType, ExprTypeCons, Lit}, functions::{BuiltInFunction, FnParam, Fun}, stmt::{StackLocation, Stmt, StmtData}, structs::Struct, ExprBump};
+use gc::GcHeap;
+
+use crate::parser::{accessors::Accessor, enums, expr::{Expr, ExprData, ExprType, ExprTypeCons, Lit}, functions::{BuiltInFunction, CaptureMode, FnParam, Fun}, operators::{BinOp, UnOp}, patterns::{MatchArm, MatchPattern}, stmt::{StackLocation, Stmt, StmtData}, structs::Struct, ExprBump};
 
 pub mod bin_op;
+pub mod gc;
 pub mod un_op;
 
 #[allow(dead_code)]
 pub struct Interpreter<'tkns, 'bumps, 'defs, 'i> {
     oxy_stack: NonNull<[u8]>,
-    gc_stack: NonNull<[u8]>,
     oxy_stack_ptr: usize,
-    gc_stack_ptr: usize,
+    gc_heap: GcHeap,
     variables: Vec<StackFrameDictAllocator<'i, String, VariableData>>,
 
     accessors: &'defs [Accessor],
     defs: &'defs [Struct],
-    functions: &'defs [Fun<'tkns, 'bumps, 'defs>]
+    functions: &'defs [Fun<'tkns, 'bumps, 'defs>],
+    /// The closures made so far, where a function value is the index of its closure
+    closures: Vec<ClosureValue>
+}
+
+/// A function along with the variables it captured
+#[derive(Clone, Debug)]
+struct ClosureValue {
+    name: String,
+    captures: Vec<(String, CapturedValue)>,
+    /// The arguments a partial application gave the function so far, in the order of its 
+    /// parameters, or nothing when it was not given any
+    args: Vec<Option<Box<[u8]>>>
+}
+
+#[derive(Clone, Debug)]
+enum CapturedValue {
+    /// A variable the closure borrows, which it uses in place
+    Borrowed(VariableData),
+    /// The value moved into the closure
+    Moved(ExprType, Box<[u8]>)
 }
 
 #[derive(Clone, Debug)]
@@ -39,6 +61,26 @@ pub enum StackIndex {
     Oxy(usize)
 }
 
+impl StackIndex {
+    /// The bytes of a reference to the value at this index, where the lowest bit tells a reference
+    /// into the GC heap apart from one into the oxy stack.
+    pub fn to_ref_bytes(self) -> [u8; std::mem::size_of::<usize>()] {
+        return match self {
+            StackIndex::GC(index) => (index << 1 | 1).to_le_bytes(),
+            StackIndex::Oxy(index) => (index << 1).to_le_bytes(),
+        };
+    }
+
+    /// The index the reference with the bytes `bytes` points to.
+    pub fn from_ref_bytes(bytes: &[u8]) -> StackIndex {
+        let encoded = usize::from_le_bytes(bytes.try_into().expect("a reference is the size of a usize"));
+        return match encoded & 1 {
+            1 => StackIndex::GC(encoded >> 1),
+            _ => StackIndex::Oxy(encoded >> 1),
+        };
+    }
+}
+
 impl Add<usize> for StackIndex {
     type Output = StackIndex;
 
@@ -59,6 +101,44 @@ impl AddAssign<usize> for StackIndex {
     }
 }
 
+/// Why [`Interpreter::interpret_statements`] stopped before running every statement
+#[derive(Debug)]
+pub enum Jump {
+    Return(Option<VariableData>),
+    Break { label: Option<String>, value: Option<VariableData> },
+    Continue { label: Option<String> }
+}
+
+impl Jump {
+    /// The value of a call to a function whose body left with `output`
+    fn return_value(output: Result<(), Jump>) -> Option<VariableData> {
+        return match output {
+            Ok(()) => None,
+            Err(Jump::Return(value)) => value,
+            Err(jump) => unreachable!("{jump:?} should not leave a function body")
+        };
+    }
+
+    /// What the loop labeled `label` does after its body left with `output`: run the next 
+    /// iteration, stop with the value a `break` carried out of it, or pass the jump on to an 
+    /// outer block
+    fn loop_control(
+        label: &Option<String>, 
+        output: Result<(), Jump>
+    ) -> Result<ControlFlow<Option<VariableData>>, Jump> {
+        return match output {
+            Ok(()) => Ok(ControlFlow::Continue(())),
+            Err(Jump::Continue { label: target }) if target.is_none() || target == *label => {
+                Ok(ControlFlow::Continue(()))
+            },
+            Err(Jump::Break { label: target, value }) if target.is_none() || target == *label => {
+                Ok(ControlFlow::Break(value))
+            },
+            Err(jump) => Err(jump)
+        };
+    }
+}
+
 type TypedExpr<'bumps, 'types, 'defs> = TypedExpression<'bumps, 'types, 'defs>;
 #[derive(Debug)]
 pub struct TypedExpression<'bumps, 'types, 'defs> {
@@ -68,7 +148,6 @@ pub struct TypedExpression<'bumps, 'types, 'defs> {
 
 impl<'tkns, 'bumps, 'defs, 'i> Interpreter<'tkns, 'bumps, 'defs, 'i> {
     const OXY_STACK_SIZE: usize = 1024;
-    const GC_STACK_SIZE: usize = 1024;
     
     pub fn new(
         (accessors, defs, functions): (
@@ -77,28 +156,22 @@ impl<'tkns, 'bumps, 'defs, 'i> Interpreter<'tkns, 'bumps, 'defs, 'i> {
             &'defs [Fun<'tkns, 'bumps, 'defs>]
         )
     ) -> Self {
-        let (oxy_stack, gc_stack) = unsafe {
+        let oxy_stack = unsafe {
             let oxy_stack = core::slice::from_raw_parts(std::alloc::alloc(
                 std::alloc::Layout::array::<u8>(Self::OXY_STACK_SIZE).unwrap_unchecked()
             ), Self::OXY_STACK_SIZE);
-            let gc_stack = core::slice::from_raw_parts(std::alloc::alloc(
-                std::alloc::Layout::array::<u8>(Self::GC_STACK_SIZE).unwrap_unchecked()
-            ), Self::GC_STACK_SIZE);
 
-            (
-                NonNull::new_unchecked(oxy_stack as *const [u8] as *mut [u8]), 
-                NonNull::new_unchecked(gc_stack as *const [u8] as *mut [u8])
-            )
+            NonNull::new_unchecked(oxy_stack as *const [u8] as *mut [u8])
         };
 
         return Interpreter {
             oxy_stack,
-            gc_stack,
             oxy_stack_ptr: 0,
-            gc_stack_ptr: 0,
+            gc_heap: GcHeap::default(),
             variables: vec![],
 
-            accessors, defs, functions
+            accessors, defs, functions,
+            closures: vec![]
         };
     }
 
@@ -120,8 +193,8 @@ impl<'tkns, 'bumps, 'defs, 'i> Interpreter<'tkns, 'bumps, 'defs, 'i> {
 
         self.variables.push(StackFrameDictAllocator::new());
         //println!("lets a go");
-        self.interpret_statements(expr_bump, &main_fun.body, &main_fun.return_type)
-            .expect("Did not expect return value");
+        let output = self.interpret_statements(expr_bump, &main_fun.body, &main_fun.return_type);
+        assert!(Jump::return_value(output).is_none(), "Did not expect return value");
     }
 
     pub fn interpret_statements(
@@ -129,9 +202,9 @@ impl<'tkns, 'bumps, 'defs, 'i> Interpreter<'tkns, 'bumps, 'defs, 'i> {
         expr_bump: &'bumps ExprBump,
         stmts: &[&'bumps StmtData<'bumps, 'defs>],
         expected_type: &ExprType
-    ) -> Result<(), VariableData> {
+    ) -> Result<(), Jump> {
         let oxy_stack_ptr_start = self.oxy_stack_ptr;
-        let gc_stack_ptr_start = self.gc_stack_ptr;
+        let gc_root_count = self.gc_heap.root_count();
         
         'stmts: for StmtData { line, stmt } in stmts {
             //println!("stmt {stmt:?} on line {line}");
@@ -147,7 +220,9 @@ impl<'tkns, 'bumps, 'defs, 'i> Interpreter<'tkns, 'bumps, 'defs, 'i> {
 
                     output?;
                 },
-                Stmt::While { cond, body } => {
+                Stmt::While { label, cond, body } => {
+                    let oxy_stack_ptr_start = self.oxy_stack_ptr;
+                    let gc_root_count = self.gc_heap.root_count();
                     loop {
                         let Some(variable_data) = self.evaluate_expression(
                             expr_bump, 
@@ -167,20 +242,88 @@ impl<'tkns, 'bumps, 'defs, 'i> Interpreter<'tkns, 'bumps, 'defs, 'i> {
                         ) else {
                             unreachable!("expr_data should be a boolean");
                         };
+                        // the temporaries of the condition are only needed until it is read
+                        self.oxy_stack_ptr = oxy_stack_ptr_start;
+                        self.gc_heap.truncate_roots(gc_root_count);
 
                         if !eval_cond {
                             break;
                         }
                         
-                        self.variables.push(self.variables(true).new_frame());
-                        let output = self.interpret_statements(
-                            expr_bump, 
-                            body.as_slice(), 
-                            expected_type
-                        );
-                        self.variables.pop();
+                        let output = self.interpret_loop_body(expr_bump, &None, &[], body, expected_type);
+                        if Jump::loop_control(label, output)?.is_break() {
+                            break;
+                        }
+                    }
+                },
+                Stmt::Loop { label, body, assign } => loop {
+                    let output = self.interpret_loop_body(expr_bump, &None, &[], body, expected_type);
+                    let ControlFlow::Break(value) = Jump::loop_control(label, output)? else {
+                        continue;
+                    };
 
-                        output?;
+                    if let Some((name, _)) = assign && let Some(value) = value {
+                        // the value is still where the body left it, above the stack pointers
+                        let bytes = unsafe {
+                            self.get_bytes_from_index(value.index, value.expr_type.size_of(self.defs))
+                        }.to_vec();
+                        self.write_variable(name, &bytes);
+                    }
+                    break;
+                },
+                Stmt::For { label, binder, iter, body } => {
+                    let iter_data = self.evaluate_expression(
+                        expr_bump, 
+                        iter.clone(), 
+                        expected_type,
+                        true
+                    ).expect(format!("line {line}: could not evaluate expression {iter:?}").as_str());
+
+                    match iter_data.expr_type.clone() {
+                        ExprType::Range { exclusive_start, inclusive_end, expr_type: element_type } => {
+                            let ExprData::BinaryOp(_, 
+                                Expr { expr_data: ExprData::Literal(Lit::IntegerLiteral(start)), .. }, 
+                                Expr { expr_data: ExprData::Literal(Lit::IntegerLiteral(end)), .. }
+                            ) = (unsafe { self.get_expr_from_variable_data(expr_bump, &iter_data).expr_data }) else {
+                                unreachable!("range bounds should be integers");
+                            };
+
+                            // the bounds are widened to i128, so inclusive ranges ending at the 
+                            // largest value of their type do not overflow
+                            let start = start + exclusive_start as i128;
+                            let end = end + inclusive_end as i128;
+
+                            for value in start..end {
+                                let bytes = self.to_interpreter_bytes(
+                                    expr_bump, 
+                                    expr_bump.alloc(ExprData::Literal(Lit::IntegerLiteral(value))), 
+                                    &element_type, 
+                                    *line, 
+                                    expected_type, 
+                                    true
+                                ).expect(format!("line {line}: could not interpret range element {value}").as_str());
+
+                                let output = self.interpret_loop_body(expr_bump, binder, &bytes, body, expected_type);
+                                if Jump::loop_control(label, output)?.is_break() {
+                                    break;
+                                }
+                            }
+                        },
+                        ExprType::Array { length: Some(length), expr_type: element_type } => {
+                            let element_size = element_type.size_of(self.defs);
+
+                            for i in 0..length {
+                                let bytes = unsafe {
+                                    self.get_bytes_from_index(iter_data.index + i * element_size, element_size)
+                                }.to_vec();
+
+                                let output = self.interpret_loop_body(expr_bump, binder, &bytes, body, expected_type);
+                                if Jump::loop_control(label, output)?.is_break() {
+                                    break;
+                                }
+                            }
+                        },
+                        iter_type => panic!("line {line}: cannot iterate over {iter_type:?}")
                     }
                 },
                 Stmt::Conditional { conds, bodies } => {
@@ -232,13 +375,46 @@ impl<'tkns, 'bumps, 'defs, 'i> Interpreter<'tkns, 'bumps, 'defs, 'i> {
                         output?;
                     }
                 },
-                Stmt::Return(expr_data_opt) => return match expr_data_opt.as_ref().map(|expr|
+                Stmt::Match { scrutinee, arms } => {
+                    let value = self.evaluate_expression(
+                        expr_bump, 
+                        scrutinee.clone(), 
+                        expected_type,
+                        true
+                    ).expect(format!("line {line}: could not evaluate expression {scrutinee:?}").as_str());
+
+                    for MatchArm { pattern, guard, body } in arms {
+                        self.variables.push(self.variables(true).new_frame());
+                        if !self.match_arm(expr_bump, pattern, guard, &value, expected_type) {
+                            self.variables.pop();
+                            continue;
+                        }
+
+                        let output = self.interpret_statements(
+                            expr_bump, 
+                            body.as_slice(), 
+                            expected_type
+                        );
+                        self.variables.pop();
+
+                        output?;
+
+                        continue 'stmts;
+                    }
+                    unreachable!("line {line}: match arms should cover every value");
+                },
+                Stmt::Return(expr_data_opt, _) => return Err(Jump::Return(expr_data_opt.as_ref().map(|expr|
                     self.evaluate_expression(expr_bump, expr.clone(), expected_type, true)
                         .expect(format!("line {line}: could not evaluate expression {expr:?}").as_str())
-                ).ok_or(()) {
-                    Ok(ok) => Err(ok),
-                    Err(err) => Ok(err)
-                },
+                ))),
+                Stmt::Break { label, value } => return Err(Jump::Break { 
+                    label: label.clone(), 
+                    value: value.as_ref().map(|expr|
+                        self.evaluate_expression(expr_bump, expr.clone(), expected_type, true)
+                            .expect(format!("line {line}: could not evaluate expression {expr:?}").as_str())
+                    )
+                }),
+                Stmt::Continue { label } => return Err(Jump::Continue { label: label.clone() }),
                 Stmt::Declare(name, stack_location, expr_type) => {
                     let expr_type = expr_type.borrow().clone();
                     self.push_variable(name, expr_type, *stack_location);
@@ -246,9 +422,13 @@ impl<'tkns, 'bumps, 'defs, 'i> Interpreter<'tkns, 'bumps, 'defs, 'i> {
                 Stmt::Assign { variable, assign } => {
                     let var_name = match variable.clone() {
                         Expr {
-                            expr_data: ExprData::Identifier(var_name),
+                            expr_data: ExprData::Identifier(var_name, _),
                             ..
-                        } => var_name,
+                        } => Some(var_name),
+                        Expr {
+                            expr_data: ExprData::UnaryOp(UnOp::Deref, _),
+                            ..
+                        } => None,
                         Expr { line, ..} => panic!("line {line}: assign expression requires an identifer")
                     };
 
@@ -259,36 +439,26 @@ impl<'tkns, 'bumps, 'defs, 'i> Interpreter<'tkns, 'bumps, 'defs, 'i> {
                         true
                     ).expect(format!("line {line}: could not evaluate expression {assign:?}").as_str());
 
-                    let TypedExpr {
-                        expr_data: assign_data,
-                        expr_type: assign_type
-                    } = unsafe {
-                        self.get_expr_from_variable_data(expr_bump, &assign_variable_data)
-                    };
-
-                    let bytes = self.to_interpreter_bytes(
-                        expr_bump, 
-                        assign_data, 
-                        assign_type, 
-                        *line, 
-                        expected_type, 
-                        true
-                    ).expect(format!("line {line}: could not interpret expression {assign:?}").as_str());
-
-                    let variable_data = unsafe { self.variables.last().unwrap_unchecked()
-                        .get_in_stack(var_name)
-                        .expect(format!("line {line}: could not find variable {var_name}").as_str())
-                        .get()
+                    let bytes = unsafe {
+                        self.get_bytes_from_index(
+                            assign_variable_data.index, 
+                            assign_variable_data.expr_type.size_of(self.defs)
+                        ).to_vec()
                     };
 
-                    let mut stack = Stack {
-                        oxy_stack: self.oxy_stack,
-                        oxy_stack_ptr: &mut self.oxy_stack_ptr,
-                        gc_stack: self.gc_stack,
-                        gc_stack_ptr: &mut self.gc_stack_ptr,
+                    // a write through a reference goes to the value the reference points to
+                    let variable_data = match var_name {
+                        Some(var_name) => unsafe { self.variables.last().unwrap_unchecked()
+                            .get_in_stack(var_name)
+                            .expect(format!("line {line}: could not find variable {var_name}").as_str())
+                            .get()
+                            .clone()
+                        },
+                        None => self.evaluate_expression(expr_bump, variable.clone(), expected_type, true)
+                            .expect(format!("line {line}: could not evaluate expression {variable:?}").as_str())
                     };
 
-                    stack.stack_write(variable_data, &bytes);
+                    self.stack_write(&variable_data, &bytes);
                 },
                 Stmt::Expr(expr) => {
                     self.evaluate_expression(expr_bump, expr.clone(), expected_type, true);
@@ -298,7 +468,7 @@ impl<'tkns, 'bumps, 'defs, 'i> Interpreter<'tkns, 'bumps, 'defs, 'i> {
         }
 
         self.oxy_stack_ptr = oxy_stack_ptr_start;
-        self.gc_stack_ptr = gc_stack_ptr_start;
+        self.gc_heap.truncate_roots(gc_root_count);
 
         return Ok(());
     }
@@ -312,13 +482,16 @@ impl<'tkns, 'bumps, 'defs, 'i> Interpreter<'tkns, 'bumps, 'defs, 'i> {
     ) -> Option<VariableData> {
         let line = expr.line;
         match expr.expr_data {
-            ExprData::Identifier(ident) => {
-                let data = self.variables(local_scoping)
-                    .get_in_stack(ident)
-                    .expect(format!("could not find variable {ident}").as_str())
-                    .get()
-                    .clone();
-                return Some(data);
+            ExprData::Identifier(ident, _) => {
+                if let Some(data) = self.variables(local_scoping).get_in_stack(ident) {
+                    return Some(data.get().clone());
+                }
+                // a function passed along as a value
+                if self.functions.iter().any(|fun| fun.name == *ident) {
+                    let closure = ClosureValue { name: ident.clone(), captures: vec![], args: vec![] };
+                    return Some(self.closure_value(line, &expr.expr_type.get(), closure));
+                }
+                panic!("could not find variable {ident}");
             }
             ExprData::Literal(_) => {
                 let variable_data = self.stack_alloc(
@@ -362,7 +535,28 @@ impl<'tkns, 'bumps, 'defs, 'i> Interpreter<'tkns, 'bumps, 'defs, 'i> {
                 self.stack_write(&variable_data, &bytes);
                 return Some(variable_data);
             }
-            ExprData::AnonymousCustom { .. } => {
+            ExprData::AnonymousCustom { .. } | ExprData::Tuple(_) => {
+                let variable_data = self.stack_alloc(
+                    line,
+                    &*expr.expr_type.get(), 
+                    StackLocation::Oxy
+                );
+                let bytes = self.to_interpreter_bytes(
+                    expr_bump, 
+                    expr.expr_data, 
+                    &*expr.expr_type.get(), 
+                    line,
+                    expected_type,
+                    local_scoping
+                ).expect(
+                    format!(
+                        "line {line}: could not interpret expression {expr:?}"
+                    ).as_str()
+                );
+                self.stack_write(&variable_data, &bytes);
+                return Some(variable_data);
+            }
+            ExprData::EnumVariant { .. } => {
                 let variable_data = self.stack_alloc(
                     line,
                     &*expr.expr_type.get(), 
@@ -399,7 +593,7 @@ impl<'tkns, 'bumps, 'defs, 'i> Interpreter<'tkns, 'bumps, 'defs, 'i> {
                     );
                 }
 
-                let ExprType::Custom { ident } = struct_data.expr_type else {
+                let ExprType::Custom { ident, type_args } = struct_data.expr_type else {
                     panic!("Type of Custom Field expression should never be not a Custom Type");
                 };
 
@@ -410,7 +604,7 @@ impl<'tkns, 'bumps, 'defs, 'i> Interpreter<'tkns, 'bumps, 'defs, 'i> {
                 let offset = custom_struct.fields.iter()
                     .scan(0, |accum, struct_field| {
                         let offset = *accum;
-                        *accum += struct_field.field_type.size_of(self.defs);
+                        *accum += custom_struct.field_type(struct_field, &type_args).size_of(self.defs);
                         return Some((offset, struct_field));
                     })
                     .find_map(|(offset, struct_field)| 
@@ -424,7 +618,7 @@ impl<'tkns, 'bumps, 'defs, 'i> Interpreter<'tkns, 'bumps, 'defs, 'i> {
                 
                 let field_data = VariableData {
                     index: struct_data.index + offset,
-                    expr_type: field.field_type.clone()
+                    expr_type: custom_struct.field_type(field, &type_args)
                 };
 
                 Some(field_data)
@@ -511,6 +705,28 @@ impl<'tkns, 'bumps, 'defs, 'i> Interpreter<'tkns, 'bumps, 'defs, 'i> {
                 
                 panic!("conditional expression did not cover all possible variants");
             }
+            ExprData::Match { scrutinee, arms } => {
+                let value = self.evaluate_expression(
+                    expr_bump,
+                    scrutinee.clone(), 
+                    expected_type,
+                    local_scoping
+                ).expect(format!("line {line}: could not evaluate expression {scrutinee:?}").as_str());
+
+                for MatchArm { pattern, guard, body } in arms {
+                    self.variables.push(self.variables(local_scoping).new_frame());
+                    if !self.match_arm(expr_bump, pattern, guard, &value, expected_type) {
+                        self.variables.pop();
+                        continue;
+                    }
+
+                    let output = self.evaluate_expression(expr_bump, body.clone(), expected_type, true);
+                    self.variables.pop();
+
+                    return output;
+                }
+                unreachable!("line {line}: match arms should cover every value");
+            }
             ExprData::Function { name, left_args, right_args } => {
                 if let Some(built_in) = BuiltInFunction::from_name(name) && built_in.match_args(&left_args, &right_args) {
                     match built_in {
@@ -642,74 +858,111 @@ impl<'tkns, 'bumps, 'defs, 'i> Interpreter<'tkns, 'bumps, 'defs, 'i> {
                     }
                 }
 
-                self.variables.push(StackFrameDictAllocator::new());
-                
                 let fun = self.functions.iter()
                     .find(|fun| fun.name == *name)
                     .expect(format!("function named {} could not be found", name).as_str());
-                
-                let oxy_stack_ptr_start = self.oxy_stack_ptr;
-
-                for (arg, input) in fun.left_args.iter().zip(left_args) {
-                    let FnParam {
-                        param_name,
-                        param_type: arg_type,
-                        ..
-                    } = arg;
+                return self.call_function(expr_bump, fun, None, left_args, right_args, expected_type, local_scoping);
+            }
+            ExprData::Closure { name, captures } => {
+                let mut captured = vec![];
+                for (capture, _, mode) in captures {
+                    let variable_data = self.variables(local_scoping)
+                        .get_in_stack(capture)
+                        .expect(format!("could not find variable {capture}").as_str())
+                        .get()
+                        .clone();
+                    captured.push((capture.clone(), match mode {
+                        CaptureMode::Borrow(_) => CapturedValue::Borrowed(variable_data),
+                        CaptureMode::Move => {
+                            let bytes = unsafe {
+                                self.get_bytes_from_index(variable_data.index, variable_data.expr_type.size_of(self.defs))
+                            }.to_vec().into_boxed_slice();
+                            CapturedValue::Moved(variable_data.expr_type, bytes)
+                        }
+                    }));
+                }
+                let closure = ClosureValue { name: name.clone(), captures: captured, args: vec![] };
+                return Some(self.closure_value(line, &expr.expr_type.get(), closure));
+            }
+            ExprData::Call { callee, left_args, right_args } => {
+                let callee_data = self.evaluate_expression(expr_bump, callee.clone(), expected_type, local_scoping)?;
+                let closure = usize::from_le_bytes(unsafe {
+                    self.get_bytes_from_index(callee_data.index, callee_data.expr_type.size_of(self.defs))
+                        .try_into()
+                        .unwrap_unchecked()
+                });
+                let fun = self.functions.iter()
+                    .find(|fun| fun.name == self.closures[closure].name)
+                    .expect(format!("function named {} could not be found", self.closures[closure].name).as_str());
+                return self.call_function(expr_bump, fun, Some(closure), left_args, right_args, expected_type, local_scoping);
+            }
+            ExprData::Partial { callee, left_args, right_args } => {
+                let callee_data = self.evaluate_expression(expr_bump, callee.clone(), expected_type, local_scoping)?;
+                let index = usize::from_le_bytes(unsafe {
+                    self.get_bytes_from_index(callee_data.index, callee_data.expr_type.size_of(self.defs))
+                        .try_into()
+                        .unwrap_unchecked()
+                });
+                let ExprType::FunctionPass { left_args: ref open_left, .. } = callee_data.expr_type else {
+                    unreachable!("line {line}: only function values can be applied partially");
+                };
 
-                    let variable_data = self.evaluate_expression(
-                        expr_bump, 
-                        input.clone(), 
-                        expected_type,
-                        false
-                    )?;
-                    
-                    let arg_name = param_name.as_ref().expect("argument name required");
-                    
-                    unsafe {
-                        let bytes = self.get_bytes_from_index(
-                            variable_data.index, 
-                            variable_data.expr_type.size_of(self.defs)
-                        ).to_vec().into_boxed_slice();
-
-                        self.push_variable(arg_name, arg_type.clone(), StackLocation::Oxy);
-                        self.write_variable(arg_name, &bytes);
-                    }
+                let mut closure = self.closures[index].clone();
+                if closure.args.is_empty() {
+                    let fun = self.functions.iter()
+                        .find(|fun| fun.name == closure.name)
+                        .expect(format!("function named {} could not be found", closure.name).as_str());
+                    closure.args = vec![None; fun.left_args.len() + fun.right_args.len()];
                 }
 
-                for (arg, input) in fun.right_args.iter().zip(right_args) {
-                    let FnParam {
-                        param_name,
-                        param_type: arg_type,
-                        ..
-                    } = arg;
+                // the arguments given fill the last of the open left arguments and the first of 
+                // the open right arguments
+                let open = closure.args.iter()
+                    .enumerate()
+                    .filter_map(|(i, arg)| arg.is_none().then_some(i))
+                    .collect::<Vec<_>>();
+                let (left_slots, right_slots) = open.split_at(open_left.len());
+                let slots = left_slots[left_slots.len() - left_args.len()..].iter().chain(&right_slots[..right_args.len()]);
+
+                let arg_bytes = self.evaluate_args(expr_bump, left_args, right_args, expected_type, local_scoping)?;
+                for (slot, bytes) in slots.zip(arg_bytes) {
+                    closure.args[*slot] = Some(bytes);
+                }
+                return Some(self.closure_value(line, &expr.expr_type.get(), closure));
+            }
+            ExprData::Array(elements) => {
+                let array_data = self.stack_alloc(
+                    line,
+                    &*expr.expr_type.get(), 
+                    StackLocation::Oxy
+                );
+                let ExprType::Array { expr_type: ref element_type, .. } = array_data.expr_type else {
+                    unreachable!("array expressions should be of an array type");
+                };
+                let element_size = element_type.size_of(self.defs);
 
-                    let variable_data = self.evaluate_expression(
+                let mut index = array_data.index;
+                for element in elements {
+                    let element_data = self.evaluate_expression(
                         expr_bump, 
-                        input.clone(), 
-                        expected_type,
-                        false
-                    )?;
-                    
-                    let arg_name = param_name.as_ref().expect("argument name required");
-                    
-                    unsafe {
-                        let bytes = self.get_bytes_from_index(
-                            variable_data.index, 
-                            variable_data.expr_type.size_of(self.defs)
-                        ).to_vec().into_boxed_slice();
-
-                        self.push_variable(arg_name, arg_type.clone(), StackLocation::Oxy);
-                        self.write_variable(arg_name, &bytes);
-                    }
-                }
+                        Expr {
+                            line,
+                            expr_data: element.expr_data,
+                            expr_type: ExprTypeCons::new(expr_bump, (**element_type).clone())
+                        }, 
+                        expected_type, 
+                        local_scoping
+                    ).expect(format!("line {line}: could not evaluate expression {element:?}").as_str());
+
+                    let bytes = unsafe {
+                        self.get_bytes_from_index(element_data.index, element_size).to_vec()
+                    };
+                    self.stack_write(&VariableData { index, expr_type: element_data.expr_type }, &bytes);
 
-                ////println!("calculating {:?}", fun.body);
-                let out = self.interpret_statements(expr_bump, &fun.body, &fun.return_type).err();
-                self.variables.pop();
-                self.oxy_stack_ptr = oxy_stack_ptr_start;
+                    index += element_size;
+                }
 
-                return out;
+                return Some(array_data);
             }
             ExprData::BinaryOp(binary_operator, left, right) => {
                 return bin_op::evaluate_bin_op(
@@ -734,10 +987,378 @@ impl<'tkns, 'bumps, 'defs, 'i> Interpreter<'tkns, 'bumps, 'defs, 'i> {
                     local_scoping
                 );
             }
+            ExprData::Index { expr: array, index } => {
+                let array_data = self.evaluate_expression(
+                    expr_bump,
+                    array.clone(), 
+                    expected_type,
+                    local_scoping
+                ).expect("evaluating an indexed array should yield something");
+                // destructuring indexes with literals, which do not need to be stored first
+                let element = match index.expr_data {
+                    ExprData::Literal(Lit::IntegerLiteral(element)) => *element,
+                    _ => {
+                        let index_data = self.evaluate_expression(
+                            expr_bump,
+                            index.clone(), 
+                            expected_type,
+                            local_scoping
+                        ).expect("evaluating an index should yield something");
+
+                        let ExprData::Literal(Lit::IntegerLiteral(element)) = (unsafe { 
+                            self.get_expr_from_variable_data(expr_bump, &index_data).expr_data 
+                        }) else {
+                            unreachable!("indexes should be integers");
+                        };
+                        *element
+                    }
+                };
+                if let ExprType::Tuple { start: ref element_types, .. } = array_data.expr_type {
+                    return Some(self.tuple_elements(&array_data, element_types).swap_remove(element as usize));
+                }
+                let ExprType::Array { length, expr_type: element_type } = array_data.expr_type else {
+                    panic!("line {line}: only arrays and tuples can be indexed, not {:?}", array_data.expr_type);
+                };
+                if let Some(length) = length && element as usize >= length {
+                    panic!("line {line}: index {element} is out of bounds for an array of length {length}");
+                }
+
+                Some(VariableData {
+                    index: array_data.index + element as usize * element_type.size_of(self.defs),
+                    expr_type: *element_type
+                })
+            }
             data => panic!("{data:?} is not supported yet"),
         }
     }
 
+    /// Calls `fun` with the arguments evaluated in the scope of the caller. When `fun` is the 
+    /// function of a closure, `closure` is the index of the closure, whose captured variables are 
+    /// in scope for the call and whose moved values keep what the call changes them to.
+    fn call_function(
+        &mut self,
+        expr_bump: &'bumps ExprBump,
+        fun: &'defs Fun<'tkns, 'bumps, 'defs>,
+        closure: Option<usize>,
+        left_args: &[Expr<'bumps, 'defs>],
+        right_args: &[Expr<'bumps, 'defs>],
+        expected_type: &ExprType,
+        local_scoping: bool
+    ) -> Option<VariableData> {
+        let oxy_stack_ptr_start = self.oxy_stack_ptr;
+        let gc_root_count = self.gc_heap.root_count();
+
+        // the arguments are evaluated in the scope of the caller, before the frame of the 
+        // function hides its variables
+        let arg_bytes = self.evaluate_args(expr_bump, left_args, right_args, expected_type, local_scoping)?;
+        // the arguments of a call to a partially applied function take the places left open
+        let arg_bytes = match closure.map(|closure| self.closures[closure].args.clone()) {
+            Some(bound) if !bound.is_empty() => {
+                let mut given = arg_bytes.into_iter();
+                bound.into_iter()
+                    .map(|arg| arg.or_else(|| given.next()))
+                    .collect::<Option<Vec<_>>>()
+                    .expect("a call should give every argument a partial application left open")
+            },
+            _ => arg_bytes
+        };
+
+        self.variables.push(StackFrameDictAllocator::new());
+
+        // the arguments the call leaves out are their defaults, evaluated in the scope of the function
+        let mut arg_bytes = arg_bytes;
+        let params = fun.left_args.iter().chain(fun.right_args.iter()).collect::<Vec<_>>();
+        for param in &params[arg_bytes.len()..] {
+            let default = Expr {
+                line: param.tkn.line_number,
+                expr_data: param.param_default.expect("arguments left out of a call should have defaults"),
+                expr_type: ExprTypeCons::new(expr_bump, param.param_type.clone())
+            };
+            let variable_data = self.evaluate_expression(expr_bump, default, &fun.return_type, true)?;
+            arg_bytes.push(unsafe {
+                self.get_bytes_from_index(variable_data.index, variable_data.expr_type.size_of(self.defs))
+            }.to_vec().into_boxed_slice());
+        }
+
+        let captures = closure.map(|closure| self.closures[closure].captures.clone()).unwrap_or_default();
+        let mut moved = vec![];
+        for (i, (name, captured)) in captures.into_iter().enumerate() {
+            match captured {
+                CapturedValue::Borrowed(variable_data) => {
+                    self.variables(true).push(name, variable_data);
+                },
+                CapturedValue::Moved(expr_type, bytes) => {
+                    self.push_variable(&name, expr_type, StackLocation::Oxy);
+                    self.write_variable(&name, &bytes);
+                    moved.push((i, self.variables(true).get_in_stack(&name).unwrap().get().clone()));
+                }
+            }
+        }
+
+        for (arg, bytes) in fun.left_args.iter().chain(fun.right_args.iter()).zip(arg_bytes) {
+            let FnParam {
+                param_name,
+                param_type: arg_type,
+                ..
+            } = arg;
+            
+            let arg_name = param_name.as_ref().expect("argument name required");
+            
+            self.push_variable(arg_name, arg_type.clone(), StackLocation::Oxy);
+            self.write_variable(arg_name, &bytes);
+        }
+
+        ////println!("calculating {:?}", fun.body);
+        let out = Jump::return_value(self.interpret_statements(expr_bump, &fun.body, &fun.return_type));
+        if let Some(closure) = closure {
+            for (i, variable_data) in moved {
+                let bytes = unsafe {
+                    self.get_bytes_from_index(variable_data.index, variable_data.expr_type.size_of(self.defs))
+                }.to_vec().into_boxed_slice();
+                self.closures[closure].captures[i].1 = CapturedValue::Moved(variable_data.expr_type, bytes);
+            }
+        }
+        self.variables.pop();
+        self.oxy_stack_ptr = oxy_stack_ptr_start;
+        self.gc_heap.truncate_roots(gc_root_count);
+
+        // a GC value the function returns stays alive for the scope of the caller
+        if let Some(VariableData { index: StackIndex::GC(index), .. }) = out {
+            self.gc_heap.root(index);
+        }
+
+        return out;
+    }
+
+    /// Evaluates the arguments of a call, returning the bytes of each one.
+    fn evaluate_args(
+        &mut self,
+        expr_bump: &'bumps ExprBump,
+        left_args: &[Expr<'bumps, 'defs>],
+        right_args: &[Expr<'bumps, 'defs>],
+        expected_type: &ExprType,
+        local_scoping: bool
+    ) -> Option<Vec<Box<[u8]>>> {
+        let mut arg_bytes = vec![];
+        for input in left_args.iter().chain(right_args.iter()) {
+            let variable_data = self.evaluate_expression(
+                expr_bump, 
+                input.clone(), 
+                expected_type,
+                local_scoping
+            )?;
+
+            unsafe {
+                arg_bytes.push(self.get_bytes_from_index(
+                    variable_data.index, 
+                    variable_data.expr_type.size_of(self.defs)
+                ).to_vec().into_boxed_slice());
+            }
+        }
+        return Some(arg_bytes);
+    }
+
+    /// Stores `closure`, and allocates the value referring to it.
+    fn closure_value(&mut self, line: usize, expr_type: &ExprType, closure: ClosureValue) -> VariableData {
+        //TODO free the closures no value refers to anymore
+        self.closures.push(closure);
+        let variable_data = self.stack_alloc(line, expr_type, StackLocation::Oxy);
+        self.stack_write(&variable_data, &(self.closures.len() - 1).to_le_bytes());
+        return variable_data;
+    }
+
+    /// Runs one iteration of a loop, with the binder of a for loop (if any) holding `element`.
+    fn interpret_loop_body(
+        &mut self,
+        expr_bump: &'bumps ExprBump,
+        binder: &Option<(String, &RefCell<ExprType>)>,
+        element: &[u8],
+        body: &[&'bumps StmtData<'bumps, 'defs>],
+        expected_type: &ExprType
+    ) -> Result<(), Jump> {
+        let oxy_stack_ptr_start = self.oxy_stack_ptr;
+        let gc_root_count = self.gc_heap.root_count();
+
+        self.variables.push(self.variables(true).new_frame());
+        if let Some((name, expr_type)) = binder {
+            self.push_variable(name, expr_type.borrow().clone(), StackLocation::Oxy);
+            self.write_variable(name, element);
+        }
+        let output = self.interpret_statements(expr_bump, body, expected_type);
+        self.variables.pop();
+        self.oxy_stack_ptr = oxy_stack_ptr_start;
+        self.gc_heap.truncate_roots(gc_root_count);
+
+        return output;
+    }
+
+    /// Whether `value` matches the pattern and the guard of an arm.  The variables the pattern 
+    /// binds are declared in the current frame, so the guard and the body can use them.
+    fn match_arm(
+        &mut self,
+        expr_bump: &'bumps ExprBump,
+        pattern: &MatchPattern,
+        guard: &Option<Expr<'bumps, 'defs>>,
+        value: &VariableData,
+        expected_type: &ExprType
+    ) -> bool {
+        if !self.match_pattern(expr_bump, pattern, value) {
+            return false;
+        }
+        let Some(guard) = guard else {
+            return true;
+        };
+
+        let guard_data = self.evaluate_expression(expr_bump, guard.clone(), expected_type, true)
+            .expect("evaluating guard should yield something");
+        let ExprData::Literal(Lit::BooleanLiteral(guard)) = (unsafe {
+            self.get_expr_from_variable_data(expr_bump, &guard_data).expr_data
+        }) else {
+            unreachable!("expr_data should be a boolean");
+        };
+        return *guard;
+    }
+
+    fn match_pattern(
+        &mut self,
+        expr_bump: &'bumps ExprBump,
+        pattern: &MatchPattern,
+        value: &VariableData
+    ) -> bool {
+        match pattern {
+            MatchPattern::Discard => return true,
+            MatchPattern::Bind(name) => {
+                let bytes = unsafe {
+                    self.get_bytes_from_index(value.index, value.expr_type.size_of(self.defs)).to_vec()
+                };
+                self.push_variable(name, value.expr_type.clone(), StackLocation::Oxy);
+                self.write_variable(name, &bytes);
+                return true;
+            },
+            MatchPattern::Literal(literal) => {
+                let ExprData::Literal(value) = (unsafe {
+                    self.get_expr_from_variable_data(expr_bump, value).expr_data
+                }) else {
+                    unreachable!("literal patterns only match values with literals");
+                };
+                return value == literal;
+            },
+            MatchPattern::Range { start, end } => {
+                let value = match unsafe { self.get_expr_from_variable_data(expr_bump, value).expr_data } {
+                    ExprData::Literal(Lit::IntegerLiteral(int)) => *int,
+                    ExprData::Literal(Lit::CharLiteral(chr)) => *chr as i128,
+                    _ => unreachable!("range patterns only match integers and chars")
+                };
+                return (*start..=*end).contains(&value);
+            },
+            MatchPattern::Group { start, end } => {
+                let elements = match &value.expr_type {
+                    ExprType::Array { length: Some(length), expr_type } => {
+                        let element_types = vec![(**expr_type).clone(); *length];
+                        self.field_offsets(value, element_types.iter().map(|expr_type| ("", expr_type)))
+                            .into_iter()
+                            .map(|(_, element)| element)
+                            .collect()
+                    },
+                    ExprType::Tuple { start, .. } => self.tuple_elements(value, start),
+                    expr_type => unreachable!("group patterns cannot match {expr_type:?}")
+                };
+
+                let end_start = elements.len() - end.len();
+                let patterns = start.iter().zip(&elements)
+                    .chain(end.iter().zip(&elements[end_start..]));
+                for (pattern, element) in patterns {
+                    if !self.match_pattern(expr_bump, pattern, element) {
+                        return false;
+                    }
+                }
+                return true;
+            },
+            MatchPattern::Fields(fields) => {
+                let struct_fields = match &value.expr_type {
+                    ExprType::Custom { ident, type_args } => {
+                        let custom_struct = self.defs.iter()
+                            .find(|custom_struct| custom_struct.name == *ident)
+                            .expect(format!("{ident} Type does not exist").as_str());
+                        custom_struct.fields.iter()
+                            .map(|field| (field.field_name.clone(), custom_struct.field_type(field, type_args)))
+                            .collect::<Vec<_>>()
+                    },
+                    ExprType::AnonymousCustom { fields } => fields.to_vec(),
+                    expr_type => unreachable!("field patterns cannot match {expr_type:?}")
+                };
+                let struct_fields = self.field_offsets(
+                    value, 
+                    struct_fields.iter().map(|(field_name, field_type)| (field_name.as_str(), field_type))
+                );
+
+                return self.match_field_patterns(expr_bump, fields, &struct_fields);
+            },
+            MatchPattern::Variant { tag, fields } => {
+                let ExprType::Enum { variants, .. } = &value.expr_type else {
+                    unreachable!("variant patterns only match enums");
+                };
+
+                let tag_size = enums::tag_size(variants.len());
+                let mut tag_bytes = [0; std::mem::size_of::<usize>()];
+                tag_bytes[..tag_size].copy_from_slice(unsafe {
+                    self.get_bytes_from_index(value.index, tag_size)
+                });
+                if usize::from_le_bytes(tag_bytes) != *tag {
+                    return false;
+                }
+
+                let payload = VariableData { index: value.index + tag_size, expr_type: ExprType::Void };
+                let variant_fields = self.field_offsets(
+                    &payload, 
+                    variants[*tag].fields.iter().map(|(field_name, field_type)| (field_name.as_str(), field_type))
+                );
+
+                return self.match_field_patterns(expr_bump, fields, &variant_fields);
+            },
+        }
+    }
+
+    fn match_field_patterns(
+        &mut self,
+        expr_bump: &'bumps ExprBump,
+        patterns: &[(String, MatchPattern)],
+        fields: &[(String, VariableData)]
+    ) -> bool {
+        for (field_name, pattern) in patterns {
+            let (_, field) = fields.iter()
+                .find(|(name, _)| name == field_name)
+                .expect(format!("field {field_name} should exist").as_str());
+            if !self.match_pattern(expr_bump, pattern, field) {
+                return false;
+            }
+        }
+        return true;
+    }
+
+    /// Where each of the fields laid out one after another from the start of `value` is.
+    fn field_offsets<'a>(
+        &self, 
+        value: &VariableData, 
+        fields: impl Iterator<Item = (&'a str, &'a ExprType)>
+    ) -> Vec<(String, VariableData)> {
+        let mut index = value.index;
+        let mut offsets = vec![];
+        for (field_name, field_type) in fields {
+            offsets.push((field_name.to_string(), VariableData { index, expr_type: field_type.clone() }));
+            index += field_type.size_of(self.defs);
+        }
+        return offsets;
+    }
+
+    /// Where each of the elements of the tuple `value` is, with the padding that aligns them.
+    fn tuple_elements(&self, value: &VariableData, element_types: &[ExprType]) -> Vec<VariableData> {
+        let (offsets, _) = ExprType::tuple_layout(element_types.iter(), self.defs);
+        return element_types.iter().zip(offsets)
+            .map(|(element_type, offset)| VariableData { index: value.index + offset, expr_type: element_type.clone() })
+            .collect();
+    }
+
     pub fn variables(&self, local_scoping: bool) -> &StackFrameDictAllocator<'i, String, VariableData> {
         if local_scoping {
             return unsafe { self.variables.last().unwrap_unchecked() };
@@ -759,18 +1380,7 @@ impl<'tkns, 'bumps, 'defs, 'i> Interpreter<'tkns, 'bumps, 'defs, 'i> {
 
         match stack_location {
             StackLocation::GC => {
-                self.gc_stack_ptr += (type_size - (self.gc_stack_ptr % type_size)) % 
-                    type_size;
-                if self.gc_stack_ptr + type_size > Self::GC_STACK_SIZE {
-                    panic!("gc stack overflow, ptr exceeded size of {} with offset {}", 
-                        Self::GC_STACK_SIZE, 
-                        self.gc_stack_ptr
-                    );
-                }
-                unsafe {
-                    self.gc_stack.as_mut_ptr().add(self.gc_stack_ptr).write_bytes(0, type_size);
-                }
-                let index = StackIndex::GC(self.gc_stack_ptr);
+                let index = StackIndex::GC(self.gc_heap.alloc(type_size));
                 let variable_data = VariableData {
                     index,
                     expr_type
@@ -778,7 +1388,6 @@ impl<'tkns, 'bumps, 'defs, 'i> Interpreter<'tkns, 'bumps, 'defs, 'i> {
                 self.variables(true).push(name, variable_data);
                 ////println!("declaring variable {name}");
                 ////self.variables().print();
-                self.gc_stack_ptr += type_size;
             },
             StackLocation::Oxy => {
                 self.oxy_stack_ptr += (type_size - (self.oxy_stack_ptr % type_size)) % 
@@ -813,7 +1422,7 @@ impl<'tkns, 'bumps, 'defs, 'i> Interpreter<'tkns, 'bumps, 'defs, 'i> {
 
         let variable_ptr = unsafe { 
             match variable_index {
-                StackIndex::GC(index) => self.gc_stack.cast::<u8>().add(index),
+                StackIndex::GC(index) => return self.gc_heap.write(index, bytes),
                 StackIndex::Oxy(index) => self.oxy_stack.cast::<u8>().add(index)
             }
         };
@@ -834,8 +1443,7 @@ impl<'tkns, 'bumps, 'defs, 'i> Interpreter<'tkns, 'bumps, 'defs, 'i> {
         Stack::stack_alloc(&mut Stack {
             oxy_stack: self.oxy_stack,
             oxy_stack_ptr: &mut self.oxy_stack_ptr,
-            gc_stack: self.gc_stack,
-            gc_stack_ptr: &mut self.gc_stack_ptr,
+            gc_heap: &mut self.gc_heap,
         }, line, self.defs, expr_type.clone(), stack_location)
     }
 
@@ -843,8 +1451,7 @@ impl<'tkns, 'bumps, 'defs, 'i> Interpreter<'tkns, 'bumps, 'defs, 'i> {
         Stack::stack_write(&mut Stack { 
             oxy_stack: self.oxy_stack,
             oxy_stack_ptr: &mut self.oxy_stack_ptr,
-            gc_stack: self.gc_stack,
-            gc_stack_ptr: &mut self.gc_stack_ptr
+            gc_heap: &mut self.gc_heap
         }, variable_data, bytes);
     }
 
@@ -912,16 +1519,58 @@ impl<'tkns, 'bumps, 'defs, 'i> Interpreter<'tkns, 'bumps, 'defs, 'i> {
             ExprType::Bool => ExprData::Literal(Lit::BooleanLiteral(u8::from_le_bytes(unsafe {
                 self.get_bytes_from_index(variable_data.index, expr_type_size).try_into().unwrap_unchecked()
             }) != 0)),
-            ExprType::Ref(_expression_type) => todo!(),
-            ExprType::MutRef(_expression_type) => todo!(),
+            ExprType::Ref { .. } => todo!(),
+            ExprType::MutRef { .. } => todo!(),
+            ExprType::ImRef { .. } => todo!(),
             ExprType::Array { .. } => todo!(),
-            ExprType::Tuple { .. } => todo!(),
+            ExprType::Range { exclusive_start, inclusive_end, expr_type: element_type } => {
+                // laid out as its start followed by its end, the same as ranges are built
+                let element_size = element_type.size_of(self.defs);
+                let bound = |index| Expr {
+                    line: 0,
+                    expr_data: self.get_expr_from_variable_data(
+                        expr_bump, 
+                        &VariableData { index, expr_type: (**element_type).clone() }
+                    ).expr_data,
+                    expr_type: ExprTypeCons::new(expr_bump, (**element_type).clone())
+                };
+                let op = match (exclusive_start, inclusive_end) {
+                    (false, false) => BinOp::Range,
+                    (false, true) => BinOp::RangeEquals,
+                    (true, false) => BinOp::BangRange,
+                    (true, true) => BinOp::BangRangeEquals
+                };
+
+                ExprData::BinaryOp(op, bound(variable_data.index), bound(variable_data.index + element_size))
+            },
+            ExprType::Tuple { start, .. } => {
+                let mut elements = vec![];
+
+                for element_data in self.tuple_elements(variable_data, start) {
+                    let element_expr = self.get_expr_from_variable_data(
+                        expr_bump,
+                        &element_data
+                    ).expr_data;
+
+                    // a value read back off the stack has no line of its own
+                    elements.push(Expr {
+                        line: 0,
+                        expr_data: element_expr,
+                        expr_type: ExprTypeCons::new(expr_bump, element_data.expr_type)
+                    });
+                }
+
+                ExprData::Tuple(elements)
+            },
             ExprType::AmbiguousGroup { .. } => todo!(),
             ExprType::Function { .. } => todo!(),
-            ExprType::FunctionPass { .. } => todo!(),
+            ExprType::FunctionPass { .. } => ExprData::Literal(Lit::IntegerLiteral(usize::from_le_bytes(unsafe {
+                self.get_bytes_from_index(variable_data.index, expr_type_size).try_into().unwrap_unchecked()
+            }) as i128)),
             ExprType::DiscardSingle => todo!(),
             ExprType::DiscardMany => todo!(),
-            ExprType::Custom { ident } => {
+            ExprType::Generic { .. } => unreachable!("only the instances of generic functions are run"),
+            ExprType::Custom { ident, type_args } => {
                 let mut fields = HashMap::new();
 
                 let custom_struct = self.defs.iter()
@@ -931,9 +1580,10 @@ impl<'tkns, 'bumps, 'defs, 'i> Interpreter<'tkns, 'bumps, 'defs, 'i> {
                 let mut index = variable_data.index;
 
                 for field in &custom_struct.fields {
+                    let field_type = custom_struct.field_type(field, type_args);
                     let field_data = VariableData {
                         index,
-                        expr_type: field.field_type.clone()
+                        expr_type: field_type.clone()
                     };
 
                     let field_expr = self.get_expr_from_variable_data(
@@ -943,7 +1593,7 @@ impl<'tkns, 'bumps, 'defs, 'i> Interpreter<'tkns, 'bumps, 'defs, 'i> {
 
                     fields.insert(field.field_name.as_str(), field_expr);
 
-                    index += field.field_type.size_of(self.defs);
+                    index += field_type.size_of(self.defs);
                 }
 
                 ExprData::Custom { fields }
@@ -971,6 +1621,39 @@ impl<'tkns, 'bumps, 'defs, 'i> Interpreter<'tkns, 'bumps, 'defs, 'i> {
                 
                 ExprData::AnonymousCustom { fields: anonymous_fields.into_boxed_slice() }
             }
+            ExprType::Enum { ident, variants } => {
+                let tag_size = enums::tag_size(variants.len());
+                let mut tag_bytes = [0; std::mem::size_of::<usize>()];
+                tag_bytes[..tag_size].copy_from_slice(unsafe {
+                    self.get_bytes_from_index(variable_data.index, tag_size)
+                });
+                let tag = usize::from_le_bytes(tag_bytes);
+
+                let variant = variants.get(tag)
+                    .expect(format!("{tag} is not a valid tag for enum {ident}").as_str());
+
+                let mut variant_fields = vec![];
+
+                let mut index = variable_data.index + tag_size;
+
+                for (field_name, field_type) in variant.fields.iter() {
+                    let field_data = VariableData {
+                        index,
+                        expr_type: field_type.clone()
+                    };
+
+                    let field_expr = self.get_expr_from_variable_data(
+                        expr_bump,
+                        &field_data
+                    ).expr_data;
+
+                    variant_fields.push((field_name.clone(), field_expr));
+
+                    index += field_type.size_of(self.defs);
+                }
+
+                ExprData::EnumVariant { tag, fields: variant_fields.into_boxed_slice() }
+            }
             ExprType::Void => todo!(),
             ExprType::Never => panic!("Encountered Never Type")
         };
@@ -984,7 +1667,7 @@ impl<'tkns, 'bumps, 'defs, 'i> Interpreter<'tkns, 'bumps, 'defs, 'i> {
     //TODO use this more to avoid unneccessary copies and allocations
     pub unsafe fn get_bytes_from_index(&self, index: StackIndex, size: usize) -> &[u8] {
         match index {
-            StackIndex::GC(i) => &self.gc_stack.as_ref()[i..i+size],
+            StackIndex::GC(i) => self.gc_heap.bytes(i, size),
             StackIndex::Oxy(i) => &self.oxy_stack.as_ref()[i..i+size],
         }
     } 
@@ -1023,6 +1706,9 @@ impl<'tkns, 'bumps, 'defs, 'i> Interpreter<'tkns, 'bumps, 'defs, 'i> {
                 //TODO detect the endian of the machine and work off that
                 vec.extend_from_slice(&(*value as u128).to_le_bytes());
             },
+            (ExprData::Literal(Lit::IntegerLiteral(value)), ExprType::FunctionPass { .. }) => {
+                vec.extend_from_slice(&(*value as usize).to_le_bytes());
+            },
             (ExprData::Literal(Lit::FloatLiteral(value)), ExprType::F32) => {
                 vec.extend_from_slice(&(*value as f32).to_le_bytes());
             },
@@ -1030,15 +1716,14 @@ impl<'tkns, 'bumps, 'defs, 'i> Interpreter<'tkns, 'bumps, 'defs, 'i> {
                 vec.extend_from_slice(&(*value as f64).to_le_bytes());
             },
             (ExprData::Literal(Lit::CharLiteral(value)), ExprType::Char) => {
-                let mut bytes = vec![];
-                value.encode_utf8(bytes.as_mut_slice());
-                vec.extend_from_slice(&bytes);
+                // a char is stored as its code point, the same as the compiled backends
+                vec.extend_from_slice(&(*value as u32).to_le_bytes());
             },
             (ExprData::Literal(Lit::StringLiteral(value)), ExprType::StringLiteral) => {
                 vec.extend_from_slice(&(value.as_ptr().addr().to_le_bytes()));
                 vec.extend_from_slice(&(value.len().to_le_bytes()));
             },
-            (ExprData::Custom {fields}, ExprType::Custom { ident }) => {
+            (ExprData::Custom {fields}, ExprType::Custom { ident, type_args }) => {
                 let custom_struct = self.defs.iter()
                     .find(|custom_struct| custom_struct.name == *ident)
                     .expect(format!("Type {ident} does not exist").as_str());
@@ -1049,31 +1734,16 @@ impl<'tkns, 'bumps, 'defs, 'i> Interpreter<'tkns, 'bumps, 'defs, 'i> {
                         Expr {
                             line,
                             expr_data: &*fields[field.field_name.as_str()],
-                            expr_type: ExprTypeCons::new(expr_bump, field.field_type.clone())
+                            expr_type: ExprTypeCons::new(expr_bump, custom_struct.field_type(field, type_args))
                         }, 
                         expected_type,
                         local_scoping
                     )?;
 
-                    let TypedExpr {
-                        expr_data: field_data,
-                        expr_type: field_type
-                    } = unsafe {
-                        self.get_expr_from_variable_data(expr_bump, &variable_data)
-                    };
-
-                    let field_data = expr_bump.alloc(field_data);
-
-                    vec.extend_from_slice(
-                        &self.to_interpreter_bytes(
-                            expr_bump,
-                            field_data,
-                            field_type, 
-                            line,
-                            expected_type,
-                            local_scoping
-                        )?
-                    );
+                    // the field is already laid out, and a reference has no literal to go through
+                    vec.extend_from_slice(unsafe {
+                        self.get_bytes_from_index(variable_data.index, variable_data.expr_type.size_of(self.defs))
+                    });
                 }
             },
             (
@@ -1097,26 +1767,67 @@ impl<'tkns, 'bumps, 'defs, 'i> Interpreter<'tkns, 'bumps, 'defs, 'i> {
                         local_scoping
                     )?;
 
-                    let TypedExpr {
-                        expr_data: field_data,
-                        expr_type: field_type
-                    } = unsafe {
-                        self.get_expr_from_variable_data(expr_bump, &variable_data)
-                    };
+                    vec.extend_from_slice(unsafe {
+                        self.get_bytes_from_index(variable_data.index, variable_data.expr_type.size_of(self.defs))
+                    });
+                }
+            }
+            (ExprData::Tuple(elements), ExprType::Tuple { start: element_types, .. }) => {
+                assert_eq!(elements.len(), element_types.len());
+                let (offsets, size) = ExprType::tuple_layout(element_types.iter(), self.defs);
+                // the padding between the elements is left zeroed
+                vec.resize(size, 0);
 
-                    let field_data = expr_bump.alloc(field_data);
+                for ((element, element_type), offset) in elements.iter().zip(element_types).zip(offsets) {
+                    let variable_data = self.evaluate_expression(
+                        expr_bump, 
+                        Expr {
+                            line,
+                            expr_data: element.expr_data,
+                            expr_type: ExprTypeCons::new(expr_bump, element_type.clone())
+                        }, 
+                        expected_type, 
+                        local_scoping
+                    )?;
 
-                    vec.extend_from_slice(
-                        &self.to_interpreter_bytes(
-                            expr_bump,
-                            field_data,
-                            field_type, 
+                    let element_size = element_type.size_of(self.defs);
+                    vec[offset..offset + element_size].copy_from_slice(unsafe {
+                        self.get_bytes_from_index(variable_data.index, element_size)
+                    });
+                }
+            },
+            (
+                ExprData::EnumVariant { tag, fields: data_fields }, 
+                ExprType::Enum { variants, .. }
+            ) => {
+                let tag_size = enums::tag_size(variants.len());
+                vec.extend_from_slice(&tag.to_le_bytes()[..tag_size]);
+
+                let type_fields = &variants[*tag].fields;
+                assert_eq!(data_fields.len(), type_fields.len());
+                for (
+                    (field_data_name, field_data), 
+                    (field_type_name, field_type)
+                ) in data_fields.iter().zip(type_fields) {
+                    assert_eq!(field_data_name, field_type_name);
+                    let variable_data = self.evaluate_expression(
+                        expr_bump, 
+                        Expr {
                             line,
-                            expected_type,
-                            local_scoping
-                        )?
-                    );
+                            expr_data: field_data,
+                            expr_type: ExprTypeCons::new(expr_bump, field_type.clone())
+                        }, 
+                        expected_type, 
+                        local_scoping
+                    )?;
+
+                    vec.extend_from_slice(unsafe {
+                        self.get_bytes_from_index(variable_data.index, variable_data.expr_type.size_of(self.defs))
+                    });
                 }
+
+                // smaller variants leave the rest of the payload zeroed
+                vec.resize(expr_type.size_of(self.defs), 0);
             }
             (expr_data, expr_type) => {
                 eprintln!("data {:?} of type {:?} is not supported", expr_data, expr_type);
@@ -1131,8 +1842,7 @@ impl<'tkns, 'bumps, 'defs, 'i> Interpreter<'tkns, 'bumps, 'defs, 'i> {
 pub struct Stack<'stack> {
     oxy_stack: NonNull<[u8]>,
     oxy_stack_ptr: &'stack mut usize,
-    gc_stack: NonNull<[u8]>, 
-    gc_stack_ptr: &'stack mut usize, 
+    gc_heap: &'stack mut GcHeap
 }
 
 impl<'stack> Stack<'stack> {
@@ -1152,23 +1862,7 @@ impl<'stack> Stack<'stack> {
         let index;
         match stack_location {
             StackLocation::GC => {
-                //TODO add actual garbage collector where everything on stack is a pointer
-
-                //align ptr
-                *self.gc_stack_ptr += (type_size - (*self.gc_stack_ptr % type_size)) % 
-                    type_size;
-                if *self.gc_stack_ptr + type_size > Interpreter::GC_STACK_SIZE {
-                    panic!("gc stack overflow, ptr exceeded size of {} with offset {}", 
-                        Interpreter::GC_STACK_SIZE, 
-                        *self.gc_stack_ptr
-                    );
-                }
-                index = StackIndex::GC(*self.gc_stack_ptr);
-                unsafe {
-                    self.gc_stack.as_mut_ptr().add(*self.gc_stack_ptr).write_bytes(0, type_size);
-                }
-
-                *self.gc_stack_ptr += type_size;
+                index = StackIndex::GC(self.gc_heap.alloc(type_size));
             },
             StackLocation::Oxy => {
                 //align ptr
@@ -1195,7 +1889,7 @@ impl<'stack> Stack<'stack> {
     pub fn stack_write(&mut self, variable_data: &VariableData, bytes: &[u8]) {
         let variable_ptr = unsafe { 
             match variable_data.index {
-                StackIndex::GC(index) => self.gc_stack.cast::<u8>().add(index),
+                StackIndex::GC(index) => return self.gc_heap.write(index, bytes),
                 StackIndex::Oxy(index) => self.oxy_stack.cast::<u8>().add(index)
             }
         };
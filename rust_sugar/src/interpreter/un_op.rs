@@ -1,6 +1,6 @@
 use crate::parser::{expr::{Expr, ExprData, ExprType, ExprTypeCons, Lit}, operators::UnOp, stmt::StackLocation, ExprBump};
 
-use super::{Interpreter, VariableData};
+use super::{Interpreter, StackIndex, VariableData};
 
 pub fn evaluate_un_op<'tkns, 'bumps, 'defs>(
     interpreter: &mut Interpreter<'tkns, 'bumps, 'defs, '_>,
@@ -12,6 +12,38 @@ pub fn evaluate_un_op<'tkns, 'bumps, 'defs>(
     local_scoping: bool,
 ) -> Option<VariableData> {
     let expr_data = interpreter.evaluate_expression(expr_bump, expr.clone(), expected_type, local_scoping)?;
+    match unary_operator {
+        // a borrow points at where its operand is, instead of copying the value
+        UnOp::Borrow | UnOp::BorrowInteriorMutable | UnOp::BorrowMutable => {
+            let pointee = Box::new(expr_data.expr_type.clone());
+            let ref_type = match unary_operator {
+                UnOp::Borrow => ExprType::Ref { lifetime: None, expr_type: pointee },
+                UnOp::BorrowMutable => ExprType::MutRef { lifetime: None, expr_type: pointee },
+                _ => ExprType::ImRef { lifetime: None, expr_type: pointee }
+            };
+            let value_data = interpreter.stack_alloc(line, &ref_type, StackLocation::Oxy);
+            interpreter.stack_write(&value_data, &expr_data.index.to_ref_bytes());
+            return Some(value_data);
+        },
+        UnOp::Deref => {
+            let (
+                ExprType::Ref { expr_type: pointee, .. } | 
+                ExprType::MutRef { expr_type: pointee, .. } | 
+                ExprType::ImRef { expr_type: pointee, .. }
+            ) = &expr_data.expr_type else {
+                panic!("line {line}: cannot dereference a value of type {:?}", expr_data.expr_type);
+            };
+            let bytes = unsafe {
+                interpreter.get_bytes_from_index(expr_data.index, expr_data.expr_type.size_of(interpreter.defs))
+            };
+            return Some(VariableData {
+                index: StackIndex::from_ref_bytes(bytes),
+                expr_type: (**pointee).clone()
+            });
+        },
+        _ => ()
+    }
+
     let expr = unsafe {
         interpreter.get_expr_from_variable_data(expr_bump, &expr_data)
     };
@@ -93,8 +125,6 @@ pub fn evaluate_un_op<'tkns, 'bumps, 'defs>(
             );
             panic!("line {line}: type {:?} for unary operation {:?} in expression {expr:?} is not supported.", expr.expr_type, unary_operator);
         }
-        UnOp::Borrow => todo!(),
-        UnOp::BorrowInteriorMutable => todo!(),
-        UnOp::BorrowMutable => todo!(),
+        UnOp::Borrow | UnOp::BorrowInteriorMutable | UnOp::BorrowMutable | UnOp::Deref => unreachable!(),
     }
 }
\ No newline at end of file
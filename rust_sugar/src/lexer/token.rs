@@ -10,22 +10,40 @@ pub struct Token {
     pub token: TknType,
     pub file_name: String,
     pub line_index: usize,
-    pub line_number: usize
+    pub line_number: usize,
+    pub span: Span,
+    /// The full path of the namespace the token is written in, filled in once namespaces are resolved
+    pub namespace: String
 }
 
 impl Token {
     pub fn new (
-        token: TknType, file_name: String, line_index: usize, line_number: usize
+        token: TknType, file_name: String, line_index: usize, line_number: usize, span: Span
     ) -> Token {
         return Token{
             token, 
             file_name, 
             line_index, 
-            line_number
+            line_number,
+            span,
+            namespace: String::new()
         };
     }
 }
 
+/// Byte offsets `start..end` of a token in its source file
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize
+}
+
+impl Span {
+    pub fn slice<'a>(&self, src: &'a str) -> &'a str {
+        return &src[self.start..self.end];
+    }
+}
+
 impl Display for Token {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{} token in file {} on line {} index {}", 
@@ -40,7 +58,11 @@ pub enum TokenType {
     Keyword(Kwrd),
     Type(Type),
     Identifier(String),
+    /// 'name, naming a loop for break and continue
+    Label(String),
     Operation(Op),
+    /// An operator ending in a backtick, like +`, which the types it is used on define
+    CustomOperation(String),
 
     IntegerLiteral { int: i128, len: usize },
     FloatLiteral { float: f64, len: usize },
@@ -83,7 +105,9 @@ impl TokenType {
             TokenType::Keyword(keyword)           => keyword.len(),
             TokenType::Type(sgr_type)             => sgr_type.len(),
             TokenType::Identifier(ident)          => ident.len(),
+            TokenType::Label(label)               => label.len() + 1,
             TokenType::Operation(operator)        => operator.len(),
+            TokenType::CustomOperation(symbol)    => symbol.len(),
             TokenType::IntegerLiteral { len, .. } => *len,
             TokenType::FloatLiteral { len, .. }   => *len,
             TokenType::CharLiteral(_)             => 1,
@@ -120,7 +144,9 @@ impl Display for TokenType {
             TokenType::Keyword(keyword) => write!(f, "keyword {keyword}"),
             TokenType::Type(sgr_type) => write!(f, "type {sgr_type}"),
             TokenType::Identifier(ident) => write!(f, "identifier {ident}"),
+            TokenType::Label(label) => write!(f, "label '{label}"),
             TokenType::Operation(operator) => write!(f, "operator {operator}"),
+            TokenType::CustomOperation(symbol) => write!(f, "operator {symbol}"),
             TokenType::IntegerLiteral {int, ..} => write!(f, "integer {int}"),
             TokenType::FloatLiteral {float, ..} => write!(f, "float {float}"),
             TokenType::CharLiteral(chr) => write!(f, "char {chr}"),
@@ -174,7 +200,9 @@ impl PartialEq for TknType {
             (TknType::Keyword(left), TknType::Keyword(right)) => left == right,
             (TknType::Type(left), TknType::Type(right)) => left == right,
             (TknType::Identifier(left), TknType::Identifier(right)) => left == right,
+            (TknType::Label(left), TknType::Label(right)) => left == right,
             (TknType::Operation(left), TknType::Operation(right)) => left == right,
+            (TknType::CustomOperation(left), TknType::CustomOperation(right)) => left == right,
             (
                 TknType::IntegerLiteral { int: left, .. }, 
                 TknType::IntegerLiteral { int: right, .. }
@@ -185,6 +213,7 @@ impl PartialEq for TknType {
             ) => left == right,
             (TknType::CharLiteral(left), TknType::CharLiteral(right)) => left == right,
             (TknType::StringLiteral(left), TknType::StringLiteral(right)) => left == right,
+            (TknType::BooleanLiteral(left), TknType::BooleanLiteral(right)) => left == right,
             (TknType::Semicolon, TknType::Semicolon) => true,
             (TknType::Dollar,TknType::Dollar) => true,
             (TknType::OpenParen,TknType::OpenParen) => true,
@@ -200,6 +229,8 @@ impl PartialEq for TknType {
             (TknType::Dot,TknType::Dot) => true,
             (TknType::Comma,TknType::Comma) => true,
             (TknType::Borrow,TknType::Borrow) => true,
+            (TknType::DiscardSingle,TknType::DiscardSingle) => true,
+            (TknType::DiscardMany,TknType::DiscardMany) => true,
             (TknType::EndOfFile,TknType::EndOfFile) => true,
             (TknType::Invalid,TknType::Invalid) => true,
 
@@ -226,14 +257,24 @@ pub enum Keyword {
 
     /// for
     For,
+    /// in
+    In,
     /// while
     While,
     /// loop
     Loop,
+    /// break
+    Break,
+    /// continue
+    Continue,
     /// if
     If,
     /// else
     Else,
+    /// match
+    Match,
+    /// as
+    As,
 
     /// mut
     Mutable,
@@ -245,6 +286,8 @@ pub enum Keyword {
     Oxidize,
     /// unsafe
     Unsafe,
+    /// move
+    Move,
     
     /// fn
     Function,
@@ -256,6 +299,12 @@ pub enum Keyword {
     Exclave,
     /// struct
     Struct,
+    /// enum
+    Enum,
+    /// trait
+    Trait,
+    /// impl
+    Impl,
     
     /// prefix
     Prefix,
@@ -268,6 +317,8 @@ pub enum Keyword {
     Namespace,
     /// alias
     Alias,
+    /// use
+    Use,
 
     /// pub
     Public,
@@ -283,25 +334,35 @@ impl Kwrd {
             Keyword::Let             => 3,
             Keyword::Return          => 6,
             Keyword::For             => 3,
+            Keyword::In              => 2,
             Keyword::While           => 5,
             Keyword::Loop            => 4,
+            Keyword::Break           => 5,
+            Keyword::Continue        => 8,
             Keyword::If              => 2,
             Keyword::Else            => 4,
+            Keyword::Match           => 5,
+            Keyword::As              => 2,
             Keyword::Mutable         => 3,
             Keyword::InteriorMutable => 2,
             Keyword::Recursive       => 3,
             Keyword::Oxidize         => 3,
             Keyword::Unsafe          => 6,
+            Keyword::Move            => 4,
             Keyword::Function        => 2,
             Keyword::Accessor        => 8,
             Keyword::Enclave         => 7,
             Keyword::Exclave         => 7,
             Keyword::Struct          => 6,
+            Keyword::Enum            => 4,
+            Keyword::Trait           => 5,
+            Keyword::Impl            => 4,
             Keyword::Prefix          => 6,
             Keyword::Infix           => 5,
             Keyword::Postfix         => 7,
             Keyword::Namespace       => 9,
             Keyword::Alias           => 5,
+            Keyword::Use             => 3,
             Keyword::Public          => 3,
             Keyword::Private         => 3,
             Keyword::Package         => 3,
@@ -315,25 +376,35 @@ impl Display for Kwrd {
             Keyword::Let => "let",
             Keyword::Return => "return",
             Keyword::For => "for",
+            Keyword::In => "in",
             Keyword::While => "while",
             Keyword::Loop => "loop",
+            Keyword::Break => "break",
+            Keyword::Continue => "continue",
             Keyword::If => "if",
             Keyword::Else => "else",
+            Keyword::Match => "match",
+            Keyword::As => "as",
             Keyword::Mutable => "mut",
             Keyword::InteriorMutable => "im",
             Keyword::Recursive => "rec",
             Keyword::Oxidize => "oxy",
             Keyword::Unsafe => "unsafe",
+            Keyword::Move => "move",
             Keyword::Function => "fn",
             Keyword::Accessor => "accessor",
             Keyword::Enclave => "enclave",
             Keyword::Exclave => "exclave",
             Keyword::Struct => "struct",
+            Keyword::Enum => "enum",
+            Keyword::Trait => "trait",
+            Keyword::Impl => "impl",
             Keyword::Prefix => "prefix",
             Keyword::Infix => "infix",
             Keyword::Postfix => "postfix",
             Keyword::Namespace => "namespace",
             Keyword::Alias => "alias",
+            Keyword::Use => "use",
             Keyword::Public => "pub",
             Keyword::Private => "prv",
             Keyword::Package => "pkg",
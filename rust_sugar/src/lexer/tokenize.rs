@@ -1,6 +1,4 @@
-use crate::string_utils::StringUtils;
-
-use super::token::{Kwrd, Op, Type, Tkn, TknType};
+use super::token::{Kwrd, Op, Span, Type, Tkn, TknType};
 
 const SINGLE_TOKEN_CHARACTERS: &str = "(){}[];?.,";
 const OPERATOR_CHARACTERS: &str = "+-*/%&|^=!~:<>.";
@@ -8,7 +6,7 @@ const OPERATORS: &[&str] = &[
     "!..=", "!..", "..=", "..",
     "++=", "+.=", "+=", "-.=", "-=", "**.=", "**=", "*.=", "*=", "/.=", "/=", "%.=", "%=",
     "&&=", "||=", "^^=", "=!", "&=", "|=", "^=", "=~", "<<=", ">>=",
-    "==", "!=", "<=", ">=", "=",
+    "=>", "==", "!=", "<=", ">=", "=",
     "++", "+.", "+", "-.", "-", "**.", "**", "*.", "*", "/.", "/", "%.", "%",
     "&&", "||", "^^", "!", "&", "|", "^", "~", "<<", ">>",
     "<", ">",
@@ -17,6 +15,7 @@ const OPERATORS: &[&str] = &[
 pub struct Lexer<'l> {
     file_name: &'l str,
     source_code: &'l str,
+    /// byte offset of the cursor into `source_code`
     index: usize,
     line_index: usize,
     line_number: usize,
@@ -45,20 +44,34 @@ impl<'l> Lexer<'l> {
         self.line_number = 1;
     }
 
-    fn consume(&mut self, num: usize) {
-        self.index += num;
+    /// Moves the cursor forward `bytes` bytes, keeping the line number and
+    /// the (character based) index into the line up to date.
+    fn consume(&mut self, bytes: usize) {
+        for chr in self.source_code[self.index..self.index + bytes].chars() {
+            match chr {
+                '\n' => {
+                    self.line_number += 1;
+                    self.line_index = 1;
+                },
+                '\r' => (),
+                _ => self.line_index += 1
+            }
+        }
+        self.index += bytes;
     }
     
     fn peek(&self) -> Option<char> {
-        return self.source_code.chars().nth(self.index);
+        return self.peek_at(self.index);
     }
     
+    /// The character starting at byte offset `index`
     fn peek_at(&self, index: usize) -> Option<char> {
-        return self.source_code.chars().nth(index);
+        return self.source_code.get(index..)?.chars().next();
     }
 
+    /// The character starting `after` bytes past the cursor
     fn peek_after(&self, after: usize) -> Option<char> {
-        return self.source_code.chars().nth(self.index + after);
+        return self.peek_at(self.index + after);
     }
     
     pub fn tokenize(&mut self) -> Vec<Tkn> {
@@ -66,6 +79,7 @@ impl<'l> Lexer<'l> {
 
         while let Some(chr) = self.peek() {
             let token: TknType;
+            let start = self.index;
             let index = self.line_index;
             let line = self.line_number;
 
@@ -78,11 +92,9 @@ impl<'l> Lexer<'l> {
                                 if chr == '\n' || chr == '\r' {
                                     break;
                                 }
-                                after += 1;
+                                after += chr.len_utf8();
                             }
-                            println!("comment: {}", self.source_code.slice(self.index..self.index+after));
                             self.consume(after);
-                            self.line_index += after;
                             continue;
                         },
                         Some(',') => {
@@ -90,8 +102,8 @@ impl<'l> Lexer<'l> {
                             let mut count = 1;
                             
                             while 
-                                let (Some(chr1), Some(chr2)) = 
-                                (self.peek_after(after), self.peek_after(after + 1)) 
+                                let Some(chr1) = self.peek_after(after) &&
+                                let Some(chr2) = self.peek_after(after + chr1.len_utf8())
                             {
                                 if chr1 == '/' && chr2 == ',' {
                                     count += 1;
@@ -99,20 +111,15 @@ impl<'l> Lexer<'l> {
                                 } else if chr1 == ',' && chr2 == '/' {
                                     count -= 1;
                                     after += 2;
-                                } else if chr1 == '\n' || chr1 == '\r' {
-                                    after += 1;
-                                    self.line_number += 1;
                                 } else {
-                                    after += 1;
+                                    after += chr1.len_utf8();
                                 }
 
                                 if count == 0 {
                                     break;
                                 }
                             }
-                            println!("comment: {}", self.source_code.slice(self.index..self.index+after));
                             self.consume(after);
-                            self.line_index += after;
                             continue;
                         },
                         _ => {
@@ -123,75 +130,65 @@ impl<'l> Lexer<'l> {
                 ',' => {
                     token = TknType::Comma;
                     self.consume(1);
-                    self.line_index += 1;
                 }
                 '$' => {
                     token = TknType::Dollar;
                     self.consume(1);
-                    self.line_index += 1;
                 },
                 '.' => {
-                    token = TknType::Dot;
-                    self.consume(1);
-                    self.line_index += 1;
+                    // "..", "..=" are operators
+                    if let Some('.') = self.peek_after(1) {
+                        token = self.get_multi_character_token();
+                    } else {
+                        token = TknType::Dot;
+                        self.consume(1);
+                    }
                 },
                 ':' => {
                     if let Some(':') = self.peek_after(1) {
                         token = TknType::ColonColon;
                         self.consume(2);
-                        self.line_index += 2;
                     } else {
                         token = TknType::Colon;
                         self.consume(1);
-                        self.line_index += 1;
                     }
                 },
                 ';' => {
                     token = TknType::Semicolon;
                     self.consume(1);
-                    self.line_index += 1;
                 },
                 '(' => {
                     token = TknType::OpenParen;
                     self.consume(1);
-                    self.line_index += 1;
                 },
                 ')' => {
                     token = TknType::CloseParen;
                     self.consume(1);
-                    self.line_index += 1;
                 },
                 '{' => {
                     token = TknType::OpenCurlyBrace;
                     self.consume(1);
-                    self.line_index += 1;
                 },
                 '}' => {
                     token = TknType::CloseCurlyBrace;
                     self.consume(1);
-                    self.line_index += 1;
                 },
                 '[' => {
                     token = TknType::OpenSquareBracket;
                     self.consume(1);
-                    self.line_index += 1;
                 },
                 ']' => {
                     token = TknType::CloseSquareBracket;
                     self.consume(1);
-                    self.line_index += 1;
                 },
                 '\n' | '\r' => {
                     self.consume(1);
-                    self.line_index = 1;
-                    self.line_number += 1;
                     
                     continue;
                 },
                 ' ' => {
                     let spaces = self.count_spaces();
                     self.consume(spaces);
-                    self.line_index += spaces;
 
                     continue;
                 },
@@ -204,7 +201,8 @@ impl<'l> Lexer<'l> {
                 token, 
                 self.file_name.to_string(), 
                 index, 
-                line
+                line,
+                Span { start, end: self.index }
             ));
         }
 
@@ -212,7 +210,8 @@ impl<'l> Lexer<'l> {
             TknType::EndOfFile, 
             self.file_name.to_string(), 
             self.line_index, 
-            self.line_number
+            self.line_number,
+            Span { start: self.index, end: self.index }
         ));
 
         return tokens;
@@ -242,11 +241,19 @@ impl<'l> Lexer<'l> {
             if !chr.is_alphanumeric() {
                 break;
             }
-            end_index += 1;
+            end_index += chr.len_utf8();
         }
 
-        multi_character = self.source_code.slice(start_index..end_index);
-        if let Some(token) = self.get_integer_literal(multi_character) {
+        multi_character = &self.source_code[start_index..end_index];
+        // a digit after the dot makes it a float, unlike the dot of a range 0..6 or of the
+        // tuple field t.0.1
+        let is_field = self.source_code[..start_index].strip_suffix('.')
+            .and_then(|before| before.chars().next_back())
+            .is_some_and(|chr| chr.is_alphanumeric() || chr == '_' || chr == ')');
+        let continues_as_float = self.peek_at(end_index) == Some('.')
+            && self.peek_at(end_index + 1).is_some_and(|chr| chr.is_ascii_digit())
+            && !is_field;
+        if !continues_as_float && let Some(token) = self.get_integer_literal(multi_character) {
             return token;
         }
 
@@ -266,10 +273,10 @@ impl<'l> Lexer<'l> {
                 }
                 contains_dot = true;
             }
-            end_index += 1;
+            end_index += chr.len_utf8();
         }
 
-        multi_character = self.source_code.slice(start_index..end_index);
+        multi_character = &self.source_code[start_index..end_index];
         if let Some(token) = self.get_float_literal(multi_character) {
             return token;
         }
@@ -281,149 +288,167 @@ impl<'l> Lexer<'l> {
             if is_invalid_character(chr) {
                 break;
             }
-            end_index += 1;
+            end_index += chr.len_utf8();
         }
 
-        multi_character = self.source_code.slice(start_index..end_index);
+        multi_character = &self.source_code[start_index..end_index];
         match multi_character {
             "let" => {
                 self.consume(3);
-                self.line_index += 3;
                 return TknType::Keyword(Kwrd::Let);
             },
             "return" => {
                 self.consume(6);
-                self.line_index += 6;
                 return TknType::Keyword(Kwrd::Return);
             },
             "if" => {
                 self.consume(2);
-                self.line_index += 2;
                 return TknType::Keyword(Kwrd::If);
             },
             "else" => {
                 self.consume(4);
-                self.line_index += 4;
                 return TknType::Keyword(Kwrd::Else);
             },
+            "match" => {
+                self.consume(5);
+                return TknType::Keyword(Kwrd::Match);
+            },
+            "as" => {
+                self.consume(2);
+                return TknType::Keyword(Kwrd::As);
+            },
             "for" => {
                 self.consume(3);
-                self.line_index += 3;
                 return TknType::Keyword(Kwrd::For);
             },
+            "in" => {
+                self.consume(2);
+                return TknType::Keyword(Kwrd::In);
+            },
             "while" => {
                 self.consume(5);
-                self.line_index += 5;
                 return TknType::Keyword(Kwrd::While);
             },
             "loop" => {
                 self.consume(4);
-                self.line_index += 4;
                 return TknType::Keyword(Kwrd::Loop);
             },
+            "break" => {
+                self.consume(5);
+                return TknType::Keyword(Kwrd::Break);
+            },
+            "continue" => {
+                self.consume(8);
+                return TknType::Keyword(Kwrd::Continue);
+            },
             "mut" => {
                 self.consume(3);
-                self.line_index += 3;
                 return TknType::Keyword(Kwrd::Mutable);
             },
             "im" => {
                 self.consume(2);
-                self.line_index += 2;
                 return TknType::Keyword(Kwrd::InteriorMutable);
             },
             "rec" => {
                 self.consume(3);
-                self.line_index += 3;
                 return TknType::Keyword(Kwrd::Recursive);
             },
             "oxy" => {
                 self.consume(3);
-                self.line_index += 3;
                 return TknType::Keyword(Kwrd::Oxidize);
             },
             "unsafe" => {
                 self.consume(6);
-                self.line_index += 6;
                 return TknType::Keyword(Kwrd::Unsafe);
             },
+            "move" => {
+                self.consume(4);
+                return TknType::Keyword(Kwrd::Move);
+            },
             "fn" => {
                 self.consume(multi_character.len());
-                self.line_index += multi_character.len();
                 return TknType::Keyword(Kwrd::Function);
             },
             "accessor" => {
                 self.consume(8);
-                self.line_index += 8;
                 return TknType::Keyword(Kwrd::Accessor);
             },
             "enclave" => {
                 self.consume(7);
-                self.line_index += 7;
                 return TknType::Keyword(Kwrd::Enclave);
             },
             "exclave" => {
                 self.consume(7);
-                self.line_index += 7;
                 return TknType::Keyword(Kwrd::Exclave);
             },
             "struct" => {
                 self.consume(multi_character.len());
-                self.line_index += multi_character.len();
                 return TknType::Keyword(Kwrd::Struct);
             },
+            "enum" => {
+                self.consume(multi_character.len());
+                return TknType::Keyword(Kwrd::Enum);
+            },
+            "trait" => {
+                self.consume(5);
+                return TknType::Keyword(Kwrd::Trait);
+            },
+            "impl" => {
+                self.consume(4);
+                return TknType::Keyword(Kwrd::Impl);
+            },
             "namespace" => {
                 self.consume(9);
-                self.line_index += 9;
                 return TknType::Keyword(Kwrd::Namespace);
             },
             "alias" => {
                 self.consume(5);
-                self.line_index += 5;
                 return TknType::Keyword(Kwrd::Alias);
             },
+            "use" => {
+                self.consume(3);
+                return TknType::Keyword(Kwrd::Use);
+            },
+            "_" => {
+                self.consume(1);
+                return TknType::DiscardSingle;
+            },
             "true" => {
                 self.consume(4);
-                self.line_index += 4;
                 return TknType::BooleanLiteral(true);
             },
             "false" => {
                 self.consume(5);
-                self.line_index += 5;
                 return TknType::BooleanLiteral(false);
             },
             "pub" => {
                 self.consume(3);
-                self.line_index += 3;
                 return TknType::Keyword(Kwrd::Public);
             },
             "prv" => {
                 self.consume(3);
-                self.line_index += 3;
                 return TknType::Keyword(Kwrd::Private);
             },
             "pkg" => {
                 self.consume(3);
-                self.line_index += 3;
                 return TknType::Keyword(Kwrd::Package);
             },
             "prefix" => {
                 self.consume(6);
-                self.line_index += 6;
                 return TknType::Keyword(Kwrd::Prefix);
             },
             "infix" => {
                 self.consume(5);
-                self.line_index += 5;
                 return TknType::Keyword(Kwrd::Infix);
             },
             "postfix" => {
                 self.consume(7);
-                self.line_index += 7;
                 return TknType::Keyword(Kwrd::Postfix);
             },
             _ => {
                 return self.get_operation()
                     .or_else(|| self.get_char_literal())
+                    .or_else(|| self.get_label(multi_character))
                     .or_else(|| self.get_string_literal())
                     .or_else(|| self.get_type(multi_character))
                     .or_else(|| self.get_identifier(multi_character))
@@ -441,16 +466,18 @@ impl<'l> Lexer<'l> {
             return None;
         }
         
-        for i in 0..OPERATORS.len() {
-            if self.source_code.len() - self.index < OPERATORS[i].len() {
-                continue;
-            }
-            
-            let operator = self.source_code.slice(self.index..self.index + OPERATORS[i].len());
-            if operator == OPERATORS[i] {
-                self.consume(OPERATORS[i].len());
-                self.line_index += OPERATORS[i].len();
-                return match operator {
+        let remaining = &self.source_code[self.index..];
+        let symbol_len = remaining.find(|chr| !OPERATOR_CHARACTERS.contains(chr)).unwrap_or(remaining.len());
+        if remaining[symbol_len..].starts_with('`') {
+            let symbol = remaining[..=symbol_len].to_string();
+            self.consume(symbol.len());
+            return Some(TknType::CustomOperation(symbol));
+        }
+
+        for operator in OPERATORS {
+            if remaining.starts_with(operator) {
+                self.consume(operator.len());
+                return match *operator {
                     "!..=" => Some(TknType::Operation(Op::BangRangeEquals)), 
                     "!.." => Some(TknType::Operation(Op::BangRange)), 
                     "..=" => Some(TknType::Operation(Op::RangeEquals)), 
@@ -521,7 +548,6 @@ impl<'l> Lexer<'l> {
         match multi_character.parse() {
             Ok(int) => {
                 self.consume(multi_character.len());
-                self.line_index += multi_character.len();
                 return Some(TknType::IntegerLiteral { 
                     int, 
                     len: multi_character.len() 
@@ -535,7 +561,6 @@ impl<'l> Lexer<'l> {
         match multi_character.parse() {
             Ok(float) => {
                 self.consume(multi_character.len());
-                self.line_index += multi_character.len();
                 return Some(TknType::FloatLiteral {
                     float,
                     len: multi_character.len()
@@ -555,26 +580,36 @@ impl<'l> Lexer<'l> {
         let mut index = self.index + 1;
         while let Some(chr) = self.peek_at(index) {
             if chr == '\\' {
-                index += 2;
+                index += 1 + self.peek_at(index + 1).map_or(0, char::len_utf8);
             } else if chr == '\'' {
                 break;
             } else {
-                index += 1;
+                index += chr.len_utf8();
             }
         }
 
         let len = index - self.index + 1;
-        let multi_character = self.source_code.slice(self.index + 1..index);
+        let multi_character = self.source_code.get(self.index + 1..index)?;
         match multi_character.parse() {
             Ok(chr) => {
                 self.consume(len);
-                self.line_index += len;
                 return Some(TknType::CharLiteral(chr));
             },
             Err(_) => return None
         }
     }
 
+    fn get_label(&mut self, multi_character: &str) -> Option<TknType> {
+        let label = multi_character.strip_prefix('\'')?;
+        // 'a' is a char literal, 'a is a label
+        if label.chars().next()?.is_numeric() || label.contains('\'') {
+            return None;
+        }
+
+        self.consume(multi_character.len());
+        return Some(TknType::Label(label.to_string()));
+    }
+
     fn get_string_literal(&mut self) -> Option<TknType> {
         match self.peek() {
             Some('\"') => (),
@@ -584,16 +619,15 @@ impl<'l> Lexer<'l> {
         let mut index = self.index + 1;
         while let Some(chr) = self.peek_at(index) {
             if chr == '\\' {
-                index += 2;
+                index += 1 + self.peek_at(index + 1).map_or(0, char::len_utf8);
                 continue;
             } else if chr == '\"' {
                 let len = index - self.index + 1;
-                let multi_character = self.source_code.slice(self.index + 1..index);
+                let multi_character = &self.source_code[self.index + 1..index];
                 self.consume(len);
-                self.line_index += len;
                 return Some(TknType::StringLiteral(multi_character.to_string()));
             } else {
-                index += 1;
+                index += chr.len_utf8();
                 continue;
             }
         }
@@ -606,72 +640,58 @@ impl<'l> Lexer<'l> {
         match multi_character {
             "i8" => {
                 self.consume(2);
-                self.line_index += 2;
                 return Some(TknType::Type(Type::I8));
             },
             "i16" => {
                 self.consume(3);
-                self.line_index += 3;
                 return Some(TknType::Type(Type::I16));
             },
             "i32" => {
                 self.consume(3);
-                self.line_index += 3;
                 return Some(TknType::Type(Type::I32));
             },
             "i64" => {
                 self.consume(3);
-                self.line_index += 3;
                 return Some(TknType::Type(Type::I64));
             },
             "i128" => {
                 self.consume(4);
-                self.line_index += 4;
                 return Some(TknType::Type(Type::I128));
             },
             "u8" => {
                 self.consume(2);
-                self.line_index += 2;
                 return Some(TknType::Type(Type::U8));
             },
             "u16" => {
                 self.consume(3);
-                self.line_index += 3;
                 return Some(TknType::Type(Type::U16));
             },
             "u32" => {
                 self.consume(3);
-                self.line_index += 3;
                 return Some(TknType::Type(Type::U32));
             },
             "u64" => {
                 self.consume(3);
-                self.line_index += 3;
                 return Some(TknType::Type(Type::U64));
             },
             "u128" => {
                 self.consume(4);
-                self.line_index += 4;
                 return Some(TknType::Type(Type::U128));
             },
             "f32" => {
                 self.consume(3);
-                self.line_index += 3;
                 return Some(TknType::Type(Type::F32));
             },
             "f64" => {
                 self.consume(3);
-                self.line_index += 3;
                 return Some(TknType::Type(Type::F64));
             },
             "char" => {
                 self.consume(4);
-                self.line_index += 4;
                 return Some(TknType::Type(Type::Char));
             },
             "bool" => {
                 self.consume(4);
-                self.line_index += 4;
                 return Some(TknType::Type(Type::Bool));
             },
             _ => (),
@@ -681,32 +701,22 @@ impl<'l> Lexer<'l> {
     }
 
     fn get_identifier(&mut self, multi_character: &str) -> Option<TknType> {
-        let mut break_index: usize = 0;
-
-        if let Some(chr) = multi_character.chars().nth(0) {
-            if chr.is_numeric() {
-                return None;
-            }
-        } else {
+        if multi_character.chars().next()?.is_numeric() {
             return None;
         }
 
-        while let Some(chr) = multi_character.chars().nth(break_index) {
-            if chr == ':' || chr == '.' {
-                break;
-            }
-            break_index += 1;
-        }
+        let break_index = multi_character.find([':', '.']).unwrap_or(multi_character.len());
         
-        let multi_character = multi_character.slice(0..break_index);
+        let multi_character = &multi_character[..break_index];
         self.consume(break_index);
-        self.line_index += break_index;
 
         return Some(TknType::Identifier(multi_character.to_string()));
     }
 
     fn get_invalid(&mut self, multi_character: &str) -> TknType {
-        self.consume(multi_character.len());
+        // always make progress, even on a lone character no token can start with
+        let len = multi_character.len().max(self.peek().map_or(0, char::len_utf8));
+        self.consume(len);
         return TknType::Invalid;
     }
 }
@@ -19,5 +19,4 @@ pub mod interpreter;
 pub mod lexer;
 pub mod parser;
 pub mod full_result;
-pub mod string_utils;
 pub mod term;
\ No newline at end of file
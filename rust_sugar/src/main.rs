@@ -3,19 +3,26 @@
 
 use std::env;
 use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
 
 use once_cell::sync::OnceCell;
+use sugar::compiler;
 use sugar::interpreter;
 use sugar::{
     lexer::{
         self, 
-        //token::Token
+        token::Tkn
     }, 
     parser::{
         self, 
         accessors::Accessor, 
+        enums::{Enum, Variant},
+        expr::ExprType,
         functions::{Fun, FnParam},
+        parser_error::Sources,
         structs::{Field, Struct},
+        traits::{Trait, SELF_TYPE},
         ExprBump,
         StmtBump,
         FnParamBump,
@@ -23,7 +30,7 @@ use sugar::{
     }
 };
 
-fn main() {
+fn main() -> ExitCode {
     let args = env::args().collect::<Vec<_>>();
     let mut files = vec![];
     let mut settings = Settings::default();
@@ -37,7 +44,7 @@ fn main() {
         Some("run") => Command::Run,
         Some(command) => {
             println!("Could not recognize command {}, try help to see commands", command);
-            return;
+            return ExitCode::FAILURE;
         }
     };
 
@@ -64,7 +71,7 @@ fn main() {
                     "cannot apply flag --minimal because lexer message setting is already defined as {:?}", 
                     settings.message_settings
                 );
-                return;
+                return ExitCode::FAILURE;
             } else {
                 settings.message_settings = MessageSetting::Minimal;
             },
@@ -73,13 +80,13 @@ fn main() {
                     "cannot apply flag --verbose because lexer message setting is already defined as {:?}", 
                     settings.message_settings
                 );
-                return;
+                return ExitCode::FAILURE;
             } else {
                 settings.message_settings = MessageSetting::Verbose;
             },
             arg => {
                 println!("could not recognize flag {}", arg);
-                return;
+                return ExitCode::FAILURE;
             }
         }
         index += 1;
@@ -108,31 +115,38 @@ fn main() {
                 "\t--minimal - prints the lexed tokens with minimal information\n",
                 "\t--verbose - prints the lexed tokens with all their information\n"
             )),
-            Some("build") => println!("not implemented yet\n"),
-            Some("run") => println!("not implemented yet \n"),
+            Some("build") => println!("{}", concat!(
+                "Compiles the provided file paths into an executable next to the source file.\n",
+                "The program is lowered to C99 and built with the system C compiler ($CC, or cc if unset).\n",
+                "The C backend does not support references, tuples, arrays, function values, ranges outside\n",
+                "of for loops or string concatenation yet; programs using them only work with interpret.\n"
+            )),
+            Some("run") => println!("{}", concat!(
+                "JIT compiles the provided file paths in memory and runs the result.\n",
+                "The program is lowered to C99, built with the system C compiler ($CC, or cc if unset)\n",
+                "and loaded into the running process; no executable is written to disk.  Outside of Linux\n",
+                "it is built into a temporary executable instead, which is removed once it has run.\n",
+                "The C backend does not support references, tuples, arrays, function values, ranges outside\n",
+                "of for loops or string concatenation yet; programs using them only work with interpret.\n"
+            )),
             Some(command) => println!("Could not recognize command {command}\n")
         }
-        return;
+        return ExitCode::SUCCESS;
     }
 
-    if files.len() > 1 {
-        println!("currently multiple files are not supported, please provide only 1 file");
-        return;
-    } else if files.len() == 0 {
+    if files.len() == 0 {
         println!("no file is provided.  A file is needed for the command");
-        return;
+        return ExitCode::FAILURE;
     }
 
-    match command {
+    return match command {
         Command::Lex => lex(files, settings),
         Command::Parse => parse(files, settings),
         Command::Interpret => interpret(files, settings),
-        Command::Build => println!("not implemented yet"),
-        Command::Run => println!("not implemented yet"),
+        Command::Build => build(files, settings),
+        Command::Run => run(files, settings),
         Command::Help => unreachable!()
-    }
-
-    return;
+    };
 }
 
 #[derive(PartialEq)]
@@ -163,32 +177,42 @@ impl Default for MessageSetting {
     }
 }
 
-fn lex(filepaths: Vec<&str>, settings: Settings) {
-    let filepath = filepaths.first().unwrap();
-    let contents = fs::read_to_string(filepath).unwrap();
-    let mut lexer = lexer::tokenize::Lexer::new(&filepath, &contents);
-    let tokens = lexer.tokenize();
+/// Reads and tokenizes every file, keeping their source code around for error messages.
+fn lex_files(filepaths: &[&str]) -> (Vec<Vec<Tkn>>, Sources) {
+    let mut files = vec![];
+    let mut sources = Sources::new();
+    for filepath in filepaths {
+        let contents = fs::read_to_string(filepath).unwrap();
+        let mut lexer = lexer::tokenize::Lexer::new(filepath, &contents);
+        files.push(lexer.tokenize());
+        sources.add(filepath, contents);
+    }
+    return (files, sources);
+}
 
-    match settings.message_settings {
-        MessageSetting::Default |
-        MessageSetting::Minimal => {
-            print!("[");
-            for token in tokens {
-                print!("{}, ", token.token);
+fn lex(filepaths: Vec<&str>, settings: Settings) -> ExitCode {
+    let (files, _) = lex_files(&filepaths);
+
+    for tokens in files {
+        match settings.message_settings {
+            MessageSetting::Default |
+            MessageSetting::Minimal => {
+                print!("[");
+                for token in tokens {
+                    print!("{}, ", token.token);
+                }
+                println!("]");
+            },
+            MessageSetting::Verbose => {
+                println!("{tokens:#?}");
             }
-            println!("]");
-        },
-        MessageSetting::Verbose => {
-            println!("{tokens:#?}");
         }
     }
+    return ExitCode::SUCCESS;
 }
 
-fn parse(filepaths: Vec<&str>, settings: Settings) {
-    let filepath = filepaths.first().unwrap();
-    let contents = fs::read_to_string(filepath).unwrap();
-    let mut lexer = lexer::tokenize::Lexer::new(&filepath, &contents);
-    let tokens = lexer.tokenize();
+fn parse(filepaths: Vec<&str>, settings: Settings) -> ExitCode {
+    let (files, sources) = lex_files(&filepaths);
 
     let expr_bump = ExprBump::new();
     let stmt_bump = StmtBump::new();
@@ -196,19 +220,22 @@ fn parse(filepaths: Vec<&str>, settings: Settings) {
 
     let accessors = OnceCell::new();
     let structs = OnceCell::new();
+    let enums = OnceCell::new();
+    let traits = OnceCell::new();
     let functions = OnceCell::new();
+    let tokens = OnceCell::new();
 
     match settings.message_settings {
         MessageSetting::Default |
         MessageSetting::Minimal => {
             match parser::parse(
                 &expr_bump, &stmt_bump, &fn_param_bump, 
-                &accessors, &structs, &functions, 
-                &tokens
+                &accessors, &structs, &enums, &traits, &functions, 
+                &tokens, &files
             ) {
                 Ok(()) => {
                     println!("parsed accessors:\n");
-                    for Accessor { ref name, ref whitelist, ref blacklist } in accessors.get().unwrap() {
+                    for Accessor { name, whitelist, blacklist, .. } in accessors.get().unwrap() {
                         print!("{name} whitelists [");
                         for white in whitelist {
                             print!("{white}, ");
@@ -222,8 +249,13 @@ fn parse(filepaths: Vec<&str>, settings: Settings) {
                     print!("\n");
         
                     println!("parsed structs:\n");
-                    for Struct { accessibility, location: _, name, fields } in structs.get().unwrap() {
-                        println!("{name} with {accessibility} accessibility and fields {{");
+                    for Struct { accessibility, location: _, file_name: _, line: _, name, type_params, fields } in structs.get().unwrap() {
+                        let type_params = if type_params.is_empty() {
+                            String::new()
+                        } else {
+                            format!("<{}>", type_params.join(", "))
+                        };
+                        println!("{name}{type_params} with {accessibility} accessibility and fields {{");
                         for Field { accessibility, field_name, field_type } in fields {
                             println!("\t{field_name} of type {field_type:?} and accessibility {accessibility},");
                         }
@@ -231,10 +263,32 @@ fn parse(filepaths: Vec<&str>, settings: Settings) {
                     }
                     print!("\n");
         
+                    println!("parsed enums:\n");
+                    for Enum { accessibility, location: _, name, variants } in enums.get().unwrap() {
+                        println!("{name} with {accessibility} accessibility and variants {{");
+                        for Variant { variant_name, shape, fields } in variants {
+                            println!("\t{variant_name} of shape {shape:?} and fields {fields:?},");
+                        }
+                        println!("}}")
+                    }
+                    print!("\n");
+        
+                    println!("parsed traits:\n");
+                    for Trait { accessibility, location: _, name, methods, implementations } in traits.get().unwrap() {
+                        println!("{name} with {accessibility} accessibility and methods {{");
+                        for method in methods {
+                            println!("\t{} {},", method.name, method.signature(&ExprType::Custom { ident: SELF_TYPE.to_string(), type_args: vec![] }));
+                        }
+                        println!("}} implemented for {implementations:?}")
+                    }
+                    print!("\n");
+        
                     println!("parsed functions:\n");
                     for Fun { 
                         accessibility, 
                         location: _, 
+                        file_name: _, 
+                        line: _, 
                         name, 
                         mutable, 
                         recursive, 
@@ -263,16 +317,19 @@ fn parse(filepaths: Vec<&str>, settings: Settings) {
                     }
                     print!("\n");
                 },
-                Err(errors) => for parser_error in errors {
-                    parser_error.write(&mut std::io::stdout(), contents.as_str()).unwrap();
+                Err(errors) => {
+                    for parser_error in errors {
+                        parser_error.write(&mut std::io::stdout(), &sources).unwrap();
+                    }
+                    return ExitCode::FAILURE;
                 }
             };
         },
         MessageSetting::Verbose => {
             match parser::parse(
                 &expr_bump, &stmt_bump, &fn_param_bump,
-                &accessors, &structs, &functions,
-                &tokens
+                &accessors, &structs, &enums, &traits, &functions,
+                &tokens, &files
             ) {
                 Ok(()) => {
                     println!("accessors:");
@@ -287,25 +344,32 @@ fn parse(filepaths: Vec<&str>, settings: Settings) {
                     }
                     println!("");
 
+                    println!("enums:");
+                    for dbg_enum in enums.get().unwrap() {
+                        println!("{dbg_enum:#?}");
+                    }
+                    println!("");
+
                     println!("accessors:");
                     for function in functions.get().unwrap() {
                         println!("{function:#?}");
                     }
                     println!("")
                 },
-                Err(errors) => for parser_error in errors {
-                    parser_error.write(&mut std::io::stdout(), contents.as_str()).unwrap();
+                Err(errors) => {
+                    for parser_error in errors {
+                        parser_error.write(&mut std::io::stdout(), &sources).unwrap();
+                    }
+                    return ExitCode::FAILURE;
                 }
             }
         }
     }
+    return ExitCode::SUCCESS;
 }
 
-fn interpret(filepaths: Vec<&str>, settings: Settings) {
-    let filepath = filepaths.first().unwrap();
-    let contents = fs::read_to_string(filepath).unwrap();
-    let mut lexer = lexer::tokenize::Lexer::new(&filepath, &contents);
-    let tokens = lexer.tokenize();
+fn interpret(filepaths: Vec<&str>, settings: Settings) -> ExitCode {
+    let (files, sources) = lex_files(&filepaths);
 
     let expr_bump = ExprBump::new();
     let stmt_bump = StmtBump::new();
@@ -313,9 +377,12 @@ fn interpret(filepaths: Vec<&str>, settings: Settings) {
 
     let accessors = OnceCell::new();
     let structs = OnceCell::new();
+    let enums = OnceCell::new();
+    let traits = OnceCell::new();
     let functions = OnceCell::new();
+    let tokens = OnceCell::new();
 
-    let parsed = parser::parse(&expr_bump, &stmt_bump, &fn_param_bump, &accessors, &structs, &functions, &tokens);
+    let parsed = parser::parse(&expr_bump, &stmt_bump, &fn_param_bump, &accessors, &structs, &enums, &traits, &functions, &tokens, &files);
 
     match parsed {
         Err(errors) => {
@@ -323,15 +390,15 @@ fn interpret(filepaths: Vec<&str>, settings: Settings) {
                 MessageSetting::Default |
                 MessageSetting::Minimal => {
                     for parser_error in errors {
-                        parser_error.write(&mut std::io::stdout(), contents.as_str()).unwrap();
+                        parser_error.write(&mut std::io::stdout(), &sources).unwrap();
                     }
-                    return;
+                    return ExitCode::FAILURE;
                 },
                 MessageSetting::Verbose => {
                     for parser_error in errors {
-                        parser_error.write(&mut std::io::stdout(), contents.as_str()).unwrap();
+                        parser_error.write(&mut std::io::stdout(), &sources).unwrap();
                     }
-                    return;
+                    return ExitCode::FAILURE;
                 }
             }
         },
@@ -341,4 +408,76 @@ fn interpret(filepaths: Vec<&str>, settings: Settings) {
     let mut interpreter = interpreter::Interpreter::new((accessors.get().unwrap(), structs.get().unwrap(), functions.get().unwrap()));
     //println!("starting");
     interpreter.interpret(&expr_bump);
-}
\ No newline at end of file
+    return ExitCode::SUCCESS;
+}
+
+fn build(filepaths: Vec<&str>, _settings: Settings) -> ExitCode {
+    let (files, sources) = lex_files(&filepaths);
+
+    let expr_bump = ExprBump::new();
+    let stmt_bump = StmtBump::new();
+    let fn_param_bump = FnParamBump::new();
+
+    let accessors = OnceCell::new();
+    let structs = OnceCell::new();
+    let enums = OnceCell::new();
+    let traits = OnceCell::new();
+    let functions = OnceCell::new();
+    let tokens = OnceCell::new();
+
+    let parsed = parser::parse(&expr_bump, &stmt_bump, &fn_param_bump, &accessors, &structs, &enums, &traits, &functions, &tokens, &files);
+
+    if let Err(errors) = parsed {
+        for parser_error in errors {
+            parser_error.write(&mut std::io::stdout(), &sources).unwrap();
+        }
+        return ExitCode::FAILURE;
+    }
+
+    let filepath = filepaths.first().unwrap();
+    let mut output = Path::new(filepath).with_extension("");
+    if output == Path::new(filepath) {
+        output.set_extension("out");
+    }
+
+    match compiler::build(structs.get().unwrap(), functions.get().unwrap(), &output) {
+        Ok(()) => {
+            println!("built {}", output.display());
+            return ExitCode::SUCCESS;
+        },
+        Err(error) => {
+            error.write(&mut std::io::stdout(), filepaths.first().unwrap(), &sources).unwrap();
+            return ExitCode::FAILURE;
+        }
+    }
+}
+
+fn run(filepaths: Vec<&str>, _settings: Settings) -> ExitCode {
+    let (files, sources) = lex_files(&filepaths);
+
+    let expr_bump = ExprBump::new();
+    let stmt_bump = StmtBump::new();
+    let fn_param_bump = FnParamBump::new();
+
+    let accessors = OnceCell::new();
+    let structs = OnceCell::new();
+    let enums = OnceCell::new();
+    let traits = OnceCell::new();
+    let functions = OnceCell::new();
+    let tokens = OnceCell::new();
+
+    let parsed = parser::parse(&expr_bump, &stmt_bump, &fn_param_bump, &accessors, &structs, &enums, &traits, &functions, &tokens, &files);
+
+    if let Err(errors) = parsed {
+        for parser_error in errors {
+            parser_error.write(&mut std::io::stdout(), &sources).unwrap();
+        }
+        return ExitCode::FAILURE;
+    }
+
+    if let Err(error) = compiler::run(structs.get().unwrap(), functions.get().unwrap()) {
+        error.write(&mut std::io::stdout(), filepaths.first().unwrap(), &sources).unwrap();
+        return ExitCode::FAILURE;
+    }
+    return ExitCode::SUCCESS;
+}
@@ -1,20 +1,106 @@
 use crate::{full_result::{FullResult, OptionToFullResult}, lexer::token::{Kwrd, Tkn, TknType}};
 
-use super::{tokens, ParserError};
+use super::{functions::FullFnDef, structs::{Field, Struct}, tokens, ParserError};
 
 #[derive(Debug)]
 pub struct AccessorDefinition<'tkns> {
+    pub tkn: &'tkns Tkn,
     pub name: String,
     pub body_tokens: &'tkns [Tkn],
 }
 
-#[derive(Debug)]
+/// `accessor Name { enclave { ... } exclave { ... } }`, where both lists hold the full paths of 
+/// namespaces.  A namespace also covers the namespaces inside of it.
+#[derive(Clone, Debug, PartialEq)]
 pub struct Accessor {
+    /// The name of the accessor where it is defined
+    pub tkn: Tkn,
     pub name: String,
     pub whitelist: Vec<String>,
     pub blacklist: Vec<String>
 }
 
+impl Accessor {
+    /// Code in the namespace an item is declared in can always use it.  Anywhere else has to be 
+    /// enclaved, when the accessor enclaves anything, and must not be exclaved.
+    pub fn allows(&self, namespace: &str, location: &str) -> bool {
+        if is_within(namespace, location) {
+            return true;
+        }
+        if self.blacklist.iter().any(|exclaved| is_within(namespace, exclaved)) {
+            return false;
+        }
+        return self.whitelist.is_empty() 
+            || self.whitelist.iter().any(|enclaved| is_within(namespace, enclaved));
+    }
+}
+
+/// Where a struct, field, enum or function can be used from
+#[derive(Clone, Debug, PartialEq)]
+pub enum Accessibility {
+    /// `pub`, usable from anywhere
+    Public,
+    /// `prv`, usable from the namespace it is declared in and the namespaces inside of it
+    Private(Tkn),
+    /// `pkg`, usable from the file it is declared in
+    Package(Tkn),
+    Accessor(Accessor),
+}
+
+impl Accessibility {
+    /// Whether the code at `tkn` can use something declared in the namespace `location` of `file_name`.
+    pub fn allows(&self, tkn: &Tkn, location: &str, file_name: &str) -> bool {
+        match self {
+            Accessibility::Public => return true,
+            // every namespace is inside the root one
+            Accessibility::Private(_) => return location.is_empty() || is_within(&tkn.namespace, location),
+            Accessibility::Package(_) => return tkn.file_name == file_name,
+            Accessibility::Accessor(accessor) => return accessor.allows(&tkn.namespace, location),
+        }
+    }
+}
+
+impl std::fmt::Display for Accessibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Accessibility::Public => write!(f, "public"),
+            Accessibility::Private(_) => write!(f, "private"),
+            Accessibility::Package(_) => write!(f, "package"),
+            Accessibility::Accessor(accessor) => write!(f, "{}", accessor.name),
+        }
+    }
+}
+
+/// Rejects using `field` of `custom_struct` at `tkn` when the field is not accessible from there.
+pub fn check_field<'tkns, 'bumps, 'defs>(
+    tkn: &'tkns Tkn, 
+    custom_struct: &'defs Struct, 
+    field: &'defs Field
+) -> Result<(), ParserError<'tkns, 'bumps, 'defs>> {
+    if field.accessibility.allows(tkn, &custom_struct.location, &custom_struct.file_name) {
+        return Ok(());
+    }
+    return Err(ParserError::FieldNotAccessible { tkn, custom_struct, field });
+}
+
+/// Rejects naming `function` at `tkn` when the function is not accessible from there.
+pub fn check_function<'tkns, 'bumps, 'defs>(
+    tkn: &'tkns Tkn, 
+    function: &FullFnDef<'tkns, 'bumps, 'defs>
+) -> Result<(), ParserError<'tkns, 'bumps, 'defs>> {
+    if function.accessibility.allows(tkn, &function.location, &function.file_name) {
+        return Ok(());
+    }
+    return Err(ParserError::FunctionNotAccessible { tkn, function: function.clone() });
+}
+
+/// Whether `namespace` is `outer` or nested inside of it.  The root namespace is only within itself, 
+/// so that an accessor on a root item still keeps out the namespaces it exclaves.
+fn is_within(namespace: &str, outer: &str) -> bool {
+    return namespace == outer 
+        || namespace.strip_prefix(outer).is_some_and(|rest| rest.starts_with("::"));
+}
+
 pub fn define_accessor<'tkns, 'exprs, 'defs>(
     tokens: &'tkns [Tkn], 
     index: &mut usize
@@ -23,6 +109,7 @@ pub fn define_accessor<'tkns, 'exprs, 'defs>(
     let name: String;
 
     tokens::expect_token(tokens, TknType::Keyword(Kwrd::Accessor), &mut peek).ok_or_soft::<_, Vec<_>>(())?;
+    let tkn = &tokens[peek];
     if let Some(TknType::Identifier(ident)) = tokens.get(peek).map(|e| &e.token) {
         name = ident.clone();
         peek += 1;
@@ -55,6 +142,7 @@ pub fn define_accessor<'tkns, 'exprs, 'defs>(
                 body_tokens = &tokens[start..end];
                 *index = peek;
                 return FullResult::Ok(AccessorDefinition { 
+                    tkn,
                     name, 
                     body_tokens
                 });
@@ -80,7 +168,7 @@ pub fn parse_accessor<'tkns, 'exprs, 'defs>(
     accessor_definition: &AccessorDefinition<'tkns>
 ) -> Result<Accessor, Vec<ParserError<'tkns, 'exprs, 'defs>>> {
     let mut peek = 0;
-    let AccessorDefinition { name, body_tokens } = accessor_definition;
+    let AccessorDefinition { tkn, name, body_tokens } = accessor_definition;
     let mut errors = vec![];
 
     let mut whitelist = vec![];
@@ -117,10 +205,10 @@ pub fn parse_accessor<'tkns, 'exprs, 'defs>(
             }
 
             'whitelist_body: loop {
-                if peek > body_tokens.len() {
+                if peek >= body_tokens.len() {
                     if expect_closing_brace {
                         errors.push(ParserError::ExpectedClosingBrace { 
-                            tkn: &body_tokens[peek - 2], 
+                            tkn: &body_tokens[peek - 1], 
                             open_brace: unsafe { open_brace.unwrap_unchecked() } 
                         });
                     }
@@ -134,10 +222,9 @@ pub fn parse_accessor<'tkns, 'exprs, 'defs>(
                     break 'whitelist_body;
                 } else if tokens::is_token(body_tokens, TknType::Keyword(Kwrd::Exclave), peek) {
                     break 'whitelist_body;
-                } else if let Some(Tkn { 
-                    token: TknType::Identifier(ident), .. 
-                }) = tokens::get_token(body_tokens, peek) {
-                    whitelist.push(ident.clone());
+                } else if let Some(path) = get_namespace_path(body_tokens, &mut peek) {
+                    whitelist.push(path);
+                    tokens::expect_token(&body_tokens, TknType::Comma, &mut peek);
                 } else {
                     errors.push(ParserError::ExpectedEndOfWhitelist { tkn: &body_tokens[peek] });
                     peek += 1;
@@ -169,10 +256,10 @@ pub fn parse_accessor<'tkns, 'exprs, 'defs>(
             }
 
             'blacklist_body: loop {
-                if peek > body_tokens.len() {
+                if peek >= body_tokens.len() {
                     if expect_closing_brace {
                         errors.push(ParserError::ExpectedClosingBrace { 
-                            tkn: &body_tokens[peek - 2], 
+                            tkn: &body_tokens[peek - 1], 
                             open_brace: unsafe { open_brace.unwrap_unchecked() } 
                         });
                     }
@@ -184,12 +271,11 @@ pub fn parse_accessor<'tkns, 'exprs, 'defs>(
                 ) {
                     tokens::expect_token(&body_tokens, TknType::Comma, &mut peek);
                     break 'blacklist_body;
-                } else if tokens::is_token(body_tokens, TknType::Keyword(Kwrd::Exclave), peek) {
+                } else if tokens::is_token(body_tokens, TknType::Keyword(Kwrd::Enclave), peek) {
                     break 'blacklist_body;
-                } else if let Some(Tkn { 
-                    token: TknType::Identifier(ident), .. 
-                }) = tokens::get_token(body_tokens, peek) {
-                    blacklist.push(ident.clone());
+                } else if let Some(path) = get_namespace_path(body_tokens, &mut peek) {
+                    blacklist.push(path);
+                    tokens::expect_token(&body_tokens, TknType::Comma, &mut peek);
                 } else {
                     errors.push(ParserError::ExpectedEndOfBlacklist { 
                         tkn: &body_tokens[peek] 
@@ -208,6 +294,7 @@ pub fn parse_accessor<'tkns, 'exprs, 'defs>(
 
     if errors.is_empty() {
         return Ok(Accessor {
+            tkn: (*tkn).clone(),
             name: name.clone(),
             whitelist,
             blacklist
@@ -216,36 +303,56 @@ pub fn parse_accessor<'tkns, 'exprs, 'defs>(
     return Err(errors);
 }
 
-pub fn get_accessor_string(
+/// Reads a namespace path like `shapes::geometry` out of an accessor's enclave or exclave list.
+fn get_namespace_path(tokens: &[Tkn], index: &mut usize) -> Option<String> {
+    let mut peek = *index;
+    let mut path = String::new();
+    loop {
+        let Some(TknType::Identifier(ident)) = tokens.get(peek).map(|e| &e.token) else {
+            return None;
+        };
+        path += ident;
+        peek += 1;
+
+        if !tokens::is_expected_token(tokens, TknType::ColonColon, &mut peek) {
+            break;
+        }
+        path += "::";
+    }
+    *index = peek;
+    return Some(path);
+}
+
+pub fn get_accessibility(
     token: Option<&Tkn>,
     index: &mut usize,
-    accessors: &[&str]
-) -> Option<String> {
+    accessors: &[Accessor]
+) -> Option<Accessibility> {
     match &token {
         Some(Tkn {token: TknType::Identifier(ident), ..}) => {
             for i in accessors {
-                if i == ident {
+                if i.name == *ident {
                     *index += 1;
-                    return Some(ident.clone());
+                    return Some(Accessibility::Accessor(i.clone()));
                 }
             }
             return None;
         },
         Some(Tkn {token: TknType::Keyword(Kwrd::Public), ..}) => {
             *index += 1;
-            return Some("public".to_string());
+            return Some(Accessibility::Public);
         },
-        Some(Tkn {token: TknType::Keyword(Kwrd::Private), ..}) => {
+        Some(tkn @ Tkn {token: TknType::Keyword(Kwrd::Private), ..}) => {
             *index += 1;
-            return Some("private".to_string());
+            return Some(Accessibility::Private((*tkn).clone()));
         },
-        Some(Tkn {token: TknType::Keyword(Kwrd::Package), ..}) => {
+        Some(tkn @ Tkn {token: TknType::Keyword(Kwrd::Package), ..}) => {
             *index += 1;
-            return Some("package".to_string());
+            return Some(Accessibility::Package((*tkn).clone()));
         },
         _ => {
             dbg!(&token);
             return None;
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,258 @@
+use crate::{full_result::{FullResult, OptionToFullResult}, lexer::token::{Kwrd, Tkn, TknType}};
+
+use super::{accessors::{self, Accessibility, Accessor}, expr::ExprType, namespaces, tokens, ParserError};
+
+#[derive(Clone, Debug)]
+pub struct Enum {
+    pub accessibility: Accessibility,
+    pub location: String,
+    pub name: String,
+    pub variants: Vec<Variant>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Variant {
+    pub variant_name: String,
+    pub shape: VariantShape,
+    /// payload of the variant in declaration order, tuple variants name their fields `0`, `1`, ...
+    pub fields: Box<[(String, ExprType)]>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum VariantShape {
+    /// `Name`
+    Unit,
+    /// `Name(i32, bool)`
+    Tuple,
+    /// `Name { value: i32 }`
+    Struct,
+}
+
+#[derive(Debug)]
+pub struct EnumDefinition<'tkns> {
+    pub accessibility: Option<&'tkns Tkn>,
+    pub name: String,
+    pub body_tokens: &'tkns [Tkn],
+}
+
+impl Enum {
+    pub fn to_expr_type(&self) -> ExprType {
+        return ExprType::Enum {
+            ident: self.name.clone(),
+            variants: self.variants.clone().into_boxed_slice()
+        };
+    }
+}
+
+/// Number of bytes the tag in front of an enum's payload takes up.
+pub fn tag_size(variant_count: usize) -> usize {
+    return if variant_count <= u8::MAX as usize + 1 {
+        1
+    } else if variant_count <= u16::MAX as usize + 1 {
+        2
+    } else {
+        4
+    };
+}
+
+pub fn define_enum<'tkns, 'bumps, 'defs>(
+    tokens: &'tkns [Tkn],
+    index: &mut usize
+) -> FullResult<EnumDefinition<'tkns>, (), Vec<ParserError<'tkns, 'bumps, 'defs>>> {
+    let mut peek = *index;
+    let accessibility = match &tokens[peek].token {
+        TknType::Keyword(Kwrd::Public)
+        | TknType::Keyword(Kwrd::Private)
+        | TknType::Keyword(Kwrd::Package)
+        | TknType::Identifier(_) => {
+            peek += 1;
+            Some(&tokens[peek - 1])
+        },
+        TknType::Keyword(Kwrd::Enum) => None,
+        _ => {
+            return FullResult::SoftErr(());
+        }
+    };
+
+    tokens::expect_token(tokens, TknType::Keyword(Kwrd::Enum), &mut peek).ok_or_soft::<_, Vec<_>>(())?;
+    let name;
+    if let Some(TknType::Identifier(ident)) = tokens.get(peek).map(|e| &e.token) {
+        name = ident.clone();
+        peek += 1;
+    } else {
+        return FullResult::HardErr(vec![ParserError::ExpectedIdentifier { tkn: &tokens[peek] }]);
+    }
+
+    let mut open_braces = vec![];
+    tokens::expect_token(tokens, TknType::OpenCurlyBrace, &mut peek)
+        .ok_or_else_hard(|| vec![ParserError::ExpectedToken {
+            tkn: &tokens[peek],
+            expected: TknType::OpenCurlyBrace
+        }])?;
+    open_braces.push(&tokens[peek - 1]);
+
+    let body_tokens: &[Tkn];
+    let start = peek;
+    let end;
+    let mut count = 0;
+    loop {
+        if tokens::is_expected_token(tokens, TknType::OpenCurlyBrace, &mut peek) {
+            count += 1;
+            open_braces.push(&tokens[peek - 1]);
+            continue;
+        } else if tokens::is_expected_token(tokens, TknType::CloseCurlyBrace, &mut peek) {
+            if count == 0 {
+                end = peek - 1;
+                body_tokens = &tokens[start..end];
+                *index = peek;
+                return FullResult::Ok(EnumDefinition {accessibility, name, body_tokens});
+            }
+            count -= 1;
+            open_braces.pop();
+            continue;
+        } else if tokens::is_expected_token(tokens, TknType::EndOfFile, &mut peek) {
+            let mut errors = vec![];
+            for open_brace in open_braces.into_iter().rev() {
+                errors.push(ParserError::ExpectedClosingBrace {
+                    tkn: &tokens[peek - 1],
+                    open_brace
+                });
+            }
+            return FullResult::HardErr(errors);
+        }
+        peek += 1;
+    }
+}
+
+/// Payload types can name any struct, but only enums declared before this one,
+/// since an enum cannot contain itself without indirection.
+pub fn parse_enum<'tkns, 'bumps, 'defs>(
+    enum_def: &EnumDefinition<'tkns>,
+    accessors: &[Accessor],
+    structs: &[&str],
+    enums: &[Enum]
+) -> Result<Enum, ParserError<'tkns, 'bumps, 'defs>> {
+    let EnumDefinition {accessibility, name, body_tokens} = enum_def;
+    let mut peek: usize = 0;
+    let mut variants: Vec<Variant> = vec![];
+    let mut variant_tkns: Vec<&Tkn> = vec![];
+
+    let mut need_comma = false;
+    loop {
+        if peek >= body_tokens.len() {
+            break;
+        }
+
+        if need_comma {
+            return Err(ParserError::ExpectedToken {
+                tkn: &body_tokens[peek],
+                expected: TknType::Comma
+            });
+        }
+
+        let Some(TknType::Identifier(variant_name)) = tokens::get_token(body_tokens, peek).map(|e| &e.token) else {
+            return Err(ParserError::ExpectedIdentifier { tkn: &body_tokens[peek] });
+        };
+        if let Some(position) = variants.iter().position(|variant| variant.variant_name == *variant_name) {
+            return Err(ParserError::AlreadyDefinedVariant {
+                tkn: &body_tokens[peek],
+                defined_variant: variant_tkns[position]
+            });
+        }
+        variant_tkns.push(&body_tokens[peek]);
+        peek += 1;
+
+        let shape;
+        let mut fields = vec![];
+        if tokens::is_expected_token(body_tokens, TknType::OpenParen, &mut peek) {
+            shape = VariantShape::Tuple;
+            while !tokens::is_expected_token(body_tokens, TknType::CloseParen, &mut peek) {
+                if !fields.is_empty() {
+                    tokens::expect_token(body_tokens, TknType::Comma, &mut peek)
+                        .ok_or_else(|| ParserError::ExpectedToken {
+                            tkn: tokens::get_token(body_tokens, peek).unwrap_or(&body_tokens[peek - 1]),
+                            expected: TknType::Comma
+                        })?;
+                    if tokens::is_expected_token(body_tokens, TknType::CloseParen, &mut peek) {
+                        break;
+                    }
+                }
+
+                let field_type = super::get_type_token_expr_type(
+                    body_tokens,
+                    &mut peek,
+                    structs,
+                    &[],
+                    enums
+                ).ok_or_else(|| ParserError::ExpectedType {
+                    tkn: tokens::get_token(body_tokens, peek).unwrap_or(&body_tokens[peek - 1])
+                })?;
+                fields.push((fields.len().to_string(), field_type));
+            }
+        } else if tokens::is_expected_token(body_tokens, TknType::OpenCurlyBrace, &mut peek) {
+            shape = VariantShape::Struct;
+            while !tokens::is_expected_token(body_tokens, TknType::CloseCurlyBrace, &mut peek) {
+                if !fields.is_empty() {
+                    tokens::expect_token(body_tokens, TknType::Comma, &mut peek)
+                        .ok_or_else(|| ParserError::ExpectedToken {
+                            tkn: tokens::get_token(body_tokens, peek).unwrap_or(&body_tokens[peek - 1]),
+                            expected: TknType::Comma
+                        })?;
+                    if tokens::is_expected_token(body_tokens, TknType::CloseCurlyBrace, &mut peek) {
+                        break;
+                    }
+                }
+
+                let field_name = super::get_ident_token_string(
+                    tokens::get_token(body_tokens, peek),
+                    &mut peek,
+                    structs
+                ).ok_or_else(|| ParserError::ExpectedIdentifier {
+                    tkn: tokens::get_token(body_tokens, peek).unwrap_or(&body_tokens[peek - 1])
+                })?;
+
+                tokens::expect_token(body_tokens, TknType::Colon, &mut peek)
+                    .ok_or_else(|| ParserError::ExpectedToken {
+                        tkn: tokens::get_token(body_tokens, peek).unwrap_or(&body_tokens[peek - 1]),
+                        expected: TknType::Colon
+                    })?;
+
+                let field_type = super::get_type_token_expr_type(
+                    body_tokens,
+                    &mut peek,
+                    structs,
+                    &[],
+                    enums
+                ).ok_or_else(|| ParserError::ExpectedType {
+                    tkn: tokens::get_token(body_tokens, peek).unwrap_or(&body_tokens[peek - 1])
+                })?;
+                fields.push((field_name, field_type));
+            }
+        } else {
+            shape = VariantShape::Unit;
+        }
+
+        if tokens::expect_token(
+            body_tokens,
+            TknType::Comma,
+            &mut peek
+        ).is_none() {
+            need_comma = true;
+        };
+
+        variants.push(Variant {
+            variant_name: variant_name.clone(),
+            shape,
+            fields: fields.into_boxed_slice()
+        });
+    }
+    return Ok(Enum {
+        accessibility: accessors::get_accessibility(
+            *accessibility,
+            &mut 0,
+            accessors
+        ).unwrap(),
+        name: name.to_string(),
+        location: namespaces::location_of(name), variants
+    });
+}
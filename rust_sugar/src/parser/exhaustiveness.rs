@@ -0,0 +1,364 @@
+//! Checks the arms of a `match`: every value of the matched type has to be covered by an arm, and
+//! every arm has to cover a value the arms before it do not.  Both questions are answered by the
+//! usefulness algorithm from "Warnings for pattern matching" (Maranget, 2007), which also gives
+//! back a value that is not covered, to show in the error.
+
+use super::{enums::VariantShape, expr::{ExprType, Lit}, patterns::MatchPattern, structs::Struct};
+
+/// A pattern broken down into the constructor it matches and the patterns of its fields.
+#[derive(Clone, Debug)]
+enum Pat {
+    Wild,
+    Ctor(Ctor, Vec<Pat>)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Ctor {
+    /// The only constructor of structs, anonymous structs, tuples and arrays
+    Single,
+    Bool(bool),
+    /// The variant with this tag
+    Variant(usize),
+    /// Every integer (or char, as its code point) from the start to the end, both included
+    Range(i128, i128),
+    /// Floats and strings, which can only be covered by a wildcard
+    Opaque(Lit)
+}
+
+/// Why the arms of a `match` are rejected.
+#[derive(Debug)]
+pub enum MatchProblem {
+    /// The arm at this index can never be reached
+    UnreachableArm(usize),
+    /// A value that none of the arms cover, written as a pattern
+    NonExhaustive(String)
+}
+
+/// The smallest and largest values of an integer type, `None` for other types and for `u128`,
+/// whose values do not all fit into an `i128`.
+pub fn integer_bounds(expr_type: &ExprType) -> Option<(i128, i128)> {
+    return match expr_type {
+        ExprType::I8 => Some((i8::MIN as i128, i8::MAX as i128)),
+        ExprType::I16 => Some((i16::MIN as i128, i16::MAX as i128)),
+        ExprType::I32 => Some((i32::MIN as i128, i32::MAX as i128)),
+        ExprType::I64 | ExprType::ISize => Some((i64::MIN as i128, i64::MAX as i128)),
+        ExprType::I128 => Some((i128::MIN, i128::MAX)),
+        ExprType::U8 => Some((0, u8::MAX as i128)),
+        ExprType::U16 => Some((0, u16::MAX as i128)),
+        ExprType::U32 => Some((0, u32::MAX as i128)),
+        ExprType::U64 | ExprType::USize => Some((0, u64::MAX as i128)),
+        _ => None
+    };
+}
+
+/// Checks the arms in order, where each arm is its pattern and whether it has an `if` guard.
+/// Guarded arms can fail to match any value, so they never count towards covering the type.
+pub fn check_match(
+    arms: &[(&MatchPattern, bool)],
+    expr_type: &ExprType,
+    structs: &[Struct]
+) -> Result<(), MatchProblem> {
+    let types = [expr_type.clone()];
+    let mut rows: Vec<Vec<Pat>> = vec![];
+
+    for (i, (pattern, guarded)) in arms.iter().enumerate() {
+        let row = vec![deconstruct(pattern, expr_type, structs)];
+        if useful(&rows, &row, &types, structs).is_none() {
+            return Err(MatchProblem::UnreachableArm(i));
+        }
+        if !guarded {
+            rows.push(row);
+        }
+    }
+
+    if let Some(witness) = useful(&rows, &[Pat::Wild], &types, structs) {
+        return Err(MatchProblem::NonExhaustive(write_pat(&witness[0], expr_type, structs)));
+    }
+    return Ok(());
+}
+
+fn deconstruct(pattern: &MatchPattern, expr_type: &ExprType, structs: &[Struct]) -> Pat {
+    return match pattern {
+        MatchPattern::Discard | MatchPattern::Bind(_) => Pat::Wild,
+        MatchPattern::Literal(Lit::BooleanLiteral(bool)) => Pat::Ctor(Ctor::Bool(*bool), vec![]),
+        MatchPattern::Literal(Lit::IntegerLiteral(int)) => Pat::Ctor(Ctor::Range(*int, *int), vec![]),
+        MatchPattern::Literal(Lit::CharLiteral(chr)) => Pat::Ctor(Ctor::Range(*chr as i128, *chr as i128), vec![]),
+        MatchPattern::Literal(literal) => Pat::Ctor(Ctor::Opaque(literal.clone()), vec![]),
+        MatchPattern::Range { start, end } => Pat::Ctor(Ctor::Range(*start, *end), vec![]),
+        MatchPattern::Group { start, end } => {
+            let types = field_types(&Ctor::Single, expr_type, structs);
+            let mut fields = vec![Pat::Wild; types.len()];
+            let end_start = types.len() - end.len();
+            for (i, pattern) in start.iter().enumerate() {
+                fields[i] = deconstruct(pattern, &types[i], structs);
+            }
+            for (i, pattern) in end.iter().enumerate() {
+                fields[end_start + i] = deconstruct(pattern, &types[end_start + i], structs);
+            }
+            Pat::Ctor(Ctor::Single, fields)
+        },
+        MatchPattern::Fields(field_patterns) => {
+            let fields = deconstruct_fields(&Ctor::Single, field_patterns, expr_type, structs);
+            Pat::Ctor(Ctor::Single, fields)
+        },
+        MatchPattern::Variant { tag, fields: field_patterns } => {
+            let fields = deconstruct_fields(&Ctor::Variant(*tag), field_patterns, expr_type, structs);
+            Pat::Ctor(Ctor::Variant(*tag), fields)
+        },
+    };
+}
+
+/// Lines up the patterns of named fields with the fields of `ctor` in declaration order,
+/// the fields that are left out become wildcards.
+fn deconstruct_fields(
+    ctor: &Ctor,
+    field_patterns: &[(String, MatchPattern)],
+    expr_type: &ExprType,
+    structs: &[Struct]
+) -> Vec<Pat> {
+    let names = field_names(ctor, expr_type, structs);
+    let types = field_types(ctor, expr_type, structs);
+
+    return names.iter().zip(types.iter()).map(|(name, field_type)| {
+        match field_patterns.iter().find(|(field_name, _)| field_name == name) {
+            Some((_, pattern)) => deconstruct(pattern, field_type, structs),
+            None => Pat::Wild
+        }
+    }).collect();
+}
+
+fn field_names(ctor: &Ctor, expr_type: &ExprType, structs: &[Struct]) -> Vec<String> {
+    return match (ctor, expr_type) {
+        (Ctor::Single, ExprType::Custom { ident, .. }) => find_struct(ident, structs).fields.iter()
+            .map(|field| field.field_name.clone())
+            .collect(),
+        (Ctor::Single, ExprType::AnonymousCustom { fields }) => fields.iter()
+            .map(|(field_name, _)| field_name.clone())
+            .collect(),
+        (Ctor::Variant(tag), ExprType::Enum { variants, .. }) => variants[*tag].fields.iter()
+            .map(|(field_name, _)| field_name.clone())
+            .collect(),
+        _ => (0..field_types(ctor, expr_type, structs).len()).map(|i| i.to_string()).collect()
+    };
+}
+
+/// The types of the fields of `ctor`, which is a constructor of `expr_type`.
+fn field_types(ctor: &Ctor, expr_type: &ExprType, structs: &[Struct]) -> Vec<ExprType> {
+    return match (ctor, expr_type) {
+        (Ctor::Single, ExprType::Custom { ident, type_args }) => {
+            let custom_struct = find_struct(ident, structs);
+            custom_struct.fields.iter()
+                .map(|field| custom_struct.field_type(field, type_args))
+                .collect()
+        },
+        (Ctor::Single, ExprType::AnonymousCustom { fields }) => fields.iter()
+            .map(|(_, field_type)| field_type.clone())
+            .collect(),
+        (Ctor::Single, ExprType::Tuple { start, .. }) => start.clone(),
+        (Ctor::Single, ExprType::Array { length: Some(length), expr_type }) => vec![*expr_type.clone(); *length],
+        (Ctor::Variant(tag), ExprType::Enum { variants, .. }) => variants[*tag].fields.iter()
+            .map(|(_, field_type)| field_type.clone())
+            .collect(),
+        _ => vec![]
+    };
+}
+
+fn find_struct<'defs>(ident: &str, structs: &'defs [Struct]) -> &'defs Struct {
+    return structs.iter()
+        .find(|custom_struct| custom_struct.name == ident)
+        .expect(format!("struct {ident} does not exist").as_str());
+}
+
+/// The values of a type as ranges of integers, char code points leave out the surrogates.
+fn integer_domain(expr_type: &ExprType) -> Option<Vec<(i128, i128)>> {
+    if *expr_type == ExprType::Char {
+        return Some(vec![(0, 0xD7FF), (0xE000, 0x10FFFF)]);
+    }
+    return integer_bounds(expr_type).map(|bounds| vec![bounds]);
+}
+
+/// Every constructor of `expr_type`, where integer ranges are cut up at the start and end of the
+/// ranges in `heads`, so that each of them is either inside of or apart from every range in `heads`.
+/// `None` when the constructors cannot be listed, like for floats and strings.
+fn all_ctors(expr_type: &ExprType, heads: &[&Ctor]) -> Option<Vec<Ctor>> {
+    return match expr_type {
+        ExprType::Bool => Some(vec![Ctor::Bool(true), Ctor::Bool(false)]),
+        ExprType::Enum { variants, .. } => Some((0..variants.len()).map(Ctor::Variant).collect()),
+        ExprType::Custom { .. } |
+        ExprType::AnonymousCustom { .. } |
+        ExprType::Tuple { .. } |
+        ExprType::Array { length: Some(_), .. } => Some(vec![Ctor::Single]),
+        _ => Some(split_ranges(&integer_domain(expr_type)?, heads))
+    };
+}
+
+/// Cuts the ranges in `domain` at the start and end of every range in `heads`.
+fn split_ranges(domain: &[(i128, i128)], heads: &[&Ctor]) -> Vec<Ctor> {
+    let mut boundaries = vec![];
+    for head in heads {
+        if let Ctor::Range(start, end) = head {
+            boundaries.push(*start);
+            if *end < i128::MAX {
+                boundaries.push(*end + 1);
+            }
+        }
+    }
+    boundaries.sort_unstable();
+    boundaries.dedup();
+
+    let mut ranges = vec![];
+    for (domain_start, domain_end) in domain {
+        let mut start = *domain_start;
+        for boundary in &boundaries {
+            if *boundary > start && *boundary <= *domain_end {
+                ranges.push(Ctor::Range(start, *boundary - 1));
+                start = *boundary;
+            }
+        }
+        ranges.push(Ctor::Range(start, *domain_end));
+    }
+    return ranges;
+}
+
+/// Whether every value of the constructor `ctor` is a value of the constructor `head`.
+fn covers(head: &Ctor, ctor: &Ctor) -> bool {
+    return match (head, ctor) {
+        (Ctor::Range(head_start, head_end), Ctor::Range(start, end)) => head_start <= start && end <= head_end,
+        (head, ctor) => head == ctor
+    };
+}
+
+/// The rows that match `ctor` in their first column, with that column replaced by its fields.
+fn specialize(rows: &[Vec<Pat>], ctor: &Ctor, arity: usize) -> Vec<Vec<Pat>> {
+    let mut specialized = vec![];
+    for row in rows {
+        let mut fields = match &row[0] {
+            Pat::Wild => vec![Pat::Wild; arity],
+            Pat::Ctor(head, fields) if covers(head, ctor) => fields.clone(),
+            Pat::Ctor(..) => continue,
+        };
+        fields.extend_from_slice(&row[1..]);
+        specialized.push(fields);
+    }
+    return specialized;
+}
+
+/// Whether a value matches `row` without matching any of `rows`.  When it does, returns such a value.
+fn useful(rows: &[Vec<Pat>], row: &[Pat], types: &[ExprType], structs: &[Struct]) -> Option<Vec<Pat>> {
+    if row.is_empty() {
+        return if rows.is_empty() { Some(vec![]) } else { None };
+    }
+
+    let heads = rows.iter().filter_map(|row| match &row[0] {
+        Pat::Ctor(head, _) => Some(head),
+        Pat::Wild => None
+    }).collect::<Vec<_>>();
+
+    let ctors = match &row[0] {
+        Pat::Ctor(Ctor::Range(start, end), _) => split_ranges(&[(*start, *end)], &heads),
+        Pat::Ctor(ctor, _) => vec![ctor.clone()],
+        Pat::Wild => {
+            let all = all_ctors(&types[0], &heads);
+            let missing = all.as_ref().map(|all| all.iter()
+                .find(|ctor| !heads.iter().any(|head| covers(head, ctor)))
+                .cloned()
+            );
+
+            match (all, missing) {
+                (Some(all), Some(None)) => all,
+                (_, missing) => {
+                    // some constructor is not covered by the heads, so only the rows starting
+                    // with a wildcard can match the values made with it
+                    let defaults = rows.iter()
+                        .filter(|row| matches!(row[0], Pat::Wild))
+                        .map(|row| row[1..].to_vec())
+                        .collect::<Vec<_>>();
+                    let mut witness = useful(&defaults, &row[1..], &types[1..], structs)?;
+
+                    // with no constructor in the column, `_` says more than any one missing constructor
+                    let head = match missing.flatten() {
+                        Some(ctor) if !heads.is_empty() => {
+                            let arity = field_types(&ctor, &types[0], structs).len();
+                            Pat::Ctor(ctor, vec![Pat::Wild; arity])
+                        },
+                        _ => Pat::Wild
+                    };
+                    witness.insert(0, head);
+                    return Some(witness);
+                }
+            }
+        }
+    };
+
+    for ctor in ctors {
+        let fields = field_types(&ctor, &types[0], structs);
+        let arity = fields.len();
+
+        let specialized_rows = specialize(rows, &ctor, arity);
+        let Some(specialized_row) = specialize(&[row.to_vec()], &ctor, arity).pop() else {
+            continue;
+        };
+
+        let mut specialized_types = fields;
+        specialized_types.extend_from_slice(&types[1..]);
+
+        if let Some(mut witness) = useful(&specialized_rows, &specialized_row, &specialized_types, structs) {
+            let rest = witness.split_off(arity);
+            let mut full = vec![Pat::Ctor(ctor, witness)];
+            full.extend(rest);
+            return Some(full);
+        }
+    }
+    return None;
+}
+
+fn write_pat(pat: &Pat, expr_type: &ExprType, structs: &[Struct]) -> String {
+    let Pat::Ctor(ctor, fields) = pat else {
+        return String::from("_");
+    };
+
+    let types = field_types(ctor, expr_type, structs);
+    let names = field_names(ctor, expr_type, structs);
+    let written = fields.iter().zip(types.iter())
+        .map(|(field, field_type)| write_pat(field, field_type, structs))
+        .collect::<Vec<_>>();
+
+    return match (ctor, expr_type) {
+        (Ctor::Bool(bool), _) => bool.to_string(),
+        (Ctor::Range(start, end), ExprType::Char) => {
+            let chr = |value: i128| char::from_u32(value as u32).unwrap_or_default();
+            if start == end { format!("{:?}", chr(*start)) } else { format!("{:?}..={:?}", chr(*start), chr(*end)) }
+        },
+        (Ctor::Range(start, end), _) => if start == end { start.to_string() } else { format!("{start}..={end}") },
+        (Ctor::Opaque(literal), _) => format!("{literal:?}"),
+        (Ctor::Single, ExprType::Custom { ident, .. }) => format!("{ident} {}", write_named_fields(&names, &written)),
+        (Ctor::Single, ExprType::AnonymousCustom { .. }) => write_named_fields(&names, &written),
+        (Ctor::Single, ExprType::Array { .. }) => format!("[{}]", written.join(", ")),
+        (Ctor::Single, _) => format!("({})", written.join(", ")),
+        (Ctor::Variant(tag), ExprType::Enum { ident, variants }) => {
+            let variant = &variants[*tag];
+            match variant.shape {
+                VariantShape::Unit => format!("{ident}::{}", variant.variant_name),
+                VariantShape::Tuple => format!("{ident}::{}({})", variant.variant_name, written.join(", ")),
+                VariantShape::Struct => format!("{ident}::{} {}", variant.variant_name, write_named_fields(&names, &written)),
+            }
+        },
+        (Ctor::Variant(_), _) => unreachable!("variant of a type that is not an enum"),
+    };
+}
+
+/// Writes `{ field: pattern, .. }`, leaving out the fields that match anything.
+fn write_named_fields(names: &[String], written: &[String]) -> String {
+    let mut fields = names.iter().zip(written.iter())
+        .filter(|(_, written)| *written != "_")
+        .map(|(name, written)| format!("{name}: {written}"))
+        .collect::<Vec<_>>();
+    if fields.len() < names.len() {
+        fields.push(String::from(".."));
+    }
+
+    if fields.is_empty() {
+        return String::from("{}");
+    }
+    return format!("{{ {} }}", fields.join(", "));
+}
@@ -4,7 +4,7 @@ use stack_frame_allocators::stack_frame_dict_allocator::StackFrameDictAllocator;
 
 use crate::{lexer::token::{Kwrd, Op, Tkn, TknType}, parser::tokens};
 
-use super::{functions::{BuiltInFunction, FullFnDef}, operators::{BinOp, OpAssoc, UnOp, OPERATOR_INFO_MAP}, stmt::StmtData, structs::{Field, Struct}, tokens::{expect_token, is_expected_token}, ExprBump, ParserError};
+use super::{accessors::{self, Accessibility}, functions::{self, BuiltInFunction, Capture, CaptureMode, ClosureDefinition, FullFnDef}, operators::{BinOp, OpAssoc, UnOp, OPERATOR_INFO_MAP}, enums::{self, Enum, Variant, VariantShape}, ownership::{self, BorrowKind}, patterns::{self, MatchArm}, stmt::StmtData, structs::{Field, Struct}, tokens::{expect_token, is_expected_token}, traits, ExprBump, ParserError};
 
 pub type Expr<'bumps, 'defs> = Expression<'bumps, 'defs>;
 #[derive(Clone, Debug)]
@@ -18,7 +18,8 @@ pub type ExprData<'bumps, 'defs> = ExpressionData<'bumps, 'defs>;
 
 #[derive(Clone, Debug)]
 pub enum ExpressionData<'bumps, 'defs> {
-    Identifier(String),
+    /// A variable or function, and the index of the token its name is written at
+    Identifier(String, usize),
     Literal(Lit),
     Custom {
         fields: HashMap<&'defs str, &'bumps ExprData<'bumps, 'defs>>
@@ -34,15 +35,43 @@ pub enum ExpressionData<'bumps, 'defs> {
         data: &'bumps Expr<'bumps, 'defs>,
         field_name: String
     },
+    /// `Name::Variant`, with the payload in the variant's declaration order
+    EnumVariant {
+        tag: usize,
+        fields: Box<[(String, &'bumps ExprData<'bumps, 'defs>)]>
+    },
     Conditional{ 
         conds: Vec<Expr<'bumps, 'defs>>, 
         bodies: Vec<&'bumps StmtData<'bumps, 'defs>> 
     },
+    /// `match scrutinee { pattern => value, .. }`, the value of the first arm that matches
+    Match {
+        scrutinee: Expr<'bumps, 'defs>,
+        arms: Vec<MatchArm<'bumps, 'defs, Expr<'bumps, 'defs>>>
+    },
     Function {
         name: String,
         left_args: Vec<Expr<'bumps, 'defs>>,
         right_args: Vec<Expr<'bumps, 'defs>>
     },
+    /// `fn $ x: T = R { .. }`, the function `name` along with the variables it captures and where 
+    /// it first uses each of them
+    Closure {
+        name: String,
+        captures: Vec<(String, usize, CaptureMode)>
+    },
+    /// A call to a function value, like a closure or a function passed as an argument
+    Call {
+        callee: Expr<'bumps, 'defs>,
+        left_args: Vec<Expr<'bumps, 'defs>>,
+        right_args: Vec<Expr<'bumps, 'defs>>
+    },
+    /// `1 add`, a function value given some of its arguments, which waits for the rest of them
+    Partial {
+        callee: Expr<'bumps, 'defs>,
+        left_args: Vec<Expr<'bumps, 'defs>>,
+        right_args: Vec<Expr<'bumps, 'defs>>
+    },
     BinaryOp(BinOp, Expr<'bumps, 'defs>, Expr<'bumps, 'defs>),
     UnaryOp(UnOp, Expr<'bumps, 'defs>),
     Array(Vec<Expr<'bumps, 'defs>>),
@@ -129,6 +158,55 @@ impl<'bumps> ExprTypeCons<'bumps> {
 
         return Some(self.clone().combine(other.clone()));
     }
+
+    /// Pins an integer or float type nothing else decided on to the type its literals default to.
+    pub fn default_literal_type(&mut self, expr_bump: &'bumps ExprBump) {
+        let default_type = match *self.get() {
+            ExprType::AmbiguousNegInteger | ExprType::AmbiguousPosInteger => ExprType::I32,
+            ExprType::AmbiguousFloat => ExprType::F32,
+            _ => return
+        };
+        self.match_type(&mut ExprTypeCons::new(expr_bump, default_type));
+    }
+
+    /// Pins the literal elements of a tuple nothing decided on yet. An element read out of a tuple
+    /// only gets a copy of its type, so nothing can pin them down once the tuple is read from.
+    pub fn default_element_literal_types(&mut self, expr_bump: &'bumps ExprBump) {
+        if !matches!(*self.get(), ExprType::Tuple { .. }) {
+            return;
+        }
+        let default_type = self.clone_inner().with_default_literals();
+        self.match_type(&mut ExprTypeCons::new(expr_bump, default_type));
+    }
+
+    /// Matches this type against `param_type`, which is written in terms of `type_params`, while 
+    /// binding each of the type parameters in `bindings` to the type it stands for here.
+    pub fn match_type_params(
+        &mut self,
+        expr_bump: &'bumps ExprBump,
+        param_type: &ExprType,
+        type_params: &[String],
+        bindings: &mut [ExprTypeCons<'bumps>]
+    ) -> Option<ExprTypeCons<'bumps>> {
+        if let ExprType::Generic { name } = param_type
+            && let Some(i) = type_params.iter().position(|type_param| type_param == name) 
+        {
+            // sharing the binding lets later arguments pin down an ambiguous type for this one too
+            let matched = self.match_type(&mut bindings[i])?;
+            bindings[i] = matched.clone();
+            return Some(matched);
+        }
+
+        let type_args = bindings.iter().map(|binding| binding.clone_inner()).collect::<Vec<_>>();
+        let matched = self.match_type(&mut ExprTypeCons::new(expr_bump, param_type.substitute(type_params, &type_args)))?;
+
+        let mut bound = vec![];
+        param_type.bind_type_params(type_params, &matched.get(), &mut bound);
+        for (i, bound_type) in bound {
+            bindings[i] = bindings[i].match_type(&mut ExprTypeCons::new(expr_bump, bound_type))?;
+        }
+        return Some(matched);
+    }
 }
 
 impl<'etp> PartialEq for ExprTypeCons<'etp> {
@@ -145,12 +223,30 @@ pub enum ExpressionType {
     U8, U16, U32, U64, U128, USize, AmbiguousPosInteger,
     F32, F64, AmbiguousFloat,
     Char, StringLiteral, Bool,
-    Ref(Box<ExprType>),
-    MutRef(Box<ExprType>),
+    /// `&'lifetime T`, where the lifetime is `None` when it is elided
+    Ref {
+        lifetime: Option<String>,
+        expr_type: Box<ExprType>
+    },
+    MutRef {
+        lifetime: Option<String>,
+        expr_type: Box<ExprType>
+    },
+    /// `&im T`, a shared reference that can still be written through
+    ImRef {
+        lifetime: Option<String>,
+        expr_type: Box<ExprType>
+    },
     Array {
         length: Option<usize>, 
         expr_type: Box<ExprType>
     },
+    /// The type of `start..end`, where `!` excludes the start and `=` includes the end
+    Range {
+        exclusive_start: bool,
+        inclusive_end: bool,
+        expr_type: Box<ExprType>
+    },
     //TODO separate into PatternGroup and PatternAmbiguousGroup, since actual Tuple types cannot use the DiscardMany type
     Tuple { start: Vec<ExprType>, end: Vec<ExprType> },
     AmbiguousGroup { start: Vec<ExprType>, end: Vec<ExprType> },
@@ -168,11 +264,22 @@ pub enum ExpressionType {
     DiscardSingle,
     DiscardMany,
     Custom {
-        ident: String
+        ident: String,
+        /// The types the type parameters of a generic struct are instantiated with
+        type_args: Vec<ExprType>
+    },
+    /// A type parameter of the generic struct or function being defined
+    Generic {
+        name: String
     },
     AnonymousCustom {
         fields: Box<[(String, ExprType)]>
     },
+    /// A tag followed by the payload of the largest variant
+    Enum {
+        ident: String,
+        variants: Box<[Variant]>
+    },
     Void,
     Never
 }
@@ -240,7 +347,9 @@ impl ExprType {
                 }
             ) => {
                 if l_length.is_some() && r_length.is_some() {
-                    return unsafe { l_length.zip(*r_length).map(|(l, r)| l == r).unwrap_unchecked() };
+                    if l_length != r_length {
+                        return false;
+                    }
                 } else if l_length.is_some() {
                     *r_length = l_length.clone();
                 } else if r_length.is_some() {
@@ -281,6 +390,71 @@ impl ExprType {
                     .chain(r_end_types.iter_mut().zip(l_end_types.iter_mut().rev()).map(|(r, l)| r.match_type(l)))
                     .all(|e| e);
             },
+            (
+                ET::Range { 
+                    exclusive_start: l_exclusive_start, 
+                    inclusive_end: l_inclusive_end, 
+                    expr_type: l_type 
+                },
+                ET::Range { 
+                    exclusive_start: r_exclusive_start, 
+                    inclusive_end: r_inclusive_end, 
+                    expr_type: r_type 
+                }
+            ) => {
+                if l_exclusive_start != r_exclusive_start || l_inclusive_end != r_inclusive_end {
+                    return false;
+                }
+
+                return l_type.match_type(r_type.as_mut());
+            },
+            // lifetimes only constrain where a reference comes from, which the lifetime checker checks
+            (ET::Ref { expr_type: l_type, .. }, ET::Ref { expr_type: r_type, .. }) |
+            (ET::MutRef { expr_type: l_type, .. }, ET::MutRef { expr_type: r_type, .. }) |
+            (ET::ImRef { expr_type: l_type, .. }, ET::ImRef { expr_type: r_type, .. }) => return l_type.match_type(r_type.as_mut()),
+            (
+                ET::Custom { ident: l_ident, type_args: l_type_args },
+                ET::Custom { ident: r_ident, type_args: r_type_args }
+            ) if l_ident == r_ident && l_type_args.len() == r_type_args.len() => {
+                return l_type_args.iter_mut().zip(r_type_args.iter_mut()).all(|(l, r)| l.match_type(r));
+            },
+            // a function is passed along as a function value, whether it was named or written as a closure
+            (
+                l @ (ET::Function { .. } | ET::FunctionPass { .. }),
+                r @ (ET::Function { .. } | ET::FunctionPass { .. })
+            ) if matches!(l, ET::FunctionPass { .. }) || matches!(r, ET::FunctionPass { .. }) => {
+                let (
+                    ET::Function { return_type: l_return, left_args: l_left, right_args: l_right, .. } |
+                    ET::FunctionPass { return_type: l_return, left_args: l_left, right_args: l_right }
+                ) = l else {
+                    unreachable!();
+                };
+                let (
+                    ET::Function { return_type: r_return, left_args: r_left, right_args: r_right, .. } |
+                    ET::FunctionPass { return_type: r_return, left_args: r_left, right_args: r_right }
+                ) = r else {
+                    unreachable!();
+                };
+
+                if l_left.len() != r_left.len() || l_right.len() != r_right.len() {
+                    return false;
+                }
+                if !l_left.iter_mut().zip(r_left.iter_mut())
+                    .chain(l_right.iter_mut().zip(r_right.iter_mut()))
+                    .all(|(l, r)| l.match_type(r)) 
+                    || !l_return.match_type(r_return)
+                {
+                    return false;
+                }
+
+                let function_pass = ET::FunctionPass { 
+                    return_type: l_return.clone(), 
+                    left_args: l_left.clone(), 
+                    right_args: l_right.clone() 
+                };
+                *l = function_pass.clone();
+                *r = function_pass;
+            },
             ( ET::Tuple {..}, ET::Tuple {..}) => unreachable!("Both Group Types include a Discard Many Type"),
             (
                 l @ ET::AmbiguousGroup { .. }, 
@@ -312,6 +486,114 @@ impl ExprType {
     }
 
 
+    /// The type with each of `type_params` replaced by the type at the same position in `type_args`.
+    pub fn substitute(&self, type_params: &[String], type_args: &[ExprType]) -> ExprType {
+        let substitute_all = |expr_types: &[ExprType]| expr_types.iter()
+            .map(|expr_type| expr_type.substitute(type_params, type_args))
+            .collect::<Vec<_>>();
+
+        return match self {
+            ExprType::Generic { name } => match type_params.iter().position(|type_param| type_param == name) {
+                Some(i) => type_args[i].clone(),
+                None => self.clone()
+            },
+            ExprType::Ref { lifetime, expr_type } => ExprType::Ref { 
+                lifetime: lifetime.clone(), 
+                expr_type: Box::new(expr_type.substitute(type_params, type_args)) 
+            },
+            ExprType::MutRef { lifetime, expr_type } => ExprType::MutRef { 
+                lifetime: lifetime.clone(), 
+                expr_type: Box::new(expr_type.substitute(type_params, type_args)) 
+            },
+            ExprType::ImRef { lifetime, expr_type } => ExprType::ImRef { 
+                lifetime: lifetime.clone(), 
+                expr_type: Box::new(expr_type.substitute(type_params, type_args)) 
+            },
+            ExprType::Array { length, expr_type } => ExprType::Array { 
+                length: *length, 
+                expr_type: Box::new(expr_type.substitute(type_params, type_args)) 
+            },
+            ExprType::Range { exclusive_start, inclusive_end, expr_type } => ExprType::Range { 
+                exclusive_start: *exclusive_start, 
+                inclusive_end: *inclusive_end, 
+                expr_type: Box::new(expr_type.substitute(type_params, type_args)) 
+            },
+            ExprType::Tuple { start, end } => ExprType::Tuple { start: substitute_all(start), end: substitute_all(end) },
+            ExprType::AmbiguousGroup { start, end } => ExprType::AmbiguousGroup { 
+                start: substitute_all(start), 
+                end: substitute_all(end) 
+            },
+            ExprType::Function { name, return_type, left_args, right_args } => ExprType::Function { 
+                name: name.clone(), 
+                return_type: Box::new(return_type.substitute(type_params, type_args)), 
+                left_args: substitute_all(left_args), 
+                right_args: substitute_all(right_args) 
+            },
+            ExprType::FunctionPass { return_type, left_args, right_args } => ExprType::FunctionPass { 
+                return_type: Box::new(return_type.substitute(type_params, type_args)), 
+                left_args: substitute_all(left_args), 
+                right_args: substitute_all(right_args) 
+            },
+            ExprType::Custom { ident, type_args: custom_type_args } => ExprType::Custom { 
+                ident: ident.clone(), 
+                type_args: substitute_all(custom_type_args) 
+            },
+            ExprType::AnonymousCustom { fields } => ExprType::AnonymousCustom { 
+                fields: fields.iter()
+                    .map(|(field_name, field_type)| (field_name.clone(), field_type.substitute(type_params, type_args)))
+                    .collect() 
+            },
+            _ => self.clone()
+        };
+    }
+
+    /// Collects what each of `type_params` stands for wherever it shows up in this type, by lining 
+    /// this type up with `expr_type`, as the index of the type parameter and the type it lines up with.
+    pub fn bind_type_params(&self, type_params: &[String], expr_type: &ExprType, bound: &mut Vec<(usize, ExprType)>) {
+        use ExprType as ET;
+
+        let mut bind_all = |params: &[ExprType], args: &[ExprType]| {
+            for (param, arg) in params.iter().zip(args) {
+                param.bind_type_params(type_params, arg, bound);
+            }
+        };
+
+        match (self, expr_type) {
+            (ET::Generic { name }, _) => {
+                if let Some(i) = type_params.iter().position(|type_param| type_param == name) {
+                    bound.push((i, expr_type.clone()));
+                }
+            },
+            (ET::Ref { expr_type: param, .. }, ET::Ref { expr_type: arg, .. }) | 
+            (ET::MutRef { expr_type: param, .. }, ET::MutRef { expr_type: arg, .. }) |
+            (ET::ImRef { expr_type: param, .. }, ET::ImRef { expr_type: arg, .. }) |
+            (ET::Array { expr_type: param, .. }, ET::Array { expr_type: arg, .. }) |
+            (ET::Range { expr_type: param, .. }, ET::Range { expr_type: arg, .. }) => {
+                param.bind_type_params(type_params, arg, bound);
+            },
+            (ET::Custom { type_args: params, .. }, ET::Custom { type_args: args, .. }) => bind_all(params, args),
+            (ET::Tuple { start: param_start, end: param_end }, ET::Tuple { start: arg_start, end: arg_end }) => {
+                bind_all(param_start, arg_start);
+                bind_all(param_end, arg_end);
+            },
+            (ET::AnonymousCustom { fields: params }, ET::AnonymousCustom { fields: args }) => {
+                for ((_, param), (_, arg)) in params.iter().zip(args.iter()) {
+                    param.bind_type_params(type_params, arg, bound);
+                }
+            },
+            (
+                ET::FunctionPass { return_type: param_return, left_args: param_left, right_args: param_right },
+                ET::Function { return_type: arg_return, left_args: arg_left, right_args: arg_right, .. } |
+                ET::FunctionPass { return_type: arg_return, left_args: arg_left, right_args: arg_right }
+            ) => {
+                bind_all(param_left, arg_left);
+                bind_all(param_right, arg_right);
+                param_return.bind_type_params(type_params, arg_return, bound);
+            },
+            _ => ()
+        }
+    }
+
     pub fn size_of(&self, structs: &[Struct]) -> usize {
         const ARCHITECTURE_SIZE: usize = std::mem::size_of::<usize>();
 
@@ -320,33 +602,36 @@ impl ExprType {
             ExprType::AmbiguousNegInteger | 
             ExprType::AmbiguousPosInteger => ARCHITECTURE_SIZE,
             ExprType::AmbiguousFloat => 4,
-            ExprType::I8 | ExprType::U8 | ExprType::Char | ExprType::Bool => 1,
+            ExprType::I8 | ExprType::U8 | ExprType::Bool => 1,
             ExprType::I16 | ExprType::U16 => 2,
-            ExprType::I32 | ExprType::U32 | ExprType::F32 => 4,
+            ExprType::I32 | ExprType::U32 | ExprType::F32 | ExprType::Char => 4,
             ExprType::I64 | ExprType::U64 | ExprType::F64 => 8,
             ExprType::I128 | ExprType::U128 => 16,
             ExprType::ISize | ExprType::USize => ARCHITECTURE_SIZE,
-            ExprType::Ref(_) => ARCHITECTURE_SIZE,
-            ExprType::MutRef(_) => ARCHITECTURE_SIZE,
+            ExprType::Ref { .. } => ARCHITECTURE_SIZE,
+            ExprType::MutRef { .. } => ARCHITECTURE_SIZE,
+            ExprType::ImRef { .. } => ARCHITECTURE_SIZE,
             ExprType::StringLiteral => ARCHITECTURE_SIZE * 2,
             ExprType::Array { length: Some(length), expr_type } => expr_type.size_of(structs) * length,
             ExprType::Array { length: None, .. } => panic!("type does not have constant size"),
-            ExprType::Tuple {..} => todo!("not implemented yet; requires padding"),
-            ExprType::AmbiguousGroup { .. } => todo!("not implemented yet; requires padding"),
-            ExprType::Function { .. } => todo!("not implemented yet"),
-            ExprType::FunctionPass { .. } => todo!("not implemented yet"),
+            ExprType::Range { expr_type, .. } => expr_type.size_of(structs) * 2,
+            ExprType::Tuple { start, end } | 
+            ExprType::AmbiguousGroup { start, end } => ExprType::tuple_layout(start.iter().chain(end), structs).1,
+            ExprType::Function { .. } => ARCHITECTURE_SIZE,
+            ExprType::FunctionPass { .. } => ARCHITECTURE_SIZE,
             ExprType::DiscardSingle => 0,
             ExprType::DiscardMany => 0,
-            ExprType::Custom { ident } => {
+            ExprType::Custom { ident, type_args } => {
                 let custom_struct = structs.iter()
                     .find(|custom_struct| custom_struct.name == *ident)
                     .expect(format!("struct {ident} does not exist").as_str());
                 let mut size = 0;
                 for field in &custom_struct.fields {
-                    size += field.field_type.size_of(structs);
+                    size += custom_struct.field_type(field, type_args).size_of(structs);
                 }
                 size
             },
+            ExprType::Generic { .. } => panic!("type parameters are replaced before anything is laid out"),
             ExprType::AnonymousCustom { fields } => {
                 let mut size = 0;
                 for (_, field_type) in fields.iter() {
@@ -354,11 +639,72 @@ impl ExprType {
                 }
                 size
             },
+            ExprType::Enum { variants, .. } => {
+                let mut payload_size = 0;
+                for variant in variants.iter() {
+                    let mut size = 0;
+                    for (_, field_type) in variant.fields.iter() {
+                        size += field_type.size_of(structs);
+                    }
+                    payload_size = payload_size.max(size);
+                }
+                enums::tag_size(variants.len()) + payload_size
+            },
             ExprType::Void => 0,
             ExprType::Never => 0
         };
     }
 
+    fn with_default_literals(self) -> ExprType {
+        return match self {
+            ExprType::AmbiguousNegInteger | ExprType::AmbiguousPosInteger => ExprType::I32,
+            ExprType::AmbiguousFloat => ExprType::F32,
+            ExprType::Tuple { start, end } => ExprType::Tuple {
+                start: start.into_iter().map(ExprType::with_default_literals).collect(),
+                end: end.into_iter().map(ExprType::with_default_literals).collect()
+            },
+            expr_type => expr_type
+        };
+    }
+
+    /// The boundary a value of this type is placed on inside a tuple. Structs and enums are laid 
+    /// out packed, so they can start anywhere.
+    pub fn align_of(&self, structs: &[Struct]) -> usize {
+        return match self {
+            ExprType::StringLiteral => std::mem::size_of::<usize>(),
+            ExprType::Array { expr_type, .. } | 
+            ExprType::Range { expr_type, .. } => expr_type.align_of(structs),
+            ExprType::Tuple { start, end } | 
+            ExprType::AmbiguousGroup { start, end } => start.iter().chain(end)
+                .map(|element_type| element_type.align_of(structs))
+                .max()
+                .unwrap_or(1),
+            ExprType::Custom { .. } | 
+            ExprType::AnonymousCustom { .. } | 
+            ExprType::Enum { .. } => 1,
+            expr_type => expr_type.size_of(structs).max(1)
+        };
+    }
+
+    /// The offset of each element of a tuple, and the size of the whole tuple, which is padded to 
+    /// a multiple of its largest alignment so that tuples in an array stay aligned too.
+    pub fn tuple_layout<'a>(
+        element_types: impl Iterator<Item = &'a ExprType>, 
+        structs: &[Struct]
+    ) -> (Vec<usize>, usize) {
+        let mut offsets = vec![];
+        let mut size: usize = 0;
+        let mut align = 1;
+        for element_type in element_types {
+            let element_align = element_type.align_of(structs);
+            size = size.next_multiple_of(element_align);
+            offsets.push(size);
+            size += element_type.size_of(structs);
+            align = align.max(element_align);
+        }
+        return (offsets, size.next_multiple_of(align));
+    }
+
     pub fn is_real_type(&self) -> bool {
         return !matches!(self, 
             Self::AmbiguousType | 
@@ -409,15 +755,53 @@ impl std::fmt::Display for ExprType {
         }
         
         write!(f, "{}", match self {
-            ExpressionType::Ref(expression_type) => format!("&{expression_type}"),
-            ExpressionType::MutRef(expression_type) => format!("&mut {expression_type}"),
+            ExpressionType::Ref { lifetime: Some(lifetime), expr_type } => format!("&'{lifetime} {expr_type}"),
+            ExpressionType::Ref { lifetime: None, expr_type } => format!("&{expr_type}"),
+            ExpressionType::MutRef { lifetime: Some(lifetime), expr_type } => format!("&'{lifetime} mut {expr_type}"),
+            ExpressionType::MutRef { lifetime: None, expr_type } => format!("&mut {expr_type}"),
+            ExpressionType::ImRef { lifetime: Some(lifetime), expr_type } => format!("&'{lifetime} im {expr_type}"),
+            ExpressionType::ImRef { lifetime: None, expr_type } => format!("&im {expr_type}"),
             ExpressionType::Array { length: Some(length), expr_type } => format!("[{expr_type}; {length}]"),
             ExpressionType::Array { length: None, expr_type } => format!("[{expr_type}; ?]"),
-            ExpressionType::Tuple { .. } => todo!(),
-            ExpressionType::AmbiguousGroup { .. } => todo!(),
-            ExpressionType::Function { .. } => todo!(),
-            ExpressionType::FunctionPass { .. } => todo!(),
-            ExpressionType::Custom { ident } => ident.clone(),
+            ExpressionType::Range { exclusive_start, inclusive_end, expr_type } => format!(
+                "{expr_type}{}..{}{expr_type}",
+                if *exclusive_start { "!" } else { "" },
+                if *inclusive_end { "=" } else { "" }
+            ),
+            ExpressionType::Tuple { start, end } | ExpressionType::AmbiguousGroup { start, end } => {
+                let mut elements = start.iter().map(|element| element.to_string()).collect::<Vec<_>>();
+                if !end.is_empty() {
+                    elements.push(String::from(".."));
+                    elements.extend(end.iter().map(|element| element.to_string()));
+                }
+                match elements.as_slice() {
+                    [element] => format!("({element},)"),
+                    _ => format!("({})", elements.join(", "))
+                }
+            },
+            ExpressionType::Function { return_type, left_args, right_args, .. } |
+            ExpressionType::FunctionPass { return_type, left_args, right_args } => {
+                let group = |args: &[ExprType]| format!(
+                    "({})", 
+                    args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>().join(", ")
+                );
+                let mut output = match (left_args.is_empty(), right_args.is_empty()) {
+                    (true, _) => format!("fn{}", group(right_args)),
+                    (false, true) => format!("fn{} postfix", group(left_args)),
+                    (false, false) => format!("fn{} infix {}", group(left_args), group(right_args))
+                };
+                if **return_type != ExprType::Void {
+                    output += &format!(" = {return_type}");
+                }
+                output
+            },
+            ExpressionType::Custom { ident, type_args } if type_args.is_empty() => ident.clone(),
+            ExpressionType::Custom { ident, type_args } => format!(
+                "{ident}<{}>", 
+                type_args.iter().map(|type_arg| type_arg.to_string()).collect::<Vec<_>>().join(", ")
+            ),
+            ExpressionType::Generic { name } => name.clone(),
+            ExpressionType::Enum { ident, .. } => ident.clone(),
             ExpressionType::AnonymousCustom { fields } => 'str: {
                 let mut output = String::new();
 
@@ -526,10 +910,62 @@ impl Literal {
     }
 }
 
+/// `param_type` with its type parameters replaced by what `bindings` bound them to so far.
+fn bound_type(param_type: &ExprType, type_params: &[String], bindings: &[ExprTypeCons]) -> ExprType {
+    let type_args = bindings.iter().map(|binding| binding.clone_inner()).collect::<Vec<_>>();
+    return param_type.substitute(type_params, &type_args);
+}
+
+/// Checks that the place `expr_data` can be borrowed with `&mut` or `&im`, which it can when the
+/// variable it is a part of is `mut`, or when it is reached through a reference that can be written
+/// through.  `tkn` is where the place is written.
+fn check_mutable_place<'tkns, 'bumps, 'defs>(
+    tkn: &'tkns Tkn,
+    expr_data: &ExprData<'bumps, 'defs>,
+    variables: &StackFrameDictAllocator<'_, String, VariableData<'tkns, 'bumps>>
+) -> Result<(), ParserError<'tkns, 'bumps, 'defs>> {
+    return match expr_data {
+        ExprData::Identifier(name, _) => match variables.get_in_stack(name).map(|variable| variable.get()) {
+            Some(VariableData { mutable: false, tkn: variable_def, .. }) => Err(ParserError::BorrowMutOfImmutable { 
+                tkn, 
+                variable_def 
+            }),
+            _ => Ok(())
+        },
+        ExprData::CustomField { data, .. } | ExprData::AnonymousCustomField { data, .. } => {
+            check_mutable_place(tkn, data.expr_data, variables)
+        },
+        ExprData::Index { expr, .. } => check_mutable_place(tkn, expr.expr_data, variables),
+        ExprData::UnaryOp(UnOp::Deref, reference) => check_writable_reference(tkn, &reference.expr_type.get()),
+        _ => Ok(())
+    };
+}
+
+/// Checks that a reference of `expr_type` can be written through, which `&` references cannot.
+pub fn check_writable_reference<'tkns, 'bumps, 'defs>(
+    tkn: &'tkns Tkn,
+    expr_type: &ExprType
+) -> Result<(), ParserError<'tkns, 'bumps, 'defs>> {
+    if let ExprType::Ref { .. } = expr_type {
+        return Err(ParserError::MutateThroughSharedRef { tkn, expr_type: expr_type.clone() });
+    }
+    return Ok(());
+}
+
+/// Whether reading `expr_data` again gives the same value without running anything twice.
+pub fn is_place(expr_data: &ExprData) -> bool {
+    return match expr_data {
+        ExprData::Identifier(..) | ExprData::Literal(_) => true,
+        ExprData::CustomField { data, .. } | ExprData::AnonymousCustomField { data, .. } => is_place(data.expr_data),
+        _ => false,
+    };
+}
+
 //recursive
 fn parse_expression<'tkns, 'bumps, 'defs>(
     expr_bump: &'bumps ExprBump,
     structs: &'defs [Struct],
+    enums: &'defs [Enum],
     tokens: &'tkns [Tkn], 
     index: &mut usize,
     line: usize,
@@ -543,27 +979,49 @@ fn parse_expression<'tkns, 'bumps, 'defs>(
         expr_data: mut left_expr_data, 
         expr_type: mut left_expr_type,
         ..
-    } = parse_atom(expr_bump, structs, tokens, &mut peek, line, functions, variables)?;
+    } = parse_atom(expr_bump, structs, enums, tokens, &mut peek, line, functions, variables)?;
 
     loop {
-        let mut operator = &tokens[peek].token;
+        let Some(mut operator) = tokens.get(peek).map(|tkn| &tkn.token) else {
+            break;
+        };
 
-        if let TknType::Either(left, right) = operator {
-            if OPERATOR_INFO_MAP.contains_key(left) {
-                operator = left;
-            } else if OPERATOR_INFO_MAP.contains_key(right) {
-                operator = right;
-            } else {
-                break;
-            }
+        let custom_operator = match operator {
+            TknType::CustomOperation(symbol) => match custom_operator(
+                tokens, peek, functions, &left_expr_type.clone_inner(), symbol
+            ) {
+                Ok(fun_def) => Some(fun_def),
+                // the operator can still be defined on the operand an outer operator is building up
+                Err(ParserError::OperatorNotDefined { .. }) if min_prec > 0 => break,
+                Err(err) => return Err(err)
+            },
+            // with nothing after it, the function is applied partially by the expression set instead
+            TknType::Identifier(name) if !ends_expression(tokens, peek + 1) => {
+                fixity_function(tokens, peek, functions, &left_expr_type.clone_inner(), name)?
+            },
+            _ => None
+        };
+
+        let (prec, assoc) = if let Some(FullFnDef { fixity: Some(fixity), .. }) = &custom_operator {
+            (fixity.precedence, fixity.associativity)
         } else {
-            if !OPERATOR_INFO_MAP.contains_key(operator) {
-                break;
+            if let TknType::Either(left, right) = operator {
+                if OPERATOR_INFO_MAP.contains_key(left) {
+                    operator = left;
+                } else if OPERATOR_INFO_MAP.contains_key(right) {
+                    operator = right;
+                } else {
+                    break;
+                }
+            } else {
+                if !OPERATOR_INFO_MAP.contains_key(operator) {
+                    break;
+                }
             }
-        }
 
-        let (prec, assoc) = OPERATOR_INFO_MAP[operator];
-        let prec = prec as u32;
+            let (prec, assoc) = OPERATOR_INFO_MAP[operator];
+            (prec as u32, assoc)
+        };
 
         if prec < min_prec {
             break;
@@ -575,8 +1033,40 @@ fn parse_expression<'tkns, 'bumps, 'defs>(
             prec
         };
 
+        let operator_tkn = &tokens[peek];
         peek += 1;
 
+        if *operator == TknType::Keyword(Kwrd::As) {
+            let cast_type = super::get_type(tokens, &mut peek, structs, enums)
+                .ok_or(ParserError::ExpectedType { tkn: &tokens[peek] })?;
+            let left_operand_type = left_expr_type.clone();
+
+            left_expr_type = BinOp::Cast.transform_type(
+                expr_bump,
+                &mut left_expr_type,
+                &mut ExprTypeCons::new(expr_bump, cast_type.clone())
+            ).ok_or_else(|| ParserError::InvalidCast { 
+                tkn: operator_tkn, 
+                from: left_operand_type.clone_inner(), 
+                to: cast_type.clone() 
+            })?;
+
+            left_expr_data = expr_bump.alloc(ExprData::BinaryOp(
+                BinOp::Cast,
+                Expr {
+                    line,
+                    expr_data: left_expr_data,
+                    expr_type: left_operand_type,
+                },
+                Expr {
+                    line,
+                    expr_data: expr_bump.alloc(ExprData::Tuple(vec![])),
+                    expr_type: ExprTypeCons::new(expr_bump, cast_type)
+                }
+            ));
+            continue;
+        }
+
         let start_expr = peek;
         let Expr {
             expr_data: right_expr_data, 
@@ -585,6 +1075,7 @@ fn parse_expression<'tkns, 'bumps, 'defs>(
         } = parse_expression(
             expr_bump, 
             structs,
+            enums,
             tokens, 
             &mut peek, 
             line,
@@ -593,7 +1084,51 @@ fn parse_expression<'tkns, 'bumps, 'defs>(
             next_min_prec
         )?;
 
+        if let Some(fun_def) = custom_operator {
+            let type_params = &fun_def.type_params;
+            let mut bindings = type_params.iter()
+                .map(|_| ExprTypeCons::new(expr_bump, ExprType::AmbiguousType))
+                .collect::<Vec<_>>();
+
+            let left_param = &fun_def.left_args[0].param_type;
+            let mut left_operand_type = left_expr_type
+                .match_type_params(expr_bump, left_param, type_params, &mut bindings)
+                .ok_or_else(|| ParserError::CouldNotMatchType { 
+                    tkns: &tokens[*index..start_expr - 1], 
+                    calculated_type: left_expr_type.clone_inner(), 
+                    expected_type: bound_type(left_param, type_params, &bindings)
+                })?;
+            let right_param = &fun_def.right_args[0].param_type;
+            let mut right_expr_type = right_expr_type
+                .match_type_params(expr_bump, right_param, type_params, &mut bindings)
+                .ok_or_else(|| ParserError::CouldNotMatchType { 
+                    tkns: &tokens[start_expr..peek], 
+                    calculated_type: right_expr_type.clone_inner(), 
+                    expected_type: bound_type(right_param, type_params, &bindings)
+                })?;
+
+            let mut name = fun_def.name.clone();
+            let mut return_type = fun_def.return_type.clone();
+            if !type_params.is_empty() {
+                let type_args = infer_type_args(expr_bump, operator_tkn, type_params, &mut bindings)?;
+                left_operand_type.match_type(&mut ExprTypeCons::new(expr_bump, left_param.substitute(type_params, &type_args)));
+                right_expr_type.match_type(&mut ExprTypeCons::new(expr_bump, right_param.substitute(type_params, &type_args)));
+
+                return_type = return_type.substitute(type_params, &type_args);
+                name = functions::instantiate(expr_bump, functions, &name, &type_args);
+            }
+
+            left_expr_data = expr_bump.alloc(ExprData::Function { 
+                name, 
+                left_args: vec![Expr { line, expr_data: left_expr_data, expr_type: left_operand_type }], 
+                right_args: vec![Expr { line, expr_data: right_expr_data, expr_type: right_expr_type }]
+            });
+            left_expr_type = ExprTypeCons::new(expr_bump, return_type);
+            continue;
+        }
+
         //let (_temp_left_expr_type, _temp_right_expr_type) = (left_expr_type.clone(), right_expr_type.clone());
+        let left_operand_type = left_expr_type.clone();
 
         match BinOp::get_bin_op(operator).transform_type(
             expr_bump, 
@@ -617,7 +1152,7 @@ fn parse_expression<'tkns, 'bumps, 'defs>(
             Expr {
                 line,
                 expr_data: left_expr_data,
-                expr_type: left_expr_type.clone(),
+                expr_type: left_operand_type,
             },
             Expr {
                 line,
@@ -635,9 +1170,69 @@ fn parse_expression<'tkns, 'bumps, 'defs>(
     });
 }
 
+/// The method of `left_type` defining the custom operator at `index`, which is called with the 
+/// operands on either side of it.
+fn custom_operator<'tkns, 'bumps, 'defs>(
+    tokens: &'tkns [Tkn],
+    index: usize,
+    functions: &RefCell<HashMap<String, FullFnDef<'tkns, 'bumps, 'defs>>>,
+    left_type: &ExprType,
+    symbol: &str
+) -> Result<FullFnDef<'tkns, 'bumps, 'defs>, ParserError<'tkns, 'bumps, 'defs>> {
+    let fun_def = traits::method_name_of(left_type, symbol)
+        .and_then(|name| functions.borrow().get(&name).cloned())
+        .ok_or_else(|| ParserError::OperatorNotDefined { tkn: &tokens[index], expr_type: left_type.clone() })?;
+    accessors::check_function(&tokens[index], &fun_def)?;
+    return Ok(fun_def);
+}
+
+/// The function `name` at `index`, either a function or a method of `left_type`, when it is 
+/// declared with a fixity, which takes the operands on either side of it by its precedence like 
+/// an operator does.
+fn fixity_function<'tkns, 'bumps, 'defs>(
+    tokens: &'tkns [Tkn],
+    index: usize,
+    functions: &RefCell<HashMap<String, FullFnDef<'tkns, 'bumps, 'defs>>>,
+    left_type: &ExprType,
+    name: &str
+) -> Result<Option<FullFnDef<'tkns, 'bumps, 'defs>>, ParserError<'tkns, 'bumps, 'defs>> {
+    let fun_def = functions.borrow().get(name).cloned().or_else(|| {
+        traits::method_name_of(left_type, name).and_then(|name| functions.borrow().get(&name).cloned())
+    });
+    let Some(fun_def) = fun_def.filter(|fun_def| fun_def.fixity.is_some()) else {
+        return Ok(None);
+    };
+    accessors::check_function(&tokens[index], &fun_def)?;
+    return Ok(Some(fun_def));
+}
+
+/// The types the `bindings` of a call bound the `type_params` of the function to, where the 
+/// literals left ambiguous take their default types.
+fn infer_type_args<'tkns, 'bumps, 'defs>(
+    expr_bump: &'bumps ExprBump,
+    tkn: &'tkns Tkn,
+    type_params: &[String],
+    bindings: &mut [ExprTypeCons<'bumps>]
+) -> Result<Vec<ExprType>, ParserError<'tkns, 'bumps, 'defs>> {
+    let mut type_args = vec![];
+    for (type_param, binding) in type_params.iter().zip(bindings.iter_mut()) {
+        binding.default_literal_type(expr_bump);
+        let type_arg = binding.clone_inner();
+        if !type_arg.is_real_type() {
+            return Err(ParserError::CouldNotInferTypeParameter { 
+                tkn, 
+                type_param: type_param.clone() 
+            });
+        }
+        type_args.push(type_arg);
+    }
+    return Ok(type_args);
+}
+
 pub fn parse_expression_set<'tkns, 'bumps, 'defs>(
     expr_bump: &'bumps ExprBump,
     structs: &'defs [Struct],
+    enums: &'defs [Enum],
     tokens: &'tkns [Tkn],
     index: &mut usize,
     line: usize,
@@ -654,52 +1249,111 @@ pub fn parse_expression_set<'tkns, 'bumps, 'defs>(
     let last_expr_err;
 
     'parse_set: loop {
+        // a default value is parsed from the tokens of the signature, which end right after it
+        if peek >= tokens.len() {
+            last_expr_err = ParserError::InvalidExpressionAtom { tkn: &tokens[peek - 1] };
+            break 'parse_set;
+        }
         let start_expr = peek;
         
-        let expr;
+        let mut expr;
 
-        match parse_expression(
-            expr_bump, 
-            structs,
-            tokens, 
-            &mut peek, 
-            line,
-            functions,
-            variables, 
-            0
-        ) {
-            Ok(value) => expr = value,
-            Err(err) => {
-                last_expr_err = err;
-                break 'parse_set;
-            }
+        let method = match exprs.first() {
+            Some(receiver) => parse_method(expr_bump, tokens, &mut peek, line, functions, receiver)?,
+            None => None
+        };
+        if let Some(method) = method {
+            expr = method;
+        } else {
+            match parse_expression(
+                expr_bump, 
+                structs,
+                enums,
+                tokens, 
+                &mut peek, 
+                line,
+                functions,
+                variables, 
+                0
+            ) {
+                Ok(value) => expr = value,
+                Err(err) => {
+                    last_expr_err = err;
+                    break 'parse_set;
+                }
+            };
+        }
+
+        let signature = match expr.expr_type.clone_inner() {
+            ExprType::Function { name, return_type, left_args, right_args } => {
+                Some((Some(name), return_type, left_args, right_args))
+            },
+            ExprType::FunctionPass { return_type, left_args, right_args } => {
+                Some((None, return_type, left_args, right_args))
+            },
+            _ => None
         };
 
-        if let ExprType::Function { 
-            name,
-            return_type, 
-            left_args, 
-            right_args 
-        } = expr.expr_type.clone_inner() {
-            if exprs.len() != left_args.len() {
+        if let Some((name, return_type, mut left_args, mut right_args)) = signature {
+            // the trailing arguments on the right that calls can leave out
+            let defaults = name.as_ref()
+                .and_then(|name| functions.borrow().get(name).map(|fun_def| {
+                    fun_def.right_args.iter().filter(|arg| arg.default_tokens.is_some()).count()
+                }))
+                .unwrap_or_default();
+
+            // a function that takes arguments with nothing to call it with is passed along as a value, 
+            // as is a closure when it is written
+            let takes_args = left_args.len() + right_args.len() > defaults;
+            if exprs.is_empty() 
+                && ends_expression(tokens, peek)
+                && (matches!(expr.expr_data, ExprData::Closure { .. }) || takes_args && name.as_ref().is_none_or(|name| {
+                    functions.borrow().get(name).is_some_and(|fun_def| fun_def.type_params.is_empty())
+                }))
+            {
+                expr.expr_type = ExprTypeCons::new(expr_bump, ExprType::FunctionPass { return_type, left_args, right_args });
+                exprs.push(expr);
+                expr_start_indices.push(start_expr);
+                expr_end_indices.push(peek);
+                continue 'parse_set;
+            }
+
+            // a function written by name is only applied partially when it is more than a built in
+            let can_apply_partially = name.as_ref().is_none_or(|name| functions.borrow().contains_key(name));
+            if exprs.len() > left_args.len() || exprs.len() < left_args.len() && !can_apply_partially {
                 return Err(ParserError::IncorrectNumberPrefixArguments { 
-                    tkn: &tokens[peek], 
+                    tkn: &tokens[start_expr], 
                     args: exprs.into_iter().map(|e| e.expr_type.clone_inner()).collect::<Vec<_>>().into_boxed_slice(), 
                     expected_args: left_args.into_boxed_slice(), 
-                    function: functions.borrow().get(name.as_str()).unwrap().clone()
+                    function: name.unwrap_or_else(|| match expr.expr_data {
+                        ExprData::Identifier(ident, _) => ident.clone(),
+                        _ => expr.expr_type.clone_inner().to_string()
+                    })
                 });
             }
 
+            // a call to a generic function calls the instance of it for the types its arguments bind
+            let type_params = name.as_ref()
+                .and_then(|name| functions.borrow().get(name.as_str()).map(|fun_def| fun_def.type_params.clone()))
+                .unwrap_or_default();
+            let mut bindings = type_params.iter()
+                .map(|_| ExprTypeCons::new(expr_bump, ExprType::AmbiguousType))
+                .collect::<Vec<_>>();
+
             let mut left_exprs = vec![];
             let mut right_exprs = vec![];
 
+            // fewer prefix arguments than the function takes are the last of its left arguments,
+            // the ones written next to it
+            let left_start = left_args.len() - exprs.len();
             for (i, mut expr) in exprs.drain(..).enumerate() {
+                let param = &left_args[left_start + i];
                 expr.expr_type = expr.expr_type
-                    .match_type(&mut ExprTypeCons::new(expr_bump, left_args[i].clone()))
+                    .match_type_params(expr_bump, param, &type_params, &mut bindings)
                     .ok_or_else(|| ParserError::CouldNotMatchType {
                         tkns: &tokens[expr_start_indices[i]..expr_end_indices[i]],
                         calculated_type: expr.expr_type.clone_inner(),
-                        expected_type: left_args[i].clone()
+                        expected_type: bound_type(param, &type_params, &bindings)
                     })?;
 
                 left_exprs.push(Expr {
@@ -713,11 +1367,15 @@ pub fn parse_expression_set<'tkns, 'bumps, 'defs>(
             //handle c function syntax vs typical group expressions
             // foo(arg1, arg2) vs. foo arg1 arg2
 
-            'parse_right_args: for arg in right_args {
+            for arg in &right_args {
+                if can_apply_partially && ends_expression(tokens, peek) {
+                    break;
+                }
                 let start_expr = peek;
                 let mut expr = parse_expression(
                     expr_bump, 
                     structs,
+                    enums,
                     tokens, 
                     &mut peek, 
                     line,
@@ -726,11 +1384,11 @@ pub fn parse_expression_set<'tkns, 'bumps, 'defs>(
                     0
                 )?;
 
-                expr.expr_type = expr.expr_type.match_type(&mut ExprTypeCons::new(expr_bump, arg.clone()))
+                expr.expr_type = expr.expr_type.match_type_params(expr_bump, arg, &type_params, &mut bindings)
                     .ok_or_else(|| ParserError::CouldNotMatchType { 
                         tkns: &tokens[start_expr..peek], 
                         calculated_type: expr.expr_type.clone_inner(), 
-                        expected_type: arg
+                        expected_type: bound_type(arg, &type_params, &bindings)
                     })?;
 
                 right_exprs.push(Expr {
@@ -740,14 +1398,59 @@ pub fn parse_expression_set<'tkns, 'bumps, 'defs>(
                 });
             }
 
+            // a call that leaves out arguments with defaults is still a call, which fills them in
+            if left_start == 0 && right_exprs.len() >= right_args.len() - defaults {
+                right_args.truncate(right_exprs.len());
+            }
+
+            let mut name = name;
+            let mut return_type = return_type;
+            if let Some(name) = &mut name && !type_params.is_empty() {
+                let type_args = infer_type_args(expr_bump, &tokens[start_expr], &type_params, &mut bindings)?;
+
+                for param in left_args.iter_mut().chain(right_args.iter_mut()) {
+                    *param = param.substitute(&type_params, &type_args);
+                }
+                let params = left_args[left_start..].iter().chain(right_args.iter());
+                for (expr, param) in left_exprs.iter_mut().chain(right_exprs.iter_mut()).zip(params) {
+                    expr.expr_type.match_type(&mut ExprTypeCons::new(expr_bump, param.clone()));
+                }
+
+                *return_type = return_type.substitute(&type_params, &type_args);
+                *name = functions::instantiate(expr_bump, functions, name, &type_args);
+            }
+
+            let (expr_data, expr_type) = if left_start > 0 || right_exprs.len() < right_args.len() {
+                // the arguments left out make up the signature of the function value
+                let expr_type = ExprType::FunctionPass {
+                    return_type: return_type.clone(),
+                    left_args: left_args[..left_start].to_vec(),
+                    right_args: right_args[right_exprs.len()..].to_vec()
+                };
+                let name_index = match expr.expr_data {
+                    ExprData::Identifier(_, name_index) => *name_index,
+                    _ => 0
+                };
+                let callee = match name {
+                    Some(name) => Expr {
+                        line,
+                        expr_data: expr_bump.alloc(ExprData::Identifier(name, name_index)),
+                        expr_type: ExprTypeCons::new(expr_bump, ExprType::FunctionPass { return_type, left_args, right_args })
+                    },
+                    None => expr
+                };
+                (ExprData::Partial { callee, left_args: left_exprs, right_args: right_exprs }, expr_type)
+            } else {
+                let expr_data = match name {
+                    Some(name) => ExprData::Function { name, left_args: left_exprs, right_args: right_exprs },
+                    None => ExprData::Call { callee: expr, left_args: left_exprs, right_args: right_exprs }
+                };
+                (expr_data, *return_type)
+            };
             let expr = Expr {
                 line,
-                expr_data: expr_bump.alloc(ExprData::Function { 
-                    name, 
-                    left_args: left_exprs, 
-                    right_args: right_exprs 
-                }),
-                expr_type: ExprTypeCons::new(expr_bump, *return_type)
+                expr_data: expr_bump.alloc(expr_data),
+                expr_type: ExprTypeCons::new(expr_bump, expr_type)
             };
 
             exprs.push(expr);
@@ -775,9 +1478,68 @@ pub fn parse_expression_set<'tkns, 'bumps, 'defs>(
     };
 }
 
+/// Whether the expression set being parsed ends before the token at `index`.
+fn ends_expression(tokens: &[Tkn], index: usize) -> bool {
+    return matches!(tokens.get(index).map(|tkn| &tkn.token), None | Some(
+        TknType::Semicolon | TknType::Comma | TknType::CloseParen | 
+        TknType::CloseSquareBracket | TknType::CloseCurlyBrace | TknType::EndOfFile
+    ));
+}
+
+/// `receiver method ...`, where the method is looked up on the type of the first argument of the
+/// call, so it is only found when `receiver` is the first expression of the set.
+fn parse_method<'tkns, 'bumps, 'defs>(
+    expr_bump: &'bumps ExprBump,
+    tokens: &'tkns [Tkn],
+    index: &mut usize,
+    line: usize,
+    functions: &RefCell<HashMap<String, FullFnDef<'tkns, 'bumps, 'defs>>>,
+    receiver: &Expr<'bumps, 'defs>
+) -> Result<Option<Expr<'bumps, 'defs>>, ParserError<'tkns, 'bumps, 'defs>> {
+    let Some(tkn @ Tkn { token: TknType::Identifier(method), .. }) = tokens.get(*index) else {
+        return Ok(None);
+    };
+    let Some(name) = traits::method_name_of(&receiver.expr_type.clone_inner(), method) else {
+        return Ok(None);
+    };
+    let functions = functions.borrow();
+    let Some(fun_def) = functions.get(&name).filter(|fun_def| !fun_def.left_args.is_empty()) else {
+        return Ok(None);
+    };
+
+    accessors::check_function(tkn, fun_def)?;
+    let value = function_value(expr_bump, line, &name, *index, fun_def);
+    *index += 1;
+    return Ok(Some(value));
+}
+
+/// The function `name`, written at the token at `name_index`, where a value is expected, waiting 
+/// for the arguments of its call.
+fn function_value<'bumps, 'defs>(
+    expr_bump: &'bumps ExprBump,
+    line: usize,
+    name: &str,
+    name_index: usize,
+    fun_def: &FullFnDef
+) -> Expr<'bumps, 'defs> {
+    let left_args = fun_def.left_args.iter().map(|e| e.param_type.clone()).collect();
+    let right_args = fun_def.right_args.iter().map(|e| e.param_type.clone()).collect();
+    return Expr {
+        line,
+        expr_data: expr_bump.alloc(ExprData::Identifier(name.to_string(), name_index)),
+        expr_type: ExprTypeCons::new(expr_bump, ExprType::Function { 
+            name: name.to_string(), 
+            return_type: Box::new(fun_def.return_type.clone()), 
+            left_args, 
+            right_args 
+        })
+    };
+}
+
 fn parse_group_expression<'tkns, 'bumps, 'defs>(
     expr_bump: &'bumps ExprBump,
     structs: &'defs [Struct],
+    enums: &'defs [Enum],
     tokens: &'tkns [Tkn], 
     index: &mut usize,
     line: usize,
@@ -785,11 +1547,33 @@ fn parse_group_expression<'tkns, 'bumps, 'defs>(
     variables: &StackFrameDictAllocator<'_, String, VariableData<'tkns, 'bumps>>
 ) -> Result<Expr<'bumps, 'defs>, ParserError<'tkns, 'bumps, 'defs>> {
     if is_expected_token(tokens, TknType::OpenParen, index) {
-        let expr = parse_expression_set(expr_bump, structs, tokens, index, line, functions, variables)?;
-        expect_token(tokens, TknType::CloseParen, index).unwrap();
-        return Ok(expr);
+        let expr = parse_expression_set(expr_bump, structs, enums, tokens, index, line, functions, variables)?;
+        if !tokens::is_token(tokens, TknType::Comma, *index) {
+            expect_token(tokens, TknType::CloseParen, index).ok_or_else(|| ParserError::ExpectedToken { 
+                tkn: &tokens[*index], 
+                expected: TknType::CloseParen 
+            })?;
+            return Ok(expr);
+        }
+
+        // `(a, b)` is a tuple, and so is `(a,)`, while `(a)` is just `a`
+        let mut elements = vec![expr];
+        while is_expected_token(tokens, TknType::Comma, index) && !tokens::is_token(tokens, TknType::CloseParen, *index) {
+            elements.push(parse_expression_set(expr_bump, structs, enums, tokens, index, line, functions, variables)?);
+        }
+        expect_token(tokens, TknType::CloseParen, index).ok_or_else(|| ParserError::ExpectedToken { 
+            tkn: &tokens[*index], 
+            expected: TknType::CloseParen 
+        })?;
+
+        let element_types = elements.iter().map(|element| element.expr_type.clone_inner()).collect();
+        return Ok(Expr {
+            line,
+            expr_data: expr_bump.alloc(ExprData::Tuple(elements)),
+            expr_type: ExprTypeCons::new(expr_bump, ExprType::Tuple { start: element_types, end: vec![] })
+        });
     } else if is_expected_token(tokens, TknType::Dollar, index) {
-        let expr = parse_expression_set(expr_bump, structs, tokens, index, line, functions, variables)?;
+        let expr = parse_expression_set(expr_bump, structs, enums, tokens, index, line, functions, variables)?;
         //TODO return ambiguous group instead
         return Ok(expr);
     }
@@ -805,6 +1589,7 @@ fn parse_group_expression<'tkns, 'bumps, 'defs>(
 fn parse_atom<'tkns, 'bumps, 'defs>(
     expr_bump: &'bumps ExprBump,
     structs: &'defs [Struct],
+    enums: &'defs [Enum],
     tokens: &'tkns [Tkn], 
     index: &mut usize,
     line: usize,
@@ -833,7 +1618,7 @@ fn parse_atom<'tkns, 'bumps, 'defs>(
     } else if let Some(TknType::Operation(Op::BitwiseNegate)) = curr_token {
         op = Some(UnOp::BitwiseNegate);
         peek += 1;
-    } else if let Some(TknType::Borrow) = curr_token {
+    } else if tokens::is_token(tokens, TknType::Borrow, peek) {
         if let Some(TknType::Keyword(Kwrd::Mutable)) = tokens.get(peek + 1).map(|e| &e.token) {
             op = Some(UnOp::BorrowMutable);
             peek += 2;
@@ -844,28 +1629,42 @@ fn parse_atom<'tkns, 'bumps, 'defs>(
             op = Some(UnOp::Borrow);
             peek += 1;
         }
+    } else if let Some(TknType::Operation(Op::Multiply)) = curr_token {
+        op = Some(UnOp::Deref);
+        peek += 1;
     } else {
         op = None;
     }
 
+    let operand_start = peek;
     curr_token = tokens.get(peek).map(|e| &e.token);
 
     let mut expr_data: &ExprData;
     let mut expr_type;
     if let Some(TknType::Identifier(ident)) = curr_token {
-        if let Some(fun_def) = functions.borrow().get(ident) {
-            expr_data = expr_bump.alloc(ExprData::Identifier(ident.clone()));
-            let left_args = fun_def.left_args.iter().map(|e| e.param_type.clone()).collect();
-            let right_args = fun_def.right_args.iter().map(|e| e.param_type.clone()).collect();
-            expr_type = ExprTypeCons::new(expr_bump, ExprType::Function { 
-                name: ident.clone(), 
-                return_type: Box::new(fun_def.return_type.clone()), 
-                left_args, 
-                right_args 
-            });
+        // a closure in a struct literal below defines a function, so the functions cannot stay
+        // borrowed by a condition for the whole chain
+        let is_function = functions.borrow().contains_key(ident);
+        let method = match (tokens.get(peek + 1).map(|e| &e.token), tokens.get(peek + 2).map(|e| &e.token)) {
+            (Some(TknType::ColonColon), Some(TknType::Identifier(method))) => Some(format!("{ident}::{method}"))
+                .filter(|method| functions.borrow().contains_key(method)),
+            _ => None
+        };
+        if is_function {
+            let functions = functions.borrow();
+            let fun_def = &functions[ident];
+            accessors::check_function(&tokens[peek], fun_def)?;
+            Expr {expr_data, expr_type, ..} = function_value(expr_bump, line, ident, peek, fun_def);
             peek += 1;
+        } else if let Some(method) = method {
+            // `Type::method`, a method called without a value of the type in front of it
+            let functions = functions.borrow();
+            let fun_def = &functions[&method];
+            accessors::check_function(&tokens[peek + 2], fun_def)?;
+            Expr {expr_data, expr_type, ..} = function_value(expr_bump, line, &fun_def.name, peek + 2, fun_def);
+            peek += 3;
         } else if let Some(built_in_fn) = BuiltInFunction::from_name(ident) {
-            expr_data = expr_bump.alloc(ExprData::Identifier(ident.clone()));
+            expr_data = expr_bump.alloc(ExprData::Identifier(ident.clone(), peek));
             expr_type = ExprTypeCons::new(expr_bump, BuiltInFunction::get_type(&built_in_fn));
             peek += 1;
         } else if let Some(custom_struct) = structs.iter()
@@ -881,6 +1680,10 @@ fn parse_atom<'tkns, 'bumps, 'defs>(
 
             let mut fields: HashMap<&str, &ExprData> = HashMap::new();
             let mut field_indices: HashMap<&str, &Tkn> = HashMap::new();
+            // the type arguments of a generic struct are whatever its fields are given
+            let mut bindings = custom_struct.type_params.iter()
+                .map(|_| ExprTypeCons::new(expr_bump, ExprType::AmbiguousType))
+                .collect::<Vec<_>>();
 
             let mut needed_comma = false;
             loop {
@@ -893,20 +1696,19 @@ fn parse_atom<'tkns, 'bumps, 'defs>(
                     return Err(ParserError::ExpectedToken{ tkn: &tokens[peek], expected: TknType::Comma });
                 }
 
-                //TODO add accessor checks
-
                 let field = custom_struct.fields.iter().find(|field| 
                     field.field_name == *field_name
                 ).ok_or_else(|| ParserError::FieldDoesNotExist { 
                     tkn: &tokens[peek], 
                     custom_struct
                 })?;
+                accessors::check_field(&tokens[peek], custom_struct, field)?;
 
                 peek += 1;
 
                 if fields.contains_key(field.field_name.as_str()) {
                     return Err(ParserError::AlreadyDefinedField { 
-                        tkn: &tokens[peek], 
+                        tkn: &tokens[field_index], 
                         defined_field: field_indices.get(field.field_name.as_str()).unwrap() 
                     });
                 }
@@ -916,6 +1718,7 @@ fn parse_atom<'tkns, 'bumps, 'defs>(
                     let mut expr = parse_expression_set(
                         expr_bump, 
                         structs, 
+                        enums, 
                         tokens, 
                         &mut peek, 
                         line,
@@ -923,12 +1726,12 @@ fn parse_atom<'tkns, 'bumps, 'defs>(
                         variables
                     )?;
                     
-                    expr.expr_type.match_type(
-                        &mut ExprTypeCons::new(expr_bump, field.field_type.clone())
+                    expr.expr_type.match_type_params(
+                        expr_bump, &field.field_type, &custom_struct.type_params, &mut bindings
                     ).ok_or(ParserError::CouldNotMatchType { 
                         tkns: &tokens[start_expr..peek], 
                         calculated_type: expr.expr_type.clone_inner(), 
-                        expected_type: field.field_type.clone()
+                        expected_type: bound_type(&field.field_type, &custom_struct.type_params, &bindings)
                     })?;
 
                     fields.insert(
@@ -942,23 +1745,23 @@ fn parse_atom<'tkns, 'bumps, 'defs>(
                 } else {
                     let ident_type = &variables.get_in_stack(&field.field_name)
                         .ok_or_else(|| ParserError::FieldExpressionNotDefined { 
-                            tkn: &tokens[peek], 
+                            tkn: &tokens[field_index], 
                             field 
                         })?
                         .get().expr_type;
 
-                    ident_type.clone().match_type(
-                        &mut ExprTypeCons::new(expr_bump, field.field_type.clone())
+                    ident_type.clone().match_type_params(
+                        expr_bump, &field.field_type, &custom_struct.type_params, &mut bindings
                     ).ok_or_else(|| ParserError::CouldNotMatchType { 
                         tkns: core::slice::from_ref(&tokens[peek]), 
                         calculated_type: ident_type.clone_inner(), 
-                        expected_type: field.field_type.clone()
+                        expected_type: bound_type(&field.field_type, &custom_struct.type_params, &bindings)
                     })?;
 
                     fields.insert(
                         &field.field_name, 
                         expr_bump.alloc(
-                            ExprData::Identifier(field.field_name.clone())
+                            ExprData::Identifier(field.field_name.clone(), field_index)
                         )
                     );
                     field_indices.insert(
@@ -980,10 +1783,29 @@ fn parse_atom<'tkns, 'bumps, 'defs>(
 
             expr_data = expr_bump.alloc(ExprData::Custom { fields });
             expr_type = ExprTypeCons::new(expr_bump, ExprType::Custom { 
-                ident: custom_struct.name.clone() 
+                ident: custom_struct.name.clone(),
+                type_args: bindings.iter_mut().map(|binding| {
+                    binding.default_literal_type(expr_bump);
+                    binding.clone_inner()
+                }).collect()
             });
+        } else if let Some(custom_enum) = enums.iter()
+            .find(|custom_enum| custom_enum.name == *ident)
+        {
+            peek += 1;
+            Expr {expr_data, expr_type, ..} = parse_enum_variant(
+                expr_bump,
+                structs,
+                enums,
+                custom_enum,
+                tokens,
+                &mut peek,
+                line,
+                functions,
+                variables
+            )?;
         } else {
-            expr_data = expr_bump.alloc(ExprData::Identifier(ident.clone()));
+            expr_data = expr_bump.alloc(ExprData::Identifier(ident.clone(), peek));
             expr_type = ExprTypeCons::grab_variable_type(tokens, peek, ident, variables)?;
             peek += 1;
         }
@@ -1011,10 +1833,33 @@ fn parse_atom<'tkns, 'bumps, 'defs>(
         expr_data = expr_bump.alloc(ExprData::Literal(Lit::BooleanLiteral(*b)));
         expr_type = ExprTypeCons::new(expr_bump, ExprType::Bool);
         peek += 1;
+    } else if let Some(TknType::Keyword(Kwrd::Function | Kwrd::Move)) = curr_token {
+        Expr {expr_data, expr_type, ..} = parse_closure(
+            expr_bump, 
+            structs,
+            enums,
+            tokens, 
+            &mut peek, 
+            line,
+            functions, 
+            variables
+        )?;
+    } else if let Some(TknType::Keyword(Kwrd::Match)) = curr_token {
+        Expr {expr_data, expr_type, ..} = parse_match_expression(
+            expr_bump, 
+            structs,
+            enums,
+            tokens, 
+            &mut peek, 
+            line,
+            functions, 
+            variables
+        )?;
     } else if let Some(TknType::OpenParen) = curr_token {
         Expr {expr_data, expr_type, ..} = parse_group_expression(
             expr_bump, 
             structs,
+            enums,
             tokens, 
             &mut peek, 
             line,
@@ -1025,6 +1870,7 @@ fn parse_atom<'tkns, 'bumps, 'defs>(
         Expr {expr_data, expr_type, ..} = parse_array(
             expr_bump, 
             structs,
+            enums,
             tokens, 
             &mut peek, 
             line,
@@ -1035,6 +1881,7 @@ fn parse_atom<'tkns, 'bumps, 'defs>(
         if let Ok(expr) = parse_array(
             expr_bump, 
             structs,
+            enums,
             tokens, 
             &mut peek, 
             line,
@@ -1046,6 +1893,7 @@ fn parse_atom<'tkns, 'bumps, 'defs>(
         } else if let Ok(expr) = parse_group_expression(
             expr_bump, 
             structs, 
+            enums, 
             tokens, 
             &mut peek, 
             line,
@@ -1095,6 +1943,7 @@ fn parse_atom<'tkns, 'bumps, 'defs>(
             } = parse_expression_set(
                 expr_bump, 
                 structs, 
+                enums, 
                 tokens, 
                 &mut peek, 
                 line, 
@@ -1121,16 +1970,17 @@ fn parse_atom<'tkns, 'bumps, 'defs>(
 
     loop {
         if tokens::is_expected_token(tokens, TknType::Dot, &mut peek) {
-            if let ExprType::Custom { ident } = expr_type.clone_inner() && 
+            if let ExprType::Custom { ident, type_args } = expr_type.clone_inner() && 
                 let Some(TknType::Identifier(field_name)) = tokens::get_token(tokens, peek).map(|e| &e.token) && 
                 let Some(custom_struct) = structs.iter().find(|custom_struct| custom_struct.name == *ident) &&
                 let Some(field) = custom_struct.fields.iter().find(|field| field.field_name == *field_name)
             {
+                accessors::check_field(&tokens[peek], custom_struct, field)?;
                 expr_data = expr_bump.alloc(ExprData::CustomField { 
                     data: expr_bump.alloc(Expr {line, expr_data, expr_type}), 
                     field
                 });
-                expr_type = ExprTypeCons::new(expr_bump, field.field_type.clone());
+                expr_type = ExprTypeCons::new(expr_bump, custom_struct.field_type(field, &type_args));
 
                 peek += 1;
                 continue;
@@ -1146,6 +1996,24 @@ fn parse_atom<'tkns, 'bumps, 'defs>(
                 });
                 expr_type = ExprTypeCons::new(expr_bump, field_type.clone());
 
+                peek += 1;
+                continue;
+            } else if let ExprType::Tuple { start: element_types, .. } = expr_type.clone_inner() &&
+                let Some(TknType::IntegerLiteral { int: element, .. }) = tokens::get_token(tokens, peek).map(|e| &e.token) &&
+                let Some(element) = usize::try_from(*element).ok().filter(|element| *element < element_types.len())
+            {
+                expr_type.default_element_literal_types(expr_bump);
+                let ExprType::Tuple { start: element_types, .. } = expr_type.clone_inner() else {
+                    unreachable!("defaulting literals keeps the tuple type");
+                };
+                let tuple = Expr {line, expr_data, expr_type};
+                Expr {expr_data, expr_type, ..} = patterns::element_expr(
+                    expr_bump,
+                    &tuple,
+                    element,
+                    element_types[element].clone()
+                );
+
                 peek += 1;
                 continue;
             } else {
@@ -1163,19 +2031,440 @@ fn parse_atom<'tkns, 'bumps, 'defs>(
     match op {
         None => return Ok(Expr {line, expr_data, expr_type}),
         Some(op) => {
+            if matches!(op, UnOp::Borrow | UnOp::BorrowMutable | UnOp::BorrowInteriorMutable) {
+                // the reference type holds a copy of the operand's type, which nothing pins down later
+                expr_type.default_literal_type(expr_bump);
+            }
+            let op_type = match op {
+                UnOp::Borrow => ExprTypeCons::new(expr_bump, ExprType::Ref { 
+                    lifetime: None, 
+                    expr_type: Box::new(expr_type.clone_inner()) 
+                }),
+                UnOp::BorrowMutable | UnOp::BorrowInteriorMutable => {
+                    check_mutable_place(&tokens[operand_start], expr_data, variables)?;
+                    let pointee = Box::new(expr_type.clone_inner());
+                    ExprTypeCons::new(expr_bump, match op {
+                        UnOp::BorrowMutable => ExprType::MutRef { lifetime: None, expr_type: pointee },
+                        _ => ExprType::ImRef { lifetime: None, expr_type: pointee }
+                    })
+                },
+                UnOp::Deref => match expr_type.clone_inner() {
+                    ExprType::Ref { expr_type: pointee, .. } | 
+                    ExprType::MutRef { expr_type: pointee, .. } | 
+                    ExprType::ImRef { expr_type: pointee, .. } => ExprTypeCons::new(expr_bump, *pointee),
+                    operand_type => return Err(ParserError::InvalidDeref { 
+                        tkn: &tokens[operand_start - 1], 
+                        expr_type: operand_type 
+                    })
+                },
+                _ => expr_type.clone()
+            };
             let expr_data = expr_bump.alloc(ExprData::UnaryOp(op, Expr {
                 line,
                 expr_data,
-                expr_type: expr_type.clone()
+                expr_type
             }));
-            return Ok(Expr {line, expr_data, expr_type});
+            return Ok(Expr {line, expr_data, expr_type: op_type});
+        },
+    }
+}
+
+/// Parses a `match` used as a value, where every arm is an expression of the same type.
+fn parse_match_expression<'tkns, 'bumps, 'defs>(
+    expr_bump: &'bumps ExprBump,
+    structs: &'defs [Struct],
+    enums: &'defs [Enum],
+    tokens: &'tkns [Tkn],
+    index: &mut usize,
+    line: usize,
+    functions: &RefCell<HashMap<String, FullFnDef<'tkns, 'bumps, 'defs>>>,
+    variables: &StackFrameDictAllocator<'_, String, VariableData<'tkns, 'bumps>>
+) -> Result<Expr<'bumps, 'defs>, ParserError<'tkns, 'bumps, 'defs>> {
+    let mut arm_type: Option<ExprTypeCons> = None;
+
+    let (scrutinee, arms) = patterns::parse_match(
+        expr_bump, 
+        structs, 
+        enums, 
+        tokens, 
+        index, 
+        line, 
+        functions, 
+        variables, 
+        |variables, peek| {
+            let start_expr = *peek;
+            let mut body = parse_expression_set(
+                expr_bump, 
+                structs, 
+                enums, 
+                tokens, 
+                peek, 
+                line, 
+                functions, 
+                variables
+            )?;
+
+            // arms that never produce a value, like a call to exit, fit any type
+            if *body.expr_type.get() == ExprType::Never {
+                return Ok(body);
+            }
+
+            arm_type = Some(match arm_type.take() {
+                None => body.expr_type.clone(),
+                Some(mut arm_type) => arm_type.match_type(&mut body.expr_type)
+                    .ok_or_else(|| ParserError::CouldNotMatchType { 
+                        tkns: &tokens[start_expr..*peek], 
+                        calculated_type: body.expr_type.clone_inner(), 
+                        expected_type: arm_type.clone_inner() 
+                    })?
+            });
+            return Ok(body);
+        }
+    )?;
+
+    return Ok(Expr {
+        line,
+        expr_data: expr_bump.alloc(ExprData::Match { scrutinee, arms }),
+        expr_type: arm_type.unwrap_or_else(|| ExprTypeCons::new(expr_bump, ExprType::Never))
+    });
+}
+
+/// `fn $ x: T = R { .. }`, a function written where a value is expected. The variables of the 
+/// enclosing function that its body uses are borrowed for as long as the closure lasts, or moved 
+/// into it by `move fn`. The body is parsed after the enclosing function, as the function 
+/// `{closure N}`.
+fn parse_closure<'tkns, 'bumps, 'defs>(
+    expr_bump: &'bumps ExprBump,
+    structs: &'defs [Struct],
+    enums: &'defs [Enum],
+    tokens: &'tkns [Tkn],
+    index: &mut usize,
+    line: usize,
+    functions: &RefCell<HashMap<String, FullFnDef<'tkns, 'bumps, 'defs>>>,
+    variables: &StackFrameDictAllocator<'_, String, VariableData<'tkns, 'bumps>>
+) -> Result<Expr<'bumps, 'defs>, ParserError<'tkns, 'bumps, 'defs>> {
+    let mut peek = *index;
+
+    let moves = is_expected_token(tokens, TknType::Keyword(Kwrd::Move), &mut peek);
+    let fn_tkn = &tokens[peek];
+    expect_token(tokens, TknType::Keyword(Kwrd::Function), &mut peek)
+        .ok_or_else(|| ParserError::ExpectedToken { 
+            tkn: &tokens[peek], 
+            expected: TknType::Keyword(Kwrd::Function) 
+        })?;
+
+    let (arg_tokens, body_tokens) = functions::split_signature(tokens, &mut peek)?;
+    let body_start = peek - body_tokens.len();
+    let struct_names = structs.iter().map(|custom_struct| custom_struct.name.as_str()).collect::<Vec<_>>();
+    let (left_args, right_args, return_type, fixity) = functions::define_arguments(
+        expr_bump, 
+        arg_tokens, 
+        &struct_names, 
+        &[], 
+        enums
+    )?;
+    // calls through a function value give every argument
+    if let Some(arg) = left_args.iter().chain(right_args).find(|arg| arg.default_tokens.is_some()) {
+        return Err(ParserError::DefaultInClosure { tkn: arg.tkn });
+    }
+
+    let params = left_args.iter().chain(right_args)
+        .filter_map(|param| param.param_name.as_deref())
+        .collect::<Vec<_>>();
+    let declared = body_tokens.iter().enumerate()
+        .filter(|(i, _)| {
+            ownership::is_declared_at(body_tokens, *i) 
+                || i.checked_sub(1).is_some_and(|i| body_tokens[i].token == TknType::Keyword(Kwrd::For))
+        })
+        .filter_map(|(_, tkn)| match &tkn.token {
+            TknType::Identifier(name) => Some(name.as_str()),
+            _ => None
+        })
+        .collect::<Vec<_>>();
+
+    let mut captures: Vec<Capture> = vec![];
+    for (i, tkn) in body_tokens.iter().enumerate() {
+        let TknType::Identifier(name) = &tkn.token else {
+            continue;
+        };
+        // fields, paths and the names of struct fields are not variables
+        if i.checked_sub(1).is_some_and(|i| matches!(body_tokens[i].token, TknType::Dot | TknType::ColonColon))
+            || matches!(body_tokens.get(i + 1).map(|tkn| &tkn.token), Some(TknType::Colon | TknType::ColonColon))
+            || params.contains(&name.as_str())
+            || declared.contains(&name.as_str())
+            || captures.iter().any(|capture| capture.name == *name)
+            || functions.borrow().contains_key(name)
+        {
+            continue;
+        }
+        let Some(variable) = variables.get_in_stack(name) else {
+            continue;
+        };
+        let variable = variable.get();
+
+        let mode = if moves {
+            CaptureMode::Move
+        } else if body_tokens.iter().enumerate().any(|(j, tkn)| {
+            matches!(&tkn.token, TknType::Identifier(ident) if ident == name) && is_written_at(body_tokens, j)
+        }) {
+            CaptureMode::Borrow(BorrowKind::Mutable)
+        } else {
+            CaptureMode::Borrow(BorrowKind::Shared)
+        };
+
+        captures.push(Capture { 
+            name: name.clone(), 
+            tkn: variable.tkn, 
+            used_at: body_start + i,
+            mutable: variable.mutable, 
+            expr_type: variable.expr_type.clone(), 
+            mode 
+        });
+    }
+
+    let name = format!(
+        "{{closure {}}}", 
+        functions.borrow().values().filter(|fun_def| fun_def.closure.is_some()).count()
+    );
+    let expr_type = ExprType::FunctionPass { 
+        return_type: Box::new(return_type.clone()), 
+        left_args: left_args.iter().map(|param| param.param_type.clone()).collect(), 
+        right_args: right_args.iter().map(|param| param.param_type.clone()).collect()
+    };
+    let expr_data = ExprData::Closure { 
+        name: name.clone(), 
+        captures: captures.iter().map(|capture| (capture.name.clone(), capture.used_at, capture.mode)).collect()
+    };
+
+    functions.borrow_mut().insert(name.clone(), FullFnDef {
+        accessibility: Accessibility::Public,
+        name,
+        location: fn_tkn.namespace.clone(),
+        file_name: fn_tkn.file_name.clone(),
+        type_params: vec![],
+        instance_of: None,
+        mutable: false,
+        recursive: false,
+        left_args,
+        right_args,
+        return_type,
+        fixity,
+        closure: Some(ClosureDefinition { body_tokens, captures })
+    });
+
+    *index = peek;
+    return Ok(Expr {
+        line,
+        expr_data: expr_bump.alloc(expr_data),
+        expr_type: ExprTypeCons::new(expr_bump, expr_type)
+    });
+}
+
+/// Whether the variable at `i` gets assigned to or borrowed mutably, which a closure can only do
+/// through a mutable borrow of it.
+fn is_written_at(tokens: &[Tkn], i: usize) -> bool {
+    let before = |offset: usize| i.checked_sub(offset).map(|i| &tokens[i].token);
+    if ownership::is_declared_at(tokens, i) {
+        return false;
+    }
+    return matches!(tokens.get(i + 1).map(|tkn| &tkn.token), Some(TknType::Operation(Op::Assign)))
+        || matches!(
+            (before(2), before(1)), 
+            (Some(TknType::Borrow), Some(TknType::Keyword(Kwrd::Mutable | Kwrd::InteriorMutable)))
+        );
+}
+
+/// Parses the `::Variant` and payload following an enum's name.
+fn parse_enum_variant<'tkns, 'bumps, 'defs>(
+    expr_bump: &'bumps ExprBump,
+    structs: &'defs [Struct],
+    enums: &'defs [Enum],
+    custom_enum: &'defs Enum,
+    tokens: &'tkns [Tkn],
+    index: &mut usize,
+    line: usize,
+    functions: &RefCell<HashMap<String, FullFnDef<'tkns, 'bumps, 'defs>>>,
+    variables: &StackFrameDictAllocator<'_, String, VariableData<'tkns, 'bumps>>
+) -> Result<Expr<'bumps, 'defs>, ParserError<'tkns, 'bumps, 'defs>> {
+    let mut peek = *index;
+
+    tokens::expect_token(tokens, TknType::ColonColon, &mut peek)
+        .ok_or_else(|| ParserError::ExpectedToken { 
+            tkn: &tokens[peek], 
+            expected: TknType::ColonColon 
+        })?;
+
+    let Some(TknType::Identifier(variant_name)) = tokens::get_token(tokens, peek).map(|e| &e.token) else {
+        return Err(ParserError::ExpectedIdentifier { tkn: &tokens[peek] });
+    };
+    let (tag, variant) = custom_enum.variants.iter()
+        .enumerate()
+        .find(|(_, variant)| variant.variant_name == *variant_name)
+        .ok_or_else(|| ParserError::VariantDoesNotExist { 
+            tkn: &tokens[peek], 
+            custom_enum 
+        })?;
+    peek += 1;
+
+    let mut fields: Vec<(String, &ExprData)> = vec![];
+    match variant.shape {
+        VariantShape::Unit => (),
+        VariantShape::Tuple => {
+            tokens::expect_token(tokens, TknType::OpenParen, &mut peek)
+                .ok_or_else(|| ParserError::ExpectedToken { 
+                    tkn: &tokens[peek], 
+                    expected: TknType::OpenParen 
+                })?;
+
+            for (field_name, field_type) in variant.fields.iter() {
+                if !fields.is_empty() {
+                    tokens::expect_token(tokens, TknType::Comma, &mut peek)
+                        .ok_or_else(|| ParserError::ExpectedToken { 
+                            tkn: &tokens[peek], 
+                            expected: TknType::Comma 
+                        })?;
+                }
+
+                let start_expr = peek;
+                let mut expr = parse_expression_set(
+                    expr_bump, 
+                    structs, 
+                    enums, 
+                    tokens, 
+                    &mut peek, 
+                    line, 
+                    functions, 
+                    variables
+                )?;
+
+                expr.expr_type.match_type(
+                    &mut ExprTypeCons::new(expr_bump, field_type.clone())
+                ).ok_or(ParserError::CouldNotMatchType { 
+                    tkns: &tokens[start_expr..peek], 
+                    calculated_type: expr.expr_type.clone_inner(), 
+                    expected_type: field_type.clone()
+                })?;
+
+                fields.push((field_name.clone(), expr.expr_data));
+            }
+            
+            tokens::is_expected_token(tokens, TknType::Comma, &mut peek);
+            tokens::expect_token(tokens, TknType::CloseParen, &mut peek)
+                .ok_or_else(|| ParserError::ExpectedToken { 
+                    tkn: &tokens[peek], 
+                    expected: TknType::CloseParen 
+                })?;
         },
+        VariantShape::Struct => {
+            tokens::expect_token(tokens, TknType::OpenCurlyBrace, &mut peek)
+                .ok_or_else(|| ParserError::ExpectedToken { 
+                    tkn: &tokens[peek], 
+                    expected: TknType::OpenCurlyBrace 
+                })?;
+
+            let mut field_datas: HashMap<&str, &ExprData> = HashMap::new();
+            let mut field_indices: HashMap<&str, &Tkn> = HashMap::new();
+
+            let mut needed_comma = false;
+            loop {
+                let Some(TknType::Identifier(field_name)) = tokens::get_token(tokens, peek).map(|e| &e.token) else {
+                    break;
+                };
+                let field_index = peek;
+
+                if needed_comma {
+                    return Err(ParserError::ExpectedToken{ tkn: &tokens[peek], expected: TknType::Comma });
+                }
+
+                let (field_name, field_type) = variant.fields.iter()
+                    .find(|(variant_field_name, _)| variant_field_name == field_name)
+                    .ok_or_else(|| ParserError::VariantFieldDoesNotExist { 
+                        tkn: &tokens[peek], 
+                        custom_enum,
+                        variant
+                    })?;
+
+                peek += 1;
+
+                if let Some(defined_field) = field_indices.get(field_name.as_str()) {
+                    return Err(ParserError::AlreadyDefinedField { 
+                        tkn: &tokens[field_index], 
+                        defined_field 
+                    });
+                }
+
+                let expr_tkns: &[Tkn];
+                let mut expr = if tokens::expect_token(tokens, TknType::Colon, &mut peek).is_some() {
+                    let start_expr = peek;
+                    let expr = parse_expression_set(
+                        expr_bump, 
+                        structs, 
+                        enums, 
+                        tokens, 
+                        &mut peek, 
+                        line,
+                        functions, 
+                        variables
+                    )?;
+                    expr_tkns = &tokens[start_expr..peek];
+                    expr
+                } else {
+                    expr_tkns = core::slice::from_ref(&tokens[field_index]);
+                    Expr {
+                        line,
+                        expr_data: expr_bump.alloc(ExprData::Identifier(field_name.clone(), field_index)),
+                        expr_type: ExprTypeCons::grab_variable_type(tokens, field_index, field_name, variables)?
+                    }
+                };
+
+                expr.expr_type.match_type(
+                    &mut ExprTypeCons::new(expr_bump, field_type.clone())
+                ).ok_or_else(|| ParserError::CouldNotMatchType { 
+                    tkns: expr_tkns, 
+                    calculated_type: expr.expr_type.clone_inner(), 
+                    expected_type: field_type.clone()
+                })?;
+
+                field_datas.insert(field_name, expr.expr_data);
+                field_indices.insert(field_name, &tokens[field_index]);
+
+                needed_comma = tokens::expect_token(tokens, TknType::Comma, &mut peek).is_none();
+            }
+
+            tokens::expect_token(tokens, TknType::CloseCurlyBrace, &mut peek)
+                .ok_or_else(|| ParserError::ExpectedToken { 
+                    tkn: &tokens[peek], 
+                    expected: TknType::CloseCurlyBrace 
+                })?;
+
+            for (field_name, _) in variant.fields.iter() {
+                let field_data = field_datas.get(field_name.as_str())
+                    .ok_or_else(|| ParserError::MissingVariantField { 
+                        tkn: &tokens[peek - 1], 
+                        custom_enum,
+                        variant, 
+                        field_name 
+                    })?;
+                fields.push((field_name.clone(), field_data));
+            }
+        }
     }
+
+    *index = peek;
+    return Ok(Expr {
+        line,
+        expr_data: expr_bump.alloc(ExprData::EnumVariant { 
+            tag, 
+            fields: fields.into_boxed_slice() 
+        }),
+        expr_type: ExprTypeCons::new(expr_bump, custom_enum.to_expr_type())
+    });
 }
 
 fn parse_array<'tkns, 'bumps, 'defs>(
     expr_bump: &'bumps ExprBump,
     structs: &'defs [Struct],
+    enums: &'defs [Enum],
     tokens: &'tkns [Tkn],
     index: &mut usize,
     line: usize,
@@ -1203,6 +2492,7 @@ fn parse_array<'tkns, 'bumps, 'defs>(
         let expr_possible = parse_expression(
             expr_bump, 
             structs,
+            enums,
             tokens, 
             &mut peek, 
             line,
@@ -1250,6 +2540,7 @@ fn parse_array<'tkns, 'bumps, 'defs>(
 
     let length = elements.len();
 
+    *index = peek;
     return Ok(Expr {
         line,
         expr_data: expr_bump.alloc(ExprData::Array(elements.iter().map(|data| Expr {
@@ -1266,10 +2557,47 @@ fn parse_array<'tkns, 'bumps, 'defs>(
 
 #[cfg(test)]
 mod test {
-    use crate::parser::expr::ExprType;
+    use crate::parser::{enums::{Variant, VariantShape}, expr::ExprType};
 
     #[test]
     pub fn test_match_type() {
         assert_ne!(ExprType::StringLiteral, ExprType::Char);
     }
+
+    #[test]
+    pub fn test_enum_size_of() {
+        let shape = ExprType::Enum {
+            ident: String::from("Shape"),
+            variants: Box::new([
+                Variant { 
+                    variant_name: String::from("Empty"), 
+                    shape: VariantShape::Unit, 
+                    fields: Box::new([]) 
+                },
+                Variant { 
+                    variant_name: String::from("Rect"), 
+                    shape: VariantShape::Tuple, 
+                    fields: Box::new([(String::from("0"), ExprType::I32), (String::from("1"), ExprType::I64)]) 
+                },
+                Variant { 
+                    variant_name: String::from("Flag"), 
+                    shape: VariantShape::Struct, 
+                    fields: Box::new([(String::from("set"), ExprType::Bool)]) 
+                }
+            ])
+        };
+        assert_eq!(shape.size_of(&[]), 1 + 12);
+    }
+
+    #[test]
+    pub fn test_tuple_layout() {
+        let elements = [ExprType::U8, ExprType::I32, ExprType::Bool, ExprType::I64];
+        let (offsets, size) = ExprType::tuple_layout(elements.iter(), &[]);
+        assert_eq!(offsets, [0, 4, 8, 16]);
+        assert_eq!(size, 24);
+
+        let (offsets, size) = ExprType::tuple_layout([ExprType::I32, ExprType::U8].iter(), &[]);
+        assert_eq!(offsets, [0, 4]);
+        assert_eq!(size, 8);
+    }
 }
\ No newline at end of file
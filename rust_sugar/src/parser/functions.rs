@@ -1,10 +1,11 @@
 use std::{cell::RefCell, collections::HashMap};
 
+use bumpalo::Bump;
 use stack_frame_allocators::stack_frame_dict_allocator::StackFrameDictAllocator;
 
-use crate::{full_result::{FullResult, OptionToFullResult}, lexer::token::{Kwrd, Op, Tkn, TknType}, parser::{expr::ExprTypeCons, tokens}};
+use crate::{full_result::{FullResult, OptionToFullResult, ResultToFullResult}, lexer::token::{Kwrd, Op, Tkn, TknType}, parser::{expr::ExprTypeCons, tokens}};
 
-use super::{accessors, expr::{Expr, ExprData, ExprType, VariableData}, stmt::{self, StmtData}, structs::Struct, ExprBump, FnParamBump, ParserError, StmtBump};
+use super::{accessors::{self, Accessibility, Accessor}, expr::{self, Expr, ExprData, ExprType, VariableData}, lifetimes, operators::{Fixity, OpAssoc, OpPrec}, ownership::BorrowKind, stmt::{self, StmtData}, enums::Enum, structs::Struct, ExprBump, FnParamBump, ParserError, StmtBump};
 
 #[allow(non_camel_case_types)]
 pub enum BuiltInFunction {
@@ -93,9 +94,13 @@ impl BuiltInFunction {
 pub type Fun<'tkns, 'bumps, 'defs> = Function<'tkns, 'bumps, 'defs>;
 #[derive(Clone, Debug)]
 pub struct Function<'tkns, 'bumps, 'defs> {
+    /// The namespace the function is declared in, empty for the root
     pub location: String,
+    pub file_name: String,
+    /// The line the body of the function opens on
+    pub line: usize,
     pub name: String,
-    pub accessibility: String,
+    pub accessibility: Accessibility,
     pub mutable: bool,
     pub recursive: bool,
     pub left_args: &'bumps [FnParam<'tkns, 'bumps, 'defs>],
@@ -110,13 +115,20 @@ pub struct FunctionParamater<'tkns, 'bumps, 'defs> {
     pub tkn: &'tkns Tkn,
     pub param_type: ExprType,
     pub param_name: Option<String>,
+    /// The value the argument takes when a call leaves it out, parsed along with the body
     pub param_default: Option<&'bumps ExprData<'bumps, 'defs>>,
+    /// The tokens of the default value written after `=`
+    pub default_tokens: Option<&'tkns [Tkn]>,
 }
 
 #[derive(Clone, Debug)]
 pub struct FunctionDefinition<'tkns> {
     pub accessibility: Option<&'tkns Tkn>,
+    /// The name as it is written, where `name` is the full path of the function
+    pub tkn: &'tkns Tkn,
     pub name: String,
+    pub lifetimes: Vec<String>,
+    pub type_params: Vec<String>,
     pub mutable: bool,
     pub recursive: bool,
     pub arg_tokens: &'tkns [Tkn],
@@ -126,62 +138,177 @@ pub struct FunctionDefinition<'tkns> {
 pub type FullFnDef<'tkns, 'bumps, 'defs> = FullFunctionDefinition<'tkns, 'bumps, 'defs>;
 #[derive(Clone, Debug)]
 pub struct FullFunctionDefinition<'tkns, 'bumps, 'defs> {
-    pub accessibility: String,
+    pub accessibility: Accessibility,
     pub name: String,
+    /// The namespace the function is written in
+    pub location: String,
+    pub file_name: String,
+    /// The type parameters of a generic function, which only gets parsed through its instances
+    pub type_params: Vec<String>,
+    /// The generic function this function is an instance of
+    pub instance_of: Option<String>,
     pub mutable: bool,
     pub recursive: bool,
     pub left_args: &'bumps [FnParam<'tkns, 'bumps, 'defs>],
     pub right_args: &'bumps [FnParam<'tkns, 'bumps, 'defs>],
-    pub return_type: ExprType
+    pub return_type: ExprType,
+    /// The precedence and associativity the function is declared with after `infix`
+    pub fixity: Option<Fixity>,
+    /// The body and the captured variables of a function written as a closure, which is parsed 
+    /// after the function the closure is written in
+    pub closure: Option<ClosureDefinition<'tkns, 'bumps>>
+}
+
+/// How a closure holds on to a variable of the function it is written in
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CaptureMode {
+    /// `move fn`, which moves the value of the variable into the closure
+    Move,
+    /// `fn`, which borrows the variable for as long as the closure lasts
+    Borrow(BorrowKind)
+}
+
+/// A variable of the enclosing function that a closure uses
+#[derive(Clone, Debug)]
+pub struct Capture<'tkns, 'bumps> {
+    pub name: String,
+    /// Where the variable is declared
+    pub tkn: &'tkns Tkn,
+    /// The index of the token the closure first uses the variable at, in the tokens of the 
+    /// enclosing function
+    pub used_at: usize,
+    pub mutable: bool,
+    /// The type of the variable, shared with the enclosing function so the closure sees what it 
+    /// infers about the variable
+    pub expr_type: ExprTypeCons<'bumps>,
+    pub mode: CaptureMode
+}
+
+#[derive(Clone, Debug)]
+pub struct ClosureDefinition<'tkns, 'bumps> {
+    pub body_tokens: &'tkns [Tkn],
+    pub captures: Vec<Capture<'tkns, 'bumps>>
 }
 
 impl<'tkns, 'bumps, 'defs> FullFnDef<'tkns, 'bumps, 'defs> {
     pub fn from_partial_fn_def(
         fn_param_bump: &'bumps FnParamBump,
         fn_def: FunctionDefinition<'tkns>, 
-        accessors: &[&str],
-        struct_names: &[&str]
+        accessors: &[Accessor],
+        struct_names: &[&str],
+        structs: &[Struct],
+        enums: &[Enum]
     ) -> Result<
         (FullFnDef<'tkns, 'bumps, 'defs>, &'tkns [Tkn]), 
         ParserError<'tkns, 'bumps, 'defs>
     > {
         let FunctionDefinition {
             accessibility,
+            tkn,
             name,
+            lifetimes,
+            type_params,
             mutable,
             recursive,
             arg_tokens,
             body_tokens
         } = fn_def;
 
-        let accessibility = accessors::get_accessor_string(accessibility, &mut 0, &accessors)
+        let accessibility = accessors::get_accessibility(accessibility, &mut 0, accessors)
             .ok_or_else(|| ParserError::AccessorNotDefined { 
                 tkn: accessibility.unwrap() 
             })?;
 
-        let (left_args, right_args, return_type) = define_arguments(
+        let (left_args, right_args, return_type, fixity) = define_arguments(
             fn_param_bump,
             arg_tokens,
-            &struct_names
+            &struct_names,
+            &type_params,
+            enums
         )?;
+        // a custom operator sits between its two operands like the built in binary operators do
+        if matches!(tkn.token, TknType::CustomOperation(_)) 
+            && (left_args.len() != 1 || right_args.len() != 1 || fixity.is_none()) 
+        {
+            return Err(ParserError::InvalidCustomOperator { tkn });
+        }
+        // only a function with one operand on each side can bind them by precedence
+        if fixity.is_some() && (left_args.len() != 1 || right_args.len() != 1) {
+            return Err(ParserError::FixityWithoutTwoOperands { tkn });
+        }
+        check_defaults(left_args, right_args)?;
+        let params = left_args.iter().chain(right_args).collect::<Vec<_>>();
+        lifetimes::check_signature(arg_tokens, &lifetimes, &params, &return_type, structs)?;
         
         return Ok((FullFnDef {
             accessibility,
             name: name.clone(),
+            location: tkn.namespace.clone(),
+            file_name: tkn.file_name.clone(),
+            type_params,
+            instance_of: None,
             mutable: mutable,
             recursive: recursive,
             left_args,
             right_args,
-            return_type
+            return_type,
+            fixity,
+            closure: None
         }, body_tokens));
     }
 }
 
+/// The name of the instance of the generic function `name` for `type_args`, which is defined the 
+/// first time the function gets called with them and parsed along with the rest of the instances.
+pub fn instantiate<'tkns, 'bumps, 'defs>(
+    expr_bump: &'bumps ExprBump,
+    functions: &RefCell<HashMap<String, FullFnDef<'tkns, 'bumps, 'defs>>>,
+    name: &str,
+    type_args: &[ExprType]
+) -> String {
+    let instance_name = format!(
+        "{name}<{}>", 
+        type_args.iter().map(|type_arg| type_arg.to_string()).collect::<Vec<_>>().join(", ")
+    );
+    if functions.borrow().contains_key(&instance_name) {
+        return instance_name;
+    }
+
+    let generic = functions.borrow()[name].clone();
+    let substitute = |params: &[FnParam<'tkns, 'bumps, 'defs>]| -> &'bumps [FnParam<'tkns, 'bumps, 'defs>] {
+        expr_bump.alloc_slice_fill_iter(params.iter().map(|param| FnParam {
+            param_type: param.param_type.substitute(&generic.type_params, type_args),
+            ..param.clone()
+        }))
+    };
+    let instance = FullFnDef {
+        name: instance_name.clone(),
+        type_params: vec![],
+        instance_of: Some(name.to_string()),
+        left_args: substitute(generic.left_args),
+        right_args: substitute(generic.right_args),
+        return_type: generic.return_type.substitute(&generic.type_params, type_args),
+        ..generic.clone()
+    };
+    functions.borrow_mut().insert(instance_name.clone(), instance);
+    return instance_name;
+}
+
 #[derive(PartialEq, Debug)]
 pub enum FnType {
     Prefix, Infix, Postfix
 }
 
+impl std::fmt::Display for FnType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            FnType::Prefix => "prefix",
+            FnType::Infix => "infix",
+            FnType::Postfix => "postfix",
+        })
+    }
+}
+
 pub type FnParseState = FunctionParseState;
 #[derive(PartialEq)]
 pub enum FunctionParseState {
@@ -229,13 +356,40 @@ pub fn define_function<'tkns, 'bumps, 'defs>(
 
     let name;
     tokens::expect_token(tokens, TknType::Keyword(Kwrd::Function), &mut peek).ok_or_soft(())?;
+    let tkn = &tokens[peek];
     if let Some(TknType::Identifier(ident)) = tokens.get(peek).map(|e| &e.token) {
         peek += 1;
         name = ident.clone();
+    } else if let Some(TknType::CustomOperation(symbol)) = tokens.get(peek).map(|e| &e.token) {
+        peek += 1;
+        name = symbol.clone();
     } else {
         return FullResult::HardErr(ParserError::ExpectedIdentifier { tkn: &tokens[peek] });
     }
+    let (lifetimes, type_params) = super::get_type_params(tokens, &mut peek).harden()?;
+
+    let (arg_tokens, body_tokens) = split_signature(tokens, &mut peek).harden()?;
+    *index = peek;
+    return FullResult::Ok(FunctionDefinition {
+        accessibility,
+        tkn,
+        name,
+        lifetimes,
+        type_params,
+        mutable,
+        recursive,
+        arg_tokens,
+        body_tokens,
+    });
+}
 
+/// Splits what follows the name of a function, or the `fn` of a closure, into the tokens of its 
+/// arguments and return type and the tokens of its body, braces included.
+pub fn split_signature<'tkns, 'bumps, 'defs>(
+    tokens: &'tkns [Tkn], 
+    index: &mut usize,
+) -> Result<(&'tkns [Tkn], &'tkns [Tkn]), ParserError<'tkns, 'bumps, 'defs>> {
+    let mut peek = *index;
     let arg_tokens: &[Tkn];
     let body_tokens: &[Tkn];
     let mut start = peek;
@@ -279,7 +433,12 @@ pub fn define_function<'tkns, 'bumps, 'defs>(
 
             parsing_type_level -= 1;
             continue;
-        } 
+        } else if tokens::is_token(tokens, TknType::EndOfFile, peek) {
+            return Err(ParserError::ExpectedToken { 
+                tkn: &tokens[peek], 
+                expected: TknType::OpenCurlyBrace 
+            });
+        }
         peek += 1;
     }
     let mut count = 0;
@@ -296,13 +455,11 @@ pub fn define_function<'tkns, 'bumps, 'defs>(
             end = peek - 1;
             body_tokens = &tokens[start..=end];
             *index = peek;
-            return FullResult::Ok(FunctionDefinition {
-                accessibility,
-                name,
-                mutable,
-                recursive,
-                arg_tokens,
-                body_tokens,
+            return Ok((arg_tokens, body_tokens));
+        } else if tokens::is_token(tokens, TknType::EndOfFile, peek) {
+            return Err(ParserError::ExpectedClosingBrace { 
+                tkn: &tokens[peek], 
+                open_brace: &tokens[start] 
             });
         }
         peek += 1;
@@ -310,14 +467,17 @@ pub fn define_function<'tkns, 'bumps, 'defs>(
 }
 
 pub fn define_arguments<'tkns, 'bumps, 'defs>(
-    fn_param_bump: &'bumps FnParamBump,
+    fn_param_bump: &'bumps Bump,
     tokens: &'tkns [Tkn],
     structs: &[&str],
+    type_params: &[String],
+    enums: &[Enum],
 ) -> Result<
     (
         &'bumps [FnParam<'tkns, 'bumps, 'defs>], 
         &'bumps [FnParam<'tkns, 'bumps, 'defs>], 
-        ExprType
+        ExprType,
+        Option<Fixity>
     ), 
     ParserError<'tkns, 'bumps, 'defs>
 > {
@@ -345,7 +505,7 @@ pub fn define_arguments<'tkns, 'bumps, 'defs>(
         id = Some((FnType::Prefix, &tokens[peek - 1]));
     }
 
-    fn_params_right = fn_group_to_params(fn_param_bump, tokens, &mut peek, &structs)?;
+    fn_params_right = fn_group_to_params(fn_param_bump, tokens, &mut peek, &structs, type_params, enums)?;
 
     if let Some((FnType::Prefix, id_token)) = id {
         if tokens::is_expected_token(tokens, TknType::Operation(Op::Assign), &mut peek) {
@@ -353,12 +513,14 @@ pub fn define_arguments<'tkns, 'bumps, 'defs>(
                 tokens,
                 &mut peek,
                 structs,
+                type_params,
+                enums,
             ).ok_or_else(|| ParserError::ExpectedType { tkn: &tokens[peek] })?;
             fn_params_left = &[];
-            return Ok((fn_params_left, fn_params_right, return_type));
+            return Ok((fn_params_left, fn_params_right, return_type, None));
         } else if peek >= tokens.len() {
             fn_params_left = &[];
-            return Ok((fn_params_left, fn_params_right, return_type));
+            return Ok((fn_params_left, fn_params_right, return_type, None));
         } else {
             return Err(ParserError::DefinedIncorrectlyPlacedArgument { 
                 tkn: &tokens[peek], 
@@ -370,19 +532,28 @@ pub fn define_arguments<'tkns, 'bumps, 'defs>(
     }
 
     let start_fix = peek;
-    let is_expected_infix = tokens::is_expected_tokens(
+    let is_paren_infix = tokens::is_expected_tokens(
         tokens,
-        &[
-            TknType::OpenParen,
-            TknType::Keyword(Kwrd::Infix),
-            TknType::CloseParen,
-        ],
+        &[TknType::OpenParen, TknType::Keyword(Kwrd::Infix)],
         &mut peek,
-    ) || tokens::is_expected_tokens(
+    );
+    let is_expected_infix = is_paren_infix || tokens::is_expected_tokens(
         tokens,
         &[TknType::Dollar, TknType::Keyword(Kwrd::Infix)],
         &mut peek,
     ) || tokens::is_expected_token(tokens, TknType::Keyword(Kwrd::Infix), &mut peek);
+    let fixity = if is_expected_infix {
+        get_fixity(tokens, &mut peek)?
+    } else {
+        None
+    };
+    if is_paren_infix {
+        tokens::expect_token(tokens, TknType::CloseParen, &mut peek)
+            .ok_or_else(|| ParserError::ExpectedToken { 
+                tkn: &tokens[peek], 
+                expected: TknType::CloseParen 
+            })?;
+    }
 
     let is_expected_postfix = tokens::is_expected_tokens(
         tokens,
@@ -407,16 +578,18 @@ pub fn define_arguments<'tkns, 'bumps, 'defs>(
         }
         fn_params_left = fn_params_right;
 
-        fn_params_right = fn_group_to_params(&fn_param_bump, tokens, &mut peek, structs)?;
+        fn_params_right = fn_group_to_params(fn_param_bump, tokens, &mut peek, structs, type_params, enums)?;
         if tokens::is_expected_token(tokens, TknType::Operation(Op::Assign), &mut peek) {
             return_type = super::get_type_token_expr_type(
                 tokens,
                 &mut peek,
                 structs,
+                type_params,
+                enums,
             ).ok_or_else(|| ParserError::ExpectedType { tkn: &tokens[peek] })?;
-            return Ok((fn_params_left, fn_params_right, return_type));
+            return Ok((fn_params_left, fn_params_right, return_type, fixity));
         } else if peek >= tokens.len() {
-            return Ok((fn_params_left, fn_params_right, return_type));
+            return Ok((fn_params_left, fn_params_right, return_type, fixity));
         } else {
             return Err(ParserError::ExpectedTokens { 
                 tkn: &tokens[peek],
@@ -439,10 +612,12 @@ pub fn define_arguments<'tkns, 'bumps, 'defs>(
                 tokens,
                 &mut peek,
                 structs,
+                type_params,
+                enums,
             ).ok_or_else(|| ParserError::ExpectedType { tkn: &tokens[peek] })?;
-            return Ok((fn_params_left, fn_params_right, return_type));
+            return Ok((fn_params_left, fn_params_right, return_type, fixity));
         } else if peek >= tokens.len() {
-            return Ok((fn_params_left, fn_params_right, return_type));
+            return Ok((fn_params_left, fn_params_right, return_type, fixity));
         } else {
             return Err(ParserError::DefinedIncorrectlyPlacedArgument{ 
                 tkn: &tokens[peek], 
@@ -455,31 +630,75 @@ pub fn define_arguments<'tkns, 'bumps, 'defs>(
         return_type = super::get_type_token_expr_type(
             tokens, 
             &mut peek, 
-            structs
+            structs,
+            type_params,
+            enums
         ).ok_or_else(|| ParserError::ExpectedType { tkn: &tokens[peek] })?;
         fn_params_left = &[];
-        return Ok((fn_params_left, fn_params_right, return_type));
+        return Ok((fn_params_left, fn_params_right, return_type, fixity));
     } else if peek >= tokens.len() {
         fn_params_left = &[];
-        return Ok((fn_params_left, fn_params_right, return_type));
+        return Ok((fn_params_left, fn_params_right, return_type, fixity));
     } else {
-        return Err(ParserError::ExpectedEndOfFunctionDefinition { tkn: &tokens[peek - 1] });
+        return Err(ParserError::ExpectedEndOfFunctionDefinition { tkn: &tokens[peek] });
+    }
+}
+
+/// `11 left` or `11 right` after `infix`, where the associativity is left when it is not written.
+fn get_fixity<'tkns, 'bumps, 'defs>(
+    tokens: &'tkns [Tkn],
+    index: &mut usize
+) -> Result<Option<Fixity>, ParserError<'tkns, 'bumps, 'defs>> {
+    let Some(TknType::IntegerLiteral { int, .. }) = tokens.get(*index).map(|e| &e.token) else {
+        return Ok(None);
+    };
+    if !(1..=OpPrec::Casting as i128).contains(int) {
+        return Err(ParserError::InvalidPrecedence { tkn: &tokens[*index] });
     }
+    let precedence = *int as u32;
+    *index += 1;
+
+    let associativity = match tokens.get(*index).map(|e| &e.token) {
+        Some(TknType::Identifier(ident)) if ident == "left" => OpAssoc::Left,
+        Some(TknType::Identifier(ident)) if ident == "right" => OpAssoc::Right,
+        _ => return Ok(Some(Fixity { precedence, associativity: OpAssoc::Left }))
+    };
+    *index += 1;
+    return Ok(Some(Fixity { precedence, associativity }));
+}
+
+/// The tokens between the braces of `body_tokens`, which the statements of a function are parsed 
+/// from, so the token indexes the statements keep point into them.
+pub fn statement_tokens(body_tokens: &[Tkn]) -> &[Tkn] {
+    return &body_tokens[1..body_tokens.len()-1];
 }
 
 pub fn parse_function<'tkns, 'bumps, 'defs>(
     expr_bump: &'bumps ExprBump,
     stmt_bump: &'bumps StmtBump,
     structs: &'defs [Struct],
+    enums: &'defs [Enum],
     functions: &RefCell<HashMap<String, FullFnDef<'tkns, 'bumps, 'defs>>>,
     variables: StackFrameDictAllocator<'_, String, VariableData<'tkns, 'bumps>>,
     fn_def: FullFnDef<'tkns, 'bumps, 'defs>,
     tokens: &'tkns [Tkn]
-) -> Result<Fun<'tkns, 'bumps, 'defs>, ParserError<'tkns, 'bumps, 'defs>> {
+) -> Result<Fun<'tkns, 'bumps, 'defs>, Vec<ParserError<'tkns, 'bumps, 'defs>>> {
     let FullFnDef {
-        accessibility, name, mutable, recursive, left_args, right_args, mut return_type
+        accessibility, name, location, file_name, mutable, recursive, left_args, right_args, mut return_type, closure, ..
     } = fn_def;
 
+    // defaults are parsed in the scope of the function, before its arguments are declared
+    let right_args = parse_defaults(expr_bump, structs, enums, functions, &variables, right_args)
+        .map_err(|err| vec![err])?;
+
+    // the arguments of a closure shadow the variables it captures
+    for capture in closure.iter().flat_map(|closure| &closure.captures) {
+        variables.push(
+            capture.name.clone(), 
+            VariableData::new(capture.tkn, capture.mutable, capture.expr_type.clone())
+        );
+    }
+
     for arg in left_args {
         let variable_name = arg.param_name.as_ref().unwrap().clone();
         let param_type = arg.param_type.clone();
@@ -495,38 +714,32 @@ pub fn parse_function<'tkns, 'bumps, 'defs>(
     }
 
     let mut peek = 0;
-    let mut stmts: Vec<&StmtData> = vec![];
-    let body_tokens = &tokens[1..tokens.len()-1];
-    {
-        let variables = variables.new_frame();
-        
-        loop {
-            if peek >= body_tokens.len() {
-                break;
-            }
-            
-            let stmt_possible = stmt::parse_statement(
-                expr_bump, 
-                stmt_bump, 
-                structs,
-                functions, 
-                &variables, 
-                body_tokens, 
-                &mut return_type,
-                &mut peek
-            );
-            match stmt_possible {
-                FullResult::Ok(mut stmt) => stmts.append(&mut stmt),
-                FullResult::SoftErr(_) => break,
-                FullResult::HardErr(err) => return Err(err)
-            }
-        }
-}
+    let mut errors = vec![];
+    let body_tokens = statement_tokens(tokens);
+    let stmts = stmt::parse_statements(
+        expr_bump, 
+        stmt_bump, 
+        structs,
+        enums,
+        functions, 
+        &variables.new_frame(), 
+        body_tokens, 
+        &mut return_type,
+        &mut errors,
+        &mut vec![],
+        &mut peek
+    );
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
 
     return Ok(Function {
         accessibility,
+        location,
+        file_name,
+        line: tokens[0].line_number,
         name,
-        location: "".to_string(),
         mutable,
         recursive,
         left_args,
@@ -559,10 +772,12 @@ pub fn expect_closing_group(
 }
 
 pub fn fn_group_to_params<'tkns, 'bumps, 'defs>(
-    fn_param_bump: &'bumps FnParamBump,
+    fn_param_bump: &'bumps Bump,
     tokens: &'tkns [Tkn],  
     index: &mut usize, 
-    structs: &[&str]
+    structs: &[&str],
+    type_params: &[String],
+    enums: &[Enum]
 ) -> Result<
     &'bumps [FnParam<'tkns, 'bumps, 'defs>], 
     ParserError<'tkns, 'bumps, 'defs>
@@ -574,9 +789,10 @@ pub fn fn_group_to_params<'tkns, 'bumps, 'defs>(
         expect_closing = true;
     } else if tokens::is_expected_token(tokens, TknType::Dollar, index) {
         expect_closing = false;
-    } else {
-        *index += 1;
+    } else if expect_closing_group(tokens, false, index) {
         return Ok(fn_param_bump.alloc_slice_fill_iter(fn_params.into_iter()));
+    } else {
+        return Err(ParserError::ExpectedEndOfFunctionDefinition { tkn: &tokens[*index] });
     }
 
     let mut require_comma = false;
@@ -600,19 +816,39 @@ pub fn fn_group_to_params<'tkns, 'bumps, 'defs>(
         *index += 1;
 
         tokens::expect_token(tokens, TknType::Colon, index)
-            .expect(format!("Expected Colon at {}", tokens[*index]).as_str());
+            .ok_or_else(|| ParserError::ExpectedToken { 
+                tkn: &tokens[*index], 
+                expected: TknType::Colon 
+            })?;
 
         let expr_type = super::get_type_token_expr_type(
             tokens, 
             index, 
-            &structs
-        ).expect(format!("Expected Type at {}", tokens[*index - 1]).as_str());
+            &structs,
+            type_params,
+            enums
+        ).ok_or_else(|| ParserError::ExpectedType { tkn: &tokens[*index] })?;
+
+        // `= value` after the type gives the argument a default, unless it is the return type ending the signature
+        let default_tokens = if tokens::is_token(tokens, TknType::Operation(Op::Assign), *index) 
+            && !is_return_type(tokens, *index + 1, structs, type_params, enums) 
+        {
+            *index += 1;
+            let default_tokens = default_tokens(tokens, expect_closing, index);
+            if default_tokens.is_empty() {
+                return Err(ParserError::InvalidExpressionAtom { tkn: &tokens[*index] });
+            }
+            Some(default_tokens)
+        } else {
+            None
+        };
 
         let fn_param = FnParam {
             tkn,
             param_type: expr_type,
             param_name: Some(ident.clone()),
             param_default: None,
+            default_tokens
         };
 
         fn_params.push(fn_param);
@@ -621,4 +857,85 @@ pub fn fn_group_to_params<'tkns, 'bumps, 'defs>(
             require_comma = true;
         }
     }
-}
\ No newline at end of file
+}
+
+/// Parses the default values of `params`, which have to be of the types of the arguments.
+fn parse_defaults<'tkns, 'bumps, 'defs>(
+    expr_bump: &'bumps ExprBump,
+    structs: &'defs [Struct],
+    enums: &'defs [Enum],
+    functions: &RefCell<HashMap<String, FullFnDef<'tkns, 'bumps, 'defs>>>,
+    variables: &StackFrameDictAllocator<'_, String, VariableData<'tkns, 'bumps>>,
+    params: &'bumps [FnParam<'tkns, 'bumps, 'defs>]
+) -> Result<&'bumps [FnParam<'tkns, 'bumps, 'defs>], ParserError<'tkns, 'bumps, 'defs>> {
+    if params.iter().all(|param| param.default_tokens.is_none()) {
+        return Ok(params);
+    }
+
+    let mut defaulted = vec![];
+    for param in params {
+        let mut param = param.clone();
+        if let Some(tokens) = param.default_tokens {
+            let mut peek = 0;
+            let mut default = expr::parse_expression_set(
+                expr_bump, 
+                structs, 
+                enums, 
+                tokens, 
+                &mut peek, 
+                tokens[0].line_number, 
+                functions, 
+                variables
+            )?;
+            if peek < tokens.len() {
+                return Err(ParserError::ExpectedToken { tkn: &tokens[peek], expected: TknType::Comma });
+            }
+
+            default.expr_type.match_type(&mut ExprTypeCons::new(expr_bump, param.param_type.clone()))
+                .ok_or_else(|| ParserError::CouldNotMatchType { 
+                    tkns: tokens, 
+                    calculated_type: default.expr_type.clone_inner(), 
+                    expected_type: param.param_type.clone() 
+                })?;
+            param.param_default = Some(default.expr_data);
+        }
+        defaulted.push(param);
+    }
+    return Ok(expr_bump.alloc_slice_fill_iter(defaulted));
+}
+
+/// Checks that only the trailing arguments on the right have defaults, which are the ones a call 
+/// can leave out.
+pub fn check_defaults<'tkns, 'bumps, 'defs>(
+    left_args: &[FnParam<'tkns, 'bumps, 'defs>],
+    right_args: &[FnParam<'tkns, 'bumps, 'defs>]
+) -> Result<(), ParserError<'tkns, 'bumps, 'defs>> {
+    let trailing = right_args.iter().rposition(|arg| arg.default_tokens.is_none()).map_or(0, |i| i + 1);
+    if let Some(arg) = left_args.iter().chain(&right_args[..trailing]).find(|arg| arg.default_tokens.is_some()) {
+        return Err(ParserError::MisplacedDefaultArgument { tkn: arg.tkn });
+    }
+    return Ok(());
+}
+
+/// Whether the type at `index` is the return type, which is the last thing in the signature.
+fn is_return_type(tokens: &[Tkn], mut index: usize, structs: &[&str], type_params: &[String], enums: &[Enum]) -> bool {
+    return super::get_type_token_expr_type(tokens, &mut index, structs, type_params, enums).is_some() 
+        && index >= tokens.len();
+}
+
+/// The tokens of a default value, which go up to the comma, group or `=` that ends the argument.
+fn default_tokens<'tkns>(tokens: &'tkns [Tkn], expect_closing: bool, index: &mut usize) -> &'tkns [Tkn] {
+    let start = *index;
+    let mut depth = 0;
+    while let Some(tkn) = tokens.get(*index) {
+        match tkn.token {
+            TknType::OpenParen | TknType::OpenSquareBracket => depth += 1,
+            TknType::CloseParen | TknType::CloseSquareBracket if depth > 0 => depth -= 1,
+            TknType::Comma if depth == 0 => break,
+            _ if depth == 0 && expect_closing_group(tokens, expect_closing, &mut index.clone()) => break,
+            _ => ()
+        }
+        *index += 1;
+    }
+    return &tokens[start..*index];
+}
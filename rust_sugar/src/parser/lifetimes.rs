@@ -0,0 +1,433 @@
+//! Lifetimes on references.
+//!
+//! A reference type can name the lifetime of what it borrows, as in `&'a T`, where `'a` is
+//! declared with the type parameters of the function or struct the type is written in.  A function
+//! returning a reference borrows it from the arguments with the lifetime of the returned reference.
+//! When the returned lifetime is elided, the reference borrows from `self` when that is a
+//! reference, or else from the only argument holding a reference, and the lifetime has to be named
+//! when there is no such argument.  Struct types do not keep their lifetime arguments, so the
+//! references an argument holds inside of a struct can be returned with any lifetime.
+//!
+//! The body of such a function can only return references into those arguments, never into its
+//! own variables or into temporary values, which do not outlive the call.  What a variable holding
+//! a reference borrows from is everything assigned to it anywhere in the function, since the
+//! checker does not follow which branch runs.
+
+use std::collections::HashMap;
+
+use crate::lexer::token::{Op, Tkn, TknType};
+
+use super::{expr::{Expr, ExprData, ExprType}, functions::{self, CaptureMode, FnParam, Fun}, operators::UnOp, ownership, patterns::MatchArm, stmt::{Stmt, StmtData}, structs::Struct, ParserError};
+
+/// What a reference borrows from
+#[derive(Clone, Debug, PartialEq)]
+enum Origin {
+    /// The references held by the argument at this index
+    Param(usize),
+    /// A variable of the function, borrowed where its name is written at the token at `index`
+    Variable { name: String, index: usize },
+    /// A value only the expression on this line holds
+    Temporary(usize),
+    /// A variable of the function, borrowed by a closure using it first at the token at `index`
+    Captured { name: String, index: usize }
+}
+
+struct LifetimeChecker<'f, 'tkns, 'defs, 'bumps> {
+    tokens: &'tkns [Tkn],
+    structs: &'defs [Struct],
+    params: Vec<&'f FnParam<'tkns, 'bumps, 'defs>>,
+    return_type: &'f ExprType,
+    /// The indexes of the arguments a returned reference may borrow from
+    returnable: Vec<usize>,
+    /// What the references held by each variable in scope borrow from, innermost scope last
+    scopes: Vec<HashMap<String, Vec<Origin>>>,
+    /// The label of each enclosing loop with the variable a `break` out of it assigns its value
+    /// to, innermost loop last
+    loops: Vec<(Option<&'f String>, Option<&'f String>)>,
+    errors: Vec<ParserError<'tkns, 'bumps, 'defs>>
+}
+
+/// Checks that every lifetime in `tokens`, the tokens of a signature or of the fields of a struct,
+/// is one of the declared `lifetimes`.
+pub fn check_declared<'tkns, 'bumps, 'defs>(
+    tokens: &'tkns [Tkn],
+    lifetimes: &[String]
+) -> Result<(), ParserError<'tkns, 'bumps, 'defs>> {
+    for tkn in tokens {
+        if let TknType::Label(lifetime) = &tkn.token
+            && !lifetimes.contains(lifetime)
+        {
+            return Err(ParserError::UndeclaredLifetime { tkn });
+        }
+    }
+    return Ok(());
+}
+
+/// Checks the lifetimes of a signature, where `arg_tokens` are the tokens the arguments and the
+/// return type were parsed from: each one is declared, and a returned reference with an elided
+/// lifetime has an argument to borrow from.
+pub fn check_signature<'tkns, 'bumps, 'defs>(
+    arg_tokens: &'tkns [Tkn],
+    lifetimes: &[String],
+    params: &[&FnParam<'tkns, 'bumps, 'defs>],
+    return_type: &ExprType,
+    structs: &[Struct]
+) -> Result<(), ParserError<'tkns, 'bumps, 'defs>> {
+    check_declared(arg_tokens, lifetimes)?;
+
+    let mut returned = vec![];
+    lifetimes_in(return_type, &mut returned);
+    if returned.contains(&None) && elided_source(params, structs).is_none() {
+        let return_start = arg_tokens.iter()
+            .rposition(|tkn| tkn.token == TknType::Operation(Op::Assign))
+            .map_or(0, |i| i + 1);
+        return Err(ParserError::MissingLifetime { tkn: &arg_tokens[return_start] });
+    }
+    return Ok(());
+}
+
+/// Checks that the references `function` returns borrow from the arguments its return type allows,
+/// where `body_tokens` are the tokens the body was parsed from.
+pub fn check_function<'tkns, 'bumps, 'defs>(
+    function: &Fun<'tkns, 'bumps, 'defs>,
+    body_tokens: &'tkns [Tkn],
+    structs: &'defs [Struct]
+) -> Vec<ParserError<'tkns, 'bumps, 'defs>> {
+    // a returned closure cannot borrow from the function any more than a returned reference can
+    if !ownership::holds_reference(&function.return_type, structs) {
+        return vec![];
+    }
+    let mut returned = vec![];
+    lifetimes_in(&function.return_type, &mut returned);
+    let untracked = holds_untracked_reference(&function.return_type, structs);
+
+    let params = function.left_args.iter().chain(function.right_args).collect::<Vec<_>>();
+    let mut returnable = params.iter().enumerate()
+        .filter(|(_, param)| {
+            let mut lifetimes = vec![];
+            lifetimes_in(&param.param_type, &mut lifetimes);
+            return lifetimes.iter().any(|lifetime| lifetime.is_some() && returned.contains(lifetime))
+                || (lifetimes.is_empty() || untracked) && ownership::holds_reference(&param.param_type, structs);
+        })
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    if returned.contains(&None) {
+        returnable.extend(elided_source(&params, structs));
+    }
+
+    let mut scope = HashMap::new();
+    for (i, param) in params.iter().enumerate() {
+        if let Some(name) = &param.param_name {
+            let origins = if ownership::holds_reference(&param.param_type, structs) {
+                vec![Origin::Param(i)]
+            } else {
+                vec![]
+            };
+            scope.insert(name.clone(), origins);
+        }
+    }
+
+    let mut checker = LifetimeChecker {
+        tokens: functions::statement_tokens(body_tokens),
+        structs,
+        params,
+        return_type: &function.return_type,
+        returnable,
+        scopes: vec![scope],
+        loops: vec![],
+        errors: vec![]
+    };
+    checker.check_stmts(&function.body);
+    return checker.errors;
+}
+
+/// Pushes the lifetime of every reference in `expr_type`, `None` for the elided ones.
+fn lifetimes_in<'e>(expr_type: &'e ExprType, lifetimes: &mut Vec<Option<&'e String>>) {
+    use ExprType as ET;
+
+    match expr_type {
+        ET::Ref { lifetime, expr_type } | ET::MutRef { lifetime, expr_type } | ET::ImRef { lifetime, expr_type } => {
+            lifetimes.push(lifetime.as_ref());
+            lifetimes_in(expr_type, lifetimes);
+        },
+        ET::Array { expr_type, .. } | ET::Range { expr_type, .. } => lifetimes_in(expr_type, lifetimes),
+        ET::Tuple { start, end } => {
+            for expr_type in start.iter().chain(end) {
+                lifetimes_in(expr_type, lifetimes);
+            }
+        },
+        ET::Custom { type_args, .. } => {
+            for type_arg in type_args {
+                lifetimes_in(type_arg, lifetimes);
+            }
+        },
+        ET::AnonymousCustom { fields } => {
+            for (_, expr_type) in fields {
+                lifetimes_in(expr_type, lifetimes);
+            }
+        },
+        _ => ()
+    }
+}
+
+/// Whether `expr_type` holds a reference inside of a struct or an enum, which do not keep the
+/// lifetimes of their references.
+fn holds_untracked_reference(expr_type: &ExprType, structs: &[Struct]) -> bool {
+    use ExprType as ET;
+
+    return match expr_type {
+        ET::Custom { .. } | ET::Enum { .. } => ownership::holds_reference(expr_type, structs),
+        ET::Ref { expr_type, .. } | ET::MutRef { expr_type, .. } | ET::ImRef { expr_type, .. } |
+            ET::Array { expr_type, .. } | ET::Range { expr_type, .. } => holds_untracked_reference(expr_type, structs),
+        ET::Tuple { start, end } => start.iter()
+            .chain(end)
+            .any(|expr_type| holds_untracked_reference(expr_type, structs)),
+        ET::AnonymousCustom { fields } => fields.iter().any(|(_, expr_type)| holds_untracked_reference(expr_type, structs)),
+        _ => false
+    };
+}
+
+/// The index in `params` of the argument a returned reference with an elided lifetime borrows
+/// from: `self` when it is a reference, or else the only argument holding a reference.
+fn elided_source(params: &[&FnParam], structs: &[Struct]) -> Option<usize> {
+    if let Some(i) = params.iter().position(|param|
+        param.param_name.as_deref() == Some("self")
+            && matches!(param.param_type, ExprType::Ref { .. } | ExprType::MutRef { .. } | ExprType::ImRef { .. })
+    ) {
+        return Some(i);
+    }
+
+    let mut sources = params.iter().enumerate().filter_map(|(i, param)| {
+        let mut lifetimes = vec![];
+        lifetimes_in(&param.param_type, &mut lifetimes);
+        return match lifetimes.len() {
+            // the references inside of a struct count as one
+            0 if ownership::holds_reference(&param.param_type, structs) => Some((i, 1)),
+            0 => None,
+            count => Some((i, count))
+        };
+    });
+    return match (sources.next(), sources.next()) {
+        (Some((i, 1)), None) => Some(i),
+        _ => None
+    };
+}
+
+impl<'f, 'tkns, 'defs, 'bumps> LifetimeChecker<'f, 'tkns, 'defs, 'bumps> {
+    fn check_stmts(&mut self, stmts: &'f [&'bumps StmtData<'bumps, 'defs>]) {
+        self.scopes.push(HashMap::new());
+        for stmt in stmts {
+            self.check_stmt(stmt);
+        }
+        self.scopes.pop();
+    }
+
+    fn check_stmt(&mut self, StmtData { stmt, .. }: &'f StmtData<'bumps, 'defs>) {
+        match stmt {
+            Stmt::Compound(stmts) => self.check_stmts(stmts),
+            Stmt::While { label, body, .. } => {
+                self.loops.push((label.as_ref(), None));
+                self.check_stmts(body);
+                self.loops.pop();
+            },
+            Stmt::Loop { label, body, assign } => {
+                self.loops.push((label.as_ref(), assign.as_ref().map(|(name, _)| name)));
+                self.check_stmts(body);
+                self.loops.pop();
+            },
+            Stmt::For { label, binder, iter, body } => {
+                let origins = self.origins(iter);
+                self.scopes.push(HashMap::new());
+                if let Some((name, _)) = binder {
+                    self.declare(name, origins);
+                }
+                self.loops.push((label.as_ref(), None));
+                self.check_stmts(body);
+                self.loops.pop();
+                self.scopes.pop();
+            },
+            Stmt::Conditional { bodies, .. } => {
+                for body in bodies {
+                    self.check_stmts(body);
+                }
+            },
+            Stmt::Match { scrutinee, arms } => {
+                let origins = self.origins(scrutinee);
+                for MatchArm { pattern, body, .. } in arms {
+                    self.scopes.push(HashMap::new());
+                    self.bind_pattern(pattern, &origins);
+                    self.check_stmts(body);
+                    self.scopes.pop();
+                }
+            },
+            Stmt::Return(Some(value), return_index) => {
+                for origin in self.origins(value) {
+                    self.check_returned(*return_index, origin);
+                }
+            },
+            Stmt::Break { label, value: Some(value) } => {
+                let assign = self.loops.iter().rev()
+                    .find(|(loop_label, _)| label.is_none() || *loop_label == label.as_ref())
+                    .and_then(|(_, assign)| *assign);
+                if let Some(name) = assign {
+                    let origins = self.origins(value);
+                    self.assign(name, origins);
+                }
+            },
+            Stmt::Declare(name, _, _) => self.declare(name, vec![]),
+            Stmt::Assign { variable, assign } => {
+                let origins = self.origins(assign);
+                if let Some((name, _)) = ownership::root_identifier(variable.expr_data) {
+                    self.assign(name, origins);
+                }
+            },
+            Stmt::Return(None, _) | Stmt::Break { value: None, .. } | Stmt::Continue { .. } | Stmt::Expr(_) => ()
+        }
+    }
+
+    fn declare(&mut self, name: &str, origins: Vec<Origin>) {
+        self.scopes.last_mut().unwrap().insert(name.to_string(), origins);
+    }
+
+    fn bind_pattern(&mut self, pattern: &super::patterns::MatchPattern, origins: &[Origin]) {
+        let mut bindings = vec![];
+        ownership::pattern_bindings(pattern, &mut bindings);
+        for name in bindings {
+            self.declare(name, origins.to_vec());
+        }
+    }
+
+    /// Adds `origins` to what the variable `name` borrows from.
+    fn assign(&mut self, name: &str, origins: Vec<Origin>) {
+        if let Some(held) = self.scopes.iter_mut().rev().find_map(|scope| scope.get_mut(name)) {
+            for origin in origins {
+                if !held.contains(&origin) {
+                    held.push(origin);
+                }
+            }
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Vec<Origin>> {
+        return self.scopes.iter().rev().find_map(|scope| scope.get(name));
+    }
+
+    /// What the references in the value of `expr` borrow from.
+    fn origins(&mut self, expr: &'f Expr<'bumps, 'defs>) -> Vec<Origin> {
+        if !ownership::holds_reference(&expr.expr_type.get(), self.structs) {
+            return vec![];
+        }
+        return match expr.expr_data {
+            ExprData::UnaryOp(UnOp::Borrow | UnOp::BorrowMutable | UnOp::BorrowInteriorMutable, place) => {
+                self.place_origins(place)
+            },
+            ExprData::UnaryOp(_, expr) | ExprData::Index { expr, .. } | ExprData::AmbiguousGroup(expr) => self.origins(expr),
+            ExprData::CustomField { data, .. } | ExprData::AnonymousCustomField { data, .. } => self.origins(data),
+            ExprData::Function { left_args, right_args, .. } => left_args.iter()
+                .chain(right_args)
+                .flat_map(|arg| self.origins(arg))
+                .collect(),
+            ExprData::Call { callee, left_args, right_args } | ExprData::Partial { callee, left_args, right_args } => std::iter::once(callee)
+                .chain(left_args)
+                .chain(right_args)
+                .flat_map(|expr| self.origins(expr))
+                .collect(),
+            ExprData::Array(exprs) | ExprData::Tuple(exprs) => exprs.iter()
+                .flat_map(|expr| self.origins(expr))
+                .collect(),
+            ExprData::Custom { fields } => fields.values()
+                .flat_map(|field| self.field_origins(field))
+                .collect(),
+            ExprData::AnonymousCustom { fields } | ExprData::EnumVariant { fields, .. } => fields.iter()
+                .flat_map(|(_, field)| self.field_origins(field))
+                .collect(),
+            ExprData::Match { scrutinee, arms } => {
+                let scrutinee_origins = self.origins(scrutinee);
+                let mut origins = vec![];
+                for MatchArm { pattern, body, .. } in arms {
+                    self.scopes.push(HashMap::new());
+                    self.bind_pattern(pattern, &scrutinee_origins);
+                    origins.append(&mut self.origins(body));
+                    self.scopes.pop();
+                }
+                origins
+            },
+            expr_data => self.field_origins(expr_data)
+        };
+    }
+
+    /// What the references in `expr_data` borrow from, for the values of struct literal fields, 
+    /// which are kept without their type.
+    fn field_origins(&mut self, expr_data: &'f ExprData<'bumps, 'defs>) -> Vec<Origin> {
+        return match expr_data {
+            ExprData::Identifier(name, _) => self.lookup(name).cloned().unwrap_or_default(),
+            ExprData::UnaryOp(UnOp::Borrow | UnOp::BorrowMutable | UnOp::BorrowInteriorMutable, place) => {
+                self.place_origins(place)
+            },
+            // a closure borrows the variables it borrows, and holds what the ones it moves hold
+            ExprData::Closure { captures, .. } => captures.iter()
+                .flat_map(|(name, used_at, mode)| match (mode, self.lookup(name)) {
+                    (_, None) => vec![],
+                    (CaptureMode::Borrow(_), Some(_)) => vec![Origin::Captured { name: name.clone(), index: *used_at }],
+                    (CaptureMode::Move, Some(origins)) => origins.clone()
+                })
+                .collect(),
+            _ => vec![]
+        };
+    }
+
+    /// What a borrow of `place` borrows from.
+    fn place_origins(&mut self, place: &'f Expr<'bumps, 'defs>) -> Vec<Origin> {
+        return match place.expr_data {
+            ExprData::Identifier(name, name_index) if self.lookup(name).is_some() => vec![Origin::Variable {
+                name: name.clone(),
+                index: *name_index
+            }],
+            ExprData::CustomField { data, .. } | ExprData::AnonymousCustomField { data, .. } => self.place_origins(data),
+            ExprData::Index { expr, .. } => self.place_origins(expr),
+            // a reborrow through a reference borrows from what the reference does
+            ExprData::UnaryOp(UnOp::Deref, reference) => self.origins(reference),
+            _ => vec![Origin::Temporary(place.line)]
+        };
+    }
+
+    /// Reports returning a reference that borrows from `origin` in the `return` at the token at 
+    /// `return_index`, unless the return type allows it.
+    fn check_returned(&mut self, return_index: usize, origin: Origin) {
+        let tkn = &self.tokens[return_index];
+        let error = match origin {
+            Origin::Param(i) if self.returnable.contains(&i) => return,
+            Origin::Param(i) => ParserError::LifetimeMismatch {
+                tkn,
+                param: self.params[i].tkn,
+                return_type: self.return_type.clone()
+            },
+            Origin::Variable { index, .. } => ParserError::ReturnsLocalReference {
+                tkn,
+                borrowed: &self.tokens[index]
+            },
+            Origin::Temporary(line) => ParserError::ReturnsTemporaryReference {
+                tkn,
+                borrowed: self.find_borrowed_tkn(line)
+            },
+            Origin::Captured { index, .. } => ParserError::ReturnsBorrowingClosure {
+                tkn,
+                captured: &self.tokens[index]
+            }
+        };
+        self.errors.push(error);
+    }
+
+    /// The token after the first `&` on `line`.  A temporary value is not written at a token of 
+    /// its own, so this is only looked up to report an error.
+    fn find_borrowed_tkn(&self, line: usize) -> &'tkns Tkn {
+        let tokens = self.tokens;
+        let line_start = tokens.iter().position(|tkn| tkn.line_number >= line).unwrap_or(0);
+        let borrow = tokens[line_start..].iter()
+            .position(|tkn| tkn.token == TknType::Borrow)
+            .map_or(line_start, |i| line_start + i);
+        return tokens.get(borrow + 1)
+            .filter(|tkn| tkn.line_number == tokens[borrow].line_number)
+            .unwrap_or(&tokens[borrow]);
+    }
+}
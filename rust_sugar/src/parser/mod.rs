@@ -1,25 +1,33 @@
-use std::{cell::RefCell, collections::HashMap, ops::Deref};
+use std::{cell::RefCell, collections::{HashMap, HashSet}, ops::Deref};
 
 use accessors::{Accessor, AccessorDefinition};
 use bumpalo::Bump;
+use enums::{Enum, EnumDefinition};
 use expr::{ExprType, VariableData};
 use functions::{define_function, FullFunctionDefinition, Fun, FunctionDefinition};
 use once_cell::sync::OnceCell;
 use parser_error::ParserError;
 use stack_frame_allocators::stack_frame_dict_allocator::StackFrameDictAllocator;
 use structs::{Struct, StructDefinition};
+use traits::{ImplDefinition, Trait, TraitDefinition};
 
-use crate::{full_result::FullResult, lexer::token::{Tkn, TknType}};
+use crate::{full_result::FullResult, lexer::token::{Kwrd, Op, Tkn, TknType}};
 
 pub mod accessors;
+pub mod enums;
+pub mod exhaustiveness;
 pub mod expr;
 pub mod functions;
+pub mod lifetimes;
+pub mod namespaces;
 pub mod operators;
+pub mod ownership;
 pub mod parser_error;
 pub mod patterns;
 pub mod stmt;
 pub mod structs;
 pub mod tokens;
+pub mod traits;
 
 pub type ExprBump = ExpressionBumpAllocator;
 pub type StmtBump = StatementBumpAllocator;
@@ -73,23 +81,38 @@ impl Deref for FnParamBump {
     }
 }
 
+/// Parses a program out of the tokens of each of its `files`, where the first file is the root 
+/// namespace and the others are namespaces named after themselves.  `tokens` holds the program 
+/// once its namespaces are resolved, which the returned errors point into.
 pub fn parse<'tkns, 'bumps, 'defs>(
     expr_bump: &'bumps ExprBump,
     stmt_bump: &'bumps StmtBump,
     fn_param_bump: &'bumps FnParamBump,
     accessors: &'defs OnceCell<Box<[Accessor]>>,
     structs: &'defs OnceCell<Box<[Struct]>>,
+    enums: &'defs OnceCell<Box<[Enum]>>,
+    traits: &'defs OnceCell<Box<[Trait]>>,
     functions: &'defs OnceCell<Box<[Fun<'tkns, 'bumps, 'defs>]>>,
-    tokens: &'tkns [Tkn]
+    tokens: &'tkns OnceCell<Box<[Tkn]>>,
+    files: &'tkns [Vec<Tkn>]
 ) -> Result<(), Vec<ParserError<'tkns, 'bumps, 'defs>>> {
-    let mut errors: Vec<ParserError> = vec![];
+    let (resolved, mut errors) = namespaces::resolve(files);
+    // the paths that did not resolve would only show up again as unknown names in the items
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    tokens.set(resolved.into_boxed_slice()).unwrap();
+    let tokens: &'tkns [Tkn] = tokens.get().unwrap();
 
     let function_data = RefCell::new(HashMap::new());
     let variables = StackFrameDictAllocator::<String, VariableData>::new();
     
     let mut accessor_defs: Vec<AccessorDefinition> = vec![];
     let mut struct_defs: Vec<StructDefinition> = vec![];
+    let mut enum_defs: Vec<EnumDefinition> = vec![];
     let mut function_defs: Vec<FunctionDefinition> = vec![];
+    let mut trait_defs: Vec<TraitDefinition> = vec![];
+    let mut impl_defs: Vec<ImplDefinition> = vec![];
 
     let mut index: usize = 0;
     while index < tokens.len() {
@@ -99,7 +122,11 @@ pub fn parse<'tkns, 'bumps, 'defs>(
                 continue;
             },
             FullResult::SoftErr(()) => (),
-            FullResult::HardErr(error) => return Err(error)
+            FullResult::HardErr(mut error) => {
+                errors.append(&mut error);
+                synchronize_item(tokens, &mut index);
+                continue;
+            }
         }
         match structs::define_struct(tokens, &mut index) {
             FullResult::Ok(def) => {
@@ -107,31 +134,74 @@ pub fn parse<'tkns, 'bumps, 'defs>(
                 continue;
             },
             FullResult::SoftErr(()) => (),
-            FullResult::HardErr(error) => return Err(error)
+            FullResult::HardErr(mut error) => {
+                errors.append(&mut error);
+                synchronize_item(tokens, &mut index);
+                continue;
+            }
+        }
+        match enums::define_enum(tokens, &mut index) {
+            FullResult::Ok(def) => {
+                enum_defs.push(def);
+                continue;
+            },
+            FullResult::SoftErr(()) => (),
+            FullResult::HardErr(mut error) => {
+                errors.append(&mut error);
+                synchronize_item(tokens, &mut index);
+                continue;
+            }
+        }
+        match traits::define_trait(tokens, &mut index) {
+            FullResult::Ok(def) => {
+                trait_defs.push(def);
+                continue;
+            },
+            FullResult::SoftErr(()) => (),
+            FullResult::HardErr(mut error) => {
+                errors.append(&mut error);
+                synchronize_item(tokens, &mut index);
+                continue;
+            }
+        }
+        match traits::define_impl(tokens, &mut index) {
+            FullResult::Ok(def) => {
+                impl_defs.push(def);
+                continue;
+            },
+            FullResult::SoftErr(()) => (),
+            FullResult::HardErr(mut error) => {
+                errors.append(&mut error);
+                synchronize_item(tokens, &mut index);
+                continue;
+            }
         }
         match define_function(tokens, &mut index) {
+            FullResult::Ok(def) if matches!(def.tkn.token, TknType::CustomOperation(_)) => {
+                errors.push(ParserError::OperatorOutsideImpl { tkn: def.tkn });
+                continue;
+            },
             FullResult::Ok(def) => {
                 function_defs.push(def);
                 continue;
             },
             FullResult::SoftErr(()) => (),
-            FullResult::HardErr(error) => return Err(vec![error])
+            FullResult::HardErr(error) => {
+                errors.push(error);
+                synchronize_item(tokens, &mut index);
+                continue;
+            }
         }
 
+        // every file ends with its own end of file
         if tokens::is_expected_token(tokens, TknType::EndOfFile, &mut index) {
-            break;
+            continue;
         } else {
             errors.push(ParserError::InvalidBlock{ tkn: &tokens[index] });
-            eprintln!("accessors: {accessor_defs:#?}, structs: {struct_defs:#?}, functions: {function_defs:#?}");
-            return Err(errors);
+            synchronize_item(tokens, &mut index);
         }
     }
 
-    let accessor_names = accessor_defs.iter()
-        .map(|AccessorDefinition {name, ..}|
-            name.as_str()
-        ).collect::<Vec<&str>>();
-
     let struct_names = struct_defs
         .iter()
         .map(|StructDefinition {name, ..} | {
@@ -148,10 +218,22 @@ pub fn parse<'tkns, 'bumps, 'defs>(
     }
     accessors.set(accessor_buffer.into_boxed_slice()).unwrap();
 
+    // enums come first so struct fields can hold them
+    let mut enum_buffer = vec![];
+    for enum_def in &enum_defs {
+        match enums::parse_enum(
+            enum_def, accessors.get().unwrap(), &struct_names, &enum_buffer
+        ) {
+            Ok(custom_enum) => enum_buffer.push(custom_enum),
+            Err(err) => errors.push(err),
+        };
+    }
+    enums.set(enum_buffer.into_boxed_slice()).unwrap();
+
     let mut struct_buffer = vec![];
     for struct_def in &struct_defs {
         match structs::parse_struct(
-            struct_def, &accessor_names, &struct_names
+            struct_def, accessors.get().unwrap(), &struct_names, enums.get().unwrap()
         ) {
             Ok(structure) => struct_buffer.push(structure),
             Err(err) => errors.push(err),
@@ -159,6 +241,54 @@ pub fn parse<'tkns, 'bumps, 'defs>(
     }
     structs.set(struct_buffer.into_boxed_slice()).unwrap();
 
+    let mut trait_buffer = vec![];
+    for trait_def in &trait_defs {
+        match traits::parse_trait(
+            &fn_param_bump, trait_def, accessors.get().unwrap(), &struct_names, enums.get().unwrap()
+        ) {
+            Ok(custom_trait) => trait_buffer.push(custom_trait),
+            Err(err) => errors.push(err),
+        };
+    }
+    if !trait_buffer.iter().any(|custom_trait| custom_trait.name == traits::COPY_TRAIT) {
+        trait_buffer.push(traits::copy_trait());
+    }
+
+    // methods are functions named after the type they are implemented on, the impls of traits 
+    // are checked against the trait once every method is defined
+    let mut trait_impls = vec![];
+    for ImplDefinition { trait_tkn, type_tkn, methods } in impl_defs {
+        let TknType::Identifier(type_name) = &type_tkn.token else {
+            unreachable!("impl blocks are always for a named type");
+        };
+        let self_type = if struct_names.contains(&type_name.as_str()) {
+            ExprType::Custom { ident: type_name.clone(), type_args: vec![] }
+        } else if let Some(custom_enum) = enums.get().unwrap().iter().find(|custom_enum| custom_enum.name == *type_name) {
+            custom_enum.to_expr_type()
+        } else {
+            errors.push(ParserError::ExpectedType { tkn: type_tkn });
+            continue;
+        };
+
+        if let Some(trait_tkn) = trait_tkn {
+            let Some(trait_index) = trait_buffer.iter().position(|custom_trait| 
+                TknType::Identifier(custom_trait.name.clone()) == trait_tkn.token
+            ) else {
+                errors.push(ParserError::TraitNotDefined { tkn: trait_tkn });
+                continue;
+            };
+            trait_buffer[trait_index].implementations.push(self_type.clone());
+            let method_tkns = methods.iter().map(|method| method.tkn).collect::<Vec<_>>();
+            trait_impls.push((trait_index, trait_tkn, self_type, method_tkns));
+        }
+
+        for mut method in methods {
+            method.name = format!("{type_name}::{}", method.name);
+            function_defs.push(method);
+        }
+    }
+    traits.set(trait_buffer.into_boxed_slice()).unwrap();
+
     let mut full_function_defs = vec![];
     for function_def in function_defs {
         let (
@@ -167,8 +297,10 @@ pub fn parse<'tkns, 'bumps, 'defs>(
         ) = match FullFunctionDefinition::from_partial_fn_def(
             &fn_param_bump, 
             function_def, 
-            &accessor_names, 
-            &struct_names
+            accessors.get().unwrap(), 
+            &struct_names,
+            structs.get().unwrap(),
+            enums.get().unwrap()
         ) {
             Ok((full_function_def, body_tokens)) => (full_function_def, body_tokens),
             Err(err) => {
@@ -181,30 +313,92 @@ pub fn parse<'tkns, 'bumps, 'defs>(
         full_function_defs.push((full_function_def, body_tokens));
     }
 
+    for (trait_index, trait_tkn, self_type, method_tkns) in trait_impls {
+        errors.append(&mut traits::check_impl(
+            &traits.get().unwrap()[trait_index], 
+            trait_tkn, 
+            &self_type, 
+            &method_tkns, 
+            &function_data
+        ));
+    }
+
+    let copy_types = traits.get().unwrap().iter()
+        .find(|custom_trait| custom_trait.name == traits::COPY_TRAIT)
+        .map_or(&[][..], |custom_trait| custom_trait.implementations.as_slice());
+
     let mut function_buffer = vec![];
+    let mut generic_bodies = HashMap::new();
     for (full_function_def, body_tokens) in full_function_defs {
-        if body_tokens.is_empty() {
+        if !full_function_def.type_params.is_empty() {
+            generic_bodies.insert(full_function_def.name, body_tokens);
+        } else if body_tokens.is_empty() {
             continue;
         } else {
             let function = match functions::parse_function(
                 &expr_bump, 
                 &stmt_bump, 
                 structs.get().unwrap(),
+                enums.get().unwrap(),
                 &function_data, 
                 variables.new_frame(), 
                 full_function_def, 
                 body_tokens
             ) {
                 Ok(function) => function,
-                Err(err) => {
-                    errors.push(err);
+                Err(mut err) => {
+                    errors.append(&mut err);
                     continue;
                 }
             };
+            errors.append(&mut ownership::check_function(&function, body_tokens, structs.get().unwrap(), copy_types));
+            errors.append(&mut lifetimes::check_function(&function, body_tokens, structs.get().unwrap()));
             function_buffer.push(function);
         }
     }
 
+    // generic functions are parsed once for each instance the rest of the program calls, and 
+    // closures after the function they are written in, where either can call for more of both
+    let mut parsed_instances = HashSet::new();
+    loop {
+        let mut instances = function_data.borrow().values()
+            .filter(|fun_def| {
+                (fun_def.instance_of.is_some() || fun_def.closure.is_some()) 
+                    && !parsed_instances.contains(&fun_def.name)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        if instances.is_empty() {
+            break;
+        }
+        instances.sort_by(|l, r| l.name.cmp(&r.name));
+
+        for instance in instances {
+            parsed_instances.insert(instance.name.clone());
+            let body_tokens = match &instance.closure {
+                Some(closure) => closure.body_tokens,
+                None => generic_bodies[instance.instance_of.as_ref().unwrap()]
+            };
+            match functions::parse_function(
+                &expr_bump, 
+                &stmt_bump, 
+                structs.get().unwrap(),
+                enums.get().unwrap(),
+                &function_data, 
+                variables.new_frame(), 
+                instance, 
+                body_tokens
+            ) {
+                Ok(function) => {
+                    errors.append(&mut ownership::check_function(&function, body_tokens, structs.get().unwrap(), copy_types));
+                    errors.append(&mut lifetimes::check_function(&function, body_tokens, structs.get().unwrap()));
+                    function_buffer.push(function);
+                },
+                Err(mut err) => errors.append(&mut err)
+            }
+        }
+    }
+
     functions.set(function_buffer.into_boxed_slice()).unwrap();
     
     if errors.is_empty() {
@@ -213,29 +407,153 @@ pub fn parse<'tkns, 'bumps, 'defs>(
     return Err(errors);
 }
 
+/// Skips past a broken top-level item to the start of the next `accessor`, `struct`, `enum`, 
+/// `trait`, `impl` or `fn` (including the accessibility and modifiers written in front of it), so 
+/// the rest of the file still gets parsed and reported on.
+fn synchronize_item(tokens: &[Tkn], index: &mut usize) {
+    // step over the broken item's own accessibility, modifiers and keyword first
+    let mut peek = *index;
+    if matches!(
+        tokens[peek].token,
+        TknType::Keyword(Kwrd::Public) 
+        | TknType::Keyword(Kwrd::Private) 
+        | TknType::Keyword(Kwrd::Package) 
+        | TknType::Identifier(_)
+    ) {
+        peek += 1;
+    }
+    while tokens::is_expected_token(tokens, TknType::Keyword(Kwrd::Mutable), &mut peek)
+        || tokens::is_expected_token(tokens, TknType::Keyword(Kwrd::Recursive), &mut peek) {}
+    if matches!(
+        tokens.get(peek).map(|e| &e.token),
+        Some(
+            TknType::Keyword(Kwrd::Accessor) 
+            | TknType::Keyword(Kwrd::Struct) 
+            | TknType::Keyword(Kwrd::Enum) 
+            | TknType::Keyword(Kwrd::Trait) 
+            | TknType::Keyword(Kwrd::Impl) 
+            | TknType::Keyword(Kwrd::Function)
+        )
+    ) || peek == *index {
+        peek += 1;
+    }
+
+    let search_start = peek;
+    while let Some(tkn) = tokens.get(peek) {
+        match tkn.token {
+            TknType::Keyword(Kwrd::Accessor) | TknType::Keyword(Kwrd::Impl) | TknType::EndOfFile => break,
+            TknType::Keyword(Kwrd::Struct) 
+            | TknType::Keyword(Kwrd::Enum) 
+            | TknType::Keyword(Kwrd::Trait) 
+            | TknType::Keyword(Kwrd::Function) => {
+                while peek > search_start && matches!(
+                    tokens[peek - 1].token, 
+                    TknType::Keyword(Kwrd::Mutable) | TknType::Keyword(Kwrd::Recursive)
+                ) {
+                    peek -= 1;
+                }
+                if peek > search_start && matches!(
+                    tokens[peek - 1].token,
+                    TknType::Keyword(Kwrd::Public) 
+                    | TknType::Keyword(Kwrd::Private) 
+                    | TknType::Keyword(Kwrd::Package) 
+                    | TknType::Identifier(_)
+                ) {
+                    peek -= 1;
+                }
+                break;
+            },
+            _ => peek += 1
+        }
+    }
+    *index = peek;
+}
+
 pub fn get_type(
     tokens: &[Tkn],
     index: &mut usize,
-    structs: &[Struct]
+    structs: &[Struct],
+    enums: &[Enum]
 ) -> Option<ExprType> {
     if let Some(Tkn {token: TknType::Type(typ), ..}) = tokens.get(*index) {
         *index += 1;
         return Some(typ.to_expr_type());
     } else if let Some(Tkn {token: TknType::Identifier(typ), ..}) = tokens.get(*index) {
         for i in structs {
+            if *typ == i.name {
+                let struct_names = structs.iter().map(|custom_struct| custom_struct.name.as_str()).collect::<Vec<_>>();
+                return get_type_token_expr_type(tokens, index, &struct_names, &[], enums);
+            }
+        }
+        for i in enums {
             if *typ == i.name {
                 *index += 1;
-                return Some(ExprType::Custom {ident: typ.clone()});
+                return Some(i.to_expr_type());
             }
         }
+    } else if let Some(Tkn {token: TknType::Keyword(Kwrd::Function) | TknType::OpenParen, ..}) = tokens.get(*index) {
+        let struct_names = structs.iter().map(|custom_struct| custom_struct.name.as_str()).collect::<Vec<_>>();
+        return get_type_token_expr_type(tokens, index, &struct_names, &[], enums);
     }
     return None;
 }
 
+/// The lifetimes and the type parameters written after the name of a generic struct or function, 
+/// as in `<'a, T, U>`.
+pub fn get_type_params<'tkns, 'bumps, 'defs>(
+    tokens: &'tkns [Tkn],
+    index: &mut usize
+) -> Result<(Vec<String>, Vec<String>), ParserError<'tkns, 'bumps, 'defs>> {
+    let mut lifetimes = vec![];
+    let mut type_params = vec![];
+    if !tokens::is_expected_token(tokens, TknType::Operation(Op::LessThan), index) {
+        return Ok((lifetimes, type_params));
+    }
+
+    loop {
+        match tokens.get(*index).map(|e| &e.token) {
+            Some(TknType::Identifier(type_param)) => type_params.push(type_param.clone()),
+            Some(TknType::Label(lifetime)) => lifetimes.push(lifetime.clone()),
+            _ => return Err(ParserError::ExpectedIdentifier { tkn: &tokens[*index] })
+        }
+        *index += 1;
+
+        if tokens::is_expected_token(tokens, TknType::Operation(Op::GreaterThan), index) {
+            return Ok((lifetimes, type_params));
+        }
+        tokens::expect_token(tokens, TknType::Comma, index)
+            .ok_or_else(|| ParserError::ExpectedToken { 
+                tkn: &tokens[*index], 
+                expected: TknType::Operation(Op::GreaterThan) 
+            })?;
+    }
+}
+
 pub fn get_type_token_expr_type(
     tokens: &[Tkn], 
     index: &mut usize, 
-    structs: &[&str]
+    structs: &[&str],
+    type_params: &[String],
+    enums: &[Enum]
+) -> Option<ExprType> {
+    let mut closed_outer = false;
+    let expr_type = get_type_args_expr_type(tokens, index, structs, type_params, enums, &mut closed_outer)?;
+    // a `>>` closing more lists of type arguments than were opened
+    if closed_outer {
+        return None;
+    }
+    return Some(expr_type);
+}
+
+/// Parses a type, where `closed_outer` is set when the type ends in a `>>` that also closes the 
+/// list of type arguments the type is in.
+fn get_type_args_expr_type(
+    tokens: &[Tkn], 
+    index: &mut usize, 
+    structs: &[&str],
+    type_params: &[String],
+    enums: &[Enum],
+    closed_outer: &mut bool
 ) -> Option<ExprType> {
     let mut peek = *index;
     if let Some(TknType::Type(typ)) = tokens.get(peek).map(|e| &e.token) {
@@ -243,13 +561,70 @@ pub fn get_type_token_expr_type(
         *index = peek;
         return Some(typ.to_expr_type());
     } else if let Some(TknType::Identifier(typ)) = tokens.get(peek).map(|e| &e.token) {
+        if type_params.contains(typ) {
+            peek += 1;
+            *index = peek;
+            return Some(ExprType::Generic { name: typ.clone() });
+        }
         for i in structs {
             if typ == *i {
                 peek += 1;
+                let mut type_args = vec![];
+                if tokens::is_expected_token(tokens, TknType::Operation(Op::LessThan), &mut peek) {
+                    loop {
+                        // lifetime arguments only name the lifetimes of the references in the 
+                        // fields, the type does not hold on to them
+                        if let Some(TknType::Label(_)) = tokens.get(peek).map(|e| &e.token) {
+                            peek += 1;
+                            if tokens::is_expected_token(tokens, TknType::Operation(Op::GreaterThan), &mut peek) {
+                                break;
+                            }
+                            tokens::expect_token(tokens, TknType::Comma, &mut peek)?;
+                            continue;
+                        }
+                        let mut closed = false;
+                        type_args.push(get_type_args_expr_type(tokens, &mut peek, structs, type_params, enums, &mut closed)?);
+                        if closed || tokens::is_expected_token(tokens, TknType::Operation(Op::GreaterThan), &mut peek) {
+                            break;
+                        }
+                        if tokens::is_expected_token(tokens, TknType::Operation(Op::BitwiseShiftRight), &mut peek) {
+                            *closed_outer = true;
+                            break;
+                        }
+                        tokens::expect_token(tokens, TknType::Comma, &mut peek)?;
+                    }
+                }
                 *index = peek;
-                return Some(ExprType::Custom {ident: typ.clone()});
+                return Some(ExprType::Custom {ident: typ.clone(), type_args});
             }
         }
+        for i in enums {
+            if *typ == i.name {
+                peek += 1;
+                *index = peek;
+                return Some(i.to_expr_type());
+            }
+        }
+    } else if tokens::is_token(tokens, TknType::Borrow, peek) {
+        peek += 1;
+        let lifetime = match tokens.get(peek).map(|e| &e.token) {
+            Some(TknType::Label(lifetime)) => {
+                peek += 1;
+                Some(lifetime.clone())
+            },
+            _ => None
+        };
+        let mutable = tokens::is_expected_token(tokens, TknType::Keyword(Kwrd::Mutable), &mut peek);
+        let interior_mutable = !mutable 
+            && tokens::is_expected_token(tokens, TknType::Keyword(Kwrd::InteriorMutable), &mut peek);
+        let expr_type = Box::new(get_type_args_expr_type(tokens, &mut peek, structs, type_params, enums, closed_outer)?);
+        *index = peek;
+        if mutable {
+            return Some(ExprType::MutRef { lifetime, expr_type });
+        } else if interior_mutable {
+            return Some(ExprType::ImRef { lifetime, expr_type });
+        }
+        return Some(ExprType::Ref { lifetime, expr_type });
     } else if let Some(TknType::OpenCurlyBrace) = tokens.get(peek).map(|e| &e.token) {
         peek += 1;
         let mut fields = vec![];
@@ -271,7 +646,7 @@ pub fn get_type_token_expr_type(
 
             tokens::expect_token(tokens, TknType::Colon, &mut peek)?;
 
-            let field_type = get_type_token_expr_type(tokens, &mut peek, structs)?;
+            let field_type = get_type_token_expr_type(tokens, &mut peek, structs, type_params, enums)?;
 
             fields.push((ident.clone(), field_type));
 
@@ -279,10 +654,74 @@ pub fn get_type_token_expr_type(
                 need_comma = true;
             }
         }
+    } else if tokens::is_token(tokens, TknType::OpenParen, peek) {
+        // `(T, U)` is a tuple, and so is `(T,)`, while `(T)` is just `T`
+        peek += 1;
+        let expr_type = get_type_token_expr_type(tokens, &mut peek, structs, type_params, enums)?;
+        if tokens::is_expected_token(tokens, TknType::CloseParen, &mut peek) {
+            *index = peek;
+            return Some(expr_type);
+        }
+
+        let mut element_types = vec![expr_type];
+        while tokens::is_expected_token(tokens, TknType::Comma, &mut peek)
+            && !tokens::is_token(tokens, TknType::CloseParen, peek)
+        {
+            element_types.push(get_type_token_expr_type(tokens, &mut peek, structs, type_params, enums)?);
+        }
+        tokens::expect_token(tokens, TknType::CloseParen, &mut peek)?;
+        *index = peek;
+        return Some(ExprType::Tuple { start: element_types, end: vec![] });
+    } else if tokens::is_token(tokens, TknType::Keyword(Kwrd::Function), peek) {
+        // `fn(T, U) = R` takes its arguments on the right like a prefix function, `fn(T) infix (U)` 
+        // and `fn(T) postfix` take them on the left as well, and without `= R` it returns nothing
+        peek += 1;
+        let mut left_args = vec![];
+        let mut right_args = get_type_group(tokens, &mut peek, structs, type_params, enums)?;
+        if tokens::is_expected_token(tokens, TknType::Keyword(Kwrd::Infix), &mut peek) {
+            left_args = right_args;
+            right_args = get_type_group(tokens, &mut peek, structs, type_params, enums)?;
+        } else if tokens::is_expected_token(tokens, TknType::Keyword(Kwrd::Postfix), &mut peek) {
+            left_args = right_args;
+            right_args = vec![];
+        }
+
+        let mut return_type = ExprType::Void;
+        let mut return_peek = peek;
+        if tokens::is_expected_token(tokens, TknType::Operation(Op::Assign), &mut return_peek)
+            && let Some(expr_type) = get_type_args_expr_type(tokens, &mut return_peek, structs, type_params, enums, closed_outer)
+        {
+            return_type = expr_type;
+            peek = return_peek;
+        }
+        *index = peek;
+        return Some(ExprType::FunctionPass { return_type: Box::new(return_type), left_args, right_args });
     }
     return None;
 }
 
+/// The types in `(T, U)`, the arguments of a function type.
+fn get_type_group(
+    tokens: &[Tkn], 
+    index: &mut usize, 
+    structs: &[&str],
+    type_params: &[String],
+    enums: &[Enum]
+) -> Option<Vec<ExprType>> {
+    tokens::expect_token(tokens, TknType::OpenParen, index)?;
+    let mut expr_types = vec![];
+    if tokens::is_expected_token(tokens, TknType::CloseParen, index) {
+        return Some(expr_types);
+    }
+    loop {
+        expr_types.push(get_type_token_expr_type(tokens, index, structs, type_params, enums)?);
+        if tokens::is_expected_token(tokens, TknType::CloseParen, index) {
+            return Some(expr_types);
+        }
+        tokens::expect_token(tokens, TknType::Comma, index)?;
+    }
+}
+
 pub fn get_ident_token_string(
     token: Option<&Tkn>, 
     index: &mut usize, 
@@ -0,0 +1,402 @@
+//! Namespaces and imports.  Every file of a program is a namespace named after the file, except for
+//! the first, which is the root the program starts from, and `namespace name { ... }` blocks nest
+//! more namespaces inside of them.  Before any item is parsed the files are flattened into a single
+//! list of tokens, where every name referring to a struct, enum or function is replaced by the full
+//! path of the item, like `geometry::Coord`, so the rest of the parser sees one flat program.
+
+use std::{collections::HashSet, path::Path};
+
+use crate::lexer::token::{Kwrd, Tkn, TknType};
+
+use super::{tokens, ParserError};
+
+/// A namespace, or one of the blocks it is written in, along with the imports written in it
+struct Scope<'tkns> {
+    /// The full path of the namespace, empty for the root
+    path: String,
+    parent: Option<usize>,
+    imports: Vec<Import<'tkns>>,
+}
+
+/// `use path;`, which makes the last segment of the path name what the whole path names
+struct Import<'tkns> {
+    segments: Vec<&'tkns Tkn>,
+    /// What the path resolves to, once every item is declared
+    full_path: Option<String>,
+}
+
+struct Namespaces<'tkns> {
+    scopes: Vec<Scope<'tkns>>,
+    /// The full path of every namespace
+    namespaces: HashSet<String>,
+    /// The full path of every struct, enum, trait and function
+    items: HashSet<String>,
+    /// Runs of item tokens with the scope they are written in, in the order of the files
+    segments: Vec<(usize, &'tkns [Tkn])>,
+}
+
+/// Flattens the tokens of every file into one program, with `namespace` blocks and `use`
+/// items taken out and the names of items replaced by their full paths.  Each file keeps its
+/// own `EndOfFile` token, so a broken item cannot run on into the next file.
+pub fn resolve<'tkns, 'bumps, 'defs>(
+    files: &'tkns [Vec<Tkn>]
+) -> (Vec<Tkn>, Vec<ParserError<'tkns, 'bumps, 'defs>>) {
+    let mut errors = vec![];
+    let mut namespaces = Namespaces {
+        scopes: vec![Scope { path: String::new(), parent: None, imports: vec![] }],
+        namespaces: HashSet::new(),
+        items: HashSet::new(),
+        segments: vec![],
+    };
+
+    for (file_index, tokens) in files.iter().enumerate() {
+        let scope = match tokens.first() {
+            Some(tkn) if file_index > 0 => {
+                let name = Path::new(&tkn.file_name)
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                namespaces.open_scope(0, &name)
+            },
+            _ => 0
+        };
+        namespaces.declare(tokens, scope, &mut errors);
+    }
+
+    for scope in 0..namespaces.scopes.len() {
+        for import in 0..namespaces.scopes[scope].imports.len() {
+            let segments = namespaces.scopes[scope].imports[import].segments.clone();
+            let names = segments.iter().map(|tkn| token_name(tkn)).collect::<Vec<&str>>();
+            let full_path = namespaces.lookup(scope, &names, false, |path|
+                namespaces.items.contains(path) || namespaces.namespaces.contains(path)
+            );
+            if full_path.is_none() {
+                errors.push(namespaces.unresolved_path(scope, &segments));
+            }
+            namespaces.scopes[scope].imports[import].full_path = full_path;
+        }
+    }
+
+    let mut output = vec![];
+    for (scope, tokens) in &namespaces.segments {
+        namespaces.rewrite(tokens, *scope, &mut output, &mut errors);
+    }
+
+    return (output, errors);
+}
+
+/// The namespace an item with the full path `name` is declared in, empty for the root.
+pub fn location_of(name: &str) -> String {
+    return name.rsplit_once("::").map(|(location, _)| location.to_string()).unwrap_or_default();
+}
+
+impl<'tkns> Namespaces<'tkns> {
+    fn open_scope(&mut self, parent: usize, name: &str) -> usize {
+        let path = join(&self.scopes[parent].path, name);
+        self.namespaces.insert(path.clone());
+        self.scopes.push(Scope { path, parent: Some(parent), imports: vec![] });
+        return self.scopes.len() - 1;
+    }
+
+    /// Walks the items of a file, opening a scope for every `namespace` block, collecting the
+    /// `use` items and declaring the name of every struct, enum, trait and function.
+    fn declare<'bumps, 'defs>(
+        &mut self,
+        tokens: &'tkns [Tkn],
+        file_scope: usize,
+        errors: &mut Vec<ParserError<'tkns, 'bumps, 'defs>>
+    ) {
+        // the namespace blocks we are inside of, with the brace that opened them
+        let mut blocks: Vec<(usize, &'tkns Tkn)> = vec![];
+        // how deep into the braces of an item we are
+        let mut depth: usize = 0;
+        let mut start = 0;
+        let mut index = 0;
+
+        while index < tokens.len() {
+            let scope = blocks.last().map(|(scope, _)| *scope).unwrap_or(file_scope);
+            if depth > 0 && tokens[index].token != TknType::EndOfFile {
+                match tokens[index].token {
+                    TknType::OpenCurlyBrace => depth += 1,
+                    TknType::CloseCurlyBrace => depth -= 1,
+                    _ => ()
+                }
+                index += 1;
+                continue;
+            }
+
+            match &tokens[index].token {
+                TknType::Keyword(Kwrd::Namespace) => {
+                    self.segments.push((scope, &tokens[start..index]));
+                    let mut peek = index + 1;
+                    let Some(TknType::Identifier(name)) = tokens.get(peek).map(|e| &e.token) else {
+                        errors.push(ParserError::ExpectedIdentifier { tkn: &tokens[peek] });
+                        index += 1;
+                        start = index;
+                        continue;
+                    };
+                    peek += 1;
+                    if tokens::expect_token(tokens, TknType::OpenCurlyBrace, &mut peek).is_none() {
+                        errors.push(ParserError::ExpectedToken {
+                            tkn: &tokens[peek],
+                            expected: TknType::OpenCurlyBrace
+                        });
+                        index = peek;
+                        start = index;
+                        continue;
+                    }
+                    let inner = self.open_scope(scope, name);
+                    blocks.push((inner, &tokens[peek - 1]));
+                    index = peek;
+                    start = index;
+                },
+                TknType::CloseCurlyBrace if !blocks.is_empty() => {
+                    self.segments.push((scope, &tokens[start..index]));
+                    blocks.pop();
+                    index += 1;
+                    start = index;
+                },
+                TknType::Keyword(Kwrd::Use) => {
+                    self.segments.push((scope, &tokens[start..index]));
+                    let mut peek = index + 1;
+                    let mut segments = vec![];
+                    loop {
+                        let Some(TknType::Identifier(_)) = tokens.get(peek).map(|e| &e.token) else {
+                            errors.push(ParserError::ExpectedIdentifier { tkn: &tokens[peek] });
+                            break;
+                        };
+                        segments.push(&tokens[peek]);
+                        peek += 1;
+                        if !tokens::is_expected_token(tokens, TknType::ColonColon, &mut peek) {
+                            break;
+                        }
+                    }
+                    if segments.is_empty() {
+                        index = peek;
+                    } else if tokens::expect_token(tokens, TknType::Semicolon, &mut peek).is_none() {
+                        errors.push(ParserError::ExpectedToken {
+                            tkn: &tokens[peek],
+                            expected: TknType::Semicolon
+                        });
+                        index = peek;
+                    } else {
+                        self.scopes[scope].imports.push(Import { segments, full_path: None });
+                        index = peek;
+                    }
+                    start = index;
+                },
+                TknType::Keyword(Kwrd::Struct)
+                | TknType::Keyword(Kwrd::Enum)
+                | TknType::Keyword(Kwrd::Trait)
+                | TknType::Keyword(Kwrd::Function) => {
+                    if let Some(TknType::Identifier(name)) = tokens.get(index + 1).map(|e| &e.token) {
+                        self.items.insert(join(&self.scopes[scope].path, name));
+                    }
+                    index += 1;
+                },
+                TknType::OpenCurlyBrace => {
+                    depth += 1;
+                    index += 1;
+                },
+                TknType::EndOfFile => {
+                    if let Some((_, open_brace)) = blocks.last() {
+                        errors.push(ParserError::ExpectedClosingBrace {
+                            tkn: &tokens[index],
+                            open_brace
+                        });
+                    }
+                    self.segments.push((scope, &tokens[start..=index]));
+                    return;
+                },
+                _ => index += 1
+            }
+        }
+        self.segments.push((file_scope, &tokens[start..]));
+    }
+
+    /// Copies `tokens` into `output`, marked with the namespace they are written in, joining every 
+    /// path that names an item into one identifier holding the full path of the item.  The names of 
+    /// fields, parameters and variants, which are written after `.`, `::` or before `:`, and the 
+    /// names of methods are left alone, while `Self` inside an `impl` block becomes the path of the 
+    /// type the block is for.
+    fn rewrite<'bumps, 'defs>(
+        &self,
+        tokens: &'tkns [Tkn],
+        scope: usize,
+        output: &mut Vec<Tkn>,
+        errors: &mut Vec<ParserError<'tkns, 'bumps, 'defs>>
+    ) {
+        let start = output.len();
+        // how deep into the braces of an item we are
+        let mut depth: usize = 0;
+        // the type of the `impl` block we are in, or whose header we are reading
+        let mut impl_type: Option<String> = None;
+        let mut in_impl_header = false;
+        let mut index = 0;
+        while index < tokens.len() {
+            let tkn = &tokens[index];
+            let after_separator = index > 0 && matches!(
+                tokens[index - 1].token,
+                TknType::Dot | TknType::ColonColon
+            );
+            // methods are named after the type they are implemented on once they are parsed
+            let method_name = depth > 0 && index > 0 && tokens[index - 1].token == TknType::Keyword(Kwrd::Function);
+            let TknType::Identifier(ident) = &tkn.token else {
+                match tkn.token {
+                    TknType::Keyword(Kwrd::Impl) if depth == 0 => {
+                        impl_type = None;
+                        in_impl_header = true;
+                    },
+                    TknType::OpenCurlyBrace => {
+                        in_impl_header = false;
+                        depth += 1;
+                    },
+                    TknType::CloseCurlyBrace => {
+                        depth = depth.saturating_sub(1);
+                        if depth == 0 {
+                            impl_type = None;
+                        }
+                    },
+                    _ => ()
+                }
+                output.push(tkn.clone());
+                index += 1;
+                continue;
+            };
+            if after_separator || method_name || tokens::is_token(tokens, TknType::Colon, index + 1) {
+                output.push(tkn.clone());
+                index += 1;
+                continue;
+            }
+            if ident == "Self" && depth > 0 && let Some(impl_type) = &impl_type {
+                let mut self_type = tkn.clone();
+                self_type.token = TknType::Identifier(impl_type.clone());
+                output.push(self_type);
+                index += 1;
+                continue;
+            }
+
+            let mut segments = vec![tkn];
+            let mut peek = index + 1;
+            while let Some(TknType::ColonColon) = tokens.get(peek).map(|e| &e.token)
+                && let Some(TknType::Identifier(_)) = tokens.get(peek + 1).map(|e| &e.token)
+            {
+                segments.push(&tokens[peek + 1]);
+                peek += 2;
+            }
+            let names = segments.iter().map(|tkn| token_name(tkn)).collect::<Vec<&str>>();
+
+            // the longest part of the path naming an item, leaving `Enum::Variant` to the parser
+            let resolved = (1..=names.len()).rev().find_map(|used|
+                self.lookup(scope, &names[..used], true, |path| self.items.contains(path))
+                    .map(|full_path| (full_path, used))
+            );
+
+            match resolved {
+                Some((full_path, used)) => {
+                    let last = segments[used - 1];
+                    let mut joined = Tkn::new(
+                        TknType::Identifier(full_path.clone()),
+                        tkn.file_name.clone(),
+                        tkn.line_index,
+                        tkn.line_number,
+                        tkn.span
+                    );
+                    joined.span.end = last.span.end;
+                    output.push(joined);
+                    // `impl Type` or `impl Trait for Type`, the type always comes last
+                    if in_impl_header {
+                        impl_type = Some(full_path);
+                    }
+                    index += used * 2 - 1;
+                },
+                None => {
+                    // a path naming a namespace itself is left for accessor lists to read
+                    let is_namespace = |segments: &[&str]| self.lookup(scope, segments, true, |path|
+                        self.namespaces.contains(path)
+                    ).is_some();
+                    if segments.len() > 1 && is_namespace(&names[..1]) && !is_namespace(&names) {
+                        errors.push(self.unresolved_path(scope, &segments));
+                    }
+                    output.push(tkn.clone());
+                    index += 1;
+                }
+            }
+        }
+
+        for tkn in &mut output[start..] {
+            tkn.namespace = self.scopes[scope].path.clone();
+        }
+    }
+
+    /// Finds the full path `segments` refers to from inside of `scope`, looking in the namespace
+    /// itself and then its imports before moving out to the namespace around it.
+    fn lookup(
+        &self,
+        scope: usize,
+        segments: &[&str],
+        with_imports: bool,
+        exists: impl Fn(&str) -> bool
+    ) -> Option<String> {
+        let (first, rest) = segments.split_first()?;
+        let mut current = Some(scope);
+        while let Some(scope) = current {
+            let Scope { path, parent, imports } = &self.scopes[scope];
+
+            let full_path = join(path, &segments.join("::"));
+            if exists(&full_path) {
+                return Some(full_path);
+            }
+
+            if with_imports {
+                for Import { segments: import, full_path } in imports {
+                    if let Some(full_path) = full_path
+                        && token_name(import.last().unwrap()) == *first
+                    {
+                        let full_path = rest.iter().fold(full_path.clone(), |path, name| join(&path, name));
+                        if exists(&full_path) {
+                            return Some(full_path);
+                        }
+                    }
+                }
+            }
+
+            current = *parent;
+        }
+        return None;
+    }
+
+    /// The error for a path that does not resolve, pointing at the first segment that could not be found.
+    fn unresolved_path<'bumps, 'defs>(
+        &self,
+        scope: usize,
+        segments: &[&'tkns Tkn]
+    ) -> ParserError<'tkns, 'bumps, 'defs> {
+        let names = segments.iter().map(|tkn| token_name(tkn)).collect::<Vec<&str>>();
+        let namespace = (1..names.len()).rev().find_map(|used|
+            self.lookup(scope, &names[..used], true, |path| self.namespaces.contains(path))
+                .map(|namespace| (namespace, used))
+        );
+        match namespace {
+            Some((namespace, used)) => return ParserError::NotInNamespace {
+                tkn: segments[used],
+                namespace
+            },
+            None => return ParserError::PathDoesNotExist { tkn: segments[0] }
+        }
+    }
+}
+
+fn join(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        return name.to_string();
+    }
+    return format!("{path}::{name}");
+}
+
+fn token_name(tkn: &Tkn) -> &str {
+    match &tkn.token {
+        TknType::Identifier(name) => return name,
+        _ => return ""
+    }
+}
@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use once_cell::sync::Lazy;
 
-use crate::lexer::token::{self, Op, TknType};
+use crate::lexer::token::{self, Kwrd, Op, TknType};
 
 use super::{expr::{ExprType, ExprTypeCons, ExpressionType}, ExprBump};
 
@@ -18,6 +18,8 @@ pub enum UnaryOperator {
     Borrow,
     BorrowInteriorMutable,
     BorrowMutable,
+    /// `*`, the value a reference points to
+    Deref,
 }
 
 impl UnOp {
@@ -111,6 +113,8 @@ pub enum BinaryOperator {
     BitwiseShiftLeft,
     /// >>
     BitwiseShiftRight,
+    /// `as`, where the right operand only carries the type the left one is converted to
+    Cast,
 }
 
 impl BinOp {
@@ -131,6 +135,23 @@ impl BinOp {
             (_, ET::Never, _) => return Some(ExprTypeCons::new(expr_bump, ET::Never)),
             (_, _, ET::Never) => return Some(ExprTypeCons::new(expr_bump, ET::Never)),
             
+            //Numeric, Char and Boolean Conversions
+            // every u8 is a valid char, while wider values could be a surrogate or past the last char
+            (BO::Cast, ET::U8 | ET::Char, ET::Char) => return Some(right.clone()),
+            (
+                BO::Cast,
+                ET::I8 | ET::I16 | ET::I32 | ET::I64 | ET::I128 | ET::AmbiguousNegInteger |
+                    ET::U8 | ET::U16 | ET::U32 | ET::U64 | ET::U128 | ET::AmbiguousPosInteger |
+                    ET::F32 | ET::F64 | ET::AmbiguousFloat | ET::Char | ET::Bool,
+                ET::I8 | ET::I16 | ET::I32 | ET::I64 | ET::I128 |
+                    ET::U8 | ET::U16 | ET::U32 | ET::U64 | ET::U128 |
+                    ET::F32 | ET::F64 | ET::Bool,
+            ) => {
+                // a literal being cast is converted from the type it would have on its own
+                left.default_literal_type(expr_bump);
+                return Some(right.clone());
+            },
+
             //Unambiguous Integer Operations
             (
                 BO::Plus | BO::Minus | BO::Exponent | BO::Multiply | 
@@ -292,11 +313,64 @@ impl BinOp {
                 ET::Bool, ET::Bool
             ) => return Some(left.clone()),
 
+            //Unambiguous Integer Ranges
+            (
+                BO::Range | BO::RangeEquals | BO::BangRange | BO::BangRangeEquals,
+                l @ (ET::I8 | ET::I16 | ET::I32 | ET::I64 | ET::I128 | 
+                    ET::U8 | ET::U16 | ET::U32 | ET::U64 | ET::U128 
+                ), 
+                r @ (ET::I8 | ET::I16 | ET::I32 | ET::I64 | ET::I128 | 
+                    ET::U8 | ET::U16 | ET::U32 | ET::U64 | ET::U128 
+                ),
+            ) if l == r => return Some(ExprTypeCons::new(expr_bump, self.range_type(l))),
+
+            //Potentially Ambiguous Integer Ranges
+            (
+                BO::Range | BO::RangeEquals | BO::BangRange | BO::BangRangeEquals,
+                ET::AmbiguousPosInteger | ET::AmbiguousNegInteger, 
+                ET::I8 | ET::I16 | ET::I32 | ET::I64 | ET::I128,
+            ) |
+            (
+                BO::Range | BO::RangeEquals | BO::BangRange | BO::BangRangeEquals,
+                ET::I8 | ET::I16 | ET::I32 | ET::I64 | ET::I128,
+                ET::AmbiguousPosInteger | ET::AmbiguousNegInteger, 
+            ) |
+            (
+                BO::Range | BO::RangeEquals | BO::BangRange | BO::BangRangeEquals,
+                ET::AmbiguousPosInteger, 
+                ET::U8 | ET::U16 | ET::U32 | ET::U64 | ET::U128,
+            ) |
+            (
+                BO::Range | BO::RangeEquals | BO::BangRange | BO::BangRangeEquals,
+                ET::U8 | ET::U16 | ET::U32 | ET::U64 | ET::U128,
+                ET::AmbiguousPosInteger, 
+            ) |
+            (
+                BO::Range | BO::RangeEquals | BO::BangRange | BO::BangRangeEquals,
+                ET::AmbiguousNegInteger | ET::AmbiguousPosInteger,
+                ET::AmbiguousNegInteger | ET::AmbiguousPosInteger
+            ) => {
+                let element_type = left.match_type(right)?.clone_inner();
+                return Some(ExprTypeCons::new(expr_bump, self.range_type(element_type)));
+            },
+
             _ => return None
         }
     }
     
+    /// The type of a range built by `self` out of two `expr_type`s.
+    pub fn range_type(self, expr_type: ExprType) -> ExprType {
+        return ExprType::Range {
+            exclusive_start: matches!(self, BinOp::BangRange | BinOp::BangRangeEquals),
+            inclusive_end: matches!(self, BinOp::RangeEquals | BinOp::BangRangeEquals),
+            expr_type: Box::new(expr_type)
+        };
+    }
+
     pub fn get_bin_op(operator: &TknType) -> BinOp {
+        if *operator == TknType::Keyword(Kwrd::As) {
+            return BinOp::Cast;
+        }
         let TknType::Operation(operator) = operator else {
             panic!("Invalid TokenType, expecting applicable Operation");
         };
@@ -462,6 +536,10 @@ pub static OPERATOR_INFO_MAP: Lazy<HashMap<TknType, (OpPrec, OpAssoc)>> =
                 TknType::Operation(token::Op::PlusPlus),
                 (OpPrec::Concatenation, OpAssoc::Left),
             ),
+            (
+                TknType::Keyword(Kwrd::As),
+                (OpPrec::Casting, OpAssoc::Left),
+            ),
         ])
     }
 );
@@ -486,8 +564,16 @@ pub enum OperatorPrecedence {
 }
 
 pub type OpAssoc = OperatorAssociativity;
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum OperatorAssociativity {
     Left,
     Right,
+}
+
+/// The precedence and associativity declared after `infix`, as in `infix 11 left`, where the 
+/// precedence is on the same scale as [`OperatorPrecedence`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Fixity {
+    pub precedence: u32,
+    pub associativity: OpAssoc
 }
\ No newline at end of file
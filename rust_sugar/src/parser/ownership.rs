@@ -0,0 +1,749 @@
+//! Move and borrow checking.
+//!
+//! A variable declared with `let oxy` owns its value, so using the value moves it out of the
+//! variable unless its type is `Copy`, and the variable cannot be used again until it is assigned
+//! a new value.  Moves are tracked per field, so moving one field out of a struct leaves the other
+//! fields usable.  A move in one branch of an `if` or `match` counts as a move after it, and a
+//! move in a loop body of a variable declared outside of the loop is a second move on the next
+//! iteration.
+//!
+//! A borrow of a variable, or of a part of one, lasts until the end of its statement, or when a
+//! variable holds the reference, until the end of the block that variable is declared in.  While
+//! it lasts, a `&mut` borrow allows no other use of what it borrows, `&` borrows allow reading it
+//! but nothing that writes to it or moves it, and `&im` borrows, which write through a shared
+//! reference, allow reading and writing it but no move or borrow of another kind.
+
+use std::fmt::Display;
+
+use crate::lexer::token::{Kwrd, Tkn, TknType};
+
+use super::{expr::{Expr, ExprData, ExprType, Lit}, functions::{self, CaptureMode, Fun}, operators::UnOp, patterns::{MatchArm, MatchPattern}, stmt::{StackLocation, Stmt, StmtData}, structs::Struct, ParserError};
+
+/// The kind of reference a borrow makes
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BorrowKind {
+    /// `&`
+    Shared,
+    /// `&mut`
+    Mutable,
+    /// `&im`
+    InteriorMutable
+}
+
+impl BorrowKind {
+    /// Whether `access` is allowed while a borrow of this kind lasts.
+    fn allows(self, access: Access) -> bool {
+        use BorrowKind as BK;
+
+        return matches!(
+            (self, access),
+            (BK::Shared, Access::Read | Access::Borrow(BK::Shared)) |
+            (BK::InteriorMutable, Access::Read | Access::Write | Access::Borrow(BK::InteriorMutable))
+        );
+    }
+}
+
+impl Display for BorrowKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return write!(f, "{}", match self {
+            BorrowKind::Shared => "shared",
+            BorrowKind::Mutable => "mutable",
+            BorrowKind::InteriorMutable => "interior mutable"
+        });
+    }
+}
+
+/// What a use of a variable does with it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Access {
+    Read,
+    Write,
+    Move,
+    Borrow(BorrowKind)
+}
+
+/// A variable in scope while checking, where `id` tells the declarations apart and `tracked` is
+/// whether moves out of it are tracked
+struct Variable {
+    name: String,
+    id: usize,
+    tracked: bool,
+    expr_type: ExprType
+}
+
+/// The value, or the part of it at `path`, that moved out of variable `id`
+#[derive(Clone)]
+struct Move<'tkns> {
+    id: usize,
+    path: Vec<String>,
+    tkn: &'tkns Tkn
+}
+
+/// A borrow of variable `id`, or of the part of it at `path`, which lasts until the scope at
+/// `depth` ends, or until the end of the statement while no variable holds it
+#[derive(Clone)]
+struct Borrow<'tkns> {
+    id: usize,
+    path: Vec<String>,
+    kind: BorrowKind,
+    tkn: &'tkns Tkn,
+    depth: Option<usize>,
+    /// The variables holding the reference
+    holders: Vec<usize>
+}
+
+/// The moves and the borrows lasting at one point of the function
+#[derive(Clone, Default)]
+struct State<'tkns> {
+    moves: Vec<Move<'tkns>>,
+    borrows: Vec<Borrow<'tkns>>
+}
+
+struct OwnershipChecker<'tkns, 'defs, 'bumps> {
+    tokens: &'tkns [Tkn],
+    structs: &'defs [Struct],
+    copy_types: &'defs [ExprType],
+    scopes: Vec<Vec<Variable>>,
+    next_id: usize,
+    /// The ids of the variables used so far, in order
+    used: Vec<usize>,
+    errors: Vec<ParserError<'tkns, 'bumps, 'defs>>
+}
+
+/// Checks the moves out of the `oxy` variables and the borrows in the body of `function`, where
+/// `body_tokens` are the tokens the body was parsed from and `copy_types` are the types with an
+/// `impl Copy`.
+pub fn check_function<'tkns, 'bumps, 'defs>(
+    function: &Fun<'tkns, 'bumps, 'defs>,
+    body_tokens: &'tkns [Tkn],
+    structs: &'defs [Struct],
+    copy_types: &'defs [ExprType]
+) -> Vec<ParserError<'tkns, 'bumps, 'defs>> {
+    let mut checker = OwnershipChecker {
+        tokens: functions::statement_tokens(body_tokens),
+        structs,
+        copy_types,
+        scopes: vec![vec![]],
+        next_id: 0,
+        used: vec![],
+        errors: vec![]
+    };
+    for param in function.left_args.iter().chain(function.right_args) {
+        if let Some(name) = &param.param_name {
+            checker.declare(name, false, param.param_type.clone());
+        }
+    }
+    checker.check_stmts(&function.body, &mut State::default(), false);
+    return checker.errors;
+}
+
+/// Whether values of `expr_type` are copied instead of moved, which they are unless they hold a
+/// struct or enum without an `impl Copy`, or a mutable reference.
+pub fn is_copy(expr_type: &ExprType, copy_types: &[ExprType]) -> bool {
+    use ExprType as ET;
+
+    return match expr_type {
+        ET::Custom { ident, .. } | ET::Enum { ident, .. } => copy_types.iter().any(|copy_type| matches!(
+            copy_type,
+            ET::Custom { ident: copy_ident, .. } | ET::Enum { ident: copy_ident, .. } if copy_ident == ident
+        )),
+        ET::MutRef { .. } => false,
+        ET::Array { expr_type, .. } => is_copy(expr_type, copy_types),
+        ET::Tuple { start, end } | ET::AmbiguousGroup { start, end } => start.iter()
+            .chain(end)
+            .all(|expr_type| is_copy(expr_type, copy_types)),
+        ET::AnonymousCustom { fields } => fields.iter().all(|(_, expr_type)| is_copy(expr_type, copy_types)),
+        _ => true
+    };
+}
+
+/// Whether values of `expr_type` can hold a reference, which keeps what it borrows borrowed for as
+/// long as the variable holding it is in scope.
+pub fn holds_reference(expr_type: &ExprType, structs: &[Struct]) -> bool {
+    use ExprType as ET;
+
+    return match expr_type {
+        ET::Ref { .. } | ET::MutRef { .. } | ET::ImRef { .. } => true,
+        // a closure holds on to the variables it borrows
+        ET::FunctionPass { .. } => true,
+        ET::Array { expr_type, .. } => holds_reference(expr_type, structs),
+        ET::Tuple { start, end } | ET::AmbiguousGroup { start, end } => start.iter()
+            .chain(end)
+            .any(|expr_type| holds_reference(expr_type, structs)),
+        ET::AnonymousCustom { fields } => fields.iter().any(|(_, expr_type)| holds_reference(expr_type, structs)),
+        ET::Custom { ident, type_args } => structs.iter()
+            .find(|custom_struct| custom_struct.name == *ident)
+            .is_some_and(|custom_struct| custom_struct.fields.iter().any(|field|
+                holds_reference(&custom_struct.field_type(field, type_args), structs)
+            )),
+        ET::Enum { variants, .. } => variants.iter()
+            .flat_map(|variant| variant.fields.iter())
+            .any(|(_, expr_type)| holds_reference(expr_type, structs)),
+        _ => false
+    };
+}
+
+/// Whether the part of a value at one path overlaps the part at the other, which it does when
+/// either path leads into the other.
+fn overlaps(l: &[String], r: &[String]) -> bool {
+    return l.iter().zip(r).all(|(l, r)| l == r);
+}
+
+/// The moves that may have happened, and the borrows that may last, once one of the ways through
+/// some code was taken.
+fn merge<'tkns>(outcomes: Vec<State<'tkns>>) -> State<'tkns> {
+    let mut merged = State::default();
+    for State { moves, borrows } in outcomes {
+        for moved in moves {
+            if !merged.moves.iter().any(|merged| merged.id == moved.id && merged.path == moved.path) {
+                merged.moves.push(moved);
+            }
+        }
+        for borrow in borrows {
+            if !merged.borrows.iter().any(|merged| core::ptr::eq(merged.tkn, borrow.tkn)) {
+                merged.borrows.push(borrow);
+            }
+        }
+    }
+    return merged;
+}
+
+impl<'tkns, 'defs, 'bumps> OwnershipChecker<'tkns, 'defs, 'bumps> {
+    /// Checks a block of statements in a scope of its own, returning whether it always returns.
+    /// When the block is `valued`, its last statement is the value of an expression, and the
+    /// borrows in it last for as long as that expression's statement does.
+    fn check_stmts(&mut self, stmts: &[&StmtData<'bumps, 'defs>], state: &mut State<'tkns>, valued: bool) -> bool {
+        self.scopes.push(vec![]);
+        let depth = self.scopes.len() - 1;
+        let mut returns = false;
+        for (i, StmtData { stmt, .. }) in stmts.iter().enumerate() {
+            let stmt_returns = self.check_stmt(stmt, state);
+            if !valued || i + 1 < stmts.len() {
+                state.borrows.retain(|borrow| borrow.depth.is_some());
+            }
+            if stmt_returns {
+                // the rest of the block never runs
+                returns = true;
+                break;
+            }
+        }
+        self.scopes.pop();
+        state.borrows.retain(|borrow| borrow.depth.is_none_or(|borrow_depth| borrow_depth < depth));
+        return returns;
+    }
+
+    /// Checks a statement, returning whether it always returns.
+    fn check_stmt(&mut self, stmt: &Stmt<'bumps, 'defs>, state: &mut State<'tkns>) -> bool {
+        match stmt {
+            Stmt::Compound(stmts) => return self.check_stmts(stmts, state, false),
+            Stmt::While { cond, body, .. } => self.check_loop(state, |checker, state| {
+                checker.check_expr(cond.expr_data, state);
+                checker.check_stmts(body, state, false);
+            }),
+            Stmt::Loop { body, assign, .. } => {
+                let used_start = self.used.len();
+                self.check_loop(state, |checker, state| {
+                    checker.check_stmts(body, state, false);
+                });
+                if let Some((name, name_index)) = assign {
+                    let tkn = self.tkn_at(*name_index);
+                    self.assign(name, tkn, used_start, state);
+                }
+            },
+            Stmt::For { binder, iter, body, .. } => {
+                self.check_expr(iter.expr_data, state);
+                self.check_loop(state, |checker, state| {
+                    checker.scopes.push(vec![]);
+                    if let Some((name, expr_type)) = binder {
+                        checker.declare(name, false, expr_type.borrow().clone());
+                    }
+                    checker.check_stmts(body, state, false);
+                    checker.scopes.pop();
+                });
+            },
+            Stmt::Conditional { conds, bodies } => {
+                let bodies = bodies.iter().map(|body| body.as_slice()).collect::<Vec<_>>();
+                return self.check_conditional(conds, &bodies, state, false);
+            },
+            Stmt::Match { scrutinee, arms } => return self.check_match(
+                scrutinee,
+                arms,
+                state,
+                |checker, body, state| checker.check_stmts(body, state, false)
+            ),
+            Stmt::Return(value, _) => {
+                if let Some(value) = value {
+                    self.check_expr(value.expr_data, state);
+                }
+                return true;
+            },
+            Stmt::Break { value, .. } => {
+                if let Some(value) = value {
+                    self.check_expr(value.expr_data, state);
+                }
+            },
+            Stmt::Continue { .. } => (),
+            Stmt::Declare(name, stack_location, expr_type) => {
+                let expr_type = expr_type.borrow().clone();
+                let tracked = *stack_location == StackLocation::Oxy && !is_copy(&expr_type, self.copy_types);
+                self.declare(name, tracked, expr_type);
+            },
+            Stmt::Assign { variable, assign } => {
+                let used_start = self.used.len();
+                self.check_expr(assign.expr_data, state);
+                match variable.expr_data {
+                    ExprData::Identifier(name, name_index) => {
+                        let tkn = self.tkn_at(*name_index);
+                        self.assign(name, tkn, used_start, state);
+                    },
+                    // a write through a reference only uses the reference
+                    _ => self.check_read(variable.expr_data, state)
+                }
+            },
+            Stmt::Expr(expr) => self.check_expr(expr.expr_data, state)
+        }
+        return false;
+    }
+
+    /// Checks a loop body twice, the second time with the moves and borrows the first time left
+    /// behind, so a move or borrow that the next iteration runs into is caught.  The loop may also
+    /// not run at all.
+    fn check_loop(
+        &mut self,
+        state: &mut State<'tkns>,
+        check_iteration: impl Fn(&mut Self, &mut State<'tkns>)
+    ) {
+        let mut iterated = state.clone();
+        check_iteration(self, &mut iterated);
+        check_iteration(self, &mut iterated);
+
+        *state = merge(vec![state.clone(), iterated]);
+    }
+
+    /// Checks the conditions and bodies of an `if` chain, where the conditions run one after
+    /// another until one holds.  Returns whether every body returns.
+    fn check_conditional(
+        &mut self,
+        conds: &[Expr<'bumps, 'defs>],
+        bodies: &[&[&StmtData<'bumps, 'defs>]],
+        state: &mut State<'tkns>,
+        valued: bool
+    ) -> bool {
+        let mut outcomes = vec![];
+        for (i, body) in bodies.iter().enumerate() {
+            if let Some(cond) = conds.get(i) {
+                self.check_expr(cond.expr_data, state);
+            }
+            let mut branch = state.clone();
+            if !self.check_stmts(body, &mut branch, valued) {
+                outcomes.push(branch);
+            }
+        }
+        // without an else, none of the bodies might run
+        if bodies.len() == conds.len() {
+            outcomes.push(state.clone());
+        }
+
+        if outcomes.is_empty() {
+            return true;
+        }
+        *state = merge(outcomes);
+        return false;
+    }
+
+    /// Checks a `match`, whose scrutinee moves into the variables the patterns bind.  Returns
+    /// whether every arm returns.
+    fn check_match<Body>(
+        &mut self,
+        scrutinee: &Expr<'bumps, 'defs>,
+        arms: &[MatchArm<'bumps, 'defs, Body>],
+        state: &mut State<'tkns>,
+        check_body: impl Fn(&mut Self, &Body, &mut State<'tkns>) -> bool
+    ) -> bool {
+        let mut bindings = vec![];
+        for arm in arms {
+            pattern_bindings(&arm.pattern, &mut bindings);
+        }
+        if bindings.is_empty() {
+            self.check_read(scrutinee.expr_data, state);
+        } else {
+            self.check_expr(scrutinee.expr_data, state);
+        }
+
+        let mut outcomes = vec![];
+        for MatchArm { pattern, guard, body } in arms {
+            let mut branch = state.clone();
+            self.scopes.push(vec![]);
+            let mut bindings = vec![];
+            pattern_bindings(pattern, &mut bindings);
+            for name in bindings {
+                self.declare(name, false, ExprType::AmbiguousType);
+            }
+            if let Some(guard) = guard {
+                self.check_expr(guard.expr_data, &mut branch);
+            }
+            if !check_body(self, body, &mut branch) {
+                outcomes.push(branch);
+            }
+            self.scopes.pop();
+        }
+
+        if outcomes.is_empty() {
+            return true;
+        }
+        *state = merge(outcomes);
+        return false;
+    }
+
+    /// Checks an expression whose value is used, moving it out of the variable it is read from.
+    fn check_expr(&mut self, expr_data: &ExprData<'bumps, 'defs>, state: &mut State<'tkns>) {
+        if self.use_place(expr_data, Access::Move, state) {
+            return;
+        }
+        if let Some(reference) = self.check_behind_reference(expr_data, state) {
+            // the value stays behind the reference, so using it copies it out
+            self.check_read(reference.expr_data, state);
+            if let Some(expr_type) = self.place_type(expr_data)
+                && !is_copy(&expr_type, self.copy_types)
+                && let Some((_, name_index)) = root_identifier(reference.expr_data)
+            {
+                let tkn = self.tkn_at(name_index);
+                if !self.reported(tkn) {
+                    self.errors.push(ParserError::MoveOutOfReference { tkn, expr_type });
+                }
+            }
+            return;
+        }
+
+        match expr_data {
+            ExprData::Identifier(..) | ExprData::Literal(_) => (),
+            ExprData::Custom { fields } => {
+                for value in fields.values() {
+                    self.check_expr(value, state);
+                }
+            },
+            ExprData::AnonymousCustom { fields } | ExprData::EnumVariant { fields, .. } => {
+                for (_, value) in fields {
+                    self.check_expr(value, state);
+                }
+            },
+            ExprData::CustomField { data, .. } | ExprData::AnonymousCustomField { data, .. } => {
+                self.check_expr(data.expr_data, state);
+            },
+            ExprData::Conditional { conds, bodies } => {
+                let bodies = bodies.iter().map(core::slice::from_ref).collect::<Vec<_>>();
+                self.check_conditional(conds, &bodies, state, true);
+            },
+            ExprData::Match { scrutinee, arms } => {
+                self.check_match(scrutinee, arms, state, |checker, body, state| {
+                    checker.check_expr(body.expr_data, state);
+                    return false;
+                });
+            },
+            ExprData::Function { left_args, right_args, .. } => {
+                for arg in left_args.iter().chain(right_args) {
+                    self.check_expr(arg.expr_data, state);
+                }
+            },
+            ExprData::Closure { captures, .. } => {
+                for (name, used_at, mode) in captures {
+                    let access = match mode {
+                        CaptureMode::Move => Access::Move,
+                        CaptureMode::Borrow(kind) => Access::Borrow(*kind)
+                    };
+                    self.use_place(&ExprData::Identifier(name.clone(), *used_at), access, state);
+                }
+            },
+            ExprData::Call { callee, left_args, right_args } | ExprData::Partial { callee, left_args, right_args } => {
+                for expr in std::iter::once(callee).chain(left_args).chain(right_args) {
+                    self.check_expr(expr.expr_data, state);
+                }
+            },
+            ExprData::BinaryOp(_, l, r) => {
+                self.check_expr(l.expr_data, state);
+                self.check_expr(r.expr_data, state);
+            },
+            ExprData::UnaryOp(op @ (UnOp::Borrow | UnOp::BorrowInteriorMutable | UnOp::BorrowMutable), expr) => {
+                let kind = match op {
+                    UnOp::Borrow => BorrowKind::Shared,
+                    UnOp::BorrowMutable => BorrowKind::Mutable,
+                    _ => BorrowKind::InteriorMutable
+                };
+                if !self.use_place(expr.expr_data, Access::Borrow(kind), state) {
+                    self.check_read(expr.expr_data, state);
+                }
+            },
+            ExprData::UnaryOp(_, expr) | ExprData::AmbiguousGroup(expr) => {
+                self.check_expr(expr.expr_data, state);
+            },
+            ExprData::Array(elements) | ExprData::Tuple(elements) => {
+                for element in elements {
+                    self.check_expr(element.expr_data, state);
+                }
+            },
+            ExprData::Index { expr, index } => {
+                self.check_expr(expr.expr_data, state);
+                self.check_expr(index.expr_data, state);
+            }
+        }
+    }
+
+    /// Checks an expression that is only looked at, like the value behind a borrow.
+    fn check_read(&mut self, expr_data: &ExprData<'bumps, 'defs>, state: &mut State<'tkns>) {
+        if self.use_place(expr_data, Access::Read, state) {
+            return;
+        }
+        match self.check_behind_reference(expr_data, state) {
+            Some(reference) => self.check_read(reference.expr_data, state),
+            None => self.check_expr(expr_data, state)
+        }
+    }
+
+    /// Checks a use of `expr_data` when it is a variable or a part of one, which has to still
+    /// hold its value and not be borrowed in a way that rules out the `access`.  Moving a value
+    /// that is not `Copy` out of an `oxy` variable moves it, while moving any other value only
+    /// reads it.  Returns whether `expr_data` is a place.
+    fn use_place(
+        &mut self,
+        expr_data: &ExprData<'bumps, 'defs>,
+        access: Access,
+        state: &mut State<'tkns>
+    ) -> bool {
+        let Some((name, name_index, path)) = self.place_of(expr_data, state) else {
+            return false;
+        };
+        let Some(&Variable { id, tracked, .. }) = self.lookup(&name) else {
+            return true;
+        };
+        self.used.push(id);
+
+        let copied = self.place_type(expr_data).is_some_and(|expr_type| is_copy(&expr_type, self.copy_types));
+        let access = match access {
+            Access::Move if copied || !tracked => Access::Read,
+            access => access
+        };
+
+        let tkn = self.tkn_at(name_index);
+        if let Some(moved) = state.moves.iter().find(|moved| moved.id == id && overlaps(&moved.path, &path)) {
+            // the second time through a loop runs into the same uses again
+            if !self.reported(tkn) {
+                let moved = moved.tkn;
+                self.errors.push(if access == Access::Move {
+                    ParserError::MovedTwice { tkn, moved }
+                } else {
+                    ParserError::UseAfterMove { tkn, moved }
+                });
+            }
+            return true;
+        }
+        self.check_borrows(id, &path, access, tkn, state);
+
+        match access {
+            Access::Move => state.moves.push(Move { id, path, tkn }),
+            Access::Borrow(kind) => state.borrows.push(Borrow { id, path, kind, tkn, depth: None, holders: vec![] }),
+            Access::Read | Access::Write => ()
+        }
+        return true;
+    }
+
+    /// Reports the `access` to variable `id`, or to the part of it at `path`, when a borrow of it
+    /// that still lasts does not allow it.
+    fn check_borrows(&mut self, id: usize, path: &[String], access: Access, tkn: &'tkns Tkn, state: &State<'tkns>) {
+        let conflict = state.borrows.iter().find(|borrow|
+            borrow.id == id && overlaps(&borrow.path, path) && !borrow.kind.allows(access)
+        );
+        if let Some(borrow) = conflict && !self.reported(tkn) {
+            self.errors.push(ParserError::BorrowConflict { tkn, access, borrowed: borrow.tkn, borrow: borrow.kind });
+        }
+    }
+
+    /// When `expr_data` is the value behind a reference, or a part of it, checks the indexes on
+    /// the way to it and returns the reference.
+    fn check_behind_reference<'e>(
+        &mut self,
+        expr_data: &'e ExprData<'bumps, 'defs>,
+        state: &mut State<'tkns>
+    ) -> Option<&'e Expr<'bumps, 'defs>> {
+        return match expr_data {
+            ExprData::UnaryOp(UnOp::Deref, reference) => Some(reference),
+            ExprData::CustomField { data, .. } | ExprData::AnonymousCustomField { data, .. } => {
+                self.check_behind_reference(data.expr_data, state)
+            },
+            ExprData::Index { expr, index } => {
+                let reference = self.check_behind_reference(expr.expr_data, state)?;
+                self.check_expr(index.expr_data, state);
+                Some(reference)
+            },
+            _ => None
+        };
+    }
+
+    /// The variable `expr_data` reads from, the index of the token its name is written at, and the fields, or elements 
+    /// at a literal index, it reads on the way.  An element at any other index could be any of them, so the path stops
+    /// at the array and the index is checked as an expression of its own.
+    fn place_of(
+        &mut self,
+        expr_data: &ExprData<'bumps, 'defs>,
+        state: &mut State<'tkns>
+    ) -> Option<(String, usize, Vec<String>)> {
+        return match expr_data {
+            ExprData::Identifier(name, name_index) => Some((name.clone(), *name_index, vec![])),
+            ExprData::CustomField { data, field } => {
+                let (name, name_index, mut path) = self.place_of(data.expr_data, state)?;
+                path.push(field.field_name.clone());
+                Some((name, name_index, path))
+            },
+            ExprData::AnonymousCustomField { data, field_name } => {
+                let (name, name_index, mut path) = self.place_of(data.expr_data, state)?;
+                path.push(field_name.clone());
+                Some((name, name_index, path))
+            },
+            ExprData::Index { expr, index } => {
+                let (name, name_index, mut path) = self.place_of(expr.expr_data, state)?;
+                match index.expr_data {
+                    ExprData::Literal(Lit::IntegerLiteral(index)) => path.push(index.to_string()),
+                    _ => self.check_expr(index.expr_data, state)
+                }
+                Some((name, name_index, path))
+            },
+            _ => None
+        };
+    }
+
+    /// The type of the variable or part of one that `expr_data` reads, if it can be told.
+    fn place_type(&self, expr_data: &ExprData<'bumps, 'defs>) -> Option<ExprType> {
+        return match expr_data {
+            ExprData::Identifier(name, _) => self.lookup(name).map(|variable| variable.expr_type.clone()),
+            ExprData::CustomField { data, field } => match &*data.expr_type.get() {
+                ExprType::Custom { ident, type_args } => self.structs.iter()
+                    .find(|custom_struct| custom_struct.name == *ident)
+                    .map(|custom_struct| custom_struct.field_type(field, type_args)),
+                _ => None
+            },
+            ExprData::AnonymousCustomField { data, field_name } => match &*data.expr_type.get() {
+                ExprType::AnonymousCustom { fields } => fields.iter()
+                    .find(|(name, _)| name == field_name)
+                    .map(|(_, expr_type)| expr_type.clone()),
+                _ => None
+            },
+            ExprData::Index { expr, index } => match (&*expr.expr_type.get(), index.expr_data) {
+                (ExprType::Array { expr_type, .. }, _) => Some((**expr_type).clone()),
+                (ExprType::Tuple { start, .. }, ExprData::Literal(Lit::IntegerLiteral(index))) => {
+                    usize::try_from(*index).ok().and_then(|index| start.get(index)).cloned()
+                },
+                _ => None
+            },
+            ExprData::UnaryOp(UnOp::Deref, reference) => match &*reference.expr_type.get() {
+                ExprType::Ref { expr_type: pointee, .. } | 
+                ExprType::MutRef { expr_type: pointee, .. } | 
+                ExprType::ImRef { expr_type: pointee, .. } => Some((**pointee).clone()),
+                _ => None
+            },
+            _ => None
+        };
+    }
+
+    fn declare(&mut self, name: &str, tracked: bool, expr_type: ExprType) {
+        self.next_id += 1;
+        let id = self.next_id;
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.push(Variable { name: name.to_string(), id, tracked, expr_type });
+        }
+    }
+
+    /// Assigning a variable a new value at `tkn` makes all of it usable again.  When the value holds 
+    /// a reference, the variable holds the borrows made since `used_start`, and the borrows held by
+    /// the variables used since then.
+    fn assign(&mut self, name: &str, tkn: &'tkns Tkn, used_start: usize, state: &mut State<'tkns>) {
+        let Some((depth, &Variable { id, tracked, ref expr_type, .. })) = self.lookup_scoped(name) else {
+            return;
+        };
+        let holds = holds_reference(expr_type, self.structs);
+
+        if state.borrows.iter().any(|borrow| borrow.id == id) {
+            self.check_borrows(id, &[], Access::Write, tkn, state);
+        }
+        if holds {
+            let used = &self.used[used_start..];
+            for borrow in &mut state.borrows {
+                if borrow.depth.is_none() || borrow.holders.iter().any(|holder| used.contains(holder)) {
+                    borrow.holders.push(id);
+                    borrow.depth = Some(borrow.depth.map_or(depth, |borrow_depth| borrow_depth.min(depth)));
+                }
+            }
+        }
+        if tracked {
+            state.moves.retain(|moved| moved.id != id);
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Variable> {
+        return self.lookup_scoped(name).map(|(_, variable)| variable);
+    }
+
+    /// The variable `name` refers to along with the depth of the scope it is declared in.
+    fn lookup_scoped(&self, name: &str) -> Option<(usize, &Variable)> {
+        return self.scopes.iter()
+            .enumerate()
+            .rev()
+            .flat_map(|(depth, scope)| scope.iter().rev().map(move |variable| (depth, variable)))
+            .find(|(_, variable)| variable.name == name);
+    }
+
+    /// Whether an error was already reported at `tkn`.
+    fn reported(&self, tkn: &Tkn) -> bool {
+        return self.errors.iter().any(|error| matches!(
+            error,
+            ParserError::UseAfterMove { tkn: reported, .. } |
+            ParserError::MovedTwice { tkn: reported, .. } |
+            ParserError::BorrowConflict { tkn: reported, .. } |
+            ParserError::MoveOutOfReference { tkn: reported, .. }
+                if core::ptr::eq(*reported, tkn)
+        ));
+    }
+
+    /// The token at `index` in the body, which is where the name of a variable is written.
+    fn tkn_at(&self, index: usize) -> &'tkns Tkn {
+        return &self.tokens[index];
+    }
+}
+
+/// Whether the identifier at `i` is the name of a `let`, `let oxy` or `let mut`, rather than a
+/// variable borrowed with `&mut`.
+pub fn is_declared_at(tokens: &[Tkn], i: usize) -> bool {
+    let before = |offset: usize| i.checked_sub(offset).map(|i| &tokens[i].token);
+    return match before(1) {
+        Some(TknType::Keyword(Kwrd::Let | Kwrd::Oxidize)) => true,
+        Some(TknType::Keyword(Kwrd::Mutable)) => before(2).is_none_or(|tkn| *tkn != TknType::Borrow),
+        _ => false
+    };
+}
+
+/// The name of the variable `expr_data` is a part of and the index of the token the name is 
+/// written at, if it is one.
+pub fn root_identifier<'e>(expr_data: &'e ExprData) -> Option<(&'e str, usize)> {
+    return match expr_data {
+        ExprData::Identifier(name, name_index) => Some((name, *name_index)),
+        ExprData::CustomField { data, .. } | ExprData::AnonymousCustomField { data, .. } => root_identifier(data.expr_data),
+        ExprData::Index { expr, .. } => root_identifier(expr.expr_data),
+        _ => None
+    };
+}
+
+/// The names of the variables `pattern` binds.
+pub fn pattern_bindings<'p>(pattern: &'p MatchPattern, bindings: &mut Vec<&'p str>) {
+    match pattern {
+        MatchPattern::Bind(name) => bindings.push(name),
+        MatchPattern::Group { start, end } => {
+            for pattern in start.iter().chain(end) {
+                pattern_bindings(pattern, bindings);
+            }
+        },
+        MatchPattern::Fields(fields) | MatchPattern::Variant { fields, .. } => {
+            for (_, pattern) in fields {
+                pattern_bindings(pattern, bindings);
+            }
+        },
+        MatchPattern::Discard | MatchPattern::Literal(_) | MatchPattern::Range { .. } => ()
+    }
+}
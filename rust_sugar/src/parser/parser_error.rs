@@ -1,6 +1,6 @@
 use crate::{lexer::token::{Tkn, TknType}, term};
 
-use super::{expr::{Expr, ExprType}, functions::{FnType, FullFunctionDefinition}, patterns::Pattern, structs::{Field, Struct}};
+use super::{accessors::Accessibility, enums::{Enum, Variant, VariantShape}, expr::{Expr, ExprType}, functions::{FnType, FullFunctionDefinition}, ownership::{Access, BorrowKind}, patterns::Pattern, structs::{Field, Struct}, traits::{Trait, TraitMethod}};
 
 type TknTypeFromTkn = fn(&Tkn) -> &TknType;
 
@@ -29,6 +29,14 @@ pub enum ParserError<'tkns, 'bumps, 'defs> {
         calculated_type: ExprType, 
         expected_type: ExprType 
     },
+    ExpectedIterable { tkn: &'tkns Tkn, calculated_type: ExprType },
+    /// A `break` or `continue` that is not inside of a loop
+    NotInLoop { tkn: &'tkns Tkn },
+    UndeclaredLabel { tkn: &'tkns Tkn },
+    /// A `break` carrying a value out of a loop that does not produce one
+    BreakValueNotUsed { tkn: &'tkns Tkn, loop_tkn: &'tkns Tkn },
+    /// A `break` without a value out of a loop whose value is assigned
+    BreakValueMissing { tkn: &'tkns Tkn, loop_tkn: &'tkns Tkn },
     SecondDiscardMany { tkn: &'tkns Tkn, first_discard_many: &'tkns Tkn },
     InvalidPattern { tkn: &'tkns Tkn },
     PatternNotMatchExpectedType { 
@@ -36,6 +44,17 @@ pub enum ParserError<'tkns, 'bumps, 'defs> {
         pattern: Pattern<'tkns>, 
         expected_type: ExprType 
     },
+    /// A range pattern like `5..5` that no value can match
+    EmptyRangePattern { tkn: &'tkns Tkn },
+    /// A struct pattern without `..` that leaves out one of the fields
+    MissingFieldPattern { tkn: &'tkns Tkn, field_name: String },
+    AlreadyBoundIdentifier { tkn: &'tkns Tkn, bound: &'tkns Tkn },
+    /// A variant pattern whose payload is not written like the variant is declared
+    VariantPatternMismatch { tkn: &'tkns Tkn, custom_enum: &'defs Enum, variant: &'defs Variant },
+    /// A `match` whose arms do not cover `missing`
+    NonExhaustiveMatch { tkn: &'tkns Tkn, missing: String },
+    /// A `match` arm that only matches values the arms before it already match
+    UnreachableArm { tkn: &'tkns Tkn },
     VariableDoesNotExist { tkn: &'tkns Tkn },
     InvalidMut { tkn: &'tkns Tkn },
     //TODO add token to variable initialization for better error reporting
@@ -44,13 +63,24 @@ pub enum ParserError<'tkns, 'bumps, 'defs> {
         tkn: &'tkns Tkn, 
         args: Box<[ExprType]>, 
         expected_args: Box<[ExprType]>, 
-        function: FullFunctionDefinition<'tkns, 'bumps, 'defs>
+        /// The name of the function, or of the variable holding the function value that was called
+        function: String
     },
-    /// When a struct/accessor/function could not be parsed
+    /// When a struct/enum/accessor/function could not be parsed
     InvalidBlock { tkn: &'tkns Tkn },
     MultipleExpressions { tkn: &'tkns Tkn, expr: Box<[Expr<'bumps, 'defs>]>},
     FieldDoesNotExist { tkn: &'tkns Tkn, custom_struct: &'defs Struct },
     AlreadyDefinedField { tkn: &'tkns Tkn, defined_field: &'tkns Tkn },
+    AlreadyDefinedVariant { tkn: &'tkns Tkn, defined_variant: &'tkns Tkn },
+    VariantDoesNotExist { tkn: &'tkns Tkn, custom_enum: &'defs Enum },
+    VariantFieldDoesNotExist { tkn: &'tkns Tkn, custom_enum: &'defs Enum, variant: &'defs Variant },
+    /// A struct-like variant constructed without one of its fields
+    MissingVariantField { 
+        tkn: &'tkns Tkn, 
+        custom_enum: &'defs Enum, 
+        variant: &'defs Variant, 
+        field_name: &'defs str 
+    },
     FieldExpressionNotDefined { tkn: &'tkns Tkn, field: &'defs Field },
     InvalidExpressionAtom { tkn: &'tkns Tkn },
     InvalidDollarExpression { tkn: &'tkns Tkn },
@@ -63,11 +93,76 @@ pub enum ParserError<'tkns, 'bumps, 'defs> {
         fix_type: FnType 
     },
     ConflictingFunctionFixDefinitions { tkn: &'tkns Tkn, fix_defined: &'tkns Tkn },
-    ExpectedEndOfFunctionDefinition { tkn: &'tkns Tkn }
+    ExpectedEndOfFunctionDefinition { tkn: &'tkns Tkn },
+    /// A path whose first segment is not a namespace or item around where it is written
+    PathDoesNotExist { tkn: &'tkns Tkn },
+    /// A path naming something `namespace` does not have
+    NotInNamespace { tkn: &'tkns Tkn, namespace: String },
+    /// A field used from somewhere its accessibility does not allow
+    FieldNotAccessible { tkn: &'tkns Tkn, custom_struct: &'defs Struct, field: &'defs Field },
+    /// A function called from somewhere its accessibility does not allow
+    FunctionNotAccessible { tkn: &'tkns Tkn, function: FullFunctionDefinition<'tkns, 'bumps, 'defs> },
+    /// `impl Name for Type` where no trait is called `Name`
+    TraitNotDefined { tkn: &'tkns Tkn },
+    /// A method in an `impl` of a trait which the trait does not declare
+    NotTraitMethod { tkn: &'tkns Tkn, custom_trait: &'defs Trait },
+    /// An `impl` of a trait leaving out one of the trait's methods
+    MissingTraitMethod { tkn: &'tkns Tkn, custom_trait: &'defs Trait, method: &'defs TraitMethod },
+    /// A method in an `impl` of a trait with other arguments or return type than the trait declares
+    TraitMethodMismatch { 
+        tkn: &'tkns Tkn, 
+        custom_trait: &'defs Trait, 
+        method: &'defs TraitMethod, 
+        self_type: ExprType 
+    },
+    /// A call to a generic function whose arguments leave one of its type parameters unknown
+    CouldNotInferTypeParameter { tkn: &'tkns Tkn, type_param: String },
+    /// A use of an `oxy` variable after its value, or the part of it that is used, moved out
+    UseAfterMove { tkn: &'tkns Tkn, moved: &'tkns Tkn },
+    /// A move out of an `oxy` variable after its value, or the part of it that moves, already moved out
+    MovedTwice { tkn: &'tkns Tkn, moved: &'tkns Tkn },
+    /// A use of a variable, or a part of one, that a borrow of it still in scope does not allow
+    BorrowConflict { tkn: &'tkns Tkn, access: Access, borrowed: &'tkns Tkn, borrow: BorrowKind },
+    /// `&mut` or `&im` of a variable that is not declared `mut`
+    BorrowMutOfImmutable { tkn: &'tkns Tkn, variable_def: &'tkns Tkn },
+    /// A write, or a `&mut` or `&im` borrow, through a `&` reference
+    MutateThroughSharedRef { tkn: &'tkns Tkn, expr_type: ExprType },
+    /// `*` in front of a value that is not a reference
+    InvalidDeref { tkn: &'tkns Tkn, expr_type: ExprType },
+    /// A value that is not `Copy` used from behind a reference, which would move it out
+    MoveOutOfReference { tkn: &'tkns Tkn, expr_type: ExprType },
+    /// An `as` between types that are not both integers, floats, chars or bools
+    InvalidCast { tkn: &'tkns Tkn, from: ExprType, to: ExprType },
+    /// A precedence after `infix` outside of the scale the built in operators use
+    InvalidPrecedence { tkn: &'tkns Tkn },
+    /// A custom operator defined without exactly one argument on each side or without a precedence
+    InvalidCustomOperator { tkn: &'tkns Tkn },
+    /// A precedence declared on an infix function without exactly one argument on each side
+    FixityWithoutTwoOperands { tkn: &'tkns Tkn },
+    /// A default on an argument that is not one of the trailing arguments on the right of a function
+    MisplacedDefaultArgument { tkn: &'tkns Tkn },
+    /// A default on an argument of a closure, whose calls go through function values
+    DefaultInClosure { tkn: &'tkns Tkn },
+    /// A custom operator defined outside of an `impl`, leaving it without a type to be looked up on
+    OperatorOutsideImpl { tkn: &'tkns Tkn },
+    /// A custom operator used on a type whose `impl`s do not define it
+    OperatorNotDefined { tkn: &'tkns Tkn, expr_type: ExprType },
+    /// A lifetime in a type that the function or struct the type is written in does not declare
+    UndeclaredLifetime { tkn: &'tkns Tkn },
+    /// A returned reference with an elided lifetime, where the arguments leave what it borrows from unclear
+    MissingLifetime { tkn: &'tkns Tkn },
+    /// A returned reference to a variable of the function, which does not outlive the call
+    ReturnsLocalReference { tkn: &'tkns Tkn, borrowed: &'tkns Tkn },
+    /// A returned reference to a temporary value, which does not outlive the call
+    ReturnsTemporaryReference { tkn: &'tkns Tkn, borrowed: &'tkns Tkn },
+    /// A returned reference into an argument without the lifetime of the return type
+    LifetimeMismatch { tkn: &'tkns Tkn, param: &'tkns Tkn, return_type: ExprType },
+    /// A returned closure that borrows a variable of the function returning it
+    ReturnsBorrowingClosure { tkn: &'tkns Tkn, captured: &'tkns Tkn },
 }
 
 impl<'tkns, 'exprs, 'defs> ParserError<'tkns, 'exprs, 'defs> {
-    pub fn write(&self, f: &mut impl std::io::Write, src: &str) -> std::io::Result<()> {
+    pub fn write(&self, f: &mut impl std::io::Write, sources: &Sources) -> std::io::Result<()> {
         use ParserError as PE;
         match self {
             PE::ExpectedToken { 
@@ -75,7 +170,7 @@ impl<'tkns, 'exprs, 'defs> ParserError<'tkns, 'exprs, 'defs> {
                     token, 
                     file_name, 
                     line_index, 
-                    line_number 
+                    line_number, .. 
                 }, 
                 expected 
             } => write!(f, 
@@ -88,7 +183,7 @@ impl<'tkns, 'exprs, 'defs> ParserError<'tkns, 'exprs, 'defs> {
                 \n\
                 ",
                 pad = " ".repeat(line_number.to_string().len()),
-                line_of_code = get_line_from_contents(*line_number, src).trim_end(),
+                line_of_code = sources.get_line(file_name, *line_number).trim_end(),
                 arrow_pad = " ".repeat(*line_index - 1),
                 arrow = "^".repeat(token.len()),
 
@@ -102,7 +197,7 @@ impl<'tkns, 'exprs, 'defs> ParserError<'tkns, 'exprs, 'defs> {
                     token,
                     file_name,
                     line_index,
-                    line_number,
+                    line_number, ..
                 }, 
                 received, 
                 expected 
@@ -118,7 +213,7 @@ impl<'tkns, 'exprs, 'defs> ParserError<'tkns, 'exprs, 'defs> {
                 expected = slice_to_string(expected),
                 received = iter_to_string(&received.clone().take(expected.len())),
                 pad = " ".repeat(line_number.to_string().len()),
-                line_of_code = get_line_from_contents(*line_number, src).trim_end(),
+                line_of_code = sources.get_line(file_name, *line_number).trim_end(),
                 arrow_pad = " ".repeat(*line_index - 1),
                 arrow = "^".repeat(token.len()),
 
@@ -129,12 +224,12 @@ impl<'tkns, 'exprs, 'defs> ParserError<'tkns, 'exprs, 'defs> {
             ),
             PE::AlreadyDefinedWhitelist { 
                 tkn: Tkn { 
-                    token, file_name, line_index, line_number 
+                    token, file_name, line_index, line_number, .. 
                 }, 
                 defined_whitelist: defined 
             } | PE::AlreadyDefinedBlacklist { 
                 tkn: Tkn { 
-                    token, file_name, line_index, line_number 
+                    token, file_name, line_index, line_number, .. 
                 }, 
                 defined_blacklist: defined 
             } => if *line_number == defined.line_number {
@@ -156,7 +251,7 @@ impl<'tkns, 'exprs, 'defs> ParserError<'tkns, 'exprs, 'defs> {
                     \n\
                     ",
                     pad = " ".repeat(line_number.to_string().len()),
-                    line_of_code = get_line_from_contents(*line_number, src).trim_end(),
+                    line_of_code = sources.get_line(file_name, *line_number).trim_end(),
                     line_pad = " ".repeat(line_pad_len),
                     line = "-".repeat(line_len),
                     arrow_pad = " ".repeat(arrow_pad_len),
@@ -187,8 +282,8 @@ impl<'tkns, 'exprs, 'defs> ParserError<'tkns, 'exprs, 'defs> {
                     pad = " ".repeat(line_number.to_string().len()),
                     line_number_1 = defined.line_number,
                     line_number_2 = *line_number,
-                    line_of_code_1 = get_line_from_contents(defined.line_number, src).trim_end(),
-                    line_of_code_2 = get_line_from_contents(*line_number, src).trim_end(),
+                    line_of_code_1 = sources.get_line(&defined.file_name, defined.line_number).trim_end(),
+                    line_of_code_2 = sources.get_line(file_name, *line_number).trim_end(),
                     line_pad = " ".repeat(defined.line_index - 1),
                     arrow_pad = " ".repeat(*line_index - 1),
                     line = "-".repeat(defined.token.len()),
@@ -204,7 +299,7 @@ impl<'tkns, 'exprs, 'defs> ParserError<'tkns, 'exprs, 'defs> {
                     token,
                     file_name,
                     line_index,
-                    line_number,
+                    line_number, ..
                 }, open_brace
             } => if *line_number == open_brace.line_number {
                 let line_pad_len = open_brace.line_index - 1;
@@ -225,7 +320,7 @@ impl<'tkns, 'exprs, 'defs> ParserError<'tkns, 'exprs, 'defs> {
                     \n\
                     ",
                     pad = " ".repeat(line_number.to_string().len()),
-                    line_of_code = get_line_from_contents(*line_number, src).trim_end(),
+                    line_of_code = sources.get_line(file_name, *line_number).trim_end(),
                     line_pad = " ".repeat(line_pad_len),
                     line = "-".repeat(line_len),
                     arrow_pad = " ".repeat(arrow_pad_len),
@@ -256,8 +351,8 @@ impl<'tkns, 'exprs, 'defs> ParserError<'tkns, 'exprs, 'defs> {
                     pad = " ".repeat(line_number.to_string().len()),
                     line_number_1 = open_brace.line_number,
                     line_number_2 = *line_number,
-                    line_of_code_1 = get_line_from_contents(open_brace.line_number, src).trim_end(),
-                    line_of_code_2 = get_line_from_contents(*line_number, src).trim_end(),
+                    line_of_code_1 = sources.get_line(&open_brace.file_name, open_brace.line_number).trim_end(),
+                    line_of_code_2 = sources.get_line(file_name, *line_number).trim_end(),
                     line_pad = " ".repeat(open_brace.line_index - 1),
                     arrow_pad = " ".repeat(*line_index - 1),
                     line = "-".repeat(open_brace.token.len()),
@@ -268,15 +363,27 @@ impl<'tkns, 'exprs, 'defs> ParserError<'tkns, 'exprs, 'defs> {
                     clear = term::CLEAR_ANSI
                 )
             },
-            PE::ExpectedEndOfWhitelist { tkn } => todo!("error {:?} at token {tkn:?}", self),
-            PE::ExpectedEndOfBlacklist { tkn } => todo!("error {:?} at token {tkn:?}", self),
-            PE::NoWhitelistOrBlacklist { tkn } => todo!("error {:?} at token {tkn:?}", self),
+            PE::ExpectedEndOfWhitelist { tkn } => write_token_error(f, sources, tkn,
+                format_args!("Expected an accessor name or the end of the whitelist but received {}", tkn.token),
+                "not an accessor name",
+                Some("an enclave lists accessor names separated by commas, wrapped in '{ }' or after '$'")
+            ),
+            PE::ExpectedEndOfBlacklist { tkn } => write_token_error(f, sources, tkn,
+                format_args!("Expected an accessor name or the end of the blacklist but received {}", tkn.token),
+                "not an accessor name",
+                Some("an exclave lists accessor names separated by commas, wrapped in '{ }' or after '$'")
+            ),
+            PE::NoWhitelistOrBlacklist { tkn } => write_token_error(f, sources, tkn,
+                format_args!("Expected enclave or exclave in accessor definition but received {}", tkn.token),
+                "expected enclave or exclave here",
+                Some("an accessor body is made of an enclave (whitelist) and/or an exclave (blacklist)")
+            ),
             PE::MissingAccessor { 
                 tkn: Tkn {
                     token,
                     file_name,
                     line_index,
-                    line_number,
+                    line_number, ..
                 } 
             } => write!(f, 
                 "{red}error:{clear} Expected accessor like pub, prv, pkg, or a custom defined one but received {token}\n\
@@ -288,7 +395,7 @@ impl<'tkns, 'exprs, 'defs> ParserError<'tkns, 'exprs, 'defs> {
                 \n\
                 ",
                 pad = " ".repeat(line_number.to_string().len()),
-                line_of_code = get_line_from_contents(*line_number, src).trim_end(),
+                line_of_code = sources.get_line(file_name, *line_number).trim_end(),
                 arrow_pad = " ".repeat(*line_index - 1),
                 arrow = "^".repeat(token.len()),
 
@@ -297,27 +404,39 @@ impl<'tkns, 'exprs, 'defs> ParserError<'tkns, 'exprs, 'defs> {
                 blue = term::HIGH_BOLD_BLUE_ANSI,
                 clear = term::CLEAR_ANSI,
             ),
-            PE::ExpectedEndOfStruct { tkn } => todo!("error {:?} at token {tkn:?}", self),
-            PE::ExpectedIdentifier { tkn } => todo!("error {:?} at token {tkn:?}", self),
-            PE::ExpectedType { tkn } => todo!("error {:?} at token {tkn:?}", self),
+            PE::ExpectedEndOfStruct { tkn } => write_token_error(f, sources, tkn,
+                format_args!("Expected comma ',' or the end of the struct after {}", tkn.token),
+                "expected ',' or '}' after this",
+                Some("struct fields are separated by commas")
+            ),
+            PE::ExpectedIdentifier { tkn } => write_token_error(f, sources, tkn,
+                format_args!("Expected identifier but received {}", tkn.token),
+                "expected a name here",
+                None
+            ),
+            PE::ExpectedType { tkn } => write_token_error(f, sources, tkn,
+                format_args!("Expected type but received {}", tkn.token),
+                "expected a type here",
+                Some("types are primitives like i32, bool, char, a struct or enum name, or an anonymous struct like { value: i32 }")
+            ),
             PE::InvalidStatement { 
                 tkn: Tkn {
                     token,
                     file_name,
                     line_index,
-                    line_number,
+                    line_number, ..
                 }
             } => write!(f, 
-                "{red}error:{clear} Expected beginning of accessor, struct, or function definition but received {token}\n\
+                "{red}error:{clear} Expected statement but received {token}\n\
                 {blue}{pad}-->{clear} {file_name}:{line_number}:{line_index}\n\
                 {blue}{pad} | {clear}\n\
                 {blue}{line_number} | {clear}{line_of_code}\n\
                 {blue}{pad} | {clear}{arrow_pad}{red}{arrow}{clear}\n\
-                {blue}{pad}:::{clear} note = support for other constructs such as const declarations, traits, directives, etc. will be implemented in the future\n\
+                {blue}{pad}:::{clear} help = statements are blocks, let declarations, assignments, if, loops, break, continue, return, or expressions ending in ';'\n\
                 \n\
                 ",
                 pad = " ".repeat(line_number.to_string().len()),
-                line_of_code = get_line_from_contents(*line_number, src).trim_end(),
+                line_of_code = sources.get_line(file_name, *line_number).trim_end(),
                 arrow_pad = " ".repeat(*line_index - 1),
                 arrow = "^".repeat(token.len()),
 
@@ -340,7 +459,7 @@ impl<'tkns, 'exprs, 'defs> ParserError<'tkns, 'exprs, 'defs> {
                         token: _,
                         file_name,
                         line_index,
-                        line_number,
+                        line_number, ..
                     } = first_tkn;
                     
                     write!(f, 
@@ -353,7 +472,7 @@ impl<'tkns, 'exprs, 'defs> ParserError<'tkns, 'exprs, 'defs> {
                         \n\
                         ",
                         pad = " ".repeat(line_number.to_string().len()),
-                        line_of_code = get_line_from_contents(*line_number, src).trim_end(),
+                        line_of_code = sources.get_line(file_name, *line_number).trim_end(),
                         arrow_pad = " ".repeat(*line_index - 1),
                         arrow = "^".repeat(total_expr_len),
         
@@ -368,7 +487,7 @@ impl<'tkns, 'exprs, 'defs> ParserError<'tkns, 'exprs, 'defs> {
                         println!("line number: {line_number}");
                         lines += format!(
                             "{blue}{line_number} | {clear}{line_of_code}\n",
-                            line_of_code = get_line_from_contents(line_number, src),
+                            line_of_code = sources.get_line(&first_tkn.file_name, line_number),
                             blue = term::HIGH_BOLD_BLUE_ANSI,
                             clear = term::CLEAR_ANSI,
                         ).as_str();
@@ -378,7 +497,7 @@ impl<'tkns, 'exprs, 'defs> ParserError<'tkns, 'exprs, 'defs> {
                         token: _,
                         file_name,
                         line_index,
-                        line_number,
+                        line_number, ..
                     } = first_tkn;
                     
                     write!(f, 
@@ -406,7 +525,7 @@ impl<'tkns, 'exprs, 'defs> ParserError<'tkns, 'exprs, 'defs> {
                         token: _,
                         file_name,
                         line_index,
-                        line_number,
+                        line_number, ..
                     } = first_tkn;
 
                     let pad_amount = first_tkn.line_number.to_string().len()
@@ -433,8 +552,8 @@ impl<'tkns, 'exprs, 'defs> ParserError<'tkns, 'exprs, 'defs> {
                         second_pad = " ".repeat(last_tkn.line_index - 1),
                         line_number_1 = first_tkn.line_number,
                         line_number_2 = last_tkn.line_number,
-                        line_of_code_1 = get_line_from_contents(first_tkn.line_number, src),
-                        line_of_code_2 = get_line_from_contents(last_tkn.line_number, src),
+                        line_of_code_1 = sources.get_line(&first_tkn.file_name, first_tkn.line_number),
+                        line_of_code_2 = sources.get_line(&last_tkn.file_name, last_tkn.line_number),
         
                         red = term::HIGH_RED_ANSI,
                         blue = term::HIGH_BOLD_BLUE_ANSI,
@@ -443,44 +562,651 @@ impl<'tkns, 'exprs, 'defs> ParserError<'tkns, 'exprs, 'defs> {
                     )
                 }
             },
-            PE::SecondDiscardMany { tkn, .. } => todo!("error {:?} at token {tkn:?}", self),
-            PE::InvalidPattern { tkn } => todo!("error {:?} at token {tkn:?}", self),
-            PE::PatternNotMatchExpectedType { tkn, .. } => todo!("error {:?} at token {tkn:?}", self),
-            PE::VariableDoesNotExist { tkn } => todo!("error {:?} at token {tkn:?}", self),
-            PE::InvalidMut { tkn } => todo!("error {:?} at token {tkn:?}", self),
-            PE::CannotMutateImmutable { tkn, .. } => todo!("error {:?} at token {tkn:?}", self),
-            PE::IncorrectNumberPrefixArguments { tkn, .. } => todo!("error {:?} at token {tkn:?}", self),
-            PE::InvalidBlock { tkn } => todo!("error {:?} at token {tkn:?}", self),
-            PE::MultipleExpressions { tkn, .. } => todo!("error {:?} at token {tkn:?}", self),
-            PE::FieldDoesNotExist { tkn, .. } => todo!("error {:?} at token {tkn:?}", self),
-            PE::AlreadyDefinedField { tkn, .. } => todo!("error {:?} at token {tkn:?}", self),
-            PE::FieldExpressionNotDefined { tkn, .. } => todo!("error {:?} at token {tkn:?}", self),
-            PE::InvalidExpressionAtom { tkn } => todo!("error {:?} at token {tkn:?}", self),
-            PE::InvalidDollarExpression { tkn } => todo!("error {:?} at token {tkn:?}", self),
-            PE::InvalidDotExpression { tkn, .. } => todo!("error {:?} at token {tkn:?}", self),
-            PE::AccessorNotDefined { tkn } => todo!("error {:?} at token {tkn:?}", self),
-            PE::DefinedIncorrectlyPlacedArgument { tkn, .. } => todo!("error {:?} at token {tkn:?}", self),
-            PE::ConflictingFunctionFixDefinitions { tkn, .. } => todo!("error {:?} at token {tkn:?}", self),
-            PE::ExpectedEndOfFunctionDefinition { tkn } => todo!("error {:?} at token {tkn:?}", self),
+            PE::SecondDiscardMany { tkn, first_discard_many } => write_related_token_error(f, sources, tkn,
+                "'..' can only be used once per pattern",
+                "used again here",
+                first_discard_many,
+                "first used here",
+                Some("'..' already matches every element between the start and the end of the group")
+            ),
+            PE::ExpectedIterable { tkn, calculated_type } => write_token_error(f, sources, tkn,
+                format_args!("Cannot iterate over a value of type {calculated_type}"),
+                "expected a range or an array here",
+                Some("for loops iterate over ranges like 0..10 and over arrays")
+            ),
+            PE::NotInLoop { tkn } => write_token_error(f, sources, tkn,
+                format_args!("`{}` outside of a loop", tkn.token.to_string().trim_start_matches("keyword ")),
+                "cannot leave a loop from here",
+                None
+            ),
+            PE::UndeclaredLabel { tkn } => write_token_error(f, sources, tkn,
+                format_args!("Use of undeclared {}", tkn.token),
+                "no enclosing loop has this label",
+                Some("label a loop like 'outer: loop { }")
+            ),
+            PE::BreakValueNotUsed { tkn, loop_tkn } => write_related_token_error(f, sources, tkn,
+                "`break` with a value out of a loop that does not produce one",
+                "value given here",
+                loop_tkn,
+                "this loop does not produce a value",
+                Some("only a loop assigned to a variable, like let x = loop { }, produces a value")
+            ),
+            PE::BreakValueMissing { tkn, loop_tkn } => write_related_token_error(f, sources, tkn,
+                "`break` without a value out of a loop that produces one",
+                "expected a value after this",
+                loop_tkn,
+                "the value of this loop is assigned",
+                None
+            ),
+            PE::InvalidPattern { tkn } => write_token_error(f, sources, tkn,
+                format_args!("Expected pattern but received {}", tkn.token),
+                "expected a pattern here",
+                Some("patterns are identifiers, '_', literals, ranges like 1..=5, groups of patterns like (a, .., b) and [a, b], \
+                    structs like Point { x, .. } or variants like Shape::Circle(r)")
+            ),
+            PE::PatternNotMatchExpectedType { tkn, pattern, expected_type } => write_token_error(f, sources, tkn,
+                format_args!("Pattern {pattern} cannot match a value of type {expected_type}"),
+                "pattern does not fit this type",
+                None
+            ),
+            PE::EmptyRangePattern { tkn } => write_token_error(f, sources, tkn,
+                "Range pattern does not match any value",
+                "empty range",
+                Some("the start of a range pattern has to come before its end, use ..= to include the end")
+            ),
+            PE::MissingFieldPattern { tkn, field_name } => write_token_error(f, sources, tkn,
+                format_args!("Pattern does not mention field `{field_name}`"),
+                "missing field",
+                Some("use .. to ignore the remaining fields")
+            ),
+            PE::AlreadyBoundIdentifier { tkn, bound } => write_related_token_error(f, sources, tkn,
+                format_args!("Identifier `{}` is bound more than once in the same pattern", token_name(tkn)),
+                "bound again here",
+                bound,
+                "first bound here",
+                None
+            ),
+            PE::VariantPatternMismatch { tkn, custom_enum, variant } => write_token_error(f, sources, tkn,
+                format_args!("Pattern does not match the shape of variant `{}::{}`", custom_enum.name, variant.variant_name),
+                "written differently than declared",
+                Some(&match variant.shape {
+                    VariantShape::Unit => format!("unit variants are matched as {}::{}", custom_enum.name, variant.variant_name),
+                    VariantShape::Tuple => format!(
+                        "tuple variants are matched as {}::{}({})", 
+                        custom_enum.name, 
+                        variant.variant_name, 
+                        vec!["_"; variant.fields.len()].join(", ")
+                    ),
+                    VariantShape::Struct => format!(
+                        "struct-like variants are matched as {}::{} {{ {} }}", 
+                        custom_enum.name, 
+                        variant.variant_name, 
+                        variant.fields.iter().map(|(field_name, _)| field_name.as_str()).collect::<Vec<_>>().join(", ")
+                    ),
+                })
+            ),
+            PE::NonExhaustiveMatch { tkn, missing } => write_token_error(f, sources, tkn,
+                format_args!("Match does not cover `{missing}`"),
+                "pattern not covered",
+                Some(&format!("add an arm for {missing}, or a '_' arm to match every remaining value"))
+            ),
+            PE::UnreachableArm { tkn } => write_token_error(f, sources, tkn,
+                "Match arm can never be reached",
+                "unreachable arm",
+                Some("the arms before it already match every value this arm matches")
+            ),
+            PE::VariableDoesNotExist { tkn } => write_token_error(f, sources, tkn,
+                format_args!("Cannot find variable `{}` in this scope", token_name(tkn)),
+                "not found in this scope",
+                Some("variables have to be declared with let before they are used")
+            ),
+            PE::InvalidMut { tkn } => write_token_error(f, sources, tkn,
+                "mut can only be used when declaring a variable",
+                "remove mut here",
+                Some("mutability is part of the declaration, e.g. let mut x = 0;")
+            ),
+            PE::CannotMutateImmutable { tkn, variable_def } => write_related_token_error(f, sources, tkn,
+                format_args!("Cannot assign twice to immutable variable `{}`", token_name(tkn)),
+                "cannot assign twice to immutable variable",
+                variable_def,
+                "first assigned here",
+                Some("declare the variable with let mut to make it mutable")
+            ),
+            PE::IncorrectNumberPrefixArguments { tkn, args, expected_args, function } => write_token_error(f, sources, tkn,
+                format_args!(
+                    "Function `{}` takes {} prefix argument(s) but {} were supplied", 
+                    function, 
+                    expected_args.len(), 
+                    args.len()
+                ),
+                "called with the wrong number of prefix arguments",
+                Some(&format!("expected ({}) but received ({})", slice_to_string(expected_args), slice_to_string(args)))
+            ),
+            PE::InvalidBlock { tkn } => write_token_error(f, sources, tkn,
+                format_args!("Expected beginning of accessor, struct, enum, trait, impl, or function definition but received {}", tkn.token),
+                "expected an item here",
+                Some("support for other constructs such as const declarations, directives, etc. will be implemented in the future")
+            ),
+            PE::MultipleExpressions { tkn, expr } => write_token_error(f, sources, tkn,
+                format_args!("Expected one expression but found {}", expr.len()),
+                "multiple expressions start here",
+                Some("expressions written next to each other need an operator or a function between them")
+            ),
+            PE::FieldDoesNotExist { tkn, custom_struct } => write_token_error(f, sources, tkn,
+                format_args!("Struct `{}` has no field named `{}`", custom_struct.name, token_name(tkn)),
+                "unknown field",
+                Some(&format!(
+                    "available fields are {}", 
+                    slice_to_string(&custom_struct.fields.iter().map(|field| &field.field_name).collect::<Vec<_>>())
+                ))
+            ),
+            PE::AlreadyDefinedField { tkn, defined_field } => write_related_token_error(f, sources, tkn,
+                format_args!("Field `{}` is already defined", token_name(tkn)),
+                "used more than once",
+                defined_field,
+                "first defined here",
+                None
+            ),
+            PE::AlreadyDefinedVariant { tkn, defined_variant } => write_related_token_error(f, sources, tkn,
+                format_args!("Variant `{}` is already defined", token_name(tkn)),
+                "used more than once",
+                defined_variant,
+                "first defined here",
+                None
+            ),
+            PE::VariantDoesNotExist { tkn, custom_enum } => write_token_error(f, sources, tkn,
+                format_args!("Enum `{}` has no variant named `{}`", custom_enum.name, token_name(tkn)),
+                "unknown variant",
+                Some(&format!(
+                    "available variants are {}", 
+                    slice_to_string(&custom_enum.variants.iter().map(|variant| &variant.variant_name).collect::<Vec<_>>())
+                ))
+            ),
+            PE::VariantFieldDoesNotExist { tkn, custom_enum, variant } => write_token_error(f, sources, tkn,
+                format_args!(
+                    "Variant `{}::{}` has no field named `{}`", 
+                    custom_enum.name, 
+                    variant.variant_name, 
+                    token_name(tkn)
+                ),
+                "unknown field",
+                Some(&format!(
+                    "available fields are {}", 
+                    slice_to_string(&variant.fields.iter().map(|(field_name, _)| field_name).collect::<Vec<_>>())
+                ))
+            ),
+            PE::MissingVariantField { tkn, custom_enum, variant, field_name } => write_token_error(f, sources, tkn,
+                format_args!(
+                    "Missing field `{field_name}` in initializer of `{}::{}`", 
+                    custom_enum.name, 
+                    variant.variant_name
+                ),
+                "missing field",
+                Some("every field of a variant has to be given a value")
+            ),
+            PE::FieldExpressionNotDefined { tkn, field } => write_token_error(f, sources, tkn,
+                format_args!("Cannot find variable `{0}` to initialize field `{0}`", field.field_name),
+                "not found in this scope",
+                Some(&format!("give the field a value explicitly, e.g. {}: <expression>", field.field_name))
+            ),
+            PE::InvalidExpressionAtom { tkn } => write_token_error(f, sources, tkn,
+                format_args!("Expected expression but received {}", tkn.token),
+                "expected an expression here",
+                None
+            ),
+            PE::InvalidDollarExpression { tkn } => write_token_error(f, sources, tkn,
+                format_args!("Expected expression after '$' but received {}", tkn.token),
+                "expected an expression here",
+                Some("'$' groups everything after it into one expression, like parenthesis up to the end of the expression")
+            ),
+            PE::InvalidDotExpression { tkn, expr_type } => write_token_error(f, sources, tkn,
+                format_args!("No field on type {expr_type}"),
+                "unknown field",
+                Some("only structs, anonymous structs and tuples have fields, which tuples number from 0")
+            ),
+            PE::AccessorNotDefined { tkn } => write_token_error(f, sources, tkn,
+                format_args!("Cannot find accessor `{}`", token_name(tkn)),
+                "accessor not defined",
+                Some("use pub, prv, pkg, or an accessor defined in this file")
+            ),
+            PE::DefinedIncorrectlyPlacedArgument { tkn, arg_type, fix_defined, fix_type } => write_related_token_error(f, sources, tkn,
+                format_args!("{fix_type} functions cannot have {arg_type} arguments"),
+                "argument defined here",
+                fix_defined,
+                &format!("function is {fix_type} because of this"),
+                None
+            ),
+            PE::ConflictingFunctionFixDefinitions { tkn, fix_defined } => write_related_token_error(f, sources, tkn,
+                "Function fixity is defined more than once",
+                "defined again here",
+                fix_defined,
+                "first defined here",
+                Some("a function is either prefix, infix, or postfix")
+            ),
+            PE::ExpectedEndOfFunctionDefinition { tkn } => write_token_error(f, sources, tkn,
+                format_args!("Expected the end of the function definition, found {}", tkn.token),
+                "expected '=', '{' or a fixity here",
+                Some("function definitions look like fn name $ args = return_type { ... }")
+            ),
+            PE::PathDoesNotExist { tkn } => write_token_error(f, sources, tkn,
+                format_args!("Cannot find `{}` in this namespace or the ones around it", token_name(tkn)),
+                "not found",
+                Some("files other than the first are namespaces named after the file")
+            ),
+            PE::NotInNamespace { tkn, namespace } => write_token_error(f, sources, tkn,
+                format_args!("Namespace `{namespace}` has no `{}`", token_name(tkn)),
+                "not found in the namespace",
+                None
+            ),
+            PE::FieldNotAccessible { tkn, custom_struct, field } => write_access_error(f, sources, tkn,
+                format_args!("Field `{}` of `{}`", field.field_name, custom_struct.name),
+                &field.accessibility,
+                &custom_struct.location,
+                &custom_struct.file_name
+            ),
+            PE::FunctionNotAccessible { tkn, function } => write_access_error(f, sources, tkn,
+                format_args!("Function `{}`", function.name),
+                &function.accessibility,
+                &function.location,
+                &function.file_name
+            ),
+            PE::TraitNotDefined { tkn } => write_token_error(f, sources, tkn,
+                format_args!("Cannot find trait `{}`", token_name(tkn)),
+                "not a trait",
+                None
+            ),
+            PE::NotTraitMethod { tkn, custom_trait } => write_token_error(f, sources, tkn,
+                format_args!("Method `{}` is not a member of trait `{}`", token_name(tkn), custom_trait.name),
+                "not in the trait",
+                Some("methods of their own go in an `impl` block without a trait")
+            ),
+            PE::MissingTraitMethod { tkn, custom_trait, method } => write_related_token_error(f, sources, tkn,
+                format_args!("Method `{}` of trait `{}` is not implemented", method.name, custom_trait.name),
+                "missing in this impl",
+                &method.tkn,
+                "declared in the trait here",
+                None
+            ),
+            PE::TraitMethodMismatch { tkn, custom_trait, method, self_type } => write_related_token_error(f, sources, tkn,
+                format_args!(
+                    "Method `{}` does not have the arguments and return type trait `{}` declares",
+                    method.name, 
+                    custom_trait.name
+                ),
+                "does not match the trait",
+                &method.tkn,
+                "declared in the trait here",
+                Some(&format!("expected `fn {} {}`", method.name, method.signature(self_type)))
+            ),
+            PE::CouldNotInferTypeParameter { tkn, type_param } => write_token_error(f, sources, tkn,
+                format_args!("Cannot infer type parameter `{type_param}` of function `{}`", token_name(tkn)),
+                "called here",
+                Some("every type parameter has to show up in the type of an argument")
+            ),
+            PE::UseAfterMove { tkn, moved } => write_related_token_error(f, sources, tkn,
+                format_args!("Variable `{}` is used after its value was moved", token_name(tkn)),
+                "used after the move",
+                moved,
+                "value moved here",
+                Some("values of types without an impl Copy move out of oxy variables when they are used")
+            ),
+            PE::MovedTwice { tkn, moved } => write_related_token_error(f, sources, tkn,
+                format_args!("Value of variable `{}` is moved twice", token_name(tkn)),
+                "moved again here",
+                moved,
+                "first moved here",
+                Some("values of types without an impl Copy move out of oxy variables when they are used")
+            ),
+            PE::BorrowConflict { tkn, access, borrowed, borrow } => write_related_token_error(f, sources, tkn,
+                match access {
+                    Access::Read => format!("Cannot use `{}` while it is borrowed as {borrow}", token_name(tkn)),
+                    Access::Write => format!("Cannot assign to `{}` while it is borrowed as {borrow}", token_name(tkn)),
+                    Access::Move => format!("Cannot move out of `{}` while it is borrowed as {borrow}", token_name(tkn)),
+                    Access::Borrow(kind) => format!(
+                        "Cannot borrow `{}` as {kind} while it is borrowed as {borrow}",
+                        token_name(tkn)
+                    ),
+                },
+                match access {
+                    Access::Read => "used here",
+                    Access::Write => "assigned here",
+                    Access::Move => "moved here",
+                    Access::Borrow(_) => "borrowed again here"
+                },
+                borrowed,
+                "borrowed here",
+                Some("a value can have one &mut borrow, any number of & borrows or any number of &im borrows at a time, each lasting until the end of the block of the variable holding it")
+            ),
+            PE::BorrowMutOfImmutable { tkn, variable_def } => write_related_token_error(f, sources, tkn,
+                format_args!("Cannot borrow immutable variable `{}` as mutable", token_name(tkn)),
+                "cannot borrow as mutable",
+                variable_def,
+                "declared here",
+                Some("declare the variable with let mut to borrow it with &mut or &im")
+            ),
+            PE::MutateThroughSharedRef { tkn, expr_type } => write_token_error(f, sources, tkn,
+                format_args!("Cannot write through a reference of type {expr_type}"),
+                "behind a & reference",
+                Some("only &mut and &im references can be written through")
+            ),
+            PE::InvalidDeref { tkn, expr_type } => write_token_error(f, sources, tkn,
+                format_args!("Cannot dereference a value of type {expr_type}"),
+                "not a reference",
+                Some("only &, &mut and &im references can be dereferenced with *")
+            ),
+            PE::MoveOutOfReference { tkn, expr_type } => write_token_error(f, sources, tkn,
+                format_args!("Cannot move a value of type {expr_type} out from behind a reference"),
+                "value moved here",
+                Some("values of types without an impl Copy can only be borrowed from behind a reference")
+            ),
+            PE::InvalidCast { tkn, from, to } => write_token_error(f, sources, tkn,
+                format_args!("Cannot cast a value of type {from} to {to}"),
+                "invalid cast",
+                Some("only integer, float, char and bool types can be cast to one another with as, though only u8 and char can be cast to char")
+            ),
+            PE::InvalidPrecedence { tkn } => write_token_error(f, sources, tkn,
+                "Precedence is not between 1 and 14",
+                "invalid precedence",
+                Some("precedences go from 1, binding as loosely as ++, up to 14, binding as tightly as a cast with as")
+            ),
+            PE::InvalidCustomOperator { tkn } => write_token_error(f, sources, tkn,
+                format_args!("Custom operator `{}` needs one argument on each side and a precedence", token_name(tkn)),
+                "invalid operator definition",
+                Some("operators are defined like fn +` $ a: T infix 11 left $ b: T = T { ... }")
+            ),
+            PE::FixityWithoutTwoOperands { tkn } => write_token_error(f, sources, tkn,
+                format_args!("Function `{}` has a precedence but does not take one argument on each side", token_name(tkn)),
+                "precedence needs two operands",
+                Some("only infix functions like fn dot $ a: T infix 12 left $ b: T = T { ... } take a precedence")
+            ),
+            PE::MisplacedDefaultArgument { tkn } => write_token_error(f, sources, tkn,
+                format_args!("Argument `{}` has a default but is not one of the trailing arguments", token_name(tkn)),
+                "default not at the end",
+                Some("calls can only leave out arguments at the end of the right side, so the ones after a default need defaults too")
+            ),
+            PE::DefaultInClosure { tkn } => write_token_error(f, sources, tkn,
+                format_args!("Closure argument `{}` cannot have a default", token_name(tkn)),
+                "default on a closure argument",
+                Some("closures are called through function values, which need every argument")
+            ),
+            PE::OperatorOutsideImpl { tkn } => write_token_error(f, sources, tkn,
+                format_args!("Custom operator `{}` is not defined on a type", token_name(tkn)),
+                "defined outside of an impl",
+                Some("custom operators are defined in an impl of the type on their left")
+            ),
+            PE::OperatorNotDefined { tkn, expr_type } => write_token_error(f, sources, tkn,
+                format_args!("Operator `{}` is not defined on type {expr_type}", token_name(tkn)),
+                "operator not defined",
+                Some("custom operators are defined in an impl of the type on their left")
+            ),
+            PE::UndeclaredLifetime { tkn } => write_token_error(f, sources, tkn,
+                format_args!("Use of undeclared lifetime {}", token_name(tkn)),
+                "undeclared lifetime",
+                Some("declare the lifetime with the type parameters of the function or struct, as in fn name<'a>")
+            ),
+            PE::MissingLifetime { tkn } => write_token_error(f, sources, tkn,
+                format_args!("Missing lifetime on the returned reference"),
+                "expected a named lifetime",
+                Some("an elided lifetime borrows from self, or else from the only argument holding a reference, otherwise name it as in fn name<'a> $ x: &'a T = &'a T")
+            ),
+            PE::ReturnsLocalReference { tkn, borrowed } => write_related_token_error(f, sources, tkn,
+                format_args!("Cannot return a reference to local variable `{}`", token_name(borrowed)),
+                "returned here",
+                borrowed,
+                "borrowed here",
+                Some("the variables of a function do not outlive the call, a returned reference has to borrow from an argument")
+            ),
+            PE::ReturnsTemporaryReference { tkn, borrowed } => write_related_token_error(f, sources, tkn,
+                format_args!("Cannot return a reference to a temporary value"),
+                "returned here",
+                borrowed,
+                "borrowed here",
+                Some("temporary values do not outlive the call, a returned reference has to borrow from an argument")
+            ),
+            PE::ReturnsBorrowingClosure { tkn, captured } => write_related_token_error(f, sources, tkn,
+                format_args!("Cannot return a closure that borrows local variable `{}`", token_name(captured)),
+                "returned here",
+                captured,
+                "captured here",
+                Some("the variables of a function do not outlive the call, a returned closure has to move them in with `move fn`")
+            ),
+            PE::LifetimeMismatch { tkn, param, return_type } => write_related_token_error(f, sources, tkn,
+                format_args!("Returned reference borrows from `{}`, which does not live as long as {return_type}", token_name(param)),
+                "returned here",
+                param,
+                "declared here",
+                Some("give the argument the lifetime of the returned reference, an elided one borrows from self, or else from the only argument holding a reference")
+            ),
         }
     }
 }
 
-fn get_line_from_contents(line_number: usize, contents: &str) -> &str {
-    let mut index = 0;
-    for _ in 1..line_number {
-        while let Some(chr) = contents.chars().nth(index) && chr != '\n' {
-            index += 1;
-        }
-        index += 1;
+/// Points at both the use site and where the accessibility that rejects it is written.
+fn write_access_error(
+    f: &mut impl std::io::Write, 
+    sources: &Sources, 
+    tkn: &Tkn, 
+    subject: impl std::fmt::Display, 
+    accessibility: &Accessibility, 
+    location: &str, 
+    file_name: &str
+) -> std::io::Result<()> {
+    let (related, message, related_label) = match accessibility {
+        Accessibility::Public => unreachable!("public items are accessible from anywhere"),
+        Accessibility::Private(related) => (
+            related,
+            format!("{subject} is private to namespace `{location}`"),
+            "declared private here"
+        ),
+        Accessibility::Package(related) => (
+            related,
+            format!("{subject} can only be used in file `{file_name}`"),
+            "declared package here"
+        ),
+        Accessibility::Accessor(accessor) if tkn.namespace.is_empty() => (
+            &accessor.tkn,
+            format!("{subject} is not accessible from the root namespace"),
+            "accessor defined here"
+        ),
+        Accessibility::Accessor(accessor) => (
+            &accessor.tkn,
+            format!("{subject} is not accessible from namespace `{}`", tkn.namespace),
+            "accessor defined here"
+        ),
+    };
+    return write_related_token_error(f, sources, tkn, message, "not accessible here", related, related_label, None);
+}
+
+/// The name written in the source for identifiers, otherwise the token's description.
+fn token_name(tkn: &Tkn) -> String {
+    match &tkn.token {
+        TknType::Identifier(ident) => return ident.clone(),
+        TknType::Label(label) => return format!("'{label}"),
+        TknType::CustomOperation(symbol) => return symbol.clone(),
+        token => return token.to_string()
     }
+}
 
-    let mut len = 0;
-    while let Some(chr) = contents.chars().nth(index + len) && chr != '\n' {
-        len += 1;
+/// Writes an error pointing at a single token: the message, the line of code with the token
+/// underlined and labeled, then an optional help note.
+fn write_token_error(
+    f: &mut impl std::io::Write, 
+    sources: &Sources, 
+    tkn: &Tkn, 
+    message: impl std::fmt::Display, 
+    label: &str, 
+    help: Option<&str>
+) -> std::io::Result<()> {
+    let Tkn { token, file_name, line_index, line_number, .. } = tkn;
+    write!(f, 
+        "{red}error:{clear} {message}\n\
+        {blue}{pad}-->{clear} {file_name}:{line_number}:{line_index}\n\
+        {blue}{pad} | {clear}\n\
+        {blue}{line_number} | {clear}{line_of_code}\n\
+        {blue}{pad} | {clear}{arrow_pad}{red}{arrow} {label} {clear}\n\
+        {blue}{pad}:::{clear}{help}\n\
+        \n\
+        ",
+        pad = " ".repeat(line_number.to_string().len()),
+        line_of_code = sources.get_line(file_name, *line_number).trim_end(),
+        arrow_pad = " ".repeat(*line_index - 1),
+        arrow = "^".repeat(token.len()),
+        help = help.map(|help| format!(" help = {help}")).unwrap_or_default(),
+
+        red = term::HIGH_RED_ANSI,
+        blue = term::HIGH_BOLD_BLUE_ANSI,
+        clear = term::CLEAR_ANSI,
+    )
+}
+
+/// Writes an error pointing at a token along with an earlier token it conflicts with, 
+/// laid out like [`ParserError::ExpectedClosingBrace`].
+fn write_related_token_error(
+    f: &mut impl std::io::Write, 
+    sources: &Sources, 
+    tkn: &Tkn, 
+    message: impl std::fmt::Display, 
+    label: &str, 
+    related: &Tkn,
+    related_label: &str,
+    help: Option<&str>
+) -> std::io::Result<()> {
+    let Tkn { token, file_name, line_index, line_number, .. } = tkn;
+    let help = help.map(|help| format!(" help = {help}")).unwrap_or_default();
+
+    if *file_name == related.file_name
+        && *line_number == related.line_number 
+        && related.line_index + related.token.len() <= *line_index 
+    {
+        let line_pad_len = related.line_index - 1;
+        let line_len = related.token.len();
+        let arrow_pad_len = *line_index - line_len - line_pad_len - 1;
+
+        write!(f,
+            "{red}error:{clear} {message}\n\
+            {blue}{pad}-->{clear} {file_name}:{line_number}:{line_index}\n\
+            {blue}{pad} | {clear}\n\
+            {blue}{line_number} | {clear}{line_of_code}\n\
+            {blue}{pad} | {clear}{line_pad}{blue}{line}{clear}\
+                                 {arrow_pad}{red}{arrow} {label} {clear}\n\
+            {blue}{pad} | {clear}{line_pad}{blue}|{clear}\n\
+            {blue}{pad} | {clear}{line_pad}{blue}{related_label}{clear}\n\
+            {blue}{pad}:::{clear}{help}\n\
+            \n\
+            ",
+            pad = " ".repeat(line_number.to_string().len()),
+            line_of_code = sources.get_line(file_name, *line_number).trim_end(),
+            line_pad = " ".repeat(line_pad_len),
+            line = "-".repeat(line_len),
+            arrow_pad = " ".repeat(arrow_pad_len),
+            arrow = "^".repeat(token.len()),
+
+            red = term::HIGH_RED_ANSI,
+            blue = term::HIGH_BOLD_BLUE_ANSI,
+            clear = term::CLEAR_ANSI
+        )
+    } else if *file_name == related.file_name
+        && *line_number == related.line_number
+        && *line_index + token.len() <= related.line_index
+    {
+        let arrow_pad_len = *line_index - 1;
+        let arrow_len = token.len();
+        let line_pad_len = related.line_index - arrow_len - arrow_pad_len - 1;
+
+        write!(f,
+            "{red}error:{clear} {message}\n\
+            {blue}{pad}-->{clear} {file_name}:{line_number}:{line_index}\n\
+            {blue}{pad} | {clear}\n\
+            {blue}{line_number} | {clear}{line_of_code}\n\
+            {blue}{pad} | {clear}{arrow_pad}{red}{arrow}{clear}\
+                                 {line_pad}{blue}{line} {related_label}{clear}\n\
+            {blue}{pad} | {clear}{arrow_pad}{red}|{clear}\n\
+            {blue}{pad} | {clear}{arrow_pad}{red}{label}{clear}\n\
+            {blue}{pad}:::{clear}{help}\n\
+            \n\
+            ",
+            pad = " ".repeat(line_number.to_string().len()),
+            line_of_code = sources.get_line(file_name, *line_number).trim_end(),
+            arrow_pad = " ".repeat(arrow_pad_len),
+            arrow = "^".repeat(arrow_len),
+            line_pad = " ".repeat(line_pad_len),
+            line = "-".repeat(related.token.len()),
+
+            red = term::HIGH_RED_ANSI,
+            blue = term::HIGH_BOLD_BLUE_ANSI,
+            clear = term::CLEAR_ANSI
+        )
+    } else {
+        let pad_len = core::cmp::Ord::max(
+            line_number.to_string().len(),
+            related.line_number.to_string().len()
+        );
+        // the lines are printed one above the other, so the related one names its own file
+        let related_label = if *file_name == related.file_name {
+            related_label.to_string()
+        } else {
+            format!("{related_label} in {}", related.file_name)
+        };
+
+        write!(f,
+            "{red}error:{clear} {message}\n\
+            {blue}{pad}-->{clear} {file_name}:{line_number}:{line_index}\n\
+            {blue}{pad} | {clear}\n\
+            {blue}{line_number_1:>pad_len$} | {clear}{line_of_code_1}\n\
+            {blue}{pad} | {clear}{line_pad}{blue}{line} {related_label} {clear}\n\
+            {blue}{line_number_2:>pad_len$} | {clear}{line_of_code_2}\n\
+            {blue}{pad} | {clear}{arrow_pad}{red}{arrow} {label} {clear}\n\
+            {blue}{pad}:::{clear}{help}\n\
+            \n\
+            ",
+            pad = " ".repeat(pad_len),
+            line_number_1 = related.line_number,
+            line_number_2 = *line_number,
+            line_of_code_1 = sources.get_line(&related.file_name, related.line_number).trim_end(),
+            line_of_code_2 = sources.get_line(file_name, *line_number).trim_end(),
+            line_pad = " ".repeat(related.line_index - 1),
+            arrow_pad = " ".repeat(*line_index - 1),
+            line = "-".repeat(related.token.len()),
+            arrow = "^".repeat(token.len()),
+
+            red = term::HIGH_RED_ANSI,
+            blue = term::HIGH_BOLD_BLUE_ANSI,
+            clear = term::CLEAR_ANSI
+        )
     }
+}
+
+/// The source code of every file in a program, so an error can quote the file its token came from.
+#[derive(Default)]
+pub struct Sources {
+    files: Vec<(String, String)>
+}
+
+impl Sources {
+    pub fn new() -> Self {
+        return Sources::default();
+    }
+
+    pub fn add(&mut self, file_name: &str, src: String) {
+        self.files.push((file_name.to_string(), src));
+    }
+
+    pub fn get(&self, file_name: &str) -> &str {
+        return self.files.iter()
+            .find(|(name, _)| name == file_name)
+            .map(|(_, src)| src.as_str())
+            .unwrap_or_default();
+    }
+
+    pub fn get_line(&self, file_name: &str, line_number: usize) -> &str {
+        return get_line_from_contents(line_number, self.get(file_name));
+    }
+}
 
-    return &contents[index..index+len];
+pub(crate) fn get_line_from_contents(line_number: usize, contents: &str) -> &str {
+    return line_number.checked_sub(1)
+        .and_then(|i| contents.split('\n').nth(i))
+        .unwrap_or_default();
 }
 
 fn slice_to_string<T: std::fmt::Display>(slice: &[T]) -> Box<str> {
@@ -495,7 +1221,7 @@ fn slice_to_string<T: std::fmt::Display>(slice: &[T]) -> Box<str> {
                 output += value.to_string().as_str();
                 output += ", ";
             }
-            output += " and ";
+            output += "and ";
             output += last.to_string().as_str();
 
             output.into_boxed_str()
@@ -526,7 +1252,7 @@ where
             output += value.to_string().as_str();
             output += ", ";
         }
-        output += " and ";
+        output += "and ";
         output += last.to_string().as_str();
         
         output.into_boxed_str()
@@ -1,14 +1,25 @@
-use std::{cell::RefCell, ptr::{self}};
+use std::{cell::RefCell, collections::HashMap, ptr::{self}};
 
 use stack_frame_allocators::stack_frame_dict_allocator::StackFrameDictAllocator;
 
-use crate::{lexer::token::{Kwrd, Tkn, TknType}, parser::tokens};
+use crate::{lexer::token::{Kwrd, Op, Tkn, TknType}, parser::tokens};
 
-use super::{expr::{Expr, ExprData, ExprType, ExprTypeCons, VariableData}, stmt::{StackLocation, Stmt, StmtData}, ExprBump, ParserError, StmtBump};
+use super::{accessors, enums::{Enum, VariantShape}, exhaustiveness::{self, MatchProblem}, expr::{self, Expr, ExprData, ExprType, ExprTypeCons, Lit, VariableData}, functions::FullFnDef, stmt::{StackLocation, Stmt, StmtData}, structs::Struct, ExprBump, ParserError, StmtBump};
 
 #[derive(Clone, Debug)]
 pub enum Pattern<'tkns> {
-    Ident { tkn: &'tkns Tkn, mutable: bool, name: String },
+    /// `name` or `mut name`, where `name_index` is the index of the token of the name
+    Ident { tkn: &'tkns Tkn, name_index: usize, mutable: bool, name: String },
+    /// `5`, `-1`, `'a'`, `"text"`, `true` or `1.5`
+    Literal { tkn: &'tkns Tkn, literal: Lit },
+    /// `start..end` between two literals, where `!` excludes the start and `=` includes the end
+    Range {
+        tkn: &'tkns Tkn,
+        start: Lit,
+        end: Lit,
+        exclusive_start: bool,
+        inclusive_end: bool
+    },
     Tuple {
         start: Vec<Self>,
         end: Vec<Self>
@@ -21,8 +32,125 @@ pub enum Pattern<'tkns> {
         start: Vec<Self>,
         end: Vec<Self>
     },
+    /// `Name { field: pattern, field, .. }`
+    Struct {
+        tkn: &'tkns Tkn,
+        name: String,
+        fields: Vec<FieldPattern<'tkns>>,
+        rest: bool
+    },
+    /// `{ field: pattern, field, .. }`
+    AnonymousStruct {
+        tkn: &'tkns Tkn,
+        fields: Vec<FieldPattern<'tkns>>,
+        rest: bool
+    },
+    /// `Name::Variant`, where the payload is a [`Pattern::Tuple`] for `Name::Variant(a, b)` and 
+    /// a [`Pattern::AnonymousStruct`] for `Name::Variant { a, b }`
+    Variant {
+        tkn: &'tkns Tkn,
+        enum_name: String,
+        variant_tkn: &'tkns Tkn,
+        variant_name: String,
+        payload: Option<Box<Self>>
+    },
     DiscardSingle
-    //TODO enum and struct pattern matching
+}
+
+/// `field: pattern`, where the shorthand `field` binds the field to a variable of the same name
+#[derive(Clone, Debug)]
+pub struct FieldPattern<'tkns> {
+    pub tkn: &'tkns Tkn,
+    pub field_name: String,
+    pub pattern: Pattern<'tkns>
+}
+
+/// The pattern of a `match` arm, once it is checked against the type of the value it matches.  
+/// It carries no types, since the type of the matched value tells where each part of the value is.
+#[derive(Clone, Debug)]
+pub enum MatchPattern {
+    /// `_`, which matches anything
+    Discard,
+    /// An identifier, which matches anything and binds it to a new variable
+    Bind(String),
+    Literal(Lit),
+    /// An integer range, or a char range by code point, including both of its ends
+    Range { start: i128, end: i128 },
+    /// Tuple and array patterns, where `end` lines up with the last elements
+    Group { start: Vec<Self>, end: Vec<Self> },
+    /// Struct and anonymous struct patterns; the fields left out match anything
+    Fields(Vec<(String, Self)>),
+    /// The payload of the variant with `tag`; the fields left out match anything
+    Variant { tag: usize, fields: Vec<(String, Self)> }
+}
+
+/// `pattern if guard => body`
+#[derive(Clone, Debug)]
+pub struct MatchArm<'bumps, 'defs, Body> {
+    pub pattern: MatchPattern,
+    pub guard: Option<Expr<'bumps, 'defs>>,
+    pub body: Body
+}
+
+impl std::fmt::Display for Pattern<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (open, close, start, end) = match self {
+            Pattern::Ident { mutable: true, name, .. } => return write!(f, "mut {name}"),
+            Pattern::Ident { name, .. } => return write!(f, "{name}"),
+            Pattern::DiscardSingle => return write!(f, "_"),
+            Pattern::Literal { literal, .. } => return write_literal(f, literal),
+            Pattern::Range { start, end, exclusive_start, inclusive_end, .. } => {
+                write_literal(f, start)?;
+                write!(f, "{}..{}", if *exclusive_start { "!" } else { "" }, if *inclusive_end { "=" } else { "" })?;
+                return write_literal(f, end);
+            },
+            Pattern::Struct { name, fields, rest, .. } => {
+                write!(f, "{name} ")?;
+                return write_field_patterns(f, fields, *rest);
+            },
+            Pattern::AnonymousStruct { fields, rest, .. } => return write_field_patterns(f, fields, *rest),
+            Pattern::Variant { enum_name, variant_name, payload, .. } => return match payload.as_deref() {
+                Some(payload @ Pattern::AnonymousStruct { .. }) => write!(f, "{enum_name}::{variant_name} {payload}"),
+                Some(payload) => write!(f, "{enum_name}::{variant_name}{payload}"),
+                None => write!(f, "{enum_name}::{variant_name}"),
+            },
+            Pattern::Tuple { start, end } => ("(", ")", start, end),
+            Pattern::Array { start, end } => ("[", "]", start, end),
+            Pattern::AmbiguousGroup { start, end } => ("(", ")", start, end),
+        };
+
+        let mut elements = start.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+        if !end.is_empty() {
+            elements.push(String::from(".."));
+        }
+        elements.extend(end.iter().map(|e| e.to_string()));
+        return write!(f, "{open}{}{close}", elements.join(", "));
+    }
+}
+
+fn write_literal(f: &mut std::fmt::Formatter<'_>, literal: &Lit) -> std::fmt::Result {
+    return match literal {
+        Lit::IntegerLiteral(int) => write!(f, "{int}"),
+        Lit::FloatLiteral(float) => write!(f, "{float:?}"),
+        Lit::CharLiteral(chr) => write!(f, "{chr:?}"),
+        Lit::StringLiteral(string) => write!(f, "\"{string}\""),
+        Lit::BooleanLiteral(bool) => write!(f, "{bool}"),
+    };
+}
+
+fn write_field_patterns(f: &mut std::fmt::Formatter<'_>, fields: &[FieldPattern], rest: bool) -> std::fmt::Result {
+    let mut elements = fields.iter().map(|FieldPattern { field_name, pattern, .. }| match pattern {
+        Pattern::Ident { mutable: false, name, .. } if name == field_name => field_name.clone(),
+        pattern => format!("{field_name}: {pattern}")
+    }).collect::<Vec<_>>();
+    if rest {
+        elements.push(String::from(".."));
+    }
+
+    if elements.is_empty() {
+        return write!(f, "{{}}");
+    }
+    return write!(f, "{{ {} }}", elements.join(", "));
 }
 
 pub fn parse_identifier_pattern<'tkns, 'bumps, 'defs>(
@@ -31,103 +159,302 @@ pub fn parse_identifier_pattern<'tkns, 'bumps, 'defs>(
 ) -> Result<Pattern<'tkns>, ParserError<'tkns, 'bumps, 'defs>> {
     let mut peek = *index;
 
-    #[derive(PartialEq)]
-    enum GroupPatternParsingStage {
-        Start, End
-    }
+    if let Some(TknType::Identifier(name)) = tokens::get_token(tokens, peek).map(|e| &e.token) &&
+       tokens::is_token(tokens, TknType::OpenCurlyBrace, peek + 1)
+    {
+        let tkn = &tokens[peek];
+        peek += 2;
+        let (fields, rest) = parse_field_patterns(tokens, parse_identifier_pattern, &mut peek)?;
 
-    if let Some(TknType::Keyword(Kwrd::Mutable)) = tokens::get_token(tokens, peek).map(|e| &e.token) &&
+        *index = peek;
+        return Ok(Pattern::Struct { tkn, name: name.clone(), fields, rest });
+    } else if let Some(TknType::Keyword(Kwrd::Mutable)) = tokens::get_token(tokens, peek).map(|e| &e.token) &&
        let Some(TknType::Identifier(ident)) = tokens::get_token(tokens, peek + 1).map(|e| &e.token) 
     {
         let tkn = &tokens[*index];
         *index += 2;
 
-        return Ok(Pattern::Ident{ tkn, mutable: true, name: ident.clone() });
+        return Ok(Pattern::Ident{ tkn, name_index: peek + 1, mutable: true, name: ident.clone() });
     } else if let Some(TknType::Identifier(ident)) = tokens::get_token(tokens, peek).map(|e| &e.token) {
         let tkn = &tokens[*index];
         *index += 1;
 
-        return Ok(Pattern::Ident{ tkn, mutable: false, name: ident.clone() });
+        return Ok(Pattern::Ident{ tkn, name_index: peek, mutable: false, name: ident.clone() });
+    } else if tokens::is_expected_token(tokens, TknType::OpenCurlyBrace, &mut peek) {
+        let tkn = &tokens[peek - 1];
+        let (fields, rest) = parse_field_patterns(tokens, parse_identifier_pattern, &mut peek)?;
+
+        *index = peek;
+        return Ok(Pattern::AnonymousStruct { tkn, fields, rest });
     } else if tokens::is_expected_token(tokens, TknType::OpenParen, &mut peek) {
-        let mut start_idents = vec![];
-        let mut end_idents = vec![];
+        let (start, end) = parse_group_pattern(tokens, Some(TknType::CloseParen), parse_identifier_pattern, &mut peek)?;
 
-        let mut stage = GroupPatternParsingStage::Start;
-        let mut discard_many = None;
+        *index = peek;
+        return Ok(Pattern::Tuple { start, end });
+    } else if tokens::is_expected_token(tokens, TknType::OpenSquareBracket, &mut peek) {
+        let (start, end) = parse_group_pattern(tokens, Some(TknType::CloseSquareBracket), parse_identifier_pattern, &mut peek)?;
 
-        loop {
-            if tokens::is_expected_token(tokens, TknType::CloseParen, &mut peek) {
-                break;
-            } else if tokens::is_expected_token(tokens, TknType::DiscardMany, &mut peek) {
-                if stage == GroupPatternParsingStage::Start {
-                    stage = GroupPatternParsingStage::End;
-                    discard_many = Some(&tokens[peek - 1]);
-                    continue;
-                }
+        *index = peek;
+        return Ok(Pattern::Array { start, end });
+    } else if tokens::is_expected_token(tokens, TknType::DiscardSingle, &mut peek) {
+        *index = peek;
+        return Ok(Pattern::DiscardSingle);
+    } else if tokens::is_expected_token(tokens, TknType::Dollar, &mut peek) {
+        let (start, end) = parse_group_pattern(tokens, None, parse_identifier_pattern, &mut peek)?;
 
-                return Err(ParserError::SecondDiscardMany { 
-                    tkn: &tokens[peek - 1], 
-                    first_discard_many: unsafe { discard_many.unwrap_unchecked() }
-                });
-            }
+        *index = peek;
+        return Ok(Pattern::AmbiguousGroup { start, end });
+    }
 
-            let ident = parse_identifier_pattern(tokens, &mut peek)?;
-            match stage {
-                GroupPatternParsingStage::Start => start_idents.push(ident),
-                GroupPatternParsingStage::End => end_idents.push(ident),
+    return Err(ParserError::InvalidPattern { tkn: &tokens[peek] });
+}
+
+/// Parses the elements of a tuple or array pattern after its opening bracket, up to and including
+/// `close`.  Without `close`, the group was opened with a `$` and ends at the first token that 
+/// cannot start a pattern.  The elements after a `..` make up the end of the group.
+fn parse_group_pattern<'tkns, 'bumps, 'defs>(
+    tokens: &'tkns [Tkn],
+    close: Option<TknType>,
+    parse_element: fn(&'tkns [Tkn], &mut usize) -> Result<Pattern<'tkns>, ParserError<'tkns, 'bumps, 'defs>>,
+    index: &mut usize
+) -> Result<(Vec<Pattern<'tkns>>, Vec<Pattern<'tkns>>), ParserError<'tkns, 'bumps, 'defs>> {
+    #[derive(PartialEq)]
+    enum GroupPatternParsingStage {
+        Start, End
+    }
+
+    let mut peek = *index;
+    let mut start_idents = vec![];
+    let mut end_idents = vec![];
+
+    let mut stage = GroupPatternParsingStage::Start;
+    let mut discard_many = None;
+
+    loop {
+        let end_of_group = match &close {
+            Some(close) => tokens::is_expected_token(tokens, close.clone(), &mut peek),
+            None => !starts_pattern(tokens, peek)
+        };
+
+        if end_of_group {
+            break;
+        } else if tokens::is_expected_token(tokens, TknType::DiscardMany, &mut peek) {
+            if stage == GroupPatternParsingStage::Start {
+                stage = GroupPatternParsingStage::End;
+                discard_many = Some(&tokens[peek - 1]);
+                tokens::is_expected_token(tokens, TknType::Comma, &mut peek);
+                continue;
             }
+
+            return Err(ParserError::SecondDiscardMany { 
+                tkn: &tokens[peek - 1], 
+                first_discard_many: unsafe { discard_many.unwrap_unchecked() }
+            });
         }
 
-        return Ok(Pattern::Tuple { 
-            start: start_idents, 
-            end: end_idents 
-        });
+        let ident = parse_element(tokens, &mut peek)?;
+        match stage {
+            GroupPatternParsingStage::Start => start_idents.push(ident),
+            GroupPatternParsingStage::End => end_idents.push(ident),
+        }
+        // elements are separated by whitespace, like in array expressions, or by commas
+        tokens::is_expected_token(tokens, TknType::Comma, &mut peek);
+    }
+
+    *index = peek;
+    return Ok((start_idents, end_idents));
+}
+
+fn starts_pattern(tokens: &[Tkn], index: usize) -> bool {
+    let Some(tkn) = tokens::get_token(tokens, index) else {
+        return false;
+    };
+    return tkn.token == TknType::DiscardMany || matches!(tkn.token, 
+        TknType::Identifier(_) | TknType::Keyword(Kwrd::Mutable) | TknType::DiscardSingle | TknType::Dollar |
+        TknType::OpenParen | TknType::OpenSquareBracket | TknType::OpenCurlyBrace | 
+        TknType::IntegerLiteral { .. } | TknType::FloatLiteral { .. } | TknType::CharLiteral(_) | 
+        TknType::StringLiteral(_) | TknType::BooleanLiteral(_) | TknType::Operation(Op::Minus)
+    );
+}
+
+/// Parses the pattern of a `match` arm.  On top of what [`parse_identifier_pattern`] accepts, 
+/// these can be literals, ranges, struct patterns and enum variants.
+pub fn parse_pattern<'tkns, 'bumps, 'defs>(
+    tokens: &'tkns [Tkn], 
+    index: &mut usize
+) -> Result<Pattern<'tkns>, ParserError<'tkns, 'bumps, 'defs>> {
+    let mut peek = *index;
+    let tkn = &tokens[peek];
+
+    if let Some(start) = parse_literal(tokens, &mut peek) {
+        let range = if tokens::is_expected_token(tokens, TknType::Operation(Op::BangRangeEquals), &mut peek) {
+            Some((true, true))
+        } else if tokens::is_expected_token(tokens, TknType::Operation(Op::BangRange), &mut peek) {
+            Some((true, false))
+        } else if tokens::is_expected_token(tokens, TknType::Operation(Op::RangeEquals), &mut peek) {
+            Some((false, true))
+        } else if tokens::is_expected_token(tokens, TknType::Operation(Op::Range), &mut peek) {
+            Some((false, false))
+        } else {
+            None
+        };
+
+        let Some((exclusive_start, inclusive_end)) = range else {
+            *index = peek;
+            return Ok(Pattern::Literal { tkn, literal: start });
+        };
+
+        let end = parse_literal(tokens, &mut peek)
+            .ok_or_else(|| ParserError::InvalidPattern { tkn: &tokens[peek] })?;
+
+        *index = peek;
+        return Ok(Pattern::Range { tkn, start, end, exclusive_start, inclusive_end });
+    }
+
+    if let TknType::Identifier(name) = &tkn.token {
+        if tokens::is_token(tokens, TknType::ColonColon, peek + 1) {
+            peek += 2;
+            let variant_tkn = &tokens[peek];
+            let TknType::Identifier(variant_name) = &variant_tkn.token else {
+                return Err(ParserError::ExpectedIdentifier { tkn: variant_tkn });
+            };
+            peek += 1;
+
+            let payload = if tokens::is_token(tokens, TknType::OpenParen, peek) 
+                || tokens::is_token(tokens, TknType::OpenCurlyBrace, peek) 
+            {
+                Some(Box::new(parse_pattern(tokens, &mut peek)?))
+            } else {
+                None
+            };
+
+            *index = peek;
+            return Ok(Pattern::Variant { 
+                tkn, 
+                enum_name: name.clone(), 
+                variant_tkn, 
+                variant_name: variant_name.clone(), 
+                payload 
+            });
+        } else if tokens::is_token(tokens, TknType::OpenCurlyBrace, peek + 1) {
+            peek += 2;
+            let (fields, rest) = parse_field_patterns(tokens, parse_pattern, &mut peek)?;
+
+            *index = peek;
+            return Ok(Pattern::Struct { tkn, name: name.clone(), fields, rest });
+        }
+    }
+
+    if tokens::is_expected_token(tokens, TknType::OpenCurlyBrace, &mut peek) {
+        let (fields, rest) = parse_field_patterns(tokens, parse_pattern, &mut peek)?;
+
+        *index = peek;
+        return Ok(Pattern::AnonymousStruct { tkn, fields, rest });
+    } else if tokens::is_expected_token(tokens, TknType::OpenParen, &mut peek) {
+        let (start, end) = parse_group_pattern(tokens, Some(TknType::CloseParen), parse_pattern, &mut peek)?;
+
+        *index = peek;
+        return Ok(Pattern::Tuple { start, end });
     } else if tokens::is_expected_token(tokens, TknType::OpenSquareBracket, &mut peek) {
-        let mut start_idents = vec![];
-        let mut end_idents = vec![];
-        
-        let mut stage = GroupPatternParsingStage::Start;
-        let mut discard_many = None;
-        
-        loop {
-            if tokens::is_expected_token(tokens, TknType::CloseSquareBracket, &mut peek) {
+        let (start, end) = parse_group_pattern(tokens, Some(TknType::CloseSquareBracket), parse_pattern, &mut peek)?;
+
+        *index = peek;
+        return Ok(Pattern::Array { start, end });
+    } else if tokens::is_expected_token(tokens, TknType::Dollar, &mut peek) {
+        let (start, end) = parse_group_pattern(tokens, None, parse_pattern, &mut peek)?;
+
+        *index = peek;
+        return Ok(Pattern::AmbiguousGroup { start, end });
+    }
+
+    return parse_identifier_pattern(tokens, index);
+}
+
+/// Parses a literal, or a `-` followed by an integer or float literal.
+fn parse_literal(tokens: &[Tkn], index: &mut usize) -> Option<Lit> {
+    let negative = tokens::is_token(tokens, TknType::Operation(Op::Minus), *index);
+    let literal = match &tokens::get_token(tokens, *index + negative as usize)?.token {
+        TknType::IntegerLiteral { int, .. } => Lit::IntegerLiteral(if negative { -int } else { *int }),
+        TknType::FloatLiteral { float, .. } => Lit::FloatLiteral(if negative { -float } else { *float }),
+        TknType::CharLiteral(chr) if !negative => Lit::CharLiteral(*chr),
+        TknType::StringLiteral(string) if !negative => Lit::StringLiteral(string.clone()),
+        TknType::BooleanLiteral(bool) if !negative => Lit::BooleanLiteral(*bool),
+        _ => return None
+    };
+    *index += 1 + negative as usize;
+    return Some(literal);
+}
+
+/// Parses the fields of a struct pattern after its `{`, up to and including the `}`, with 
+/// `parse_element` parsing the pattern after a `field:`.  Returns whether the fields end with a `..`.
+fn parse_field_patterns<'tkns, 'bumps, 'defs>(
+    tokens: &'tkns [Tkn],
+    parse_element: fn(&'tkns [Tkn], &mut usize) -> Result<Pattern<'tkns>, ParserError<'tkns, 'bumps, 'defs>>,
+    index: &mut usize
+) -> Result<(Vec<FieldPattern<'tkns>>, bool), ParserError<'tkns, 'bumps, 'defs>> {
+    let mut peek = *index;
+    let mut fields: Vec<FieldPattern> = vec![];
+    let mut rest = false;
+
+    loop {
+        if tokens::is_expected_token(tokens, TknType::CloseCurlyBrace, &mut peek) {
+            break;
+        }
+
+        if !fields.is_empty() || rest {
+            tokens::expect_token(tokens, TknType::Comma, &mut peek)
+                .ok_or_else(|| ParserError::ExpectedTokens { 
+                    tkn: &tokens[peek], 
+                    received: tokens[peek..].iter().map(|tkn| &tkn.token),
+                    expected: &[TknType::Comma, TknType::CloseCurlyBrace] 
+                })?;
+            if tokens::is_expected_token(tokens, TknType::CloseCurlyBrace, &mut peek) {
                 break;
-            } else if tokens::is_expected_token(tokens, TknType::DiscardMany, &mut peek) {
-                if stage == GroupPatternParsingStage::Start {
-                    stage = GroupPatternParsingStage::End;
-                    discard_many = Some(&tokens[peek - 1]);
-                    continue;
-                }
-                
-                return Err(ParserError::SecondDiscardMany { 
-                    tkn: &tokens[peek - 1], 
-                    first_discard_many: unsafe { discard_many.unwrap_unchecked() } 
-                });
             }
+        }
 
-            let ident = parse_identifier_pattern(tokens, &mut peek)?;
-            match stage {
-                GroupPatternParsingStage::Start => start_idents.push(ident),
-                GroupPatternParsingStage::End => end_idents.push(ident),
-            }
+        if rest {
+            return Err(ParserError::ExpectedToken { 
+                tkn: &tokens[peek], 
+                expected: TknType::CloseCurlyBrace 
+            });
         }
 
-        return Ok(Pattern::Array {
-            start: start_idents, 
-            end: end_idents
-        });
-    } else if tokens::is_expected_token(tokens, TknType::DiscardSingle, &mut peek) {
-        return Ok(Pattern::DiscardSingle);
-    } else if tokens::is_expected_token(tokens, TknType::Dollar, &mut peek) {
-        todo!("dollar not implemented yet for pattern matching");
+        if tokens::is_expected_token(tokens, TknType::DiscardMany, &mut peek) {
+            rest = true;
+            continue;
+        }
+
+        let start_field = peek;
+        let mutable = tokens::is_expected_token(tokens, TknType::Keyword(Kwrd::Mutable), &mut peek);
+        let name_index = peek;
+        let tkn = &tokens[peek];
+        let TknType::Identifier(field_name) = &tkn.token else {
+            return Err(ParserError::ExpectedIdentifier { tkn });
+        };
+        peek += 1;
+
+        if let Some(defined_field) = fields.iter().find(|field| field.field_name == *field_name) {
+            return Err(ParserError::AlreadyDefinedField { tkn, defined_field: defined_field.tkn });
+        }
+
+        let pattern = if !mutable && tokens::is_expected_token(tokens, TknType::Colon, &mut peek) {
+            parse_element(tokens, &mut peek)?
+        } else {
+            Pattern::Ident { tkn: &tokens[start_field], name_index, mutable, name: field_name.clone() }
+        };
+
+        fields.push(FieldPattern { tkn, field_name: field_name.clone(), pattern });
     }
 
-    return Err(ParserError::InvalidPattern { tkn: &tokens[peek] });
+    *index = peek;
+    return Ok((fields, rest));
 }
 
 pub fn declare_variable_pattern<'tkns, 'bumps, 'defs, 'sfda, 'i>(
     expr_bump: &'bumps ExprBump,
     stmt_bump: &'bumps StmtBump,
+    structs: &'defs [Struct],
     variables: &'sfda StackFrameDictAllocator<'i, String, VariableData<'tkns, 'bumps>>,
     ident: &mut Pattern<'tkns>,
     stack_location: StackLocation,
@@ -142,7 +469,7 @@ pub fn declare_variable_pattern<'tkns, 'bumps, 'defs, 'sfda, 'i>(
     let mut stmts: Vec<&StmtData> = vec![];
     
     match ident {
-        Pattern::Ident {tkn, mutable, name} => {
+        Pattern::Ident {tkn, mutable, name, ..} => {
             let expr_type = expr_bump.alloc(RefCell::new(expr_type));
             
             stmts.push(stmt_bump.alloc(StmtData {
@@ -217,6 +544,7 @@ pub fn declare_variable_pattern<'tkns, 'bumps, 'defs, 'sfda, 'i>(
                 
                 stmts.extend(declare_variable_pattern(
                     expr_bump, stmt_bump, 
+                    structs,
                     variables, 
                     ident, 
                     stack_location, 
@@ -250,6 +578,7 @@ pub fn declare_variable_pattern<'tkns, 'bumps, 'defs, 'sfda, 'i>(
             for ident in start.into_iter().chain(end) {
                 stmts.extend(declare_variable_pattern(
                     expr_bump, stmt_bump, 
+                    structs,
                     variables, 
                     ident, 
                     stack_location, 
@@ -260,7 +589,118 @@ pub fn declare_variable_pattern<'tkns, 'bumps, 'defs, 'sfda, 'i>(
                 )?);
             } 
         },
-        Pattern::AmbiguousGroup { .. } => todo!("not implemented yet"),
+        Pattern::AmbiguousGroup { start, end } => {
+            match expr_type {
+                ExprType::Tuple { .. } => *ident = Pattern::Tuple { start: start.clone(), end: end.clone() },
+                ExprType::Array { .. } => *ident = Pattern::Array { start: start.clone(), end: end.clone() },
+                // whether this is a tuple or an array is only known once the value is assigned
+                ExprType::AmbiguousType => {
+                    for ident in start.iter_mut().chain(end) {
+                        stmts.extend(declare_variable_pattern(
+                            expr_bump, stmt_bump, 
+                            structs,
+                            variables, 
+                            ident, 
+                            stack_location, 
+                            ExprType::AmbiguousType, 
+                            tokens, 
+                            index,
+                            line
+                        )?);
+                    }
+                    return Ok(stmts);
+                },
+                _ => return Err(ParserError::PatternNotMatchExpectedType { 
+                    tkn: &tokens[index], 
+                    pattern: ident.clone(), 
+                    expected_type: expr_type 
+                }),
+            }
+
+            return declare_variable_pattern(
+                expr_bump, stmt_bump, 
+                structs, 
+                variables, 
+                ident, 
+                stack_location, 
+                expr_type, 
+                tokens, 
+                index, 
+                line
+            );
+        },
+        Pattern::Struct { tkn, name, fields, rest } => {
+            let custom_struct = structs.iter()
+                .find(|custom_struct| custom_struct.name == *name)
+                .ok_or(ParserError::ExpectedType { tkn: *tkn })?;
+            let type_args = match &expr_type {
+                ExprType::AmbiguousType => vec![ExprType::AmbiguousType; custom_struct.type_params.len()],
+                ExprType::Custom { ident, type_args } if ident == name => type_args.clone(),
+                _ => return Err(ParserError::PatternNotMatchExpectedType { 
+                    tkn: *tkn, 
+                    pattern: ident.clone(), 
+                    expected_type: expr_type 
+                })
+            };
+
+            let field_types = field_pattern_types(
+                fields, 
+                *rest, 
+                &struct_field_types(custom_struct, &type_args), 
+                *tkn, 
+                |tkn| ParserError::FieldDoesNotExist { tkn, custom_struct }
+            )?;
+            check_field_patterns(custom_struct, fields)?;
+            for (FieldPattern { pattern, .. }, expr_type) in fields.iter_mut().zip(field_types) {
+                stmts.extend(declare_variable_pattern(
+                    expr_bump, stmt_bump, 
+                    structs,
+                    variables, 
+                    pattern, 
+                    stack_location, 
+                    expr_type, 
+                    tokens, 
+                    index,
+                    line
+                )?);
+            }
+        },
+        Pattern::AnonymousStruct { tkn, fields, rest } => {
+            let pattern = Pattern::AnonymousStruct { tkn: *tkn, fields: fields.clone(), rest: *rest };
+            let field_types = match expr_type {
+                // the fields are checked against the value once it is assigned
+                ExprType::AmbiguousType => vec![ExprType::AmbiguousType; fields.len()],
+                ExprType::AnonymousCustom { fields: ref types } => field_pattern_types(
+                    fields, 
+                    *rest, 
+                    types, 
+                    *tkn, 
+                    |field_tkn| ParserError::PatternNotMatchExpectedType { 
+                        tkn: field_tkn, 
+                        pattern: pattern.clone(), 
+                        expected_type: expr_type.clone() 
+                    }
+                )?,
+                _ => return Err(ParserError::PatternNotMatchExpectedType { tkn: *tkn, pattern, expected_type: expr_type }),
+            };
+
+            for (FieldPattern { pattern, .. }, expr_type) in fields.iter_mut().zip(field_types) {
+                stmts.extend(declare_variable_pattern(
+                    expr_bump, stmt_bump, 
+                    structs,
+                    variables, 
+                    pattern, 
+                    stack_location, 
+                    expr_type, 
+                    tokens, 
+                    index,
+                    line
+                )?);
+            }
+        },
+        Pattern::Literal { .. } |
+        Pattern::Range { .. } |
+        Pattern::Variant { .. } => unreachable!("only match arms are parsed with parse_pattern"),
         Pattern::DiscardSingle => ()
     }
 
@@ -270,6 +710,7 @@ pub fn declare_variable_pattern<'tkns, 'bumps, 'defs, 'sfda, 'i>(
 pub fn assign_variable_pattern<'tkns, 'bumps, 'defs, 'sfda, 'i>(
     expr_bump: &'bumps ExprBump,
     stmt_bump: &'bumps StmtBump,
+    structs: &'defs [Struct],
     variables: &'sfda StackFrameDictAllocator<'i, String, VariableData<'tkns, 'bumps>>,
     declaration: bool,
     ident: &mut Pattern<'tkns>,
@@ -286,7 +727,7 @@ pub fn assign_variable_pattern<'tkns, 'bumps, 'defs, 'sfda, 'i>(
     let Expr { expr_data, mut expr_type, .. } = expr;
 
     match ident {
-        Pattern::Ident { mutable, name, .. } => {
+        Pattern::Ident { name_index, mutable, name, .. } => {
             let VariableData {
                 tkn: ident_tkn,
                 mutable: ident_mut,
@@ -314,13 +755,14 @@ pub fn assign_variable_pattern<'tkns, 'bumps, 'defs, 'sfda, 'i>(
                     calculated_type: ident_type.clone_inner(), 
                     expected_type: expr_type.clone_inner() 
                 })?;
+            pin_range_bounds(expr_bump, expr_data, &expr_type);
 
             stmts.push(stmt_bump.alloc(StmtData {
                 line,
                 stmt: Stmt::Assign {
                     variable: Expr {
                         line,
-                        expr_data: expr_bump.alloc(ExprData::Identifier(name.clone())),
+                        expr_data: expr_bump.alloc(ExprData::Identifier(name.clone(), *name_index)),
                         expr_type: expr_type.clone()
                     },
                     assign: Expr {
@@ -331,15 +773,747 @@ pub fn assign_variable_pattern<'tkns, 'bumps, 'defs, 'sfda, 'i>(
                 }
             }));
         },
-        Pattern::Tuple { start: _, end: _ } => {
-            todo!("not implemented yet");
+        Pattern::Tuple { start, end } => {
+            expr_type.default_element_literal_types(expr_bump);
+            let ExprType::Tuple { start: types, .. } = expr_type.clone_inner() else {
+                return Err(ParserError::PatternNotMatchExpectedType { 
+                    tkn: &tokens[index], 
+                    pattern: ident.clone(), 
+                    expected_type: expr_type.clone_inner() 
+                });
+            };
+            if start.len() + end.len() > types.len() {
+                return Err(ParserError::PatternNotMatchExpectedType { 
+                    tkn: &tokens[index], 
+                    pattern: ident.clone(), 
+                    expected_type: expr_type.clone_inner() 
+                });
+            }
+
+            let value = store_destructured(expr_bump, stmt_bump, Expr { line, expr_data, expr_type }, index, &mut stmts);
+            let end_start = types.len() - end.len();
+            for (ident, element) in start.iter_mut().zip(0..).chain(end.iter_mut().zip(end_start..)) {
+                stmts.extend(assign_variable_pattern(
+                    expr_bump, stmt_bump, 
+                    structs,
+                    variables, 
+                    declaration, 
+                    ident, 
+                    element_expr(expr_bump, &value, element, types[element].clone()), 
+                    tokens, 
+                    index, 
+                    line
+                )?);
+            }
+        },
+        Pattern::Array { start, end } => {
+            let ExprType::Array { length, expr_type: element_type } = expr_type.clone_inner() else {
+                return Err(ParserError::PatternNotMatchExpectedType { 
+                    tkn: &tokens[index], 
+                    pattern: ident.clone(), 
+                    expected_type: expr_type.clone_inner() 
+                });
+            };
+            // without a length, only the elements at the start of the array can be found
+            let length = match length {
+                Some(length) if start.len() + end.len() <= length => length,
+                None if end.is_empty() => start.len(),
+                _ => return Err(ParserError::PatternNotMatchExpectedType { 
+                    tkn: &tokens[index], 
+                    pattern: ident.clone(), 
+                    expected_type: expr_type.clone_inner() 
+                }),
+            };
+
+            let value = store_destructured(expr_bump, stmt_bump, Expr { line, expr_data, expr_type }, index, &mut stmts);
+            let end_start = length - end.len();
+            for (ident, element) in start.iter_mut().zip(0..).chain(end.iter_mut().zip(end_start..)) {
+                stmts.extend(assign_variable_pattern(
+                    expr_bump, stmt_bump, 
+                    structs,
+                    variables, 
+                    declaration, 
+                    ident, 
+                    element_expr(expr_bump, &value, element, (*element_type).clone()), 
+                    tokens, 
+                    index, 
+                    line
+                )?);
+            }
+        },
+        Pattern::AmbiguousGroup { start, end } => {
+            match &*expr_type.get() {
+                ExprType::Tuple { .. } => *ident = Pattern::Tuple { start: start.clone(), end: end.clone() },
+                ExprType::Array { .. } => *ident = Pattern::Array { start: start.clone(), end: end.clone() },
+                expr_type => return Err(ParserError::PatternNotMatchExpectedType { 
+                    tkn: &tokens[index], 
+                    pattern: ident.clone(), 
+                    expected_type: expr_type.clone() 
+                }),
+            }
+
+            return assign_variable_pattern(
+                expr_bump, stmt_bump, 
+                structs,
+                variables, 
+                declaration, 
+                ident, 
+                Expr { line, expr_data, expr_type }, 
+                tokens, 
+                index, 
+                line
+            );
         },
-        Pattern::Array { start: _, end: _ } => {
-            todo!("not implemented yet");
+        Pattern::Struct { tkn, name, fields, rest } => {
+            let custom_struct = structs.iter()
+                .find(|custom_struct| custom_struct.name == *name)
+                .ok_or(ParserError::ExpectedType { tkn: *tkn })?;
+            let pattern_type = ExprType::Custom { 
+                ident: name.clone(), 
+                type_args: vec![ExprType::AmbiguousType; custom_struct.type_params.len()] 
+            };
+            if expr_type.match_type(&mut ExprTypeCons::new(expr_bump, pattern_type)).is_none() {
+                return Err(ParserError::PatternNotMatchExpectedType { 
+                    tkn: *tkn, 
+                    pattern: ident.clone(), 
+                    expected_type: expr_type.clone_inner() 
+                });
+            }
+            let ExprType::Custom { type_args, .. } = expr_type.clone_inner() else {
+                unreachable!("the value was just matched against the struct");
+            };
+            field_pattern_types(
+                fields, 
+                *rest, 
+                &struct_field_types(custom_struct, &type_args), 
+                *tkn, 
+                |tkn| ParserError::FieldDoesNotExist { tkn, custom_struct }
+            )?;
+            check_field_patterns(custom_struct, fields)?;
+
+            let value = store_destructured(expr_bump, stmt_bump, Expr { line, expr_data, expr_type }, index, &mut stmts);
+            for FieldPattern { field_name, pattern, .. } in fields {
+                let field = custom_struct.fields.iter()
+                    .find(|field| field.field_name == *field_name)
+                    .expect("field patterns should have been checked against the struct");
+                let field_value = Expr {
+                    line,
+                    expr_data: expr_bump.alloc(ExprData::CustomField { data: expr_bump.alloc(value.clone()), field }),
+                    expr_type: ExprTypeCons::new(expr_bump, custom_struct.field_type(field, &type_args))
+                };
+
+                stmts.extend(assign_variable_pattern(
+                    expr_bump, stmt_bump, 
+                    structs,
+                    variables, 
+                    declaration, 
+                    pattern, 
+                    field_value, 
+                    tokens, 
+                    index, 
+                    line
+                )?);
+            }
         },
-        Pattern::AmbiguousGroup { .. } => todo!("not implemented yet"),
-        Pattern::DiscardSingle => ()
+        Pattern::AnonymousStruct { tkn, fields, rest } => {
+            let pattern = Pattern::AnonymousStruct { tkn: *tkn, fields: fields.clone(), rest: *rest };
+            let ExprType::AnonymousCustom { fields: types } = expr_type.clone_inner() else {
+                return Err(ParserError::PatternNotMatchExpectedType { 
+                    tkn: *tkn, 
+                    pattern, 
+                    expected_type: expr_type.clone_inner() 
+                });
+            };
+            field_pattern_types(
+                fields, 
+                *rest, 
+                &types, 
+                *tkn, 
+                |field_tkn| ParserError::PatternNotMatchExpectedType { 
+                    tkn: field_tkn, 
+                    pattern: pattern.clone(), 
+                    expected_type: expr_type.clone_inner() 
+                }
+            )?;
+
+            let value = store_destructured(expr_bump, stmt_bump, Expr { line, expr_data, expr_type }, index, &mut stmts);
+            for FieldPattern { field_name, pattern, .. } in fields {
+                let (_, field_type) = types.iter()
+                    .find(|(name, _)| name == field_name)
+                    .expect("field patterns should have been checked against the anonymous struct");
+                let field_value = Expr {
+                    line,
+                    expr_data: expr_bump.alloc(ExprData::AnonymousCustomField { 
+                        data: expr_bump.alloc(value.clone()), 
+                        field_name: field_name.clone() 
+                    }),
+                    expr_type: ExprTypeCons::new(expr_bump, field_type.clone())
+                };
+
+                stmts.extend(assign_variable_pattern(
+                    expr_bump, stmt_bump, 
+                    structs,
+                    variables, 
+                    declaration, 
+                    pattern, 
+                    field_value, 
+                    tokens, 
+                    index, 
+                    line
+                )?);
+            }
+        },
+        Pattern::Literal { .. } |
+        Pattern::Range { .. } |
+        Pattern::Variant { .. } => unreachable!("only match arms are parsed with parse_pattern"),
+        // the value is still computed for its side effects, like in `let _ = f();`
+        Pattern::DiscardSingle => stmts.push(stmt_bump.alloc(StmtData {
+            line,
+            stmt: Stmt::Expr(Expr { line, expr_data, expr_type })
+        }))
     };
     
     return Ok(stmts);
-}
\ No newline at end of file
+}
+/// Rejects a struct pattern naming fields that are not accessible from where it is written.
+fn check_field_patterns<'tkns, 'bumps, 'defs>(
+    custom_struct: &'defs Struct, 
+    fields: &[FieldPattern<'tkns>]
+) -> Result<(), ParserError<'tkns, 'bumps, 'defs>> {
+    for FieldPattern { tkn, field_name, .. } in fields {
+        if let Some(field) = custom_struct.fields.iter().find(|field| field.field_name == *field_name) {
+            accessors::check_field(tkn, custom_struct, field)?;
+        }
+    }
+    return Ok(());
+}
+
+fn struct_field_types(custom_struct: &Struct, type_args: &[ExprType]) -> Vec<(String, ExprType)> {
+    return custom_struct.fields.iter()
+        .map(|field| (field.field_name.clone(), custom_struct.field_type(field, type_args)))
+        .collect();
+}
+
+/// The types of the fields a struct pattern names, out of the `declared` fields of the struct.  
+/// Without a `..`, the pattern has to name every declared field.
+fn field_pattern_types<'tkns, 'bumps, 'defs>(
+    fields: &[FieldPattern<'tkns>],
+    rest: bool,
+    declared: &[(String, ExprType)],
+    tkn: &'tkns Tkn,
+    unknown_field: impl Fn(&'tkns Tkn) -> ParserError<'tkns, 'bumps, 'defs>
+) -> Result<Vec<ExprType>, ParserError<'tkns, 'bumps, 'defs>> {
+    let mut field_types = vec![];
+    for field in fields {
+        let (_, field_type) = declared.iter()
+            .find(|(name, _)| *name == field.field_name)
+            .ok_or_else(|| unknown_field(field.tkn))?;
+        field_types.push(field_type.clone());
+    }
+
+    if !rest && let Some((field_name, _)) = declared.iter()
+        .find(|(name, _)| !fields.iter().any(|field| field.field_name == *name)) 
+    {
+        return Err(ParserError::MissingFieldPattern { tkn, field_name: field_name.clone() });
+    }
+    return Ok(field_types);
+}
+
+/// A range only holds a copy of the type of its bounds, so once a range literal is stored its 
+/// bounds are pinned to the type of the range, or both to the type their literals default to.
+fn pin_range_bounds<'bumps>(
+    expr_bump: &'bumps ExprBump,
+    expr_data: &ExprData<'bumps, '_>,
+    expr_type: &ExprTypeCons<'bumps>
+) {
+    let (ExprData::BinaryOp(op, start, end), ExprType::Range { expr_type: element_type, .. }) = (
+        expr_data, 
+        expr_type.clone_inner()
+    ) else {
+        return;
+    };
+    let mut bounds = start.expr_type.clone().match_type(&mut end.expr_type.clone())
+        .expect("range bounds are matched when the range is parsed");
+    bounds.match_type(&mut ExprTypeCons::new(expr_bump, *element_type));
+    bounds.default_literal_type(expr_bump);
+    expr_type.clone().match_type(&mut ExprTypeCons::new(expr_bump, op.range_type(bounds.clone_inner())));
+}
+
+/// Destructuring reads the value once for every part a pattern names, so a value that is not 
+/// already a variable or a field is first stored in a variable sugar code cannot name, which is 
+/// written at the pattern starting at the token at `index`.
+fn store_destructured<'bumps, 'defs>(
+    expr_bump: &'bumps ExprBump,
+    stmt_bump: &'bumps StmtBump,
+    mut value: Expr<'bumps, 'defs>,
+    index: usize,
+    stmts: &mut Vec<&'bumps StmtData<'bumps, 'defs>>
+) -> Expr<'bumps, 'defs> {
+    if expr::is_place(value.expr_data) {
+        return value;
+    }
+
+    let line = value.line;
+    let name = String::from("$destructured");
+    let stored_type = expr_bump.alloc(RefCell::new(ExprType::AmbiguousType));
+    let expr_type = ExprTypeCons::new_stored(stored_type).match_type(&mut value.expr_type)
+        .expect("an ambiguous type matches every type");
+
+    stmts.push(stmt_bump.alloc(StmtData {
+        line,
+        stmt: Stmt::Declare(name.clone(), StackLocation::GC, stored_type)
+    }));
+    let variable = Expr { line, expr_data: expr_bump.alloc(ExprData::Identifier(name, index)), expr_type };
+    stmts.push(stmt_bump.alloc(StmtData {
+        line,
+        stmt: Stmt::Assign { variable: variable.clone(), assign: value }
+    }));
+    return variable;
+}
+
+/// `value[element]`, reading an element of a group, such as one a pattern destructures or `t.0`.
+pub fn element_expr<'bumps, 'defs>(
+    expr_bump: &'bumps ExprBump,
+    value: &Expr<'bumps, 'defs>,
+    element: usize,
+    expr_type: ExprType
+) -> Expr<'bumps, 'defs> {
+    let line = value.line;
+    return Expr {
+        line,
+        expr_data: expr_bump.alloc(ExprData::Index { 
+            expr: value.clone(), 
+            index: Expr {
+                line,
+                expr_data: expr_bump.alloc(ExprData::Literal(Lit::IntegerLiteral(element as i128))),
+                expr_type: ExprTypeCons::new(expr_bump, ExprType::USize)
+            }
+        }),
+        expr_type: ExprTypeCons::new(expr_bump, expr_type)
+    };
+}
+
+/// Parses `match scrutinee { pattern if guard => body, .. }` starting at the `match`, where 
+/// `parse_body` parses the body of an arm while the variables its pattern binds are in scope.  
+/// The arms have to cover every value of the scrutinee, and each arm has to match a value 
+/// that the arms before it do not.
+pub fn parse_match<'tkns, 'bumps, 'defs, Body>(
+    expr_bump: &'bumps ExprBump,
+    structs: &'defs [Struct],
+    enums: &'defs [Enum],
+    tokens: &'tkns [Tkn],
+    index: &mut usize,
+    line: usize,
+    functions: &RefCell<HashMap<String, FullFnDef<'tkns, 'bumps, 'defs>>>,
+    variables: &StackFrameDictAllocator<'_, String, VariableData<'tkns, 'bumps>>,
+    mut parse_body: impl FnMut(
+        &StackFrameDictAllocator<'_, String, VariableData<'tkns, 'bumps>>, 
+        &mut usize
+    ) -> Result<Body, ParserError<'tkns, 'bumps, 'defs>>
+) -> Result<
+    (Expr<'bumps, 'defs>, Vec<MatchArm<'bumps, 'defs, Body>>), 
+    ParserError<'tkns, 'bumps, 'defs>
+> {
+    let mut peek = *index;
+    let match_tkn = &tokens[peek];
+
+    tokens::expect_token(tokens, TknType::Keyword(Kwrd::Match), &mut peek)
+        .ok_or_else(|| ParserError::ExpectedToken { 
+            tkn: match_tkn, 
+            expected: TknType::Keyword(Kwrd::Match) 
+        })?;
+
+    let mut scrutinee = expr::parse_expression_set(
+        expr_bump, 
+        structs, 
+        enums, 
+        tokens, 
+        &mut peek, 
+        line, 
+        functions, 
+        variables
+    )?;
+    // integer literals in the patterns are checked against the range of the scrutinee's type
+    if matches!(*scrutinee.expr_type.get(), ExprType::AmbiguousPosInteger | ExprType::AmbiguousNegInteger) {
+        scrutinee.expr_type.match_type(&mut ExprTypeCons::new(expr_bump, ExprType::I32));
+    }
+    scrutinee.expr_type.default_element_literal_types(expr_bump);
+
+    tokens::expect_token(tokens, TknType::OpenCurlyBrace, &mut peek)
+        .ok_or_else(|| ParserError::ExpectedToken { 
+            tkn: &tokens[peek], 
+            expected: TknType::OpenCurlyBrace 
+        })?;
+    let open_brace = &tokens[peek - 1];
+
+    let mut arms = vec![];
+    let mut arm_tkns = vec![];
+    loop {
+        if tokens::is_expected_token(tokens, TknType::CloseCurlyBrace, &mut peek) {
+            break;
+        } else if tokens::is_token(tokens, TknType::EndOfFile, peek) {
+            return Err(ParserError::ExpectedClosingBrace { tkn: &tokens[peek], open_brace });
+        }
+
+        let arm_tkn = &tokens[peek];
+        let pattern = parse_pattern(tokens, &mut peek)?;
+
+        let mut arm = None;
+        variables.new_scope(|variables| {
+            arm = Some((|| {
+                let pattern = check_match_pattern(
+                    expr_bump, 
+                    structs, 
+                    enums, 
+                    &variables, 
+                    &pattern, 
+                    &mut scrutinee.expr_type.clone(), 
+                    arm_tkn, 
+                    &mut vec![]
+                )?;
+
+                let guard = if tokens::is_expected_token(tokens, TknType::Keyword(Kwrd::If), &mut peek) {
+                    let start_expr = peek;
+                    let guard = expr::parse_expression_set(
+                        expr_bump, 
+                        structs, 
+                        enums, 
+                        tokens, 
+                        &mut peek, 
+                        line, 
+                        functions, 
+                        &variables
+                    )?;
+
+                    if *guard.expr_type.get() != ExprType::Bool {
+                        return Err(ParserError::CouldNotMatchType { 
+                            tkns: &tokens[start_expr..peek], 
+                            calculated_type: guard.expr_type.clone_inner(), 
+                            expected_type: ExprType::Bool 
+                        });
+                    }
+                    Some(guard)
+                } else {
+                    None
+                };
+
+                tokens::expect_token(tokens, TknType::Operation(Op::Arrow), &mut peek)
+                    .ok_or_else(|| ParserError::ExpectedToken { 
+                        tkn: &tokens[peek], 
+                        expected: TknType::Operation(Op::Arrow) 
+                    })?;
+
+                let body = parse_body(&variables, &mut peek)?;
+                return Ok(MatchArm { pattern, guard, body });
+            })());
+        });
+        arms.push(unsafe { arm.unwrap_unchecked() }?);
+        arm_tkns.push(arm_tkn);
+
+        // arms are separated by commas, which are optional after a block
+        if !tokens::is_expected_token(tokens, TknType::Comma, &mut peek) 
+            && !tokens::is_token(tokens, TknType::CloseCurlyBrace, peek - 1) 
+            && !tokens::is_token(tokens, TknType::CloseCurlyBrace, peek) 
+        {
+            return Err(ParserError::ExpectedTokens { 
+                tkn: &tokens[peek], 
+                received: tokens[peek..].iter().map(|tkn| &tkn.token),
+                expected: &[TknType::Comma, TknType::CloseCurlyBrace] 
+            });
+        }
+    }
+
+    let checked_arms = arms.iter()
+        .map(|arm| (&arm.pattern, arm.guard.is_some()))
+        .collect::<Vec<_>>();
+    match exhaustiveness::check_match(&checked_arms, &scrutinee.expr_type.get(), structs) {
+        Ok(()) => (),
+        Err(MatchProblem::UnreachableArm(arm)) => return Err(ParserError::UnreachableArm { 
+            tkn: arm_tkns[arm] 
+        }),
+        Err(MatchProblem::NonExhaustive(missing)) => return Err(ParserError::NonExhaustiveMatch { 
+            tkn: match_tkn, 
+            missing 
+        }),
+    }
+
+    *index = peek;
+    return Ok((scrutinee, arms));
+}
+
+/// Checks `pattern` against the type of the value it matches and declares the variables it binds.
+/// `tkn` is where the arm starts, for errors in patterns that do not keep a token of their own.
+fn check_match_pattern<'tkns, 'bumps, 'defs>(
+    expr_bump: &'bumps ExprBump,
+    structs: &'defs [Struct],
+    enums: &'defs [Enum],
+    variables: &StackFrameDictAllocator<'_, String, VariableData<'tkns, 'bumps>>,
+    pattern: &Pattern<'tkns>,
+    expr_type: &mut ExprTypeCons<'bumps>,
+    tkn: &'tkns Tkn,
+    bound: &mut Vec<(&'tkns Tkn, String)>
+) -> Result<MatchPattern, ParserError<'tkns, 'bumps, 'defs>> {
+    let mismatch = |tkn, expr_type: &ExprTypeCons| ParserError::PatternNotMatchExpectedType { 
+        tkn, 
+        pattern: pattern.clone(), 
+        expected_type: expr_type.clone_inner() 
+    };
+
+    match pattern {
+        Pattern::DiscardSingle => return Ok(MatchPattern::Discard),
+        Pattern::Ident { tkn, mutable, name, .. } => {
+            if let Some((bound_tkn, _)) = bound.iter().find(|(_, bound_name)| bound_name == name) {
+                return Err(ParserError::AlreadyBoundIdentifier { tkn, bound: bound_tkn });
+            }
+            bound.push((tkn, name.clone()));
+
+            variables.push(name.clone(), VariableData::new(tkn, *mutable, expr_type.clone()));
+            return Ok(MatchPattern::Bind(name.clone()));
+        },
+        Pattern::Literal { tkn, literal } => {
+            check_literal_pattern(expr_bump, literal, expr_type).ok_or_else(|| mismatch(tkn, expr_type))?;
+            return Ok(MatchPattern::Literal(literal.clone()));
+        },
+        Pattern::Range { tkn, start, end, exclusive_start, inclusive_end } => {
+            check_literal_pattern(expr_bump, start, expr_type).ok_or_else(|| mismatch(tkn, expr_type))?;
+            check_literal_pattern(expr_bump, end, expr_type).ok_or_else(|| mismatch(tkn, expr_type))?;
+
+            let value = |literal: &Lit| match literal {
+                Lit::IntegerLiteral(int) => Some(*int),
+                Lit::CharLiteral(chr) => Some(*chr as i128),
+                _ => None
+            };
+            let (Some(start), Some(end)) = (value(start), value(end)) else {
+                return Err(mismatch(tkn, expr_type));
+            };
+
+            let start = if *exclusive_start { start.checked_add(1) } else { Some(start) };
+            let end = if *inclusive_end { Some(end) } else { end.checked_sub(1) };
+            match (start, end) {
+                (Some(start), Some(end)) if start <= end => return Ok(MatchPattern::Range { start, end }),
+                _ => return Err(ParserError::EmptyRangePattern { tkn }),
+            }
+        },
+        Pattern::Tuple { start, end } => {
+            let ExprType::Tuple { start: types, .. } = expr_type.clone_inner() else {
+                return Err(mismatch(tkn, expr_type));
+            };
+            if start.len() + end.len() > types.len() {
+                return Err(mismatch(tkn, expr_type));
+            }
+
+            let end_types = &types[types.len() - end.len()..];
+            return Ok(MatchPattern::Group { 
+                start: check_group_patterns(expr_bump, structs, enums, variables, start, &types, tkn, bound)?, 
+                end: check_group_patterns(expr_bump, structs, enums, variables, end, end_types, tkn, bound)?
+            });
+        },
+        Pattern::Array { start, end } => {
+            let ExprType::Array { length: Some(length), expr_type: element_type } = expr_type.clone_inner() else {
+                return Err(mismatch(tkn, expr_type));
+            };
+            if start.len() + end.len() > length {
+                return Err(mismatch(tkn, expr_type));
+            }
+
+            let types = vec![*element_type; length];
+            return Ok(MatchPattern::Group { 
+                start: check_group_patterns(expr_bump, structs, enums, variables, start, &types, tkn, bound)?, 
+                end: check_group_patterns(expr_bump, structs, enums, variables, end, &types, tkn, bound)?
+            });
+        },
+        Pattern::AmbiguousGroup { start, end } => {
+            let group = match &*expr_type.get() {
+                ExprType::Tuple { .. } => Pattern::Tuple { start: start.clone(), end: end.clone() },
+                ExprType::Array { .. } => Pattern::Array { start: start.clone(), end: end.clone() },
+                _ => return Err(mismatch(tkn, expr_type)),
+            };
+            return check_match_pattern(expr_bump, structs, enums, variables, &group, expr_type, tkn, bound);
+        },
+        Pattern::Struct { tkn, name, fields, rest } => {
+            let (custom_struct, type_args) = match &*expr_type.get() {
+                ExprType::Custom { ident, type_args } if ident == name => structs.iter()
+                    .find(|custom_struct| custom_struct.name == *ident)
+                    .map(|custom_struct| (custom_struct, type_args.clone())),
+                _ => None
+            }.ok_or_else(|| mismatch(tkn, expr_type))?;
+
+            let mut checked_fields = vec![];
+            for FieldPattern { tkn: field_tkn, field_name, pattern } in fields {
+                let field = custom_struct.fields.iter()
+                    .find(|field| field.field_name == *field_name)
+                    .ok_or_else(|| ParserError::FieldDoesNotExist { tkn: field_tkn, custom_struct })?;
+                accessors::check_field(field_tkn, custom_struct, field)?;
+
+                checked_fields.push((field_name.clone(), check_match_pattern(
+                    expr_bump, 
+                    structs, 
+                    enums, 
+                    variables, 
+                    pattern, 
+                    &mut ExprTypeCons::new(expr_bump, custom_struct.field_type(field, &type_args)), 
+                    field_tkn, 
+                    bound
+                )?));
+            }
+
+            if !rest && let Some(field) = custom_struct.fields.iter()
+                .find(|field| !fields.iter().any(|pattern| pattern.field_name == field.field_name)) 
+            {
+                return Err(ParserError::MissingFieldPattern { tkn, field_name: field.field_name.clone() });
+            }
+            return Ok(MatchPattern::Fields(checked_fields));
+        },
+        Pattern::AnonymousStruct { tkn, fields, rest } => {
+            let ExprType::AnonymousCustom { fields: types } = expr_type.clone_inner() else {
+                return Err(mismatch(tkn, expr_type));
+            };
+
+            let mut checked_fields = vec![];
+            for FieldPattern { tkn: field_tkn, field_name, pattern } in fields {
+                let (_, field_type) = types.iter()
+                    .find(|(name, _)| name == field_name)
+                    .ok_or_else(|| mismatch(field_tkn, expr_type))?;
+
+                checked_fields.push((field_name.clone(), check_match_pattern(
+                    expr_bump, 
+                    structs, 
+                    enums, 
+                    variables, 
+                    pattern, 
+                    &mut ExprTypeCons::new(expr_bump, field_type.clone()), 
+                    field_tkn, 
+                    bound
+                )?));
+            }
+
+            if !rest && let Some((field_name, _)) = types.iter()
+                .find(|(name, _)| !fields.iter().any(|pattern| pattern.field_name == *name)) 
+            {
+                return Err(ParserError::MissingFieldPattern { tkn, field_name: field_name.clone() });
+            }
+            return Ok(MatchPattern::Fields(checked_fields));
+        },
+        Pattern::Variant { tkn, enum_name, variant_tkn, variant_name, payload } => {
+            let custom_enum = match &*expr_type.get() {
+                ExprType::Enum { ident, .. } if ident == enum_name => enums.iter()
+                    .find(|custom_enum| custom_enum.name == *ident),
+                _ => None
+            }.ok_or_else(|| mismatch(tkn, expr_type))?;
+
+            let tag = custom_enum.variants.iter()
+                .position(|variant| variant.variant_name == *variant_name)
+                .ok_or_else(|| ParserError::VariantDoesNotExist { tkn: variant_tkn, custom_enum })?;
+            let variant = &custom_enum.variants[tag];
+            let shape_mismatch = || ParserError::VariantPatternMismatch { tkn: variant_tkn, custom_enum, variant };
+
+            let mut checked_fields = vec![];
+            match (variant.shape, payload.as_deref()) {
+                (VariantShape::Unit, None) => (),
+                (VariantShape::Tuple, Some(Pattern::Tuple { start, end })) => {
+                    if start.len() + end.len() > variant.fields.len() {
+                        return Err(shape_mismatch());
+                    }
+
+                    let end_fields = &variant.fields[variant.fields.len() - end.len()..];
+                    for (pattern, (field_name, field_type)) in start.iter().zip(variant.fields.iter())
+                        .chain(end.iter().zip(end_fields.iter())) 
+                    {
+                        checked_fields.push((field_name.clone(), check_match_pattern(
+                            expr_bump, 
+                            structs, 
+                            enums, 
+                            variables, 
+                            pattern, 
+                            &mut ExprTypeCons::new(expr_bump, field_type.clone()), 
+                            variant_tkn, 
+                            bound
+                        )?));
+                    }
+                },
+                (VariantShape::Struct, Some(Pattern::AnonymousStruct { fields, rest, .. })) => {
+                    for FieldPattern { tkn: field_tkn, field_name, pattern } in fields {
+                        let (_, field_type) = variant.fields.iter()
+                            .find(|(name, _)| name == field_name)
+                            .ok_or_else(|| ParserError::VariantFieldDoesNotExist { 
+                                tkn: field_tkn, 
+                                custom_enum, 
+                                variant 
+                            })?;
+
+                        checked_fields.push((field_name.clone(), check_match_pattern(
+                            expr_bump, 
+                            structs, 
+                            enums, 
+                            variables, 
+                            pattern, 
+                            &mut ExprTypeCons::new(expr_bump, field_type.clone()), 
+                            field_tkn, 
+                            bound
+                        )?));
+                    }
+
+                    if !rest && let Some((field_name, _)) = variant.fields.iter()
+                        .find(|(name, _)| !fields.iter().any(|pattern| pattern.field_name == *name)) 
+                    {
+                        return Err(ParserError::MissingFieldPattern { 
+                            tkn: variant_tkn, 
+                            field_name: field_name.clone() 
+                        });
+                    }
+                },
+                _ => return Err(shape_mismatch()),
+            }
+            return Ok(MatchPattern::Variant { tag, fields: checked_fields });
+        },
+    }
+}
+
+/// Checks the elements of a tuple or array pattern against the types they line up with.
+fn check_group_patterns<'tkns, 'bumps, 'defs>(
+    expr_bump: &'bumps ExprBump,
+    structs: &'defs [Struct],
+    enums: &'defs [Enum],
+    variables: &StackFrameDictAllocator<'_, String, VariableData<'tkns, 'bumps>>,
+    patterns: &[Pattern<'tkns>],
+    types: &[ExprType],
+    tkn: &'tkns Tkn,
+    bound: &mut Vec<(&'tkns Tkn, String)>
+) -> Result<Vec<MatchPattern>, ParserError<'tkns, 'bumps, 'defs>> {
+    let mut checked = vec![];
+    for (pattern, expr_type) in patterns.iter().zip(types) {
+        checked.push(check_match_pattern(
+            expr_bump, 
+            structs, 
+            enums, 
+            variables, 
+            pattern, 
+            &mut ExprTypeCons::new(expr_bump, expr_type.clone()), 
+            tkn, 
+            bound
+        )?);
+    }
+    return Ok(checked);
+}
+
+/// Matches the type of the value against the type of the literal, and checks that integer 
+/// literals fit into the value's type.
+fn check_literal_pattern<'bumps>(
+    expr_bump: &'bumps ExprBump, 
+    literal: &Lit, 
+    expr_type: &mut ExprTypeCons<'bumps>
+) -> Option<()> {
+    let literal_type = match literal {
+        Lit::IntegerLiteral(int) if *int >= 0 => ExprType::AmbiguousPosInteger,
+        literal => literal.get_type()
+    };
+    expr_type.match_type(&mut ExprTypeCons::new(expr_bump, literal_type))?;
+
+    if let Lit::IntegerLiteral(int) = literal 
+        && let Some((min, max)) = exhaustiveness::integer_bounds(&expr_type.get()) 
+        && !(min..=max).contains(int) 
+    {
+        return None;
+    }
+    return Some(());
+}
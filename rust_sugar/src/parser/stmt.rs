@@ -5,9 +5,11 @@ use stack_frame_allocators::stack_frame_dict_allocator::StackFrameDictAllocator;
 use crate::full_result::{FullResult, OptionToFullResult, ResultToFullResult};
 use crate::lexer::token::{Kwrd, Op, Tkn, TknType, TokenType};
 
-use super::expr::{Expr, ExprTypeCons};
+use super::expr::{Expr, ExprData, ExprTypeCons};
 use super::functions::FullFnDef;
-use super::structs::Struct;
+use super::{enums::Enum, structs::Struct};
+use super::patterns::{MatchArm, Pattern};
+use super::operators::UnOp;
 use super::{expr::{self, ExprType, VariableData}, patterns, tokens, ExprBump, ParserError, StmtBump};
 
 #[derive(Debug)]
@@ -22,14 +24,43 @@ pub type Stmt<'bumps, 'defs> = Statement<'bumps, 'defs>;
 pub enum Statement<'bumps, 'defs> {
     Compound(Vec<&'bumps StmtData<'bumps, 'defs>>),
     While {
+        label: Option<String>,
         cond: Expr<'bumps, 'defs>, 
         body: Vec<&'bumps StmtData<'bumps, 'defs>>
     },
+    /// `loop { body }`.  A `break` carrying a value out of it assigns the value to the 
+    /// variable `assign`, which is how `let x = loop { .. };` and `x = loop { .. };` work, 
+    /// along with the index of the token the name of the variable is written at.
+    Loop {
+        label: Option<String>,
+        body: Vec<&'bumps StmtData<'bumps, 'defs>>,
+        assign: Option<(String, usize)>
+    },
+    /// `for binder in iter { body }`, where iter is a range or an array.  
+    /// The binder is `None` when the pattern is `_`.
+    For {
+        label: Option<String>,
+        binder: Option<(String, &'bumps RefCell<ExprType>)>,
+        iter: Expr<'bumps, 'defs>,
+        body: Vec<&'bumps StmtData<'bumps, 'defs>>
+    },
     Conditional{ 
         conds: Vec<Expr<'bumps, 'defs>>, 
         bodies: Vec<Vec<&'bumps StmtData<'bumps, 'defs>>> 
     },
-    Return(Option<Expr<'bumps, 'defs>>),
+    /// `match scrutinee { pattern => body, .. }`, running the body of the first arm that matches
+    Match {
+        scrutinee: Expr<'bumps, 'defs>,
+        arms: Vec<MatchArm<'bumps, 'defs, Vec<&'bumps StmtData<'bumps, 'defs>>>>
+    },
+    /// `return value;`, where the value is optional, and the index of the `return` token
+    Return(Option<Expr<'bumps, 'defs>>, usize),
+    /// `break 'label value;`, where the label and the value are optional
+    Break {
+        label: Option<String>,
+        value: Option<Expr<'bumps, 'defs>>
+    },
+    Continue { label: Option<String> },
     Declare(String, StackLocation, &'bumps RefCell<ExprType>),
     Assign{ 
         variable: Expr<'bumps, 'defs>, 
@@ -43,14 +74,27 @@ pub enum StackLocation {
     Oxy, GC
 }
 
+/// A loop around the statements being parsed, which `break` and `continue` can leave.
+#[derive(Clone, Debug)]
+pub struct EnclosingLoop<'tkns, 'bumps> {
+    /// The `loop`, `while` or `for` keyword
+    tkn: &'tkns Tkn,
+    label: Option<&'tkns String>,
+    /// The type of the value `break` carries out, `None` when the loop does not produce one
+    value_type: Option<ExprTypeCons<'bumps>>
+}
+
 pub fn parse_statement<'tkns, 'bumps, 'defs, 'fn_defs, 'sfda, 'i>(
     expr_bump: &'bumps ExprBump,
     stmt_bump: &'bumps StmtBump,
     structs: &'defs [Struct],
+    enums: &'defs [Enum],
     functions: &'fn_defs RefCell<HashMap<String, FullFnDef<'tkns, 'bumps, 'defs>>>,
     variables: &'sfda StackFrameDictAllocator<'i, String, VariableData<'tkns, 'bumps>>,
     tokens: &'tkns [Tkn], 
     expected_type: &mut ExprType,
+    errors: &mut Vec<ParserError<'tkns, 'bumps, 'defs>>,
+    loops: &mut Vec<EnclosingLoop<'tkns, 'bumps>>,
     index: &mut usize
 ) -> FullResult<
     Vec<&'bumps StmtData<'bumps, 'defs>>, 
@@ -61,27 +105,120 @@ pub fn parse_statement<'tkns, 'bumps, 'defs, 'fn_defs, 'sfda, 'i>(
 
     let line = tokens[*index].line_number;
     //println!("parsing statement starting at token {}", &tokens[*index]);
-    return parse_compound_statement             (expr_bump, stmt_bump, structs, line, functions, variables, tokens, expected_type, index).map(|e| vec![e])
-        .or_else(|_| parse_while_statement      (expr_bump, stmt_bump, structs, line, functions, variables, tokens, expected_type, index).map(|e| vec![e]))
-        .or_else(|_| parse_conditional_statement(expr_bump, stmt_bump, structs, line, functions, variables, tokens, expected_type, index).map(|e| vec![e]))
-        .or_else(|_| parse_variable_declaration (expr_bump, stmt_bump, structs, line, functions, variables, tokens, expected_type, index)                 )
-        .or_else(|_| parse_variable_assignment  (expr_bump, stmt_bump, structs, line, functions, variables, tokens, expected_type, index)                 )
-        .or_else(|_| parse_return               (expr_bump, stmt_bump, structs, line, functions, variables, tokens, expected_type, index).map(|e| vec![e]))
-        .or_else(|_| parse_expr_statement       (expr_bump, stmt_bump, structs, line, functions, variables, tokens, expected_type, index).map(|e| vec![e]))
-        .map_soft_err(|_| ParserError::InvalidStatement { 
-            tkn: &tokens[*index] 
+    return parse_compound_statement             (expr_bump, stmt_bump, structs, enums, line, functions, variables, tokens, expected_type, errors, loops, index).map(|e| vec![e])
+        .or_else(|_| parse_while_statement      (expr_bump, stmt_bump, structs, enums, line, functions, variables, tokens, expected_type, errors, loops, index).map(|e| vec![e]))
+        .or_else(|_| parse_for_statement        (expr_bump, stmt_bump, structs, enums, line, functions, variables, tokens, expected_type, errors, loops, index).map(|e| vec![e]))
+        .or_else(|_| parse_loop_statement       (expr_bump, stmt_bump, structs, enums, line, functions, variables, tokens, expected_type, errors, loops, index).map(|e| vec![e]))
+        .or_else(|_| parse_conditional_statement(expr_bump, stmt_bump, structs, enums, line, functions, variables, tokens, expected_type, errors, loops, index).map(|e| vec![e]))
+        .or_else(|_| parse_match_statement      (expr_bump, stmt_bump, structs, enums, line, functions, variables, tokens, expected_type, errors, loops, index).map(|e| vec![e]))
+        .or_else(|_| parse_variable_declaration (expr_bump, stmt_bump, structs, enums, line, functions, variables, tokens, expected_type, errors, loops, index)                 )
+        .or_else(|_| parse_variable_assignment  (expr_bump, stmt_bump, structs, enums, line, functions, variables, tokens, expected_type, errors, loops, index)                 )
+        .or_else(|_| parse_deref_assignment     (expr_bump, stmt_bump, structs, enums, line, functions, variables, tokens, index).map(|e| vec![e]))
+        .or_else(|_| parse_return               (expr_bump, stmt_bump, structs, enums, line, functions, variables, tokens, expected_type, index).map(|e| vec![e]))
+        .or_else(|_| parse_break                (expr_bump, stmt_bump, structs, enums, line, functions, variables, tokens, loops, index).map(|e| vec![e]))
+        .or_else(|_| parse_continue             (stmt_bump, line, tokens, loops, index).map(|e| vec![e]))
+        .or_else(|_| parse_expr_statement       (expr_bump, stmt_bump, structs, enums, line, functions, variables, tokens, expected_type, index).map(|e| vec![e]))
+        .map_soft_err(|err| match err {
+            // nothing past the first token was understood, so it starts none of the statements
+            ParserError::InvalidExpressionAtom { tkn } if std::ptr::eq(tkn, &tokens[*index]) => {
+                ParserError::InvalidStatement { tkn }
+            },
+            err => err
         });
 }
 
+/// Parses statements up to the `}` closing the enclosing block (or the end of `tokens`).
+/// A statement that fails to parse is recorded in `errors` and skipped with
+/// [`synchronize_statement`], so the rest of the block still gets checked.
+pub fn parse_statements<'tkns, 'bumps, 'defs>(
+    expr_bump: &'bumps ExprBump,
+    stmt_bump: &'bumps StmtBump,
+    structs: &'defs [Struct],
+    enums: &'defs [Enum],
+    functions: &RefCell<HashMap<String, FullFnDef<'tkns, 'bumps, 'defs>>>,
+    variables: &StackFrameDictAllocator<'_, String, VariableData<'tkns, 'bumps>>,
+    tokens: &'tkns [Tkn],
+    expected_type: &mut ExprType,
+    errors: &mut Vec<ParserError<'tkns, 'bumps, 'defs>>,
+    loops: &mut Vec<EnclosingLoop<'tkns, 'bumps>>,
+    index: &mut usize
+) -> Vec<&'bumps StmtData<'bumps, 'defs>> {
+    let mut stmts = vec![];
+    loop {
+        if *index >= tokens.len() || tokens::is_token(tokens, TknType::CloseCurlyBrace, *index) {
+            return stmts;
+        }
+
+        let start = *index;
+        let stmt_possible = parse_statement(
+            expr_bump, 
+            stmt_bump, 
+            structs,
+            enums,
+            functions, 
+            variables, 
+            tokens, 
+            expected_type,
+            errors,
+            loops,
+            index
+        );
+        match stmt_possible {
+            FullResult::Ok(mut stmt) => stmts.append(&mut stmt),
+            FullResult::SoftErr(err) | FullResult::HardErr(err) => {
+                errors.push(err);
+                *index = start;
+                synchronize_statement(tokens, index);
+            }
+        }
+    }
+}
+
+/// Skips the statement starting at `index`: up to and including the next `;` outside of braces,
+/// or the `}` ending a block statement (unless an `else`, `;` or `=` follows it, or the statement
+/// is a `let`, whose braces are struct patterns or values).  Stops before a `}` that closes the 
+/// enclosing block.
+pub fn synchronize_statement(tokens: &[Tkn], index: &mut usize) {
+    let declaration = tokens::is_token(tokens, TknType::Keyword(Kwrd::Let), *index);
+    let mut depth: usize = 0;
+    while let Some(tkn) = tokens.get(*index) {
+        match tkn.token {
+            TknType::OpenCurlyBrace => depth += 1,
+            TknType::CloseCurlyBrace if depth == 0 => return,
+            TknType::CloseCurlyBrace => {
+                depth -= 1;
+                if depth == 0 
+                    && !declaration
+                    && !tokens::is_token(tokens, TknType::Keyword(Kwrd::Else), *index + 1)
+                    && !tokens::is_token(tokens, TknType::Semicolon, *index + 1) 
+                    && !tokens::is_token(tokens, TknType::Operation(Op::Assign), *index + 1) 
+                {
+                    *index += 1;
+                    return;
+                }
+            },
+            TknType::Semicolon if depth == 0 => {
+                *index += 1;
+                return;
+            },
+            _ => ()
+        }
+        *index += 1;
+    }
+}
+
 fn parse_compound_statement<'tkns, 'bumps, 'defs>(
     expr_bump: &'bumps ExprBump,
     stmt_bump: &'bumps StmtBump,
     structs: &'defs [Struct],
+    enums: &'defs [Enum],
     line: usize,
     functions: &RefCell<HashMap<String, FullFnDef<'tkns, 'bumps, 'defs>>>,
     variables: &StackFrameDictAllocator<'_, String, VariableData<'tkns, 'bumps>>,
     tokens: &'tkns [Tkn], 
     expected_type: &mut ExprType,
+    errors: &mut Vec<ParserError<'tkns, 'bumps, 'defs>>,
+    loops: &mut Vec<EnclosingLoop<'tkns, 'bumps>>,
     index: &mut usize
 ) -> FullResult<
     &'bumps StmtData<'bumps, 'defs>, 
@@ -110,28 +247,20 @@ fn parse_compound_statement<'tkns, 'bumps, 'defs>(
     }
     
     //variables.print();
-    let mut possible_error = None;
     variables.new_scope(|variables| {
-        loop {
-            let stmt_possible = parse_statement(
-                expr_bump, 
-                stmt_bump, 
-                structs,
-                functions, 
-                &variables, 
-                tokens, 
-                expected_type,
-                &mut peek
-            );
-            match stmt_possible {
-                FullResult::Ok(mut stmt) => stmts.append(&mut stmt),
-                FullResult::SoftErr(_) => break,
-                FullResult::HardErr(err) => {
-                    possible_error = Some(err);
-                    break;
-                }
-            }
-        }
+        stmts = parse_statements(
+            expr_bump, 
+            stmt_bump, 
+            structs,
+            enums,
+            functions, 
+            &variables, 
+            tokens, 
+            expected_type,
+            errors,
+            loops,
+            &mut peek
+        );
         //variables.print();
     });
 
@@ -154,11 +283,14 @@ fn parse_variable_declaration<'tkns, 'bumps, 'defs>(
     expr_bump: &'bumps ExprBump,
     stmt_bump: &'bumps StmtBump,
     structs: &'defs [Struct],
+    enums: &'defs [Enum],
     line: usize,
     functions: &RefCell<HashMap<String, FullFnDef<'tkns, 'bumps, 'defs>>>,
     variables: &StackFrameDictAllocator<'_, String, VariableData<'tkns, 'bumps>>, 
     tokens: &'tkns [Tkn], 
-    _expected_type: &mut ExprType,
+    expected_type: &mut ExprType,
+    errors: &mut Vec<ParserError<'tkns, 'bumps, 'defs>>,
+    loops: &mut Vec<EnclosingLoop<'tkns, 'bumps>>,
     index: &mut usize
 ) -> FullResult<
     Vec<&'bumps StmtData<'bumps, 'defs>>, 
@@ -186,7 +318,7 @@ fn parse_variable_declaration<'tkns, 'bumps, 'defs>(
     
     // TODO factor out type parsing
     let data_type = if tokens::is_expected_token(tokens, TknType::Colon, &mut peek) {
-        super::get_type(tokens, &mut peek, structs).ok_or_else_hard(|| ParserError::ExpectedType { 
+        super::get_type(tokens, &mut peek, structs, enums).ok_or_else_hard(|| ParserError::ExpectedType { 
             tkn: &tokens[peek]
         })?
     } else {
@@ -195,6 +327,7 @@ fn parse_variable_declaration<'tkns, 'bumps, 'defs>(
 
     let mut stmts = patterns::declare_variable_pattern(
         expr_bump, stmt_bump, 
+        structs,
         variables, 
         &mut ident, 
         stack_location, 
@@ -205,25 +338,35 @@ fn parse_variable_declaration<'tkns, 'bumps, 'defs>(
     ).harden()?;//.inspect_err(|e| {dbg!(e);})?;
 
     if tokens::is_expected_token(tokens, TknType::Operation(Op::Assign), &mut peek) {
-        let expr = expr::parse_expression_set(
-            expr_bump, 
-            structs, 
-            tokens, 
-            &mut peek, 
-            line,
-            functions,
-            &variables
-        ).harden()?;//.inspect_err(|e| {dbg!(e);})?;
+        if is_loop(tokens, peek) {
+            stmts.push(parse_assigned_loop(
+                expr_bump, stmt_bump, structs, enums, line, functions, variables, tokens, expected_type, errors, loops, 
+                true, &ident, start_ident, 
+                &mut peek
+            )?);
+        } else {
+            let expr = expr::parse_expression_set(
+                expr_bump, 
+                structs, 
+                enums, 
+                tokens, 
+                &mut peek, 
+                line,
+                functions,
+                &variables
+            ).harden()?;//.inspect_err(|e| {dbg!(e);})?;
 
-        stmts.extend(patterns::assign_variable_pattern(
-            expr_bump, stmt_bump, 
-            variables, 
-            true, &mut ident, 
-            expr, 
-            tokens, 
-            start_ident,
-            line
-        ).harden()?);//.inspect_err(|e| {dbg!(e);})?);
+            stmts.extend(patterns::assign_variable_pattern(
+                expr_bump, stmt_bump, 
+                structs,
+                variables, 
+                true, &mut ident, 
+                expr, 
+                tokens, 
+                start_ident,
+                line
+            ).harden()?);//.inspect_err(|e| {dbg!(e);})?);
+        }
     }
 
     tokens::expect_token(tokens, TknType::Semicolon, &mut peek)
@@ -240,6 +383,7 @@ fn parse_return<'tkns, 'bumps, 'defs>(
     expr_bump: &'bumps ExprBump,
     stmt_bump: &'bumps StmtBump,
     structs: &'defs [Struct],
+    enums: &'defs [Enum],
     line: usize,
     functions: &RefCell<HashMap<String, FullFnDef<'tkns, 'bumps, 'defs>>>,
     variables: &StackFrameDictAllocator<'_, String, VariableData<'tkns, 'bumps>>,
@@ -252,6 +396,7 @@ fn parse_return<'tkns, 'bumps, 'defs>(
     ParserError<'tkns, 'bumps, 'defs>
 > {
     let mut peek = *index;
+    let return_index = peek;
     //println!("parsing return statement at {}", &tokens[*index]);
 
     tokens::expect_token(tokens, TokenType::Keyword(Kwrd::Return), &mut peek)
@@ -265,13 +410,14 @@ fn parse_return<'tkns, 'bumps, 'defs>(
         *index = peek;
         return FullResult::Ok(stmt_bump.alloc(StmtData {
             line, 
-            stmt: Stmt::Return(None)
+            stmt: Stmt::Return(None, return_index)
         }));
     }
 
     let Expr { expr_data, mut expr_type, .. } = expr::parse_expression_set(
         expr_bump, 
         structs,
+        enums,
         tokens, 
         &mut peek, 
         line,
@@ -289,19 +435,159 @@ fn parse_return<'tkns, 'bumps, 'defs>(
     *index = peek;
     return FullResult::Ok(stmt_bump.alloc(StmtData {
         line, 
-        stmt: Stmt::Return(Some(Expr {line, expr_data, expr_type: expr_type.clone()}))
+        stmt: Stmt::Return(Some(Expr {line, expr_data, expr_type: expr_type.clone()}), return_index)
     }));
 }
 
+fn parse_break<'tkns, 'bumps, 'defs>(
+    expr_bump: &'bumps ExprBump,
+    stmt_bump: &'bumps StmtBump,
+    structs: &'defs [Struct],
+    enums: &'defs [Enum],
+    line: usize,
+    functions: &RefCell<HashMap<String, FullFnDef<'tkns, 'bumps, 'defs>>>,
+    variables: &StackFrameDictAllocator<'_, String, VariableData<'tkns, 'bumps>>,
+    tokens: &'tkns [Tkn], 
+    loops: &mut Vec<EnclosingLoop<'tkns, 'bumps>>,
+    index: &mut usize
+) -> FullResult<
+    &'bumps StmtData<'bumps, 'defs>, 
+    ParserError<'tkns, 'bumps, 'defs>, 
+    ParserError<'tkns, 'bumps, 'defs>
+> {
+    let mut peek = *index;
+
+    tokens::expect_token(tokens, TokenType::Keyword(Kwrd::Break), &mut peek)
+        .ok_or_else_soft(|| ParserError::ExpectedToken { 
+            tkn: &tokens[peek], 
+            expected: TknType::Keyword(Kwrd::Break) 
+        })?;
+
+    let (label, target) = parse_target_loop(tokens, loops, &mut peek).harden()?;
+
+    let value = if tokens::is_token(tokens, TknType::Semicolon, peek) {
+        if target.value_type.is_some() {
+            return FullResult::HardErr(ParserError::BreakValueMissing { 
+                tkn: &tokens[peek - 1], 
+                loop_tkn: target.tkn 
+            });
+        }
+        None
+    } else {
+        let start_expr = peek;
+        let Expr { expr_data, mut expr_type, .. } = expr::parse_expression_set(
+            expr_bump, 
+            structs,
+            enums,
+            tokens, 
+            &mut peek, 
+            line,
+            functions, 
+            variables
+        ).harden()?;
+
+        let Some(value_type) = &target.value_type else {
+            return FullResult::HardErr(ParserError::BreakValueNotUsed { 
+                tkn: &tokens[start_expr], 
+                loop_tkn: target.tkn 
+            });
+        };
+        let expr_type = value_type.clone().match_type(&mut expr_type)
+            .ok_or_else_hard(|| ParserError::CouldNotMatchType { 
+                tkns: &tokens[start_expr..peek], 
+                calculated_type: expr_type.clone_inner(), 
+                expected_type: value_type.clone_inner() 
+            })?;
+
+        Some(Expr { line, expr_data, expr_type })
+    };
+
+    tokens::expect_token(tokens, TknType::Semicolon, &mut peek)
+        .ok_or_else_hard(|| ParserError::ExpectedToken { 
+            tkn: &tokens[peek], 
+            expected: TknType::Semicolon 
+        })?;
+
+    *index = peek;
+    return FullResult::Ok(stmt_bump.alloc(StmtData {
+        line, 
+        stmt: Stmt::Break { label: label.cloned(), value }
+    }));
+}
+
+fn parse_continue<'tkns, 'bumps, 'defs>(
+    stmt_bump: &'bumps StmtBump,
+    line: usize,
+    tokens: &'tkns [Tkn], 
+    loops: &mut Vec<EnclosingLoop<'tkns, 'bumps>>,
+    index: &mut usize
+) -> FullResult<
+    &'bumps StmtData<'bumps, 'defs>, 
+    ParserError<'tkns, 'bumps, 'defs>, 
+    ParserError<'tkns, 'bumps, 'defs>
+> {
+    let mut peek = *index;
+
+    tokens::expect_token(tokens, TokenType::Keyword(Kwrd::Continue), &mut peek)
+        .ok_or_else_soft(|| ParserError::ExpectedToken { 
+            tkn: &tokens[peek], 
+            expected: TknType::Keyword(Kwrd::Continue) 
+        })?;
+
+    let (label, _) = parse_target_loop(tokens, loops, &mut peek).harden()?;
+
+    tokens::expect_token(tokens, TknType::Semicolon, &mut peek)
+        .ok_or_else_hard(|| ParserError::ExpectedToken { 
+            tkn: &tokens[peek], 
+            expected: TknType::Semicolon 
+        })?;
+
+    *index = peek;
+    return FullResult::Ok(stmt_bump.alloc(StmtData {
+        line, 
+        stmt: Stmt::Continue { label: label.cloned() }
+    }));
+}
+
+/// Finds the loop the `break` or `continue` before `index` leaves: the one named by the label 
+/// following it, or else the innermost one.  Returns the label too, if there is one.
+fn parse_target_loop<'l, 'tkns, 'bumps, 'defs>(
+    tokens: &'tkns [Tkn],
+    loops: &'l [EnclosingLoop<'tkns, 'bumps>],
+    index: &mut usize
+) -> Result<
+    (Option<&'tkns String>, &'l EnclosingLoop<'tkns, 'bumps>), 
+    ParserError<'tkns, 'bumps, 'defs>
+> {
+    let keyword = &tokens[*index - 1];
+    if loops.is_empty() {
+        return Err(ParserError::NotInLoop { tkn: keyword });
+    }
+
+    let Some(TknType::Label(label)) = tokens::get_token(tokens, *index).map(|e| &e.token) else {
+        return Ok((None, loops.last().unwrap()));
+    };
+    let label_tkn = &tokens[*index];
+    *index += 1;
+
+    return loops.iter().rev()
+        .find(|enclosing_loop| enclosing_loop.label == Some(label))
+        .map(|enclosing_loop| (Some(label), enclosing_loop))
+        .ok_or(ParserError::UndeclaredLabel { tkn: label_tkn });
+}
+
 fn parse_variable_assignment<'tkns, 'bumps, 'defs>(
     expr_bump: &'bumps ExprBump,
     stmt_bump: &'bumps StmtBump,
     structs: &'defs [Struct],
+    enums: &'defs [Enum],
     line: usize,
     functions: &RefCell<HashMap<String, FullFnDef<'tkns, 'bumps, 'defs>>>,
     variables: &StackFrameDictAllocator<'_, String, VariableData<'tkns, 'bumps>>,
     tokens: &'tkns [Tkn], 
-    _expected_type: &mut ExprType,
+    expected_type: &mut ExprType,
+    errors: &mut Vec<ParserError<'tkns, 'bumps, 'defs>>,
+    loops: &mut Vec<EnclosingLoop<'tkns, 'bumps>>,
     index: &mut usize
 ) -> FullResult<
     Vec<&'bumps StmtData<'bumps, 'defs>>, 
@@ -318,43 +604,138 @@ fn parse_variable_assignment<'tkns, 'bumps, 'defs>(
             tkn: &tokens[peek], 
             expected: TknType::Operation(Op::Assign) 
         })?;
-    let expr = expr::parse_expression_set(
+
+    let stmts = if is_loop(tokens, peek) {
+        vec![parse_assigned_loop(
+            expr_bump, stmt_bump, structs, enums, line, functions, variables, tokens, expected_type, errors, loops, 
+            false, &ident, *index, 
+            &mut peek
+        )?]
+    } else {
+        let expr = expr::parse_expression_set(
+            expr_bump, 
+            structs, 
+            enums, 
+            tokens, 
+            &mut peek, 
+            line, 
+            functions, 
+            variables
+        ).harden()?;
+
+        patterns::assign_variable_pattern(
+            expr_bump, stmt_bump, structs, variables, 
+            false, 
+            &mut ident, 
+            expr, 
+            tokens, *index, line
+        ).harden()?
+    };
+
+    tokens::expect_token(tokens, TknType::Semicolon, &mut peek)
+        .ok_or_else_hard(|| ParserError::ExpectedToken {
+            tkn: &tokens[peek],
+            expected: TknType::Semicolon
+        })?;
+    
+    *index = peek;
+    return FullResult::Ok(stmts);
+}
+
+/// Parses `*reference = value;`, a write to the value behind a `&mut` or `&im` reference.
+fn parse_deref_assignment<'tkns, 'bumps, 'defs>(
+    expr_bump: &'bumps ExprBump,
+    stmt_bump: &'bumps StmtBump,
+    structs: &'defs [Struct],
+    enums: &'defs [Enum],
+    line: usize,
+    functions: &RefCell<HashMap<String, FullFnDef<'tkns, 'bumps, 'defs>>>,
+    variables: &StackFrameDictAllocator<'_, String, VariableData<'tkns, 'bumps>>,
+    tokens: &'tkns [Tkn], 
+    index: &mut usize
+) -> FullResult<
+    &'bumps StmtData<'bumps, 'defs>, 
+    ParserError<'tkns, 'bumps, 'defs>, 
+    ParserError<'tkns, 'bumps, 'defs>
+> {
+    let mut peek = *index;
+
+    tokens::expect_token(tokens, TknType::Operation(Op::Multiply), &mut peek)
+        .ok_or_else_soft(|| ParserError::ExpectedToken { 
+            tkn: &tokens[peek], 
+            expected: TknType::Operation(Op::Multiply) 
+        })?;
+    peek = *index;
+
+    let mut variable = expr::parse_expression_set(
         expr_bump, 
         structs, 
+        enums, 
         tokens, 
         &mut peek, 
         line, 
         functions, 
         variables
-    ).harden()?;
+    ).soften()?;
+    let ExprData::UnaryOp(UnOp::Deref, reference) = variable.expr_data else {
+        return FullResult::SoftErr(ParserError::ExpectedToken { 
+            tkn: &tokens[peek], 
+            expected: TknType::Operation(Op::Assign) 
+        });
+    };
+    tokens::expect_token(tokens, TknType::Operation(Op::Assign), &mut peek)
+        .ok_or_else_soft(|| ParserError::ExpectedToken { 
+            tkn: &tokens[peek], 
+            expected: TknType::Operation(Op::Assign) 
+        })?;
+    expr::check_writable_reference(&tokens[*index], &reference.expr_type.get()).harden()?;
 
-    let stmts = patterns::assign_variable_pattern(
-        expr_bump, stmt_bump, variables, 
-        false, 
-        &mut ident, 
-        expr, 
-        tokens, *index, line
+    let value_start = peek;
+    let mut assign = expr::parse_expression_set(
+        expr_bump, 
+        structs, 
+        enums, 
+        tokens, 
+        &mut peek, 
+        line, 
+        functions, 
+        variables
     ).harden()?;
+    let expr_type = variable.expr_type.match_type(&mut assign.expr_type)
+        .ok_or_else_hard(|| ParserError::CouldNotMatchType { 
+            tkns: &tokens[value_start..peek], 
+            calculated_type: assign.expr_type.clone_inner(), 
+            expected_type: variable.expr_type.clone_inner() 
+        })?;
 
     tokens::expect_token(tokens, TknType::Semicolon, &mut peek)
         .ok_or_else_hard(|| ParserError::ExpectedToken {
             tkn: &tokens[peek],
             expected: TknType::Semicolon
         })?;
-    
+
     *index = peek;
-    return FullResult::Ok(stmts);
+    return FullResult::Ok(stmt_bump.alloc(StmtData {
+        line,
+        stmt: Stmt::Assign {
+            variable: Expr { expr_type: expr_type.clone(), ..variable },
+            assign: Expr { expr_type, ..assign }
+        }
+    }));
 }
 
 pub fn parse_while_statement<'tkns, 'bumps, 'defs>(
     expr_bump: &'bumps ExprBump,
     stmt_bump: &'bumps StmtBump,
     structs: &'defs [Struct],
+    enums: &'defs [Enum],
     line: usize,
     functions: &RefCell<HashMap<String, FullFnDef<'tkns, 'bumps, 'defs>>>,
     variables: &StackFrameDictAllocator<'_, String, VariableData<'tkns, 'bumps>>,
     tokens: &'tkns [Tkn],
     expected_type: &mut ExprType,
+    errors: &mut Vec<ParserError<'tkns, 'bumps, 'defs>>,
+    loops: &mut Vec<EnclosingLoop<'tkns, 'bumps>>,
     index: &mut usize
 ) -> FullResult<
     &'bumps StmtData<'bumps, 'defs>, 
@@ -363,17 +744,20 @@ pub fn parse_while_statement<'tkns, 'bumps, 'defs>(
 > {
     let mut peek = *index;
     
+    let label = parse_loop_label(tokens, &mut peek);
     tokens::expect_token(tokens, TokenType::Keyword(Kwrd::While), &mut peek)
         .ok_or_else_soft(|| ParserError::ExpectedToken {
             tkn: &tokens[peek],
             expected: TknType::Keyword(Kwrd::If)
         })?;
+    let loop_tkn = &tokens[peek - 1];
 
     let start_expr = peek;
 
     let Expr {expr_data, expr_type, ..} = expr::parse_expression_set(
         expr_bump, 
         structs,
+        enums,
         tokens, 
         &mut peek, 
         line,
@@ -404,33 +788,335 @@ pub fn parse_while_statement<'tkns, 'bumps, 'defs>(
 
     let mut stmts = vec![];
 
-    //variables.print();
-    let mut possible_error = None;
+    loops.push(EnclosingLoop { tkn: loop_tkn, label, value_type: None });
     variables.new_scope(|variables| {
-        loop {
-            let stmt_possible = parse_statement(
-                expr_bump, 
-                stmt_bump, 
-                structs,
-                functions,
-                &variables, 
-                tokens, 
-                expected_type,
-                &mut peek
-            );
-            match stmt_possible {
-                FullResult::Ok(mut stmt) => stmts.append(&mut stmt),
-                FullResult::SoftErr(_) => break,
-                FullResult::HardErr(err) => {
-                    possible_error = Some(err);
-                    break;
-                }
-            }
+        stmts = parse_statements(
+            expr_bump, 
+            stmt_bump, 
+            structs,
+            enums,
+            functions, 
+            &variables, 
+            tokens, 
+            expected_type,
+            errors,
+            loops,
+            &mut peek
+        );
+    });
+    loops.pop();
+
+    tokens::expect_token(tokens, TknType::CloseCurlyBrace, &mut peek)
+        .ok_or_else_hard(|| ParserError::ExpectedClosingBrace { 
+            tkn: &tokens[peek], 
+            open_brace 
+        })?;
+
+    *index = peek;
+    return FullResult::Ok(expr_bump.alloc(StmtData { 
+        line, stmt: Stmt::While { label: label.cloned(), cond, body: stmts }
+    }));
+}
+
+pub fn parse_loop_statement<'tkns, 'bumps, 'defs>(
+    expr_bump: &'bumps ExprBump,
+    stmt_bump: &'bumps StmtBump,
+    structs: &'defs [Struct],
+    enums: &'defs [Enum],
+    line: usize,
+    functions: &RefCell<HashMap<String, FullFnDef<'tkns, 'bumps, 'defs>>>,
+    variables: &StackFrameDictAllocator<'_, String, VariableData<'tkns, 'bumps>>,
+    tokens: &'tkns [Tkn],
+    expected_type: &mut ExprType,
+    errors: &mut Vec<ParserError<'tkns, 'bumps, 'defs>>,
+    loops: &mut Vec<EnclosingLoop<'tkns, 'bumps>>,
+    index: &mut usize
+) -> FullResult<
+    &'bumps StmtData<'bumps, 'defs>, 
+    ParserError<'tkns, 'bumps, 'defs>, 
+    ParserError<'tkns, 'bumps, 'defs>
+> {
+    return parse_loop(
+        expr_bump, stmt_bump, structs, enums, line, functions, variables, tokens, expected_type, errors, loops, 
+        None, 
+        index
+    );
+}
+
+/// Parses the loop in `pattern = loop { .. }`, which assigns the value a `break` carries out 
+/// of it to the pattern.
+fn parse_assigned_loop<'tkns, 'bumps, 'defs>(
+    expr_bump: &'bumps ExprBump,
+    stmt_bump: &'bumps StmtBump,
+    structs: &'defs [Struct],
+    enums: &'defs [Enum],
+    line: usize,
+    functions: &RefCell<HashMap<String, FullFnDef<'tkns, 'bumps, 'defs>>>,
+    variables: &StackFrameDictAllocator<'_, String, VariableData<'tkns, 'bumps>>,
+    tokens: &'tkns [Tkn],
+    expected_type: &mut ExprType,
+    errors: &mut Vec<ParserError<'tkns, 'bumps, 'defs>>,
+    loops: &mut Vec<EnclosingLoop<'tkns, 'bumps>>,
+    declaration: bool,
+    pattern: &Pattern<'tkns>,
+    pattern_index: usize,
+    index: &mut usize
+) -> FullResult<
+    &'bumps StmtData<'bumps, 'defs>, 
+    ParserError<'tkns, 'bumps, 'defs>, 
+    ParserError<'tkns, 'bumps, 'defs>
+> {
+    //TODO destructure the value of a loop into group patterns
+    let Pattern::Ident { name_index, mutable, name, .. } = pattern else {
+        return FullResult::HardErr(ParserError::ExpectedIdentifier { tkn: &tokens[pattern_index] });
+    };
+
+    let VariableData {
+        tkn: ident_tkn,
+        mutable: ident_mut,
+        expr_type: ident_type
+    } = variables.get_in_stack(name).ok_or_else_hard(|| ParserError::VariableDoesNotExist { 
+        tkn: &tokens[pattern_index]
+    })?.get();
+
+    if !declaration {
+        if *mutable {
+            return FullResult::HardErr(ParserError::InvalidMut { tkn: &tokens[pattern_index] });
+        }
+        if !ident_mut {
+            return FullResult::HardErr(ParserError::CannotMutateImmutable { 
+                tkn: &tokens[pattern_index], 
+                variable_def: ident_tkn 
+            });
         }
+    }
+
+    return parse_loop(
+        expr_bump, stmt_bump, structs, enums, line, functions, variables, tokens, expected_type, errors, loops, 
+        Some((name.clone(), *name_index, ident_type.clone())), 
+        index
+    );
+}
+
+/// Parses `'label: loop { body }`.  `assign` holds the variable a `break` out of the loop 
+/// assigns its value to, with the index of its name and the variable's type.
+fn parse_loop<'tkns, 'bumps, 'defs>(
+    expr_bump: &'bumps ExprBump,
+    stmt_bump: &'bumps StmtBump,
+    structs: &'defs [Struct],
+    enums: &'defs [Enum],
+    line: usize,
+    functions: &RefCell<HashMap<String, FullFnDef<'tkns, 'bumps, 'defs>>>,
+    variables: &StackFrameDictAllocator<'_, String, VariableData<'tkns, 'bumps>>,
+    tokens: &'tkns [Tkn],
+    expected_type: &mut ExprType,
+    errors: &mut Vec<ParserError<'tkns, 'bumps, 'defs>>,
+    loops: &mut Vec<EnclosingLoop<'tkns, 'bumps>>,
+    assign: Option<(String, usize, ExprTypeCons<'bumps>)>,
+    index: &mut usize
+) -> FullResult<
+    &'bumps StmtData<'bumps, 'defs>, 
+    ParserError<'tkns, 'bumps, 'defs>, 
+    ParserError<'tkns, 'bumps, 'defs>
+> {
+    let mut peek = *index;
+
+    let label = parse_loop_label(tokens, &mut peek);
+    tokens::expect_token(tokens, TokenType::Keyword(Kwrd::Loop), &mut peek)
+        .ok_or_else_soft(|| ParserError::ExpectedToken {
+            tkn: &tokens[peek],
+            expected: TknType::Keyword(Kwrd::Loop)
+        })?;
+    let loop_tkn = &tokens[peek - 1];
+
+    tokens::expect_token(tokens, TknType::OpenCurlyBrace, &mut peek)
+        .ok_or_else_hard(|| ParserError::ExpectedToken { 
+            tkn: &tokens[peek], 
+            expected: TknType::OpenCurlyBrace 
+        })?;
+    let open_brace = &tokens[peek - 1];
+
+    let (assign, value_type) = assign
+        .map(|(name, name_index, value_type)| ((name, name_index), value_type))
+        .unzip();
+    let mut stmts = vec![];
+
+    loops.push(EnclosingLoop { tkn: loop_tkn, label, value_type });
+    variables.new_scope(|variables| {
+        stmts = parse_statements(
+            expr_bump, 
+            stmt_bump, 
+            structs,
+            enums,
+            functions, 
+            &variables, 
+            tokens, 
+            expected_type,
+            errors,
+            loops,
+            &mut peek
+        );
     });
-    if let Some(err) = possible_error {
-        return FullResult::HardErr(err);
+    loops.pop();
+
+    tokens::expect_token(tokens, TknType::CloseCurlyBrace, &mut peek)
+        .ok_or_else_hard(|| ParserError::ExpectedClosingBrace { 
+            tkn: &tokens[peek], 
+            open_brace 
+        })?;
+
+    *index = peek;
+    return FullResult::Ok(stmt_bump.alloc(StmtData { 
+        line, 
+        stmt: Stmt::Loop { label: label.cloned(), body: stmts, assign }
+    }));
+}
+
+/// Parses the `'label:` in front of a loop, if there is one.
+fn parse_loop_label<'tkns>(tokens: &'tkns [Tkn], index: &mut usize) -> Option<&'tkns String> {
+    if let Some(TknType::Label(label)) = tokens::get_token(tokens, *index).map(|e| &e.token) 
+        && tokens::is_token(tokens, TknType::Colon, *index + 1) 
+    {
+        *index += 2;
+        return Some(label);
     }
+    return None;
+}
+
+/// Whether the tokens at `index` start a `loop`, labeled or not.
+fn is_loop(tokens: &[Tkn], mut index: usize) -> bool {
+    parse_loop_label(tokens, &mut index);
+    return tokens::is_token(tokens, TknType::Keyword(Kwrd::Loop), index);
+}
+
+pub fn parse_for_statement<'tkns, 'bumps, 'defs>(
+    expr_bump: &'bumps ExprBump,
+    stmt_bump: &'bumps StmtBump,
+    structs: &'defs [Struct],
+    enums: &'defs [Enum],
+    line: usize,
+    functions: &RefCell<HashMap<String, FullFnDef<'tkns, 'bumps, 'defs>>>,
+    variables: &StackFrameDictAllocator<'_, String, VariableData<'tkns, 'bumps>>,
+    tokens: &'tkns [Tkn],
+    expected_type: &mut ExprType,
+    errors: &mut Vec<ParserError<'tkns, 'bumps, 'defs>>,
+    loops: &mut Vec<EnclosingLoop<'tkns, 'bumps>>,
+    index: &mut usize
+) -> FullResult<
+    &'bumps StmtData<'bumps, 'defs>, 
+    ParserError<'tkns, 'bumps, 'defs>, 
+    ParserError<'tkns, 'bumps, 'defs>
+> {
+    let mut peek = *index;
+    
+    let label = parse_loop_label(tokens, &mut peek);
+    tokens::expect_token(tokens, TokenType::Keyword(Kwrd::For), &mut peek)
+        .ok_or_else_soft(|| ParserError::ExpectedToken {
+            tkn: &tokens[peek],
+            expected: TknType::Keyword(Kwrd::For)
+        })?;
+    let loop_tkn = &tokens[peek - 1];
+
+    let start_pattern = peek;
+    let pattern = patterns::parse_identifier_pattern(tokens, &mut peek).harden()?;
+
+    let data_type = if tokens::is_expected_token(tokens, TknType::Colon, &mut peek) {
+        super::get_type(tokens, &mut peek, structs, enums).ok_or_else_hard(|| ParserError::ExpectedType { 
+            tkn: &tokens[peek]
+        })?
+    } else {
+        ExprType::AmbiguousType
+    };
+    let end_pattern = peek;
+
+    tokens::expect_token(tokens, TknType::Keyword(Kwrd::In), &mut peek)
+        .ok_or_else_hard(|| ParserError::ExpectedToken { 
+            tkn: &tokens[peek], 
+            expected: TknType::Keyword(Kwrd::In) 
+        })?;
+
+    let start_expr = peek;
+
+    let Expr {expr_data, mut expr_type, ..} = expr::parse_expression_set(
+        expr_bump, 
+        structs,
+        enums,
+        tokens, 
+        &mut peek, 
+        line,
+        functions, 
+        &variables
+    ).harden()?;
+
+    // The element type stays linked to the bounds (or the array's elements), 
+    // so whatever pins down the binder's type pins down the literals as well
+    let mut element_type = match (expr_data, expr_type.clone_inner()) {
+        (ExprData::BinaryOp(_, start, end), ExprType::Range { .. }) => start.expr_type.clone()
+            .match_type(&mut end.expr_type.clone())
+            .expect("range bounds are matched when the range is parsed"),
+        (ExprData::Array(elements), ExprType::Array { expr_type: element_type, .. }) => match elements.first() {
+            Some(first) => first.expr_type.clone(),
+            None => ExprTypeCons::new(expr_bump, *element_type)
+        },
+        (_, ExprType::Range { expr_type: element_type, .. } | ExprType::Array { expr_type: element_type, .. }) => {
+            ExprTypeCons::new(expr_bump, *element_type)
+        },
+        (_, calculated_type) => return FullResult::HardErr(ParserError::ExpectedIterable { 
+            tkn: &tokens[start_expr], 
+            calculated_type 
+        })
+    };
+
+    let binder_type = expr_bump.alloc(RefCell::new(data_type));
+    let Some(mut element_type) = ExprTypeCons::new_stored(binder_type).match_type(&mut element_type) else {
+        return FullResult::HardErr(ParserError::CouldNotMatchType { 
+            tkns: &tokens[start_pattern..end_pattern], 
+            calculated_type: element_type.clone_inner(), 
+            expected_type: binder_type.borrow().clone() 
+        });
+    };
+
+    let binder = match pattern {
+        Pattern::Ident { tkn, mutable, name, .. } => Some((tkn, mutable, name)),
+        Pattern::DiscardSingle => None,
+        //TODO destructure group patterns once the interpreter can bind them
+        pattern => return FullResult::HardErr(ParserError::PatternNotMatchExpectedType { 
+            tkn: &tokens[start_pattern], 
+            pattern, 
+            expected_type: element_type.clone_inner() 
+        })
+    };
+
+    tokens::expect_token(tokens, TknType::OpenCurlyBrace, &mut peek)
+        .ok_or_else_hard(|| ParserError::ExpectedToken { 
+            tkn: &tokens[peek], 
+            expected: TknType::OpenCurlyBrace 
+        })?;
+    let open_brace = &tokens[peek - 1];
+
+    let mut stmts = vec![];
+
+    loops.push(EnclosingLoop { tkn: loop_tkn, label, value_type: None });
+    variables.new_scope(|variables| {
+        if let Some((tkn, mutable, name)) = &binder {
+            variables.push(name.as_str(), VariableData::new(tkn, *mutable, element_type.clone()));
+        }
+
+        stmts = parse_statements(
+            expr_bump, 
+            stmt_bump, 
+            structs,
+            enums,
+            functions, 
+            &variables, 
+            tokens, 
+            expected_type,
+            errors,
+            loops,
+            &mut peek
+        );
+    });
+    loops.pop();
 
     tokens::expect_token(tokens, TknType::CloseCurlyBrace, &mut peek)
         .ok_or_else_hard(|| ParserError::ExpectedClosingBrace { 
@@ -438,9 +1124,34 @@ pub fn parse_while_statement<'tkns, 'bumps, 'defs>(
             open_brace 
         })?;
 
+    // like in rust, integers nothing else pins down are i32s
+    if matches!(*element_type.get(), ExprType::AmbiguousNegInteger | ExprType::AmbiguousPosInteger) {
+        element_type.match_type(&mut ExprTypeCons::new(expr_bump, ExprType::I32));
+    }
+
+    let iter_type = match expr_type.clone_inner() {
+        ExprType::Range { exclusive_start, inclusive_end, .. } => ExprType::Range { 
+            exclusive_start, 
+            inclusive_end, 
+            expr_type: Box::new(element_type.clone_inner()) 
+        },
+        ExprType::Array { length, .. } => ExprType::Array { 
+            length, 
+            expr_type: Box::new(element_type.clone_inner()) 
+        },
+        _ => unreachable!("iter was checked to be a range or an array")
+    };
+    expr_type.match_type(&mut ExprTypeCons::new(expr_bump, iter_type));
+
     *index = peek;
-    return FullResult::Ok(expr_bump.alloc(StmtData { 
-        line, stmt: Stmt::While { cond, body: stmts }
+    return FullResult::Ok(stmt_bump.alloc(StmtData { 
+        line, 
+        stmt: Stmt::For { 
+            label: label.cloned(),
+            binder: binder.map(|(_, _, name)| (name, &*binder_type)), 
+            iter: Expr { line, expr_data, expr_type }, 
+            body: stmts 
+        }
     }));
 }
 
@@ -448,11 +1159,14 @@ pub fn parse_conditional_statement<'tkns, 'bumps, 'defs>(
     expr_bump: &'bumps ExprBump,
     stmt_bump: &'bumps StmtBump,
     structs: &'defs [Struct],
+    enums: &'defs [Enum],
     line: usize,
     functions: &RefCell<HashMap<String, FullFnDef<'tkns, 'bumps, 'defs>>>,
     variables: &StackFrameDictAllocator<'_, String, VariableData<'tkns, 'bumps>>,
     tokens: &'tkns [Tkn],
     expected_type: &mut ExprType,
+    errors: &mut Vec<ParserError<'tkns, 'bumps, 'defs>>,
+    loops: &mut Vec<EnclosingLoop<'tkns, 'bumps>>,
     index: &mut usize
 ) -> FullResult<
     &'bumps StmtData<'bumps, 'defs>, 
@@ -474,6 +1188,7 @@ pub fn parse_conditional_statement<'tkns, 'bumps, 'defs>(
     let Expr {expr_data, expr_type, ..} = expr::parse_expression_set(
         expr_bump, 
         structs,
+        enums,
         tokens, 
         &mut peek, 
         line,
@@ -503,32 +1218,21 @@ pub fn parse_conditional_statement<'tkns, 'bumps, 'defs>(
     let mut stmts = vec![];
 
     //variables.print();
-    let mut possible_error = None;
     variables.new_scope(|variables| {
-        loop {
-            let stmt_possible = parse_statement(
-                expr_bump, 
-                stmt_bump, 
-                structs,
-                functions,
-                &variables, 
-                tokens, 
-                expected_type,
-                &mut peek
-            );
-            match stmt_possible {
-                FullResult::Ok(mut stmt) => stmts.append(&mut stmt),
-                FullResult::SoftErr(_) => break,
-                FullResult::HardErr(err) => {
-                    possible_error = Some(err);
-                    break;
-                }
-            }
-        }
+        stmts = parse_statements(
+            expr_bump, 
+            stmt_bump, 
+            structs,
+            enums,
+            functions, 
+            &variables, 
+            tokens, 
+            expected_type,
+            errors,
+            loops,
+            &mut peek
+        );
     });
-    if let Some(err) = possible_error {
-        return FullResult::HardErr(err);
-    }
 
     tokens::expect_token(tokens, TknType::CloseCurlyBrace, &mut peek)
         .ok_or_else_hard(|| ParserError::ExpectedClosingBrace { 
@@ -552,6 +1256,7 @@ pub fn parse_conditional_statement<'tkns, 'bumps, 'defs>(
             } = expr::parse_expression_set(
                 expr_bump, 
                 structs,
+                enums,
                 tokens, 
                 &mut peek, 
                 line,
@@ -576,30 +1281,19 @@ pub fn parse_conditional_statement<'tkns, 'bumps, 'defs>(
                 })?;
             let open_brace = &tokens[peek - 1];
 
-            let mut stmts = vec![];
-            loop {
-                let stmt_possible = parse_statement(
-                    expr_bump, 
-                    stmt_bump,
-                    structs, 
-                    functions,
-                    &variables, 
-                    tokens, 
-                    expected_type,
-                    &mut peek
-                );
-                match stmt_possible {
-                    FullResult::Ok(mut stmt) => stmts.append(&mut stmt),
-                    FullResult::SoftErr(_) => break,
-                    FullResult::HardErr(err) => {
-                        possible_error = Some(err);
-                        break;
-                    }
-                }
-            }
-            if let Some(err) = possible_error {
-                return FullResult::HardErr(err);
-            }
+            let stmts = parse_statements(
+                expr_bump, 
+                stmt_bump, 
+                structs,
+                enums,
+                functions, 
+                &variables, 
+                tokens, 
+                expected_type,
+                errors,
+                loops,
+                &mut peek
+            );
 
             tokens::expect_token(tokens, TknType::CloseCurlyBrace, &mut peek)
                 .ok_or_else_hard(|| ParserError::ExpectedClosingBrace { 
@@ -618,30 +1312,19 @@ pub fn parse_conditional_statement<'tkns, 'bumps, 'defs>(
             })?;
         let open_brace = &tokens[peek - 1];
 
-        let mut stmts = vec![];
-        loop {
-            let stmt_possible = parse_statement(
-                expr_bump, 
-                stmt_bump, 
-                structs,
-                functions, 
-                &variables, 
-                tokens, 
-                expected_type,
-                &mut peek
-            );
-            match stmt_possible {
-                FullResult::Ok(mut stmt) => stmts.append(&mut stmt),
-                FullResult::SoftErr(_) => break,
-                FullResult::HardErr(err) => {
-                    possible_error = Some(err);
-                    break;
-                }
-            }
-        }
-        if let Some(err) = possible_error {
-            return FullResult::HardErr(err);
-        }
+        let stmts = parse_statements(
+            expr_bump, 
+            stmt_bump, 
+            structs,
+            enums,
+            functions, 
+            &variables, 
+            tokens, 
+            expected_type,
+            errors,
+            loops,
+            &mut peek
+        );
 
         tokens::expect_token(tokens, TknType::CloseCurlyBrace, &mut peek)
             .ok_or_else_hard(|| ParserError::ExpectedClosingBrace { 
@@ -659,10 +1342,99 @@ pub fn parse_conditional_statement<'tkns, 'bumps, 'defs>(
     }));
 }
 
+/// Parses a `match` in statement position, whose arms are either blocks or expressions.
+fn parse_match_statement<'tkns, 'bumps, 'defs>(
+    expr_bump: &'bumps ExprBump,
+    stmt_bump: &'bumps StmtBump,
+    structs: &'defs [Struct],
+    enums: &'defs [Enum],
+    line: usize,
+    functions: &RefCell<HashMap<String, FullFnDef<'tkns, 'bumps, 'defs>>>,
+    variables: &StackFrameDictAllocator<'_, String, VariableData<'tkns, 'bumps>>,
+    tokens: &'tkns [Tkn], 
+    expected_type: &mut ExprType,
+    errors: &mut Vec<ParserError<'tkns, 'bumps, 'defs>>,
+    loops: &mut Vec<EnclosingLoop<'tkns, 'bumps>>,
+    index: &mut usize
+) -> FullResult<
+    &'bumps StmtData<'bumps, 'defs>, 
+    ParserError<'tkns, 'bumps, 'defs>, 
+    ParserError<'tkns, 'bumps, 'defs>
+> {
+    let mut peek = *index;
+
+    if !tokens::is_token(tokens, TknType::Keyword(Kwrd::Match), peek) {
+        return FullResult::SoftErr(ParserError::ExpectedToken { 
+            tkn: &tokens[peek], 
+            expected: TknType::Keyword(Kwrd::Match) 
+        });
+    }
+
+    let (scrutinee, arms) = patterns::parse_match(
+        expr_bump, 
+        structs, 
+        enums, 
+        tokens, 
+        &mut peek, 
+        line, 
+        functions, 
+        variables, 
+        |variables, peek| {
+            if tokens::is_expected_token(tokens, TknType::OpenCurlyBrace, peek) {
+                let open_brace = &tokens[*peek - 1];
+
+                let mut stmts = vec![];
+                variables.new_scope(|variables| {
+                    stmts = parse_statements(
+                        expr_bump, 
+                        stmt_bump, 
+                        structs,
+                        enums,
+                        functions, 
+                        &variables, 
+                        tokens, 
+                        expected_type,
+                        errors,
+                        loops,
+                        peek
+                    );
+                });
+
+                tokens::expect_token(tokens, TknType::CloseCurlyBrace, peek)
+                    .ok_or_else(|| ParserError::ExpectedClosingBrace { 
+                        tkn: &tokens[*peek], 
+                        open_brace 
+                    })?;
+                return Ok(stmts);
+            }
+
+            let expr = expr::parse_expression_set(
+                expr_bump, 
+                structs, 
+                enums, 
+                tokens, 
+                peek, 
+                line, 
+                functions, 
+                variables
+            )?;
+            return Ok(vec![&*stmt_bump.alloc(StmtData { line, stmt: Stmt::Expr(expr) })]);
+        }
+    ).harden()?;
+
+    tokens::is_expected_token(tokens, TknType::Semicolon, &mut peek);
+
+    *index = peek;
+    return FullResult::Ok(stmt_bump.alloc(StmtData { 
+        line, stmt: Stmt::Match { scrutinee, arms }
+    }));
+}
+
 pub fn parse_expr_statement<'tkns, 'bumps, 'defs>(
     expr_bump: &'bumps ExprBump,
     stmt_bump: &'bumps StmtBump,
     structs: &'defs [Struct],
+    enums: &'defs [Enum],
     line: usize,
     functions: &RefCell<HashMap<String, FullFnDef<'tkns, 'bumps, 'defs>>>,
     variables: &StackFrameDictAllocator<'_, String, VariableData<'tkns, 'bumps>>,
@@ -682,6 +1454,7 @@ pub fn parse_expr_statement<'tkns, 'bumps, 'defs>(
             expr::parse_expression_set(
                 expr_bump, 
                 structs, 
+                enums, 
                 tokens, 
                 &mut peek, 
                 line, 
@@ -1,18 +1,30 @@
-use crate::{full_result::{FullResult, OptionToFullResult}, lexer::token::{Kwrd, Tkn, TknType}};
+use crate::{full_result::{FullResult, OptionToFullResult, ResultToFullResult}, lexer::token::{Kwrd, Tkn, TknType}};
 
-use super::{accessors, expr::ExprType, tokens, ParserError};
+use super::{accessors::{self, Accessibility, Accessor}, enums::Enum, expr::ExprType, lifetimes, namespaces, tokens, ParserError};
 
 #[derive(Clone, Debug)]
 pub struct Struct {
-    pub accessibility: String,
+    pub accessibility: Accessibility,
     pub location: String,
+    pub file_name: String,
+    /// The line the name of the struct is written on
+    pub line: usize,
     pub name: String,
+    /// The type parameters the struct is generic over, empty for a plain struct
+    pub type_params: Vec<String>,
     pub fields: Vec<Field>,
 }
 
+impl Struct {
+    /// The type of `field` in the instance of the struct for `type_args`.
+    pub fn field_type(&self, field: &Field, type_args: &[ExprType]) -> ExprType {
+        return field.field_type.substitute(&self.type_params, type_args);
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Field {
-    pub accessibility: String,
+    pub accessibility: Accessibility,
     pub field_type: ExprType,
     pub field_name: String,
 }
@@ -21,6 +33,10 @@ pub struct Field {
 pub struct StructDefinition<'tkns> {
     pub accessibility: Option<&'tkns Tkn>,
     pub name: String,
+    pub lifetimes: Vec<String>,
+    pub type_params: Vec<String>,
+    pub file_name: String,
+    pub line: usize,
     pub body_tokens: &'tkns [Tkn],
 }
 
@@ -45,12 +61,15 @@ pub fn define_struct<'tkns, 'bumps, 'defs>(
 
     tokens::expect_token(tokens, TknType::Keyword(Kwrd::Struct), &mut peek).ok_or_soft::<_, Vec<_>>(())?;
     let name;
+    let file_name = tokens[peek].file_name.clone();
+    let line = tokens[peek].line_number;
     if let Some(TknType::Identifier(ident)) = tokens.get(peek).map(|e| &e.token) {
         name = ident.clone();
         peek += 1;
     } else {
         return FullResult::HardErr(vec![ParserError::ExpectedIdentifier { tkn: &tokens[peek] }]);
     }
+    let (lifetimes, type_params) = super::get_type_params(tokens, &mut peek).map_err(|error| vec![error]).harden()?;
 
     let mut open_braces = vec![];
     tokens::expect_token(tokens, TknType::OpenCurlyBrace, &mut peek)
@@ -74,7 +93,7 @@ pub fn define_struct<'tkns, 'bumps, 'defs>(
                 end = peek - 1;
                 body_tokens = &tokens[start..end];
                 *index = peek;
-                return FullResult::Ok(StructDefinition {accessibility, name, body_tokens});
+                return FullResult::Ok(StructDefinition {accessibility, name, lifetimes, type_params, file_name, line, body_tokens});
             }
             count -= 1;
             open_braces.pop();
@@ -95,10 +114,12 @@ pub fn define_struct<'tkns, 'bumps, 'defs>(
 
 pub fn parse_struct<'tkns, 'bumps, 'defs>(
     struct_def: &StructDefinition<'tkns>, 
-    accessors: &[&str], 
-    structs: &[&str]
+    accessors: &[Accessor], 
+    structs: &[&str],
+    enums: &[Enum]
 ) -> Result<Struct, ParserError<'tkns, 'bumps, 'defs>> {
-    let StructDefinition {accessibility, name, body_tokens} = struct_def;
+    let StructDefinition {accessibility, name, lifetimes, type_params, file_name, line, body_tokens} = struct_def;
+    lifetimes::check_declared(body_tokens, lifetimes)?;
     let mut peek: usize = 0;
     let mut fields: Vec<Field> = vec![];
 
@@ -110,7 +131,13 @@ pub fn parse_struct<'tkns, 'bumps, 'defs>(
 
         let field_type;
         let field_name;
-        let accessible = accessors::get_accessor_string(
+        if need_comma {
+            return Err(ParserError::ExpectedEndOfStruct { 
+                tkn: &body_tokens[peek - 1] 
+            });
+        }
+
+        let accessible = accessors::get_accessibility(
             tokens::get_token(body_tokens, peek),  
             &mut peek, 
             accessors
@@ -118,12 +145,6 @@ pub fn parse_struct<'tkns, 'bumps, 'defs>(
             tkn: &body_tokens[peek] 
         })?;
 
-        if need_comma {
-            return Err(ParserError::ExpectedEndOfStruct { 
-                tkn: &body_tokens[peek - 1] 
-            });
-        }
-
         field_name = super::get_ident_token_string(
             tokens::get_token(body_tokens, peek), 
             &mut peek, structs
@@ -140,7 +161,9 @@ pub fn parse_struct<'tkns, 'bumps, 'defs>(
         field_type = super::get_type_token_expr_type(
             body_tokens, 
             &mut peek, 
-            structs
+            structs,
+            type_params,
+            enums
         ).ok_or_else(|| ParserError::ExpectedType { 
             tkn: &body_tokens[peek] 
         })?;
@@ -156,12 +179,16 @@ pub fn parse_struct<'tkns, 'bumps, 'defs>(
         fields.push(Field {accessibility: accessible, field_type, field_name});
     }
     return Ok(Struct {
-        accessibility: accessors::get_accessor_string(
+        accessibility: accessors::get_accessibility(
             *accessibility, 
             &mut 0, 
             accessors
         ).unwrap(), 
         name: name.to_string(),
-        location: "".to_string(), fields
+        location: namespaces::location_of(name), 
+        file_name: file_name.clone(),
+        line: *line,
+        type_params: type_params.clone(),
+        fields
     });
 }
\ No newline at end of file
@@ -0,0 +1,384 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use crate::{full_result::{FullResult, OptionToFullResult}, lexer::token::{Kwrd, Tkn, TknType}};
+
+use super::{accessors::{self, Accessibility, Accessor}, enums::Enum, expr::ExprType, functions::{self, FullFnDef, FunctionDefinition}, namespaces, tokens, FnParamBump, ParserError};
+
+/// The name trait methods use for the type implementing the trait
+pub const SELF_TYPE: &str = "Self";
+/// The built in trait marking the structs and enums whose values are copied instead of moved
+pub const COPY_TRAIT: &str = "Copy";
+
+/// `trait Name { fn method ...; }`, the methods a type needs for an `impl` of the trait
+#[derive(Clone, Debug)]
+pub struct Trait {
+    pub accessibility: Accessibility,
+    pub location: String,
+    pub name: String,
+    pub methods: Vec<TraitMethod>,
+    /// Every type with an `impl` of the trait
+    pub implementations: Vec<ExprType>,
+}
+
+/// The signature of a trait method, where `Self` stands for the type implementing the trait
+#[derive(Clone, Debug, PartialEq)]
+pub struct TraitMethod {
+    /// The name of the method where the trait declares it
+    pub tkn: Tkn,
+    pub name: String,
+    pub left_args: Vec<ExprType>,
+    pub right_args: Vec<ExprType>,
+    pub return_type: ExprType,
+}
+
+#[derive(Debug)]
+pub struct TraitDefinition<'tkns> {
+    pub accessibility: Option<&'tkns Tkn>,
+    pub tkn: &'tkns Tkn,
+    pub name: String,
+    /// The name of each method along with its arguments and return type, up to the `;` ending it
+    pub methods: Vec<(&'tkns Tkn, &'tkns [Tkn])>,
+}
+
+/// `impl Type { ... }` or `impl Trait for Type { ... }`
+#[derive(Debug)]
+pub struct ImplDefinition<'tkns> {
+    pub trait_tkn: Option<&'tkns Tkn>,
+    pub type_tkn: &'tkns Tkn,
+    pub methods: Vec<FunctionDefinition<'tkns>>,
+}
+
+impl TraitMethod {
+    /// The arguments and return type the method has in an `impl` for `self_type`, written the way
+    /// a function declares them.
+    pub fn signature(&self, self_type: &ExprType) -> String {
+        let types = |args: &[ExprType]| args.iter()
+            .map(|arg| substitute_self(arg, self_type).to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut signature = String::new();
+        if !self.left_args.is_empty() {
+            signature += &format!("$ {} ", types(&self.left_args));
+            signature += if self.right_args.is_empty() { "postfix " } else { "infix " };
+        }
+        if !self.right_args.is_empty() {
+            signature += &format!("$ {} ", types(&self.right_args));
+        }
+        if self.return_type != ExprType::Void {
+            signature += &format!("= {}", substitute_self(&self.return_type, self_type));
+        }
+        return signature.trim_end().to_string();
+    }
+}
+
+pub fn define_trait<'tkns, 'bumps, 'defs>(
+    tokens: &'tkns [Tkn],
+    index: &mut usize
+) -> FullResult<TraitDefinition<'tkns>, (), Vec<ParserError<'tkns, 'bumps, 'defs>>> {
+    let mut peek = *index;
+    let accessibility = match &tokens[peek].token {
+        TknType::Keyword(Kwrd::Public)
+        | TknType::Keyword(Kwrd::Private)
+        | TknType::Keyword(Kwrd::Package)
+        | TknType::Identifier(_) => {
+            peek += 1;
+            Some(&tokens[peek - 1])
+        },
+        TknType::Keyword(Kwrd::Trait) => None,
+        _ => {
+            return FullResult::SoftErr(());
+        }
+    };
+
+    tokens::expect_token(tokens, TknType::Keyword(Kwrd::Trait), &mut peek).ok_or_soft::<_, Vec<_>>(())?;
+    let tkn = &tokens[peek];
+    let name;
+    if let TknType::Identifier(ident) = &tkn.token {
+        name = ident.clone();
+        peek += 1;
+    } else {
+        return FullResult::HardErr(vec![ParserError::ExpectedIdentifier { tkn }]);
+    }
+
+    tokens::expect_token(tokens, TknType::OpenCurlyBrace, &mut peek)
+        .ok_or_else_hard(|| vec![ParserError::ExpectedToken {
+            tkn: &tokens[peek],
+            expected: TknType::OpenCurlyBrace
+        }])?;
+    let open_brace = &tokens[peek - 1];
+
+    let mut methods = vec![];
+    loop {
+        if tokens::is_expected_token(tokens, TknType::CloseCurlyBrace, &mut peek) {
+            *index = peek;
+            return FullResult::Ok(TraitDefinition { accessibility, tkn, name, methods });
+        } else if tokens::is_token(tokens, TknType::EndOfFile, peek) {
+            return FullResult::HardErr(vec![ParserError::ExpectedClosingBrace {
+                tkn: &tokens[peek],
+                open_brace
+            }]);
+        }
+
+        tokens::expect_token(tokens, TknType::Keyword(Kwrd::Function), &mut peek)
+            .ok_or_else_hard(|| vec![ParserError::ExpectedToken {
+                tkn: &tokens[peek],
+                expected: TknType::Keyword(Kwrd::Function)
+            }])?;
+        let method_tkn = &tokens[peek];
+        let TknType::Identifier(_) = method_tkn.token else {
+            return FullResult::HardErr(vec![ParserError::ExpectedIdentifier { tkn: method_tkn }]);
+        };
+        peek += 1;
+
+        // anonymous struct types have braces of their own, so only a `}` we did not open ends early
+        let start = peek;
+        let mut depth: usize = 0;
+        loop {
+            match tokens[peek].token {
+                TknType::Semicolon if depth == 0 => break,
+                TknType::OpenCurlyBrace => depth += 1,
+                TknType::CloseCurlyBrace if depth > 0 => depth -= 1,
+                TknType::CloseCurlyBrace | TknType::EndOfFile => {
+                    return FullResult::HardErr(vec![ParserError::ExpectedToken {
+                        tkn: &tokens[peek],
+                        expected: TknType::Semicolon
+                    }]);
+                },
+                _ => ()
+            }
+            peek += 1;
+        }
+        methods.push((method_tkn, &tokens[start..peek]));
+        peek += 1;
+    }
+}
+
+/// Method signatures can name `Self` along with every struct and enum.
+pub fn parse_trait<'tkns, 'bumps, 'defs>(
+    fn_param_bump: &'bumps FnParamBump,
+    trait_def: &TraitDefinition<'tkns>,
+    accessors: &[Accessor],
+    structs: &[&str],
+    enums: &[Enum]
+) -> Result<Trait, ParserError<'tkns, 'bumps, 'defs>> {
+    let TraitDefinition { accessibility, tkn, name, methods } = trait_def;
+    let accessibility = accessors::get_accessibility(*accessibility, &mut 0, accessors)
+        .ok_or_else(|| match accessibility {
+            Some(tkn) => ParserError::AccessorNotDefined { tkn },
+            None => ParserError::MissingAccessor { tkn }
+        })?;
+
+    let mut types = structs.to_vec();
+    types.push(SELF_TYPE);
+
+    let mut trait_methods = vec![];
+    for (method_tkn, arg_tokens) in methods {
+        let (left_args, right_args, return_type, _) = functions::define_arguments(
+            fn_param_bump,
+            arg_tokens,
+            &types,
+            &[],
+            enums
+        )?;
+        trait_methods.push(TraitMethod {
+            tkn: (*method_tkn).clone(),
+            name: token_name(method_tkn),
+            left_args: left_args.iter().map(|arg| arg.param_type.clone()).collect(),
+            right_args: right_args.iter().map(|arg| arg.param_type.clone()).collect(),
+            return_type
+        });
+    }
+
+    return Ok(Trait {
+        accessibility,
+        location: namespaces::location_of(name),
+        name: name.clone(),
+        methods: trait_methods,
+        implementations: vec![]
+    });
+}
+
+/// `trait Copy {}`, which every program has unless it declares a `Copy` of its own.
+pub fn copy_trait() -> Trait {
+    return Trait {
+        accessibility: Accessibility::Public,
+        location: String::new(),
+        name: COPY_TRAIT.to_string(),
+        methods: vec![],
+        implementations: vec![]
+    };
+}
+
+pub fn define_impl<'tkns, 'bumps, 'defs>(
+    tokens: &'tkns [Tkn],
+    index: &mut usize
+) -> FullResult<ImplDefinition<'tkns>, (), Vec<ParserError<'tkns, 'bumps, 'defs>>> {
+    let mut peek = *index;
+    tokens::expect_token(tokens, TknType::Keyword(Kwrd::Impl), &mut peek).ok_or_soft::<_, Vec<_>>(())?;
+
+    let mut trait_tkn = None;
+    let Some(TknType::Identifier(_)) = tokens.get(peek).map(|e| &e.token) else {
+        return FullResult::HardErr(vec![ParserError::ExpectedIdentifier { tkn: &tokens[peek] }]);
+    };
+    let mut type_tkn = &tokens[peek];
+    peek += 1;
+    if tokens::is_expected_token(tokens, TknType::Keyword(Kwrd::For), &mut peek) {
+        let Some(TknType::Identifier(_)) = tokens.get(peek).map(|e| &e.token) else {
+            return FullResult::HardErr(vec![ParserError::ExpectedIdentifier { tkn: &tokens[peek] }]);
+        };
+        trait_tkn = Some(type_tkn);
+        type_tkn = &tokens[peek];
+        peek += 1;
+    }
+
+    tokens::expect_token(tokens, TknType::OpenCurlyBrace, &mut peek)
+        .ok_or_else_hard(|| vec![ParserError::ExpectedToken {
+            tkn: &tokens[peek],
+            expected: TknType::OpenCurlyBrace
+        }])?;
+    let open_brace = peek - 1;
+
+    let mut methods = vec![];
+    loop {
+        if tokens::is_expected_token(tokens, TknType::CloseCurlyBrace, &mut peek) {
+            *index = peek;
+            return FullResult::Ok(ImplDefinition { trait_tkn, type_tkn, methods });
+        } else if tokens::is_token(tokens, TknType::EndOfFile, peek) {
+            return FullResult::HardErr(vec![ParserError::ExpectedClosingBrace {
+                tkn: &tokens[peek],
+                open_brace: &tokens[open_brace]
+            }]);
+        }
+
+        let error = match functions::define_function(tokens, &mut peek) {
+            FullResult::Ok(method) => {
+                methods.push(method);
+                continue;
+            },
+            FullResult::SoftErr(()) => ParserError::ExpectedToken {
+                tkn: &tokens[peek],
+                expected: TknType::Keyword(Kwrd::Function)
+            },
+            FullResult::HardErr(error) => error
+        };
+        // leave the rest of the block behind, so its methods are not read as functions
+        *index = closing_brace(tokens, open_brace).unwrap_or(*index);
+        return FullResult::HardErr(vec![error]);
+    }
+}
+
+/// Checks the methods of an `impl` of `custom_trait` for `self_type` against the ones the trait
+/// declares, once every method has been defined as a function named after `self_type`.
+pub fn check_impl<'tkns, 'bumps, 'defs>(
+    custom_trait: &'defs Trait,
+    trait_tkn: &'tkns Tkn,
+    self_type: &ExprType,
+    methods: &[&'tkns Tkn],
+    functions: &RefCell<HashMap<String, FullFnDef<'tkns, 'bumps, 'defs>>>
+) -> Vec<ParserError<'tkns, 'bumps, 'defs>> {
+    let mut errors = vec![];
+    for method_tkn in methods {
+        let method_name = token_name(method_tkn);
+        let Some(method) = custom_trait.methods.iter().find(|method| method.name == method_name) else {
+            errors.push(ParserError::NotTraitMethod { tkn: method_tkn, custom_trait });
+            continue;
+        };
+        let Some(name) = method_name_of(self_type, &method_name) else {
+            continue;
+        };
+        let functions = functions.borrow();
+        // methods that failed to define already have an error of their own
+        let Some(fun_def) = functions.get(&name) else {
+            continue;
+        };
+
+        let matches = |params: &[functions::FnParam], args: &[ExprType]| params.len() == args.len()
+            && params.iter().zip(args).all(|(param, arg)| param.param_type == substitute_self(arg, self_type));
+        if !matches(fun_def.left_args, &method.left_args)
+            || !matches(fun_def.right_args, &method.right_args)
+            || fun_def.return_type != substitute_self(&method.return_type, self_type)
+        {
+            errors.push(ParserError::TraitMethodMismatch {
+                tkn: method_tkn,
+                custom_trait,
+                method,
+                self_type: self_type.clone()
+            });
+        }
+    }
+
+    for method in &custom_trait.methods {
+        if !methods.iter().any(|method_tkn| token_name(method_tkn) == method.name) {
+            errors.push(ParserError::MissingTraitMethod { tkn: trait_tkn, custom_trait, method });
+        }
+    }
+    return errors;
+}
+
+/// The full name of the function defining `method` on values of `self_type`, which is the method
+/// name written after the path of the struct or enum.
+pub fn method_name_of(self_type: &ExprType, method: &str) -> Option<String> {
+    return match self_type {
+        ExprType::Custom { ident, .. } | ExprType::Enum { ident, .. } => Some(format!("{ident}::{method}")),
+        _ => None
+    };
+}
+
+/// `expr_type` with every `Self` replaced by `self_type`.
+pub fn substitute_self(expr_type: &ExprType, self_type: &ExprType) -> ExprType {
+    let substitute = |expr_type: &ExprType| substitute_self(expr_type, self_type);
+    return match expr_type {
+        ExprType::Custom { ident, .. } if ident == SELF_TYPE => self_type.clone(),
+        ExprType::Ref { lifetime, expr_type } => ExprType::Ref { 
+            lifetime: lifetime.clone(), 
+            expr_type: Box::new(substitute(expr_type)) 
+        },
+        ExprType::MutRef { lifetime, expr_type } => ExprType::MutRef { 
+            lifetime: lifetime.clone(), 
+            expr_type: Box::new(substitute(expr_type)) 
+        },
+        ExprType::ImRef { lifetime, expr_type } => ExprType::ImRef { 
+            lifetime: lifetime.clone(), 
+            expr_type: Box::new(substitute(expr_type)) 
+        },
+        ExprType::Array { length, expr_type } => ExprType::Array {
+            length: *length,
+            expr_type: Box::new(substitute(expr_type))
+        },
+        ExprType::AnonymousCustom { fields } => ExprType::AnonymousCustom {
+            fields: fields.iter().map(|(name, field_type)| (name.clone(), substitute(field_type))).collect()
+        },
+        ExprType::Tuple { start, end } => ExprType::Tuple {
+            start: start.iter().map(substitute).collect(),
+            end: end.iter().map(substitute).collect()
+        },
+        expr_type => expr_type.clone()
+    };
+}
+
+/// The index of the `}` closing the block opened at `open_brace`.
+fn closing_brace(tokens: &[Tkn], open_brace: usize) -> Option<usize> {
+    let mut depth: usize = 0;
+    for (index, tkn) in tokens.iter().enumerate().skip(open_brace) {
+        match tkn.token {
+            TknType::OpenCurlyBrace => depth += 1,
+            TknType::CloseCurlyBrace => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(index);
+                }
+            },
+            TknType::EndOfFile => return None,
+            _ => ()
+        }
+    }
+    return None;
+}
+
+/// The name of a method as written at `tkn`.
+fn token_name(tkn: &Tkn) -> String {
+    let TknType::Identifier(name) = &tkn.token else {
+        return tkn.token.to_string();
+    };
+    return name.clone();
+}
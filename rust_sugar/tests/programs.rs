@@ -0,0 +1,138 @@
+//! Runs the sugar programs under `test/<feature>/` and checks what they print against the `.out`
+//! file next to them.  A program is either `name.sgr` or a directory `name/` of files making up
+//! one multi-file program, whose `main.sgr` is the root namespace.  `name.out` is what
+//! `sugar interpret` prints, and `name.run.out`, when there is one, is what `sugar run` prints and
+//! what the executable `sugar build` makes prints.  A program whose expected output reports an
+//! error has to make the command fail, and every other program has to succeed.
+
+use std::{fs, path::{Path, PathBuf}, process::Command};
+
+fn strip_ansi(output: &str) -> String {
+    let mut stripped = String::new();
+    let mut chars = output.chars();
+    while let Some(chr) = chars.next() {
+        if chr == '\x1b' {
+            // an escape sequence runs up to the letter that ends it, like the `m` in `\x1b[0;91m`
+            chars.by_ref().find(|chr| chr.is_ascii_alphabetic());
+            continue;
+        }
+        stripped.push(chr);
+    }
+    return stripped;
+}
+
+fn program_files(program: &Path) -> Vec<String> {
+    if program.is_file() {
+        return vec![program.file_name().unwrap().to_string_lossy().into_owned()];
+    }
+    let mut files = fs::read_dir(program).unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .filter(|file| file.ends_with(".sgr"))
+        .collect::<Vec<_>>();
+    files.sort_by_key(|file| (file != "main.sgr", file.clone()));
+    return files;
+}
+
+fn run_in(dir: &Path, command: &mut Command, program: &Path, should_fail: bool) -> String {
+    let output = command.current_dir(dir).output().unwrap();
+    let stdout = strip_ansi(&String::from_utf8_lossy(&output.stdout));
+    assert!(
+        output.status.success() != should_fail,
+        "{command:?} {} on {}:\n{stdout}{}",
+        if should_fail { "succeeded" } else { "failed" },
+        program.display(),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    return stdout;
+}
+
+fn run_program(command: &str, program: &Path, should_fail: bool) -> String {
+    let dir = if program.is_file() { program.parent().unwrap() } else { program };
+    return run_in(
+        dir,
+        Command::new(env!("CARGO_BIN_EXE_sugar")).arg(command).args(program_files(program)),
+        program,
+        should_fail
+    );
+}
+
+/// Builds the program in a directory of its own, so the executable is not written next to it, 
+/// and runs the executable.  A program that does not build gives what `sugar build` printed.
+fn build_program(program: &Path, should_fail: bool) -> String {
+    let feature = program.parent().unwrap().file_name().unwrap();
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR"))
+        .join("programs")
+        .join(feature)
+        .join(program.file_stem().unwrap());
+    fs::create_dir_all(&dir).unwrap();
+    let files = program_files(program);
+    for file in &files {
+        let source = if program.is_file() { program.to_path_buf() } else { program.join(file) };
+        fs::copy(source, dir.join(file)).unwrap();
+    }
+
+    let built = run_in(
+        &dir, 
+        Command::new(env!("CARGO_BIN_EXE_sugar")).arg("build").args(&files), 
+        program, 
+        should_fail
+    );
+    if should_fail {
+        return built;
+    }
+    let executable = dir.join(Path::new(&files[0]).with_extension(""));
+    return run_in(&dir, &mut Command::new(executable), program, false);
+}
+
+fn programs() -> Vec<PathBuf> {
+    let test_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("test");
+    let mut programs = vec![];
+    for feature in fs::read_dir(test_dir).unwrap() {
+        let feature = feature.unwrap().path();
+        if !feature.is_dir() {
+            continue;
+        }
+        for program in fs::read_dir(feature).unwrap() {
+            let program = program.unwrap().path();
+            if program.is_dir() || program.extension().is_some_and(|extension| extension == "sgr") {
+                programs.push(program);
+            }
+        }
+    }
+    programs.sort();
+    return programs;
+}
+
+fn check_programs(out_extension: &str, run: impl Fn(&Path, bool) -> String) {
+    let mut failures = vec![];
+    for program in programs() {
+        let expected_path = program.with_extension(out_extension);
+        let Ok(expected) = fs::read_to_string(&expected_path) else {
+            continue;
+        };
+        let should_fail = expected.lines().any(|line| line.starts_with("error"));
+        let output = run(&program, should_fail);
+        if output != expected {
+            failures.push(format!(
+                "{}:\n--- expected\n{expected}--- printed\n{output}",
+                program.display()
+            ));
+        }
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn interpret_programs() {
+    check_programs("out", |program, should_fail| run_program("interpret", program, should_fail));
+}
+
+#[test]
+fn run_programs() {
+    check_programs("run.out", |program, should_fail| run_program("run", program, should_fail));
+}
+
+#[test]
+fn build_programs() {
+    check_programs("run.out", build_program);
+}